@@ -0,0 +1,23 @@
+use std::fs;
+
+/// Exposes the resolved near-sdk version to the contract as
+/// `NEAR_SDK_VERSION`, read from Cargo.lock so that it cannot drift from the
+/// version actually compiled against. Cargo.lock is committed, so the
+/// version is known in every checkout, and builds are reproducible.
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let version = lock
+        .split("[[package]]")
+        .find(|package| package.contains("name = \"near-sdk\"\n"))
+        .and_then(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("version = \""))
+                .map(|version| version.trim_end_matches('"').to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=NEAR_SDK_VERSION={}", version);
+}
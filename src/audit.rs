@@ -0,0 +1,69 @@
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminAction {
+    pub actor_id: AccountId,
+    pub method: String,
+    pub summary: String,
+    #[serde(with = "dec_string")]
+    pub timestamp: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AuditLog {
+    actions: Vector<AdminAction>,
+}
+
+/// `AuditLog` as stored in state version 2
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct AuditLogV2 {
+    pub actions: collections::Vector<AdminAction>,
+}
+
+impl AuditLog {
+    pub fn new<S>(storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            actions: Vector::new(prefix_key(&k, NestedKey::ADMIN_ACTIONS)),
+        }
+    }
+
+    /// Moves state version 2 into the current layout. `storage_key_prefix`
+    /// must be the one `old` was created with.
+    pub(crate) fn from_v2<S>(old: AuditLogV2, storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            actions: migrate_vector(old.actions, prefix_key(&k, NestedKey::ADMIN_ACTIONS)),
+        }
+    }
+
+    pub fn record(&mut self, method: &str, summary: String) {
+        self.actions.push(AdminAction {
+            actor_id: env::predecessor_account_id(),
+            method: method.to_string(),
+            summary,
+            timestamp: env::block_timestamp(),
+        });
+    }
+
+    pub fn get(&self, from_index: u64, limit: u64) -> Vec<AdminAction> {
+        self.actions
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+}
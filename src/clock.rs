@@ -0,0 +1,21 @@
+use crate::*;
+
+/// The contract's notion of the current time, used wherever something
+/// starts, ends or expires. This is the block timestamp unless the contract
+/// is built with the `sandbox` feature, where the owner can move it forward
+/// with `set_time_offset`.
+pub(crate) fn now() -> u64 {
+    #[cfg(feature = "sandbox")]
+    return env::block_timestamp().saturating_add(time_offset().get().unwrap_or(0));
+    #[cfg(not(feature = "sandbox"))]
+    env::block_timestamp()
+}
+
+/// Kept outside of the contract struct so that builds with and without the
+/// `sandbox` feature share the same state layout. The legacy `LazyOption`
+/// is used because a fresh `store::LazyOption` overwrites the stored value
+/// with its initial one when dropped.
+#[cfg(feature = "sandbox")]
+pub(crate) fn time_offset() -> near_sdk::collections::LazyOption<u64> {
+    near_sdk::collections::LazyOption::new(StorageKey::TIME_OFFSET, None)
+}
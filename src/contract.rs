@@ -1,91 +1,655 @@
 use crate::impl_ownership;
 use crate::*;
+use std::collections::HashSet;
+
+macro_rules! extract_msg {
+    ($proposal: ident, $enum: ident, $variant: ident) => {
+        match &$proposal.msg {
+            Some($enum::$variant(value)) => value,
+            Some(..) => env::panic_str("tag mismatch"),
+            _ => env::panic_str("msg value required"),
+        }
+    };
+}
+
+mod awards;
+pub use awards::*;
+
+mod bonds;
+
+mod badge_index;
+pub use badge_index::*;
+
+mod badge_store;
+pub use badge_store::*;
+
+mod gallery;
+pub use gallery::*;
+
+mod terms;
+pub use terms::*;
+
+mod visibility;
+pub use visibility::*;
+
+mod moderation;
+pub use moderation::*;
+
+mod validation;
+pub use validation::*;
+
+mod input;
+pub use input::*;
+
+mod activation;
+
+mod expiration;
+
+mod keepers;
+pub use keepers::*;
+
+mod groups;
+pub use groups::*;
+
+mod attestations;
+pub use attestations::*;
+
+mod merkle;
+pub use merkle::*;
+
+mod engagement;
+
+mod receipts;
+pub use receipts::*;
+
+mod sunset;
+pub use sunset::*;
+
+mod config;
+pub use config::*;
+
+mod supporters;
+pub use supporters::*;
+
+mod invariants;
+pub use invariants::*;
+
+mod streams;
+pub use streams::*;
+
+mod treasury;
+pub use treasury::*;
+
+mod referrals;
+
+mod promos;
+pub use promos::*;
+
+mod free_tier;
+
+mod executions;
+pub use executions::*;
+
+mod sponsor_caps;
+
+mod waitlist;
+pub use waitlist::*;
+
+mod inbox;
+pub use inbox::*;
+
+mod version;
+pub use version::*;
+
+mod migrate;
+#[cfg(test)]
+pub(crate) use migrate::*;
+
+mod previews;
+
+mod unwinds;
+
+mod lead_time;
+
+mod group_stats;
+pub use group_stats::*;
+
+mod archives;
+
+mod pruning;
+
+mod health;
+pub use health::*;
+
+mod offers;
+pub use offers::*;
+
+mod packages;
+pub use packages::*;
+
+mod signed_claims;
+pub use signed_claims::*;
+
+#[cfg(feature = "demo")]
+mod demo;
+#[cfg(feature = "demo")]
+pub use demo::*;
+
+#[cfg(feature = "sandbox")]
+mod sandbox;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
 
 pub const TAG_BADGE_CREATE: &'static str = "badge_create";
 pub const TAG_BADGE_EXTEND: &'static str = "badge_extend";
+/// A donation with no badge attached. Takes no `msg`.
+pub const TAG_GENERAL_SPONSORSHIP: &'static str = "general_sponsorship";
+/// Several badges in one group bought together as an owner-defined package
+pub const TAG_BADGE_PACKAGE: &'static str = "badge_package";
 
-#[derive(BorshStorageKey, BorshSerialize)]
-enum StorageKey {
-    OWNERSHIP,
-    SPONSORSHIP,
-    BADGES,
-}
+/// A tag, and the name of the `BadgeAction` variant it expects with a
+/// matcher for that variant, or `None` if it takes no message
+type TagBinding = (
+    &'static str,
+    Option<(&'static str, fn(&BadgeAction) -> bool)>,
+);
 
-#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
-#[serde(crate = "near_sdk::serde")]
+/// The message each tag expects. Tags not listed accept any message.
+const TAG_BINDINGS: [TagBinding; 4] = [
+    (
+        TAG_BADGE_CREATE,
+        Some(("Create", |a| matches!(a, BadgeAction::Create(_)))),
+    ),
+    (
+        TAG_BADGE_EXTEND,
+        Some(("Extend", |a| matches!(a, BadgeAction::Extend(_)))),
+    ),
+    (TAG_GENERAL_SPONSORSHIP, None),
+    (
+        TAG_BADGE_PACKAGE,
+        Some(("Package", |a| matches!(a, BadgeAction::Package(_)))),
+    ),
+];
+
+/// Serializes with the computed fields of `BadgeJson` alongside the stored
+/// ones. Those are ignored when a badge is deserialized.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", remote = "Self")]
 pub struct Badge {
     pub id: String,
     pub group_id: String,
     pub name: String,
     pub description: String,
     pub is_enabled: bool,
+    #[serde(with = "dec_string")]
     pub created_at: u64,
+    #[serde(with = "dec_string")]
     pub start_at: u64,
+    #[serde(default, with = "option_dec_string")]
     pub duration: Option<u64>,
+    #[serde(default, with = "option_dec_string")]
+    pub paused_at: Option<u64>,
+    pub sponsor_id: Option<AccountId>,
+    /// Refundable sponsor bond held against policy violations
+    #[serde(default, with = "dec_string")]
+    pub bond: Balance,
+    #[serde(default)]
+    pub hold: Option<BadgeHold>,
+    #[serde(default)]
+    pub disabled_reason: Option<String>,
+    /// Created for free by a free-tier account, e.g. a public-goods project
+    #[serde(default)]
+    pub is_community: bool,
+    /// Only the sponsor and the owner see a preview, until it is published
+    #[serde(default)]
+    pub is_preview: bool,
+    /// When a preview publishes itself, if it was given a time
+    #[serde(default, with = "option_dec_string")]
+    pub publish_at: Option<u64>,
+    /// Artwork for the badge. Views fill this in from the group's default
+    /// media when the badge has none of its own.
+    #[serde(default)]
+    pub media_url: Option<String>,
+    /// Paid by the sponsor for storing `description` and `media_url`, and
+    /// returned when the badge is archived
+    #[serde(default, with = "dec_string")]
+    pub storage_deposit: Balance,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub enum BadgeAction {
     Create(BadgeCreate),
     Extend(BadgeExtend),
+    Package(BadgePackage),
+}
+
+/// Stored form of a proposal's `msg`. When `BadgeAction` changes, its
+/// previous layout is kept here as a new variant along with the conversion
+/// to the current one, so proposals stored before an upgrade can still be
+/// read and resolved.
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedBadgeAction {
+    V1(BadgeActionV1),
+    V2(BadgeActionV2),
+    V3(BadgeActionV3),
+    V4(BadgeAction),
+}
+
+impl From<VersionedBadgeAction> for BadgeAction {
+    fn from(versioned: VersionedBadgeAction) -> Self {
+        match versioned {
+            VersionedBadgeAction::V1(action) => action.into(),
+            VersionedBadgeAction::V2(action) => action.into(),
+            VersionedBadgeAction::V3(action) => action.into(),
+            VersionedBadgeAction::V4(action) => action,
+        }
+    }
+}
+
+impl From<BadgeAction> for VersionedBadgeAction {
+    fn from(action: BadgeAction) -> Self {
+        VersionedBadgeAction::V4(action)
+    }
+}
+
+/// `BadgeAction` before payment streams
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum BadgeActionV1 {
+    Create(BadgeCreateV1),
+    Extend(BadgeExtendV1),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeCreateV1 {
+    pub id: String,
+    pub group_id: String,
+    pub name: String,
+    pub description: String,
+    pub start_at: Option<u64>,
+    pub duration: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeExtendV1 {
+    pub id: String,
+    pub duration: u64,
+}
+
+impl From<BadgeActionV1> for BadgeAction {
+    fn from(action: BadgeActionV1) -> Self {
+        match action {
+            BadgeActionV1::Create(c) => BadgeAction::Create(BadgeCreate {
+                id: c.id,
+                group_id: c.group_id,
+                name: c.name,
+                description: c.description,
+                start_at: c.start_at,
+                duration: c.duration,
+                stream_id: None,
+                preview: false,
+                publish_at: None,
+                media_url: None,
+            }),
+            BadgeActionV1::Extend(e) => BadgeAction::Extend(BadgeExtend {
+                id: e.id,
+                duration: e.duration,
+                stream_id: None,
+            }),
+        }
+    }
+}
+
+/// `BadgeAction` before badge previews
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum BadgeActionV2 {
+    Create(BadgeCreateV2),
+    Extend(BadgeExtend),
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeCreateV2 {
+    pub id: String,
+    pub group_id: String,
+    pub name: String,
+    pub description: String,
+    pub start_at: Option<u64>,
+    pub duration: u64,
+    pub stream_id: Option<String>,
+}
+
+impl From<BadgeActionV2> for BadgeAction {
+    fn from(action: BadgeActionV2) -> Self {
+        match action {
+            BadgeActionV2::Create(c) => BadgeAction::Create(BadgeCreate {
+                id: c.id,
+                group_id: c.group_id,
+                name: c.name,
+                description: c.description,
+                start_at: c.start_at,
+                duration: c.duration,
+                stream_id: c.stream_id,
+                preview: false,
+                publish_at: None,
+                media_url: None,
+            }),
+            BadgeActionV2::Extend(e) => BadgeAction::Extend(e),
+        }
+    }
+}
+
+/// `BadgeAction` before badge media
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum BadgeActionV3 {
+    Create(BadgeCreateV3),
+    Extend(BadgeExtend),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeCreateV3 {
+    pub id: String,
+    pub group_id: String,
+    pub name: String,
+    pub description: String,
+    pub start_at: Option<u64>,
+    pub duration: u64,
+    pub stream_id: Option<String>,
+    pub preview: bool,
+    pub publish_at: Option<u64>,
+}
+
+impl From<BadgeActionV3> for BadgeAction {
+    fn from(action: BadgeActionV3) -> Self {
+        match action {
+            BadgeActionV3::Create(c) => BadgeAction::Create(BadgeCreate {
+                id: c.id,
+                group_id: c.group_id,
+                name: c.name,
+                description: c.description,
+                start_at: c.start_at,
+                duration: c.duration,
+                stream_id: c.stream_id,
+                preview: c.preview,
+                publish_at: c.publish_at,
+                media_url: None,
+            }),
+            BadgeActionV3::Extend(e) => BadgeAction::Extend(e),
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct BadgeCreate {
     pub id: String,
     pub group_id: String,
     pub name: String,
     pub description: String,
+    #[serde(default, with = "option_dec_string")]
     pub start_at: Option<u64>,
+    /// Nanoseconds, `{ "days", "hours" }` or an ISO-8601 duration
+    #[serde(with = "duration")]
     pub duration: u64,
+    /// Payment stream that pays for the badge in place of the deposit. The
+    /// deposit must still cover the sponsor bond.
+    #[serde(default)]
+    pub stream_id: Option<String>,
+    /// Creates the badge as a preview, which stays out of public listings
+    /// until the sponsor or the owner calls `publish_badge`
+    #[serde(default)]
+    pub preview: bool,
+    /// Publishes a preview by itself at this time. Implies `preview`.
+    #[serde(default, with = "option_dec_string")]
+    pub publish_at: Option<u64>,
+    /// Artwork for the badge. Without it, the group's default media is shown.
+    #[serde(default)]
+    pub media_url: Option<String>,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug)]
+impl BadgeCreate {
+    /// Bytes of free-form metadata the badge will store, which the sponsor
+    /// pays to store until the badge is archived
+    pub fn metadata_bytes(&self) -> u64 {
+        (self.description.len() + self.media_url.as_ref().map_or(0, String::len)) as u64
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct BadgeExtend {
     pub id: String,
+    /// Nanoseconds, `{ "days", "hours" }` or an ISO-8601 duration
+    #[serde(with = "duration")]
     pub duration: u64,
+    /// Payment stream that pays for the extension in place of the deposit
+    #[serde(default)]
+    pub stream_id: Option<String>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgePackage {
+    pub package_id: String,
+    /// One per badge the package includes, all in the same group and each
+    /// for the package's badge duration. Streams are not accepted.
+    pub badges: Vec<BadgeCreate>,
+}
+
+/// Fields derived at call time, so that clients do not each redo the
+/// nanosecond math. Days are whole days, rounded up.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeJson<'a> {
+    #[serde(flatten, serialize_with = "serialize_stored_badge")]
+    badge: &'a Badge,
+    #[serde(with = "option_dec_string")]
+    expires_at: Option<u64>,
+    active_days_total: Option<u64>,
+    active_days_remaining: Option<u64>,
+}
+
+fn serialize_stored_badge<S: serde::Serializer>(
+    badge: &&Badge,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Badge::serialize(badge, serializer)
+}
+
+impl Serialize for Badge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BadgeJson {
+            badge: self,
+            expires_at: self.expires_at(),
+            active_days_total: self.active_days_total(),
+            active_days_remaining: self.active_days_remaining(now()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Badge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Badge::deserialize(deserializer)
+    }
 }
 
 impl Badge {
+    /// End of the active window, which opens at `start_at`
+    pub fn expires_at(&self) -> Option<u64> {
+        self.duration.map(|duration| self.start_at + duration)
+    }
+
+    /// Days from `start_at` to `expires_at`, or `None` if the badge never
+    /// expires
+    pub fn active_days_total(&self) -> Option<u64> {
+        self.expires_at()
+            .map(|expires_at| billable_days_in_duration(expires_at.saturating_sub(self.start_at)))
+    }
+
+    /// Days left in the active window, counted from `start_at` if it has not
+    /// begun. The clock of a paused badge stands still at `paused_at`.
+    pub fn active_days_remaining(&self, now: u64) -> Option<u64> {
+        let from = self.paused_at.unwrap_or(now).max(self.start_at);
+        self.expires_at()
+            .map(|expires_at| billable_days_in_duration(expires_at.saturating_sub(from)))
+    }
+
+    pub fn is_published(&self, now: u64) -> bool {
+        !self.is_preview || self.publish_at.is_some_and(|publish_at| publish_at <= now)
+    }
+
     pub fn is_expired(&self, now: u64) -> bool {
-        match self.duration {
-            Some(duration) => self.created_at + duration < now,
+        match self.expires_at() {
+            Some(expires_at) => expires_at < now,
             _ => false, // No duration = never expires
         }
     }
+
+    /// Disabling a badge pauses its expiry clock; re-enabling it extends the
+    /// duration by the time it spent disabled.
+    pub fn with_is_enabled(self, is_enabled: bool, now: u64) -> Badge {
+        match (self.is_enabled, is_enabled) {
+            (true, false) => Badge {
+                is_enabled,
+                paused_at: Some(now),
+                ..self
+            },
+            (false, true) => {
+                let paused_for = self
+                    .paused_at
+                    .map(|paused_at| now.saturating_sub(paused_at))
+                    .unwrap_or(0);
+
+                Badge {
+                    is_enabled,
+                    duration: self.duration.map(|duration| duration + paused_for),
+                    paused_at: None,
+                    disabled_reason: None,
+                    ..self
+                }
+            }
+            _ => self,
+        }
+    }
 }
 
 #[near_bindgen]
 #[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
 pub struct StatsGallery {
     ownership: Ownership,
-    sponsorship: Sponsorship<BadgeAction>,
-    badges: UnorderedMap<String, Badge>,
+    sponsorship: Sponsorship<BadgeAction, VersionedBadgeAction>,
+    badges: BadgeStore,
     badge_rate_per_day: Balance,
     badge_max_active_duration: u64,
     badge_min_creation_deposit: Balance,
+    awards: LookupMap<(String, AccountId), Award>,
+    /// Award lists kept before `account_award_sets`, moved over on the
+    /// account's next award
+    account_awards: LookupMap<AccountId, Vec<String>>,
+    /// Award sets kept before state version 3, which `migrate` cannot
+    /// enumerate, moved over on the account's next award
+    account_award_sets_v2: LookupMap<AccountId, collections::UnorderedSet<String>>,
+    account_award_sets: LookupMap<AccountId, IterableSet<String>>,
+    claim_keys: LookupMap<PublicKey, String>,
+    badge_indexes: BadgeIndexes,
+    featured_badge_ids: Vec<String>,
+    audit_log: AuditLog,
+    badge_creation_bond: Balance,
+    total_bonds_held: Balance,
+    proposal_terms: LookupMap<u64, BadgeTerms>,
+    badge_reports: LookupMap<String, Vec<BadgeReport>>,
+    badge_report_fee: Balance,
+    badge_report_threshold: Option<u64>,
+    input_limits: InputLimits,
+    /// Badges that start in the future, by `start_at`
+    activation_queue: TreeMap<(u64, String), ()>,
+    /// Badges whose window has yet to end, by `expires_at`
+    expiration_queue: TreeMap<(u64, String), ()>,
+    keeper_tasks: LookupMap<KeeperTask, KeeperTaskState>,
+    group_verifications: LookupMap<String, GroupVerification>,
+    oracles: IterableSet<AccountId>,
+    attestations: LookupMap<(String, AccountId), Attestation>,
+    merkle_drops: LookupMap<String, MerkleDrop>,
+    /// Claimed leaf bitmaps, by root and word index
+    merkle_claims: LookupMap<(Vec<u8>, u64), u128>,
+    award_validity: LookupMap<String, u64>,
+    group_transfers: LookupMap<String, GroupTransfer>,
+    view_relayers: IterableSet<AccountId>,
+    badge_views: LookupMap<String, u64>,
+    sunset: Option<Sunset>,
+    supporters: Vector<Supporter>,
+    /// Ring buffer of the latest donations, indexed by `donations_count`
+    recent_donations: Vector<Donation>,
+    donations_count: u64,
+    donor_totals: LookupMap<AccountId, Balance>,
+    /// Highest donor totals, by total
+    top_donors: TreeMap<(Balance, AccountId), ()>,
+    stream_config: Option<StreamConfig>,
+    badge_streams: LookupMap<String, BadgeStream>,
+    wrap_tokens: IterableSet<AccountId>,
+    treasury_positions: LookupMap<AccountId, TreasuryPosition>,
+    total_wrapped: Balance,
+    total_wrap_pending: Balance,
+    referral_share_bps: u16,
+    referral_rewards: LookupMap<AccountId, Balance>,
+    total_referral_rewards_owed: Balance,
+    promo_codes: IterableMap<String, PromoCode>,
+    free_tier_accounts: IterableSet<AccountId>,
+    pending_executions: IterableSet<u64>,
+    max_badges_per_sponsor: Option<u32>,
+    max_active_badges: Option<u32>,
+    waitlist: TreeMap<u64, WaitlistEntry>,
+    next_waitlist_position: u64,
+    proposal_updates: LookupMap<AccountId, Vec<ProposalUpdate>>,
+    next_update_seq: u64,
+    group_stats: LookupMap<String, GroupStats>,
+    group_media: LookupMap<String, String>,
+    total_storage_deposits_held: Balance,
+    badge_prune_after: Option<u64>,
+    pruned_badge_ids: LookupSet<String>,
+    treasury_account_id: Option<AccountId>,
+    total_forwarded: Balance,
+    total_forward_pending: Balance,
+    terms_offers: LookupMap<u64, TermsOffer>,
+    packages: IterableMap<String, Package>,
+    /// Package each package proposal was submitted for, as it was then
+    proposal_packages: LookupMap<u64, Package>,
+    /// End of the featured period of badges bought in a package
+    featured_until: LookupMap<String, u64>,
+    /// Badges featured by a package, by end of featured period
+    featured_queue: TreeMap<(u64, String), ()>,
+    /// Keys whose signatures `claim_signed` accepts, by badge
+    claim_issuers: LookupMap<String, PublicKey>,
+    /// Signed claim nonces already used, by badge
+    signed_claim_nonces: LookupSet<(String, u64)>,
 }
 
 const DAY: u64 = 1_000_000_000 * 60 * 60 * 24;
+const DEFAULT_FIND_BADGES: u64 = 10;
+const MAX_FIND_BADGES: u64 = 50;
 
 // Basically unstable_div_ceil
 pub fn billable_days_in_duration(duration: u64) -> u64 {
     duration / DAY + if duration % DAY > 0 { 1 } else { 0 }
 }
 
-macro_rules! extract_msg {
-    ($proposal: ident, $enum: ident, $variant: ident) => {
-        match &$proposal.msg {
-            Some($enum::$variant(value)) => value,
-            Some(..) => env::panic_str("tag mismatch"),
-            _ => env::panic_str("msg value required"),
-        }
-    };
-}
-
 #[near_bindgen]
 impl StatsGallery {
     #[init]
@@ -100,31 +664,125 @@ impl StatsGallery {
             ownership: Ownership::new(StorageKey::OWNERSHIP, owner_id),
             sponsorship: Sponsorship::new(
                 StorageKey::SPONSORSHIP,
-                vec![TAG_BADGE_CREATE.to_string(), TAG_BADGE_EXTEND.to_string()],
+                vec![
+                    TAG_BADGE_CREATE.to_string(),
+                    TAG_BADGE_EXTEND.to_string(),
+                    TAG_GENERAL_SPONSORSHIP.to_string(),
+                    TAG_BADGE_PACKAGE.to_string(),
+                ],
                 Some(proposal_duration.into()),
             ),
-            badges: UnorderedMap::new(StorageKey::BADGES),
+            badges: BadgeStore::new(StorageKey::BADGES),
             badge_rate_per_day: badge_rate_per_day.into(),
             badge_max_active_duration: badge_max_active_duration.into(),
             badge_min_creation_deposit: badge_min_creation_deposit.into(),
+            awards: LookupMap::new(StorageKey::AWARDS),
+            account_awards: LookupMap::new(StorageKey::ACCOUNT_AWARDS),
+            account_award_sets_v2: LookupMap::new(StorageKey::ACCOUNT_AWARD_SETS),
+            account_award_sets: LookupMap::new(StorageKey::ACCOUNT_AWARD_SETS_V3),
+            claim_keys: LookupMap::new(StorageKey::CLAIM_KEYS),
+            badge_indexes: BadgeIndexes::new(StorageKey::BADGE_INDEXES),
+            featured_badge_ids: vec![],
+            audit_log: AuditLog::new(StorageKey::AUDIT_LOG),
+            badge_creation_bond: 0,
+            total_bonds_held: 0,
+            proposal_terms: LookupMap::new(StorageKey::PROPOSAL_TERMS),
+            badge_reports: LookupMap::new(StorageKey::BADGE_REPORTS),
+            badge_report_fee: DEFAULT_BADGE_REPORT_FEE,
+            badge_report_threshold: None,
+            input_limits: InputLimits::default(),
+            activation_queue: TreeMap::new(StorageKey::ACTIVATION_QUEUE),
+            expiration_queue: TreeMap::new(StorageKey::EXPIRATION_QUEUE),
+            keeper_tasks: LookupMap::new(StorageKey::KEEPER_TASKS),
+            group_verifications: LookupMap::new(StorageKey::GROUP_VERIFICATIONS),
+            oracles: IterableSet::new(StorageKey::ORACLES),
+            attestations: LookupMap::new(StorageKey::ATTESTATIONS),
+            merkle_drops: LookupMap::new(StorageKey::MERKLE_DROPS),
+            merkle_claims: LookupMap::new(StorageKey::MERKLE_CLAIMS),
+            award_validity: LookupMap::new(StorageKey::AWARD_VALIDITY),
+            group_transfers: LookupMap::new(StorageKey::GROUP_TRANSFERS),
+            view_relayers: IterableSet::new(StorageKey::VIEW_RELAYERS),
+            badge_views: LookupMap::new(StorageKey::BADGE_VIEWS),
+            sunset: None,
+            supporters: Vector::new(StorageKey::SUPPORTERS),
+            recent_donations: Vector::new(StorageKey::RECENT_DONATIONS),
+            donations_count: 0,
+            donor_totals: LookupMap::new(StorageKey::DONOR_TOTALS),
+            top_donors: TreeMap::new(StorageKey::TOP_DONORS),
+            stream_config: None,
+            badge_streams: LookupMap::new(StorageKey::BADGE_STREAMS),
+            wrap_tokens: IterableSet::new(StorageKey::WRAP_TOKENS),
+            treasury_positions: LookupMap::new(StorageKey::TREASURY_POSITIONS),
+            total_wrapped: 0,
+            total_wrap_pending: 0,
+            referral_share_bps: 0,
+            referral_rewards: LookupMap::new(StorageKey::REFERRAL_REWARDS),
+            total_referral_rewards_owed: 0,
+            promo_codes: IterableMap::new(StorageKey::PROMO_CODES),
+            free_tier_accounts: IterableSet::new(StorageKey::FREE_TIER_ACCOUNTS),
+            pending_executions: IterableSet::new(StorageKey::PENDING_EXECUTIONS),
+            max_badges_per_sponsor: None,
+            max_active_badges: None,
+            waitlist: TreeMap::new(StorageKey::WAITLIST),
+            next_waitlist_position: 0,
+            proposal_updates: LookupMap::new(StorageKey::PROPOSAL_UPDATES),
+            next_update_seq: 0,
+            group_stats: LookupMap::new(StorageKey::GROUP_STATS),
+            group_media: LookupMap::new(StorageKey::GROUP_MEDIA),
+            total_storage_deposits_held: 0,
+            badge_prune_after: None,
+            pruned_badge_ids: LookupSet::new(StorageKey::PRUNED_BADGE_IDS),
+            treasury_account_id: None,
+            total_forwarded: 0,
+            total_forward_pending: 0,
+            terms_offers: LookupMap::new(StorageKey::TERMS_OFFERS),
+            packages: IterableMap::new(StorageKey::PACKAGES),
+            proposal_packages: LookupMap::new(StorageKey::PROPOSAL_PACKAGES),
+            featured_until: LookupMap::new(StorageKey::FEATURED_UNTIL),
+            featured_queue: TreeMap::new(StorageKey::FEATURED_QUEUE),
+            claim_issuers: LookupMap::new(StorageKey::CLAIM_ISSUERS),
+            signed_claim_nonces: LookupSet::new(StorageKey::SIGNED_CLAIM_NONCES),
         }
     }
 
-    pub fn get_badges(&self) -> Vec<Badge> {
-        let now = env::block_timestamp();
+    pub fn get_badges(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+        sort: Option<BadgeSort>,
+        descending: Option<bool>,
+    ) -> Vec<Badge> {
+        self.list_badges(BadgeVisibility::PUBLIC, from_index, limit, sort, descending)
+    }
 
+    pub fn get_badge(&self, badge_id: String) -> Option<Badge> {
         self.badges
-            .values()
-            .filter(|b| b.is_enabled && !b.is_expired(now))
-            .collect()
+            .get(&badge_id)
+            .map(|badge| self.with_default_media(badge))
     }
 
-    pub fn get_badge(&self, badge_id: String) -> Option<Badge> {
-        self.badges.get(&badge_id)
+    /// Publicly visible badges whose ID or group ID starts with `query`
+    pub fn find_badges(&self, query: String, limit: Option<U64>) -> Vec<Badge> {
+        let now = now();
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_FIND_BADGES)
+            .min(MAX_FIND_BADGES) as usize;
+        let mut seen = HashSet::new();
+
+        self.badge_indexes
+            .search(&query)
+            .filter(|badge_id| seen.insert(badge_id.clone()))
+            .filter_map(|badge_id| self.badges.get(&badge_id))
+            .filter(|b| BadgeVisibility::PUBLIC.is_visible(b, now))
+            .take(limit)
+            .map(|b| self.with_default_media(b))
+            .collect()
     }
 
     #[payable]
     pub fn set_badge_is_enabled(&mut self, badge_id: String, is_enabled: bool) -> Badge {
+        metered!("set_badge_is_enabled");
         assert_one_yocto();
         self.ownership.assert_owner();
 
@@ -132,31 +790,64 @@ impl StatsGallery {
             .badges
             .get(&badge_id)
             .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        require!(badge.hold.is_none(), "Badge is on hold");
 
-        let new_badge = Badge {
-            is_enabled,
-            ..badge
-        };
+        let new_badge = badge.with_is_enabled(is_enabled, now());
+        self.on_admin_action(
+            "set_badge_is_enabled",
+            format!("badge {} is_enabled {}", badge_id, is_enabled),
+        );
 
-        self.badges.insert(&badge_id, &new_badge);
+        self.save_badge(&new_badge);
 
         new_badge
     }
 
     #[payable]
     pub fn insert_badge(&mut self, badge: Badge) {
+        metered!("insert_badge");
         assert_one_yocto();
         self.ownership.assert_owner();
 
-        self.badges.insert(&badge.id, &badge);
+        self.check_badge_input(&badge);
+        require!(
+            self.sunset.is_none() || self.badges.get(&badge.id).is_some(),
+            "Badge creation is closed for sunset"
+        );
+        require!(
+            !self.pruned_badge_ids.contains(&badge.id),
+            "Badge ID belonged to a pruned badge"
+        );
+        require!(
+            badge.bond == self.badges.get(&badge.id).map_or(0, |b| b.bond),
+            "Badge bond cannot be changed directly"
+        );
+        require!(
+            badge.storage_deposit == self.badges.get(&badge.id).map_or(0, |b| b.storage_deposit),
+            "Badge storage deposit cannot be changed directly"
+        );
+        self.on_admin_action("insert_badge", format!("badge {}", badge.id));
+        self.save_badge(&badge);
     }
 
     #[payable]
     pub fn remove_badge(&mut self, badge_id: &String) {
+        metered!("remove_badge");
         assert_one_yocto();
         self.ownership.assert_owner();
 
-        self.badges.remove(&badge_id);
+        require!(
+            self.badges.get(badge_id).map_or(0, |b| b.bond) == 0,
+            "Badge has an outstanding sponsor bond"
+        );
+        self.on_admin_action("remove_badge", format!("badge {}", badge_id));
+        if let Some(badge) = self.delete_badge(badge_id) {
+            self.refund_storage_deposit(&badge, badge.storage_deposit);
+        }
+    }
+
+    pub fn get_api_version(&self) -> u32 {
+        API_VERSION
     }
 
     pub fn get_badge_rate_per_day(&self) -> U128 {
@@ -165,11 +856,16 @@ impl StatsGallery {
 
     #[payable]
     pub fn set_badge_rate_per_day(&mut self, badge_rate_per_day: U128) {
+        metered!("set_badge_rate_per_day");
         assert_one_yocto();
         self.ownership.assert_owner();
         let badge_rate_per_day = badge_rate_per_day.into();
         require!(badge_rate_per_day > 0, "Badge rate must be greater than 0");
 
+        self.on_admin_action(
+            "set_badge_rate_per_day",
+            format!("{} -> {}", self.badge_rate_per_day, badge_rate_per_day),
+        );
         self.badge_rate_per_day = badge_rate_per_day;
     }
 
@@ -179,6 +875,7 @@ impl StatsGallery {
 
     #[payable]
     pub fn set_badge_max_active_duration(&mut self, badge_max_active_duration: U64) {
+        metered!("set_badge_max_active_duration");
         assert_one_yocto();
         self.ownership.assert_owner();
         let badge_max_active_duration = badge_max_active_duration.into();
@@ -187,6 +884,13 @@ impl StatsGallery {
             "Badge max active duration must be greater than 0"
         );
 
+        self.on_admin_action(
+            "set_badge_max_active_duration",
+            format!(
+                "{} -> {}",
+                self.badge_max_active_duration, badge_max_active_duration
+            ),
+        );
         self.badge_max_active_duration = badge_max_active_duration;
     }
 
@@ -196,153 +900,536 @@ impl StatsGallery {
 
     #[payable]
     pub fn set_badge_min_creation_deposit(&mut self, badge_min_creation_deposit: U128) {
+        metered!("set_badge_min_creation_deposit");
         assert_one_yocto();
         self.ownership.assert_owner();
 
-        self.badge_min_creation_deposit = badge_min_creation_deposit.into();
+        let badge_min_creation_deposit = badge_min_creation_deposit.into();
+        self.on_admin_action(
+            "set_badge_min_creation_deposit",
+            format!(
+                "{} -> {}",
+                self.badge_min_creation_deposit, badge_min_creation_deposit
+            ),
+        );
+        self.badge_min_creation_deposit = badge_min_creation_deposit;
+    }
+
+    pub fn get_available_balance(&self) -> U128 {
+        self.available_balance().into()
     }
 
     #[payable]
     pub fn withdraw_owner(&mut self, amount: U128) -> Promise {
+        metered!("withdraw_owner");
         assert_one_yocto();
         self.ownership.assert_owner();
+        let amount = amount.into();
+        self.assert_available_balance(amount);
+
+        self.on_admin_action("withdraw_owner", amount.to_string());
 
         // .unwrap() is safe because of assert_owner() call
         let owner = self.ownership.owner.as_ref().unwrap().clone();
 
-        Promise::new(owner).transfer(amount.into())
+        transfer_out(owner, amount, "owner_withdrawal", BalanceRef::default())
     }
 
-    fn validate_create_proposal(
-        &self,
-        proposal: &Proposal<BadgeAction>,
-        create_request: &BadgeCreate,
-    ) {
-        // Ensure unique ID
-        require!(
-            self.badges.get(&create_request.id).is_none(),
-            "Badge ID already exists"
-        );
+    /// Contract balance not owed to proposal authors, sponsors or referrers,
+    /// held in escrow, or locked for storage
+    fn available_balance(&self) -> Balance {
+        // Sponsors' storage deposits already pay for part of the storage
+        let storage_reserve = (Balance::from(env::storage_usage())
+            * env::storage_byte_cost().as_yoctonear())
+        .saturating_sub(self.total_storage_deposits_held);
 
-        let now = env::block_timestamp();
+        env::account_balance()
+            .as_yoctonear()
+            .saturating_sub(self.sponsorship.get_total_escrowed())
+            .saturating_sub(self.total_bonds_held)
+            .saturating_sub(self.total_storage_deposits_held)
+            .saturating_sub(self.total_referral_rewards_owed)
+            .saturating_sub(storage_reserve)
+    }
 
-        // Validate start_at
+    /// Must be called before any transfer of funds out of the contract
+    fn assert_available_balance(&self, amount: Balance) {
         require!(
-            create_request.start_at.unwrap_or(now) + create_request.duration > now,
-            "Badge active period has already ended",
+            amount <= self.available_balance(),
+            "Amount exceeds available balance"
         );
+    }
 
-        // Validate duration
-        require!(
-            create_request.duration <= self.badge_max_active_duration,
-            "Exceeded maximum active duration",
-        );
+    pub fn get_admin_actions(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AdminAction> {
+        self.audit_log.get(
+            from_index.map(u64::from).unwrap_or(0),
+            limit.map(u64::from).unwrap_or(u64::MAX),
+        )
+    }
 
-        // Validate deposit
+    /// Offers to accept an extend proposal for fewer days than requested. The
+    /// unused portion of the deposit would be refunded pro rata by billable
+    /// days, but never below the price of the approved duration. The proposal
+    /// stays pending until its author agrees with `spo_confirm_terms`.
+    #[payable]
+    pub fn spo_accept_partial(&mut self, id: U64, approved_duration: U64) -> Proposal<BadgeAction> {
+        metered!("spo_accept_partial");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let proposal = self
+            .sponsorship
+            .get_proposal(id.into())
+            .unwrap_or_else(|| env::panic_str("Proposal does not exist"));
         require!(
-            proposal.deposit >= self.badge_min_creation_deposit,
-            "Deposit does not meet minimum creation deposit requirement",
+            proposal.tag == TAG_BADGE_EXTEND,
+            "Only extend proposals can be partially accepted"
         );
+        let extend_request = extract_msg!(proposal, BadgeAction, Extend);
+        let approved_duration = u64::from(approved_duration);
         require!(
-            proposal.deposit
-                >= u128::from(billable_days_in_duration(create_request.duration))
-                    * self.badge_rate_per_day,
-            "Insufficient deposit for specified duration",
+            approved_duration > 0 && approved_duration < extend_request.duration,
+            "Approved duration must be less than requested duration"
         );
+
+        let requested_days = u128::from(billable_days_in_duration(extend_request.duration));
+        let approved_days = u128::from(billable_days_in_duration(approved_duration));
+        let price = self.terms_for(&proposal).extension_price(approved_duration);
+        let unused = proposal.deposit * (requested_days - approved_days) / requested_days;
+        let refund = u128::min(unused, proposal.deposit.saturating_sub(price));
+
+        let proposal = self.sponsorship.hand_off_review(proposal.id);
+        self.offer_terms(&proposal, approved_duration, refund);
+        self.on_admin_action(
+            "spo_accept_partial",
+            format!("proposal {} for {}", proposal.id, approved_duration),
+        );
+        proposal
+    }
+
+    fn on_admin_action(&mut self, method: &str, summary: String) {
+        self.audit_log.record(method, summary);
+    }
+
+    /// All badge writes go through here to keep the badge indexes in sync
+    fn save_badge(&mut self, badge: &Badge) {
+        self.process_lazy_activations();
+
+        let previous = self.badges.insert(badge);
+        let previous_group_id = previous
+            .as_ref()
+            .filter(|previous| self.is_tracked_active(previous))
+            .map(|previous| previous.group_id.clone());
+        if let Some(previous) = &previous {
+            self.badge_indexes.remove(previous);
+        }
+
+        self.badge_indexes.insert(badge);
+        self.requeue_activation(previous.as_ref(), badge);
+        self.requeue_expiration(previous.as_ref(), badge);
+        let group_id = Some(badge.group_id.as_str()).filter(|_| self.is_tracked_active(badge));
+        self.move_active_badge(previous_group_id.as_deref(), group_id);
+    }
+
+    fn delete_badge(&mut self, badge_id: &String) -> Option<Badge> {
+        let badge = self.badges.remove(badge_id)?;
+
+        if self.is_tracked_active(&badge) {
+            self.move_active_badge(Some(&badge.group_id), None);
+        }
+        self.badge_indexes.remove(&badge);
+        self.dequeue_activation(&badge);
+        self.dequeue_expiration(&badge);
+        self.badge_views.remove(badge_id);
+        self.badge_streams.remove(badge_id);
+        self.unfeature_badge(badge_id);
+
+        Some(badge)
+    }
+
+    fn validate_create_proposal(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        create_request: &BadgeCreate,
+        terms: &BadgeTerms,
+    ) -> Result<(), ProposalValidationError> {
+        self.check_sunset_allows_create()?;
+
+        self.check_badge_creatable(proposal, create_request, terms)?;
+
+        // Validate deposit
+        if proposal.retained_deposit() < terms.creation_bond.0 {
+            return Err(ProposalValidationError::DepositBelowBond {
+                deposit: proposal.retained_deposit().into(),
+                bond: terms.creation_bond,
+            });
+        }
+        let held = terms.creation_held(create_request);
+        if proposal.retained_deposit() < held {
+            return Err(ProposalValidationError::DepositBelowStorageDeposit {
+                deposit: proposal.retained_deposit().into(),
+                bond: terms.creation_bond,
+                storage_deposit: terms.metadata_storage(create_request).into(),
+            });
+        }
+        // A stream pays for the badge instead, so only the bond and storage
+        // deposit are held
+        if create_request.stream_id.is_some() {
+            return self.check_streaming_configured();
+        }
+        if terms.is_free_tier {
+            return Ok(());
+        }
+        let deposit = proposal.retained_deposit() - held;
+        let min_creation_deposit = terms.discounted(terms.min_creation_deposit.0);
+        if deposit < min_creation_deposit {
+            return Err(ProposalValidationError::DepositBelowMinimum {
+                deposit: deposit.into(),
+                min_creation_deposit: min_creation_deposit.into(),
+            });
+        }
+        let required = terms.extension_price(create_request.duration);
+        if deposit < required {
+            return Err(ProposalValidationError::InsufficientDeposit {
+                deposit: deposit.into(),
+                required: required.into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The checks on a badge creation that also apply to each badge of a
+    /// package: all but sunset and deposit
+    fn check_badge_creatable(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        create_request: &BadgeCreate,
+        terms: &BadgeTerms,
+    ) -> Result<(), ProposalValidationError> {
+        // Ensure unique ID, including badges accepted but not yet created and
+        // badges since pruned
+        if self.badges.get(&create_request.id).is_some()
+            || self.is_badge_creation_queued(&create_request.id)
+            || self.pruned_badge_ids.contains(&create_request.id)
+        {
+            return Err(ProposalValidationError::BadgeAlreadyExists {
+                badge_id: create_request.id.clone(),
+            });
+        }
+
+        // Verified groups only accept badges from their own sponsors
+        if let Some(verification) = self
+            .group_verifications
+            .get(&create_request.group_id)
+            .cloned()
+        {
+            if !verification.may_sponsor(&proposal.author_id) {
+                return Err(ProposalValidationError::GroupNotAuthorized {
+                    group_id: create_request.group_id.clone(),
+                    group_account_id: verification.account_id,
+                });
+            }
+        }
+
+        let now = now();
+
+        // Validate start_at
+        let ends_at = create_request.start_at.unwrap_or(now) + create_request.duration;
+        if ends_at <= now {
+            return Err(ProposalValidationError::ActivePeriodEnded {
+                ends_at: ends_at.into(),
+                now: now.into(),
+            });
+        }
+
+        // Validate duration
+        if create_request.duration > terms.max_active_duration.0 {
+            return Err(ProposalValidationError::ExceedsMaxActiveDuration {
+                active_duration: create_request.duration.into(),
+                max_active_duration: terms.max_active_duration,
+            });
+        }
+
+        Ok(())
     }
 
     fn validate_extend_proposal(
         &self,
         proposal: &Proposal<BadgeAction>,
         extend_request: &BadgeExtend,
-    ) -> Badge {
-        let existing_badge = self
-            .badges
-            .get(&extend_request.id)
-            .unwrap_or_else(|| env::panic_str("Badge ID does not exist"));
+        terms: &BadgeTerms,
+    ) -> Result<Badge, ProposalValidationError> {
+        self.check_sunset_allows_extend()?;
 
-        require!(
-            existing_badge.duration.is_some(),
-            "Cannot extend: Existing badge has no duration (indefinite)"
-        );
+        let existing_badge = self.badges.get(&extend_request.id).ok_or_else(|| {
+            ProposalValidationError::BadgeDoesNotExist {
+                badge_id: extend_request.id.clone(),
+            }
+        })?;
+
+        let existing_duration =
+            existing_badge
+                .duration
+                .ok_or_else(|| ProposalValidationError::BadgeHasNoDuration {
+                    badge_id: extend_request.id.clone(),
+                })?;
 
-        let now = env::block_timestamp();
+        let now = now();
 
         // Validate duration
-        require!(
-            u64::saturating_sub(
-                existing_badge.start_at
-                    + existing_badge.duration.unwrap()
-                    + extend_request.duration,
-                now
-            ) <= self.badge_max_active_duration,
-            "Exceeded maximum active duration",
+        let active_duration = u64::saturating_sub(
+            existing_badge.start_at + existing_duration + extend_request.duration,
+            now,
         );
+        if active_duration > terms.max_active_duration.0 {
+            return Err(ProposalValidationError::ExceedsMaxActiveDuration {
+                active_duration: active_duration.into(),
+                max_active_duration: terms.max_active_duration,
+            });
+        }
 
         // Validate deposit
-        require!(
-            proposal.deposit
-                >= u128::from(billable_days_in_duration(extend_request.duration))
-                    * self.badge_rate_per_day,
-            "Insufficient deposit for specified duration",
-        );
+        if extend_request.stream_id.is_some() {
+            self.check_streaming_configured()?;
+            return Ok(existing_badge);
+        }
+        let required = terms.extension_price(extend_request.duration);
+        if proposal.retained_deposit() < required {
+            return Err(ProposalValidationError::InsufficientDeposit {
+                deposit: proposal.retained_deposit().into(),
+                required: required.into(),
+            });
+        }
+
+        Ok(existing_badge)
+    }
+
+    /// Returns whatever an accepted proposal's deposit holds beyond `price`,
+    /// e.g. if the author over-paid or the rate was lowered before acceptance
+    fn refund_surplus(&mut self, proposal: &Proposal<BadgeAction>, price: Balance) {
+        let surplus = proposal.retained_deposit().saturating_sub(price);
+        if surplus > 0 {
+            self.sponsorship.refund_surplus(proposal.id, surplus);
+        }
+    }
+}
+
+impl_ownership!(StatsGallery, ownership, on_admin_action);
+impl MessageValidator<BadgeAction> for StatsGallery {
+    fn validate_msg(tag: &str, msg: Option<&BadgeAction>) -> Result<(), String> {
+        match TAG_BINDINGS.iter().find(|(t, _)| *t == tag) {
+            Some((_, Some((variant, matches)))) => match msg {
+                Some(msg) if matches(msg) => Ok(()),
+                Some(_) => Err(format!("tag mismatch: {} expects {}", tag, variant)),
+                None => Err(format!("msg value required: {} expects {}", tag, variant)),
+            },
+            Some((_, None)) => match msg {
+                Some(_) => Err(format!("tag mismatch: {} expects no msg", tag)),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
 
-        existing_badge
+    fn handles_tag(tag: &str) -> bool {
+        TAG_BINDINGS.iter().any(|(t, _)| *t == tag)
     }
 
-    fn on_proposal_change(&mut self, proposal: &Proposal<BadgeAction>) {
+    fn check_acceptance(&self, proposal: &Proposal<BadgeAction>) -> Result<(), String> {
+        ProposalHandler::validate(self, proposal)
+    }
+}
+
+impl ProposalHandler<BadgeAction> for StatsGallery {
+    fn validate(&self, proposal: &Proposal<BadgeAction>) -> Result<(), String> {
         match (&proposal.status, proposal.tag.as_str()) {
             (ProposalStatus::PENDING, TAG_BADGE_CREATE) => {
+                self.check_proposal_input(proposal);
                 let create_request = extract_msg!(proposal, BadgeAction, Create);
-                self.validate_create_proposal(proposal, create_request);
+                let terms = BadgeTerms {
+                    discount: self.check_promo_code(proposal)?,
+                    is_free_tier: self.free_tier_accounts.contains(&proposal.author_id),
+                    ..self.current_terms()
+                };
+                self.validate_create_proposal(proposal, create_request, &terms)
+                    .map_err(|e| e.message().to_string())?;
             }
             (ProposalStatus::PENDING, TAG_BADGE_EXTEND) => {
+                self.check_proposal_input(proposal);
                 let extend_request = extract_msg!(proposal, BadgeAction, Extend);
-                self.validate_extend_proposal(proposal, extend_request);
+                let terms = BadgeTerms {
+                    discount: self.check_promo_code(proposal)?,
+                    ..self.current_terms()
+                };
+                self.validate_extend_proposal(proposal, extend_request, &terms)
+                    .map_err(|e| e.message().to_string())?;
             }
             (ProposalStatus::ACCEPTED, TAG_BADGE_CREATE) => {
                 let create_request = extract_msg!(proposal, BadgeAction, Create);
+                self.validate_create_proposal(proposal, create_request, &self.terms_for(proposal))
+                    .and_then(|_| self.check_sponsor_badge_limit(proposal, 1))
+                    .and_then(|_| self.check_lead_time(proposal, create_request))
+                    .map_err(|e| e.message().to_string())?;
+            }
+            (ProposalStatus::ACCEPTED, TAG_BADGE_EXTEND) => {
+                let extend_request = extract_msg!(proposal, BadgeAction, Extend);
+                self.validate_extend_proposal(proposal, extend_request, &self.terms_for(proposal))
+                    .map_err(|e| e.message().to_string())?;
+            }
+            (ProposalStatus::PENDING, TAG_BADGE_PACKAGE) => {
+                self.check_proposal_input(proposal);
+                if proposal.promo_code.is_some() {
+                    return Err("Promo codes do not apply to packages".to_string());
+                }
+                let package_request = extract_msg!(proposal, BadgeAction, Package);
+                if package_request.badges.iter().any(|b| b.stream_id.is_some()) {
+                    return Err("Packages cannot be paid by stream".to_string());
+                }
+                self.package_for(proposal, package_request)
+                    .and_then(|package| {
+                        self.validate_package_proposal(
+                            proposal,
+                            package_request,
+                            &package,
+                            &self.current_terms(),
+                        )
+                    })
+                    .map_err(|e| e.message().to_string())?;
+            }
+            (ProposalStatus::ACCEPTED, TAG_BADGE_PACKAGE) => {
+                let package_request = extract_msg!(proposal, BadgeAction, Package);
+                self.check_package_acceptance(proposal, package_request)
+                    .map_err(|e| e.message().to_string())?;
+            }
+            (ProposalStatus::PENDING, TAG_GENERAL_SPONSORSHIP) => {
+                self.check_proposal_input(proposal);
+                if self.sunset.is_some() {
+                    return Err("Sponsorships are closed for sunset".to_string());
+                }
+                if proposal.promo_code.is_some() {
+                    return Err("Promo codes only apply to badge proposals".to_string());
+                }
+            }
+            (ProposalStatus::ACCEPTED, TAG_GENERAL_SPONSORSHIP) => {}
+            // Tags without a handler cannot be added, but never let one
+            // take a deposit for nothing
+            (ProposalStatus::ACCEPTED, tag) => {
+                return Err(format!("Tag has no handler: {}", tag));
+            }
+            (ProposalStatus::UNWOUND, TAG_BADGE_CREATE | TAG_BADGE_EXTEND) => {}
+            (ProposalStatus::UNWOUND, TAG_BADGE_PACKAGE) => {
+                return Err("Package proposals cannot be unwound".to_string());
+            }
+            (ProposalStatus::UNWOUND, _) => {
+                return Err("Only badge proposals can be unwound".to_string());
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, proposal: &Proposal<BadgeAction>) {
+        self.record_proposal_update(proposal);
+        if proposal.status != ProposalStatus::PENDING {
+            self.terms_offers.remove(&proposal.id);
+        }
 
-                self.validate_create_proposal(proposal, create_request);
-
-                let now = env::block_timestamp();
-
-                self.badges.insert(
-                    &create_request.id.clone(),
-                    &Badge {
-                        id: create_request.id.clone(),
-                        group_id: create_request.group_id.clone(),
-                        name: create_request.name.clone(),
-                        description: create_request.description.clone(),
-                        created_at: now,
-                        start_at: create_request.start_at.unwrap_or(now),
-                        duration: Some(create_request.duration),
-                        is_enabled: true,
-                    },
-                );
+        match (&proposal.status, proposal.tag.as_str()) {
+            (ProposalStatus::PENDING, TAG_BADGE_CREATE) => {
+                let terms = BadgeTerms {
+                    discount: self.redeem_promo_code(proposal),
+                    is_free_tier: self.free_tier_accounts.contains(&proposal.author_id),
+                    ..self.current_terms()
+                };
+                self.proposal_terms.insert(proposal.id, terms);
+            }
+            (ProposalStatus::PENDING, TAG_BADGE_EXTEND) => {
+                let terms = BadgeTerms {
+                    discount: self.redeem_promo_code(proposal),
+                    ..self.current_terms()
+                };
+                self.proposal_terms.insert(proposal.id, terms);
+            }
+            (ProposalStatus::PENDING, TAG_BADGE_PACKAGE) => {
+                let package_request = extract_msg!(proposal, BadgeAction, Package);
+                let terms = self.current_terms();
+                self.proposal_terms.insert(proposal.id, terms);
+                // .unwrap() is safe because validation found the package
+                let package = self
+                    .packages
+                    .get(&package_request.package_id)
+                    .cloned()
+                    .unwrap();
+                self.proposal_packages.insert(proposal.id, package);
+            }
+            (ProposalStatus::ACCEPTED, TAG_BADGE_CREATE) => {
+                let create_request = extract_msg!(proposal, BadgeAction, Create);
+                let terms = self.terms_for(proposal);
+                let held = terms.creation_held(create_request);
+                let revenue = if create_request.stream_id.is_some() {
+                    self.refund_surplus(proposal, held);
+                    0
+                } else {
+                    let price = terms.creation_price(create_request);
+                    self.refund_surplus(proposal, price);
+                    self.credit_referral(proposal, price - held);
+                    price - held
+                };
+                self.add_group_sale(&create_request.group_id, create_request.duration, revenue);
+                self.vest_at_start(proposal, create_request);
+                self.queue_creation(proposal);
             }
             (ProposalStatus::ACCEPTED, TAG_BADGE_EXTEND) => {
                 let extend_request = extract_msg!(proposal, BadgeAction, Extend);
-                let existing_badge = self.validate_extend_proposal(proposal, extend_request);
-
-                self.badges.insert(
-                    &existing_badge.id.clone(),
-                    &Badge {
-                        duration: Some(existing_badge.duration.unwrap() + extend_request.duration),
-                        ..existing_badge
-                    },
-                );
+                let revenue = if extend_request.stream_id.is_some() {
+                    self.refund_surplus(proposal, 0);
+                    0
+                } else {
+                    let price = self
+                        .terms_for(proposal)
+                        .extension_price(extend_request.duration);
+                    self.refund_surplus(proposal, price);
+                    self.credit_referral(proposal, price);
+                    price
+                };
+                if let Some(badge) = self.badges.get(&extend_request.id) {
+                    self.add_group_sale(&badge.group_id, extend_request.duration, revenue);
+                }
+                self.queue_execution(proposal);
+            }
+            (ProposalStatus::ACCEPTED, TAG_BADGE_PACKAGE) => {
+                self.accept_package(proposal);
+            }
+            (ProposalStatus::ACCEPTED, TAG_GENERAL_SPONSORSHIP) => {
+                self.credit_referral(proposal, proposal.retained_deposit());
+                self.queue_execution(proposal);
+            }
+            (ProposalStatus::RESCINDED, _) => {
+                self.proposal_terms.remove(&proposal.id);
+                self.proposal_packages.remove(&proposal.id);
             }
             _ => {}
         }
     }
+
+    fn rollback(&mut self, proposal: &Proposal<BadgeAction>) {
+        self.revert_unwound(proposal);
+    }
 }
 
-impl_ownership!(StatsGallery, ownership);
 impl_sponsorship!(
     StatsGallery,
     sponsorship,
     BadgeAction,
     ownership,
-    on_proposal_change
+    on_proposal_change,
+    on_admin_action,
+    on_proposals_swept,
+    on_deposit_settled
 );
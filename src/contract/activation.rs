@@ -0,0 +1,93 @@
+use super::*;
+
+const DEFAULT_PROCESS_ACTIVATIONS: u64 = 10;
+const MAX_PROCESS_ACTIVATIONS: u64 = 50;
+/// Due activations processed alongside every badge write, so the queue
+/// drains even if nobody calls `process_activations`
+const LAZY_PROCESS_ACTIVATIONS: u64 = 3;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeActivatedEvent<'a> {
+    badge_id: &'a str,
+    start_at: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Emits `badge_activated` for badges whose active window has begun, in
+    /// `start_at` order. Anyone may call this, and is paid the keeper reward
+    /// for each event emitted. Returns how many activations were processed.
+    pub fn process_activations(&mut self, limit: Option<U64>) -> U64 {
+        metered!("process_activations");
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_PROCESS_ACTIVATIONS)
+            .min(MAX_PROCESS_ACTIVATIONS);
+
+        let activated = self.process_due_activations(limit);
+        self.pay_keeper(KeeperTask::BADGE_ACTIVATIONS, activated);
+
+        activated.into()
+    }
+
+    /// Number of queued activations whose window has already begun
+    pub fn get_due_activations_count(&self) -> U64 {
+        let now = now();
+
+        (self
+            .activation_queue
+            .iter()
+            .take_while(|((start_at, _), _)| *start_at <= now)
+            .count() as u64)
+            .into()
+    }
+}
+
+impl StatsGallery {
+    /// Moves a badge's queue entry when it is saved. A badge is queued while
+    /// it starts in the future, and stays queued if it already was, so that
+    /// saving it before its activation is processed does not drop the event.
+    pub(crate) fn requeue_activation(&mut self, previous: Option<&Badge>, badge: &Badge) {
+        let was_queued = previous.is_some_and(|p| self.dequeue_activation(p));
+
+        if was_queued || badge.start_at > now() {
+            self.activation_queue
+                .insert((badge.start_at, badge.id.clone()), ());
+        }
+    }
+
+    pub(crate) fn dequeue_activation(&mut self, badge: &Badge) -> bool {
+        self.activation_queue
+            .remove(&(badge.start_at, badge.id.clone()))
+            .is_some()
+    }
+
+    pub(crate) fn process_due_activations(&mut self, limit: u64) -> u64 {
+        let now = now();
+        let due: Vec<(u64, String)> = self
+            .activation_queue
+            .iter()
+            .map(|(key, _)| key.clone())
+            .take_while(|(start_at, _)| *start_at <= now)
+            .take(limit as usize)
+            .collect();
+
+        for (start_at, badge_id) in due.iter() {
+            self.activation_queue.remove(&(*start_at, badge_id.clone()));
+            emit_event(
+                "badge_activated",
+                [BadgeActivatedEvent {
+                    badge_id,
+                    start_at: (*start_at).into(),
+                }],
+            );
+        }
+
+        due.len() as u64
+    }
+
+    pub(crate) fn process_lazy_activations(&mut self) {
+        self.process_due_activations(LAZY_PROCESS_ACTIVATIONS);
+    }
+}
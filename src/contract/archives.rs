@@ -0,0 +1,89 @@
+use super::*;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeArchivedEvent<'a> {
+    badge_id: &'a str,
+    archived_by: &'a AccountId,
+    storage_deposit: U128,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Storage deposits paid by sponsors for badges not yet archived
+    pub fn get_total_storage_deposits_held(&self) -> U128 {
+        self.total_storage_deposits_held.into()
+    }
+
+    /// Clears the description and media of an expired badge and returns its
+    /// storage deposit to the sponsor. The badge itself is kept, so its awards
+    /// remain valid. Either the sponsor or the owner may archive it.
+    #[payable]
+    pub fn archive_badge(&mut self, badge_id: String) -> Badge {
+        metered!("archive_badge");
+        assert_one_yocto();
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        let caller = env::predecessor_account_id();
+        let is_sponsor = badge.sponsor_id.as_ref() == Some(&caller);
+        require!(
+            is_sponsor || self.ownership.is_owner(&caller),
+            "Badge can only be archived by its sponsor or the owner"
+        );
+        require!(
+            badge.is_expired(now()),
+            "Badge cannot be archived until it expires"
+        );
+        require!(
+            !badge.description.is_empty() || badge.media_url.is_some(),
+            "Badge is already archived"
+        );
+
+        if !is_sponsor {
+            self.on_admin_action("archive_badge", badge_id.clone());
+        }
+        let storage_deposit = badge.storage_deposit;
+        let badge = Badge {
+            description: String::new(),
+            media_url: None,
+            storage_deposit: 0,
+            ..badge
+        };
+        self.save_badge(&badge);
+
+        emit_event(
+            "badge_archived",
+            [BadgeArchivedEvent {
+                badge_id: &badge_id,
+                archived_by: &caller,
+                storage_deposit: storage_deposit.into(),
+            }],
+        );
+        self.refund_storage_deposit(&badge, storage_deposit);
+
+        badge
+    }
+}
+
+impl StatsGallery {
+    /// Returns a storage deposit taken off `badge` to its sponsor
+    pub(crate) fn refund_storage_deposit(&mut self, badge: &Badge, storage_deposit: Balance) {
+        if storage_deposit == 0 {
+            return;
+        }
+
+        self.total_storage_deposits_held -= storage_deposit;
+        // .unwrap() is safe because storage deposits are only taken from
+        // sponsored badges
+        let sponsor_id = badge.sponsor_id.clone().unwrap();
+        transfer_out(
+            sponsor_id,
+            storage_deposit,
+            "storage_deposit_refund",
+            BalanceRef::badge(&badge.id),
+        );
+    }
+}
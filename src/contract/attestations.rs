@@ -0,0 +1,103 @@
+use super::*;
+
+/// An oracle's statement that an account has met a criterion for a badge,
+/// e.g. one derived from stats.gallery's indexer. The account can then claim
+/// the badge itself with `claim_badge_with_attestation`.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Attestation {
+    pub oracle_id: AccountId,
+    pub badge_id: String,
+    pub account_id: AccountId,
+    pub criterion: String,
+    #[serde(with = "dec_string")]
+    pub attested_at: u64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_oracles(&self) -> Vec<AccountId> {
+        self.oracles.iter().cloned().collect()
+    }
+
+    #[payable]
+    pub fn add_oracle(&mut self, account_id: AccountId) {
+        metered!("add_oracle");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action("add_oracle", account_id.to_string());
+        self.oracles.insert(account_id);
+    }
+
+    /// Attestations the oracle has posted but which have not been claimed yet
+    /// can no longer be claimed.
+    #[payable]
+    pub fn remove_oracle(&mut self, account_id: AccountId) {
+        metered!("remove_oracle");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(self.oracles.remove(&account_id), "Not an oracle");
+        self.on_admin_action("remove_oracle", account_id.to_string());
+    }
+
+    pub fn get_attestation(&self, badge_id: String, account_id: AccountId) -> Option<Attestation> {
+        self.attestations.get(&(badge_id, account_id)).cloned()
+    }
+
+    /// Called by an oracle to attest that `account_id` satisfied `criterion`
+    /// of the badge. Replaces any unclaimed attestation for the same account
+    /// and badge.
+    pub fn post_attestation(
+        &mut self,
+        badge_id: String,
+        account_id: AccountId,
+        criterion: String,
+    ) -> Attestation {
+        metered!("post_attestation");
+        let oracle_id = env::predecessor_account_id();
+        require!(self.oracles.contains(&oracle_id), "Oracle only");
+
+        self.input_limits.check_reason(&criterion);
+        require!(self.badges.get(&badge_id).is_some(), "Badge does not exist");
+        require!(
+            self.awards
+                .get(&(badge_id.clone(), account_id.clone()))
+                .cloned()
+                .is_none(),
+            "Badge already awarded to account"
+        );
+
+        let attestation = Attestation {
+            oracle_id,
+            badge_id: badge_id.clone(),
+            account_id: account_id.clone(),
+            criterion,
+            attested_at: now(),
+        };
+        self.attestations
+            .insert((badge_id, account_id), attestation.clone());
+        emit_event("attestation_posted", [&attestation]);
+
+        attestation
+    }
+
+    /// Awards a badge to the caller on the strength of an attestation from an
+    /// oracle that is still on the allowlist. The attestation is used up.
+    pub fn claim_badge_with_attestation(&mut self, badge_id: String) -> Award {
+        metered!("claim_badge_with_attestation");
+        let account_id = env::predecessor_account_id();
+        let attestation = self
+            .attestations
+            .remove(&(badge_id.clone(), account_id.clone()))
+            .unwrap_or_else(|| env::panic_str("Attestation does not exist"));
+        require!(
+            self.oracles.contains(&attestation.oracle_id),
+            "Attesting oracle is no longer approved"
+        );
+
+        self.award_badge(badge_id, account_id)
+    }
+}
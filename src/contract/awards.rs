@@ -0,0 +1,344 @@
+use super::*;
+
+/// Gas allowance granted to each claim link access key
+const CLAIM_KEY_ALLOWANCE: NearToken = NearToken::from_millinear(100);
+const CLAIM_KEY_METHOD_NAMES: &str = "claim_badge_with_key";
+
+const NEP171_STANDARD: &str = "nep171";
+const NEP171_STANDARD_VERSION: &str = "1.0.0";
+
+const DEFAULT_GET_ACCOUNT_BADGES: u64 = 10;
+const MAX_GET_ACCOUNT_BADGES: u64 = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Award {
+    pub badge_id: String,
+    pub account_id: AccountId,
+    #[serde(with = "dec_string")]
+    pub awarded_at: u64,
+    /// When the award stops counting, if the badge's awards have a validity
+    /// period. Expired awards are kept for history.
+    #[serde(default, with = "option_dec_string")]
+    pub expires_at: Option<u64>,
+}
+
+impl Award {
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at < now,
+            _ => false,
+        }
+    }
+
+    /// How the award is named in NEP-171 events. Badge IDs cannot contain
+    /// `:`, so this is unambiguous.
+    pub fn token_id(&self) -> String {
+        format!("{}:{}", self.badge_id, self.account_id)
+    }
+}
+
+/// Data of a NEP-171 `nft_mint` or `nft_burn` event
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct NftEventData<'a> {
+    owner_id: &'a AccountId,
+    token_ids: [String; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authorized_id: Option<&'a AccountId>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_award(&self, badge_id: String, account_id: AccountId) -> Option<Award> {
+        self.awards.get(&(badge_id, account_id)).cloned()
+    }
+
+    /// Awards held by an account, oldest first until one is revoked, which
+    /// moves the latest into its place. Expired awards on the page
+    /// are left out unless `include_expired` is set, so a page may come back
+    /// short.
+    pub fn get_account_badges(
+        &self,
+        account_id: AccountId,
+        include_expired: Option<bool>,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<Award> {
+        let now = now();
+        let include_expired = include_expired.unwrap_or(false);
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_ACCOUNT_BADGES)
+            .min(MAX_GET_ACCOUNT_BADGES);
+
+        self.account_badge_ids(&account_id, from_index.map(u64::from).unwrap_or(0), limit)
+            .into_iter()
+            .filter_map(|badge_id| self.awards.get(&(badge_id, account_id.clone())).cloned())
+            .filter(|award| include_expired || !award.is_expired(now))
+            .collect()
+    }
+
+    /// Takes back an award, e.g. one claimed by mistake or by abuse. The
+    /// account may be awarded the badge again later.
+    #[payable]
+    pub fn revoke_award(&mut self, badge_id: String, account_id: AccountId) -> Award {
+        metered!("revoke_award");
+        assert_one_yocto();
+        self.ownership.assert_owner_or_role(Role::MODERATOR);
+
+        let award = self
+            .awards
+            .remove(&(badge_id.clone(), account_id.clone()))
+            .unwrap_or_else(|| env::panic_str("Award does not exist"));
+        self.account_award_set(&account_id).remove(&badge_id);
+
+        self.on_admin_action(
+            "revoke_award",
+            format!("badge {} account {}", badge_id, account_id),
+        );
+        emit_standard_event(
+            NEP171_STANDARD,
+            NEP171_STANDARD_VERSION,
+            "nft_burn",
+            [NftEventData {
+                owner_id: &account_id,
+                token_ids: [award.token_id()],
+                authorized_id: Some(&env::predecessor_account_id()),
+            }],
+        );
+
+        award
+    }
+
+    /// Number of awards the account holds, expired ones included
+    pub fn get_account_badge_count(&self, account_id: AccountId) -> U64 {
+        if let Some(badge_ids) = self.account_award_sets.get(&account_id) {
+            return u64::from(badge_ids.len()).into();
+        }
+        if let Some(badge_ids) = self.account_award_sets_v2.get(&account_id) {
+            return badge_ids.len().into();
+        }
+        self.account_awards
+            .get(&account_id)
+            .map_or(0, |badge_ids| badge_ids.len() as u64)
+            .into()
+    }
+
+    pub fn get_award_validity(&self, badge_id: String) -> Option<U64> {
+        self.award_validity
+            .get(&badge_id)
+            .cloned()
+            .map(|x| x.into())
+    }
+
+    /// Sets how long awards of a badge remain valid from when they are
+    /// granted, e.g. one year for a season badge. Only affects later awards.
+    /// May be called by the owner or the badge's sponsor.
+    #[payable]
+    pub fn set_award_validity(&mut self, badge_id: String, validity: Option<U64>) {
+        metered!("set_award_validity");
+        assert_one_yocto();
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        if badge.sponsor_id != Some(env::predecessor_account_id()) {
+            self.ownership.assert_owner();
+            self.on_admin_action(
+                "set_award_validity",
+                format!("badge {} validity {:?}", badge_id, validity.map(u64::from)),
+            );
+        }
+
+        match validity {
+            Some(validity) => {
+                require!(validity.0 > 0, "Validity must be greater than 0");
+                self.award_validity.insert(badge_id, validity.0);
+            }
+            None => {
+                self.award_validity.remove(&badge_id);
+            }
+        }
+    }
+
+    /// Adds a function call access key to the contract account that can only
+    /// be used to claim the given badge once, e.g. from a QR code link.
+    #[payable]
+    pub fn create_claim_link(&mut self, badge_id: String, public_key: PublicKey) -> Promise {
+        metered!("create_claim_link");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        self.input_limits.check_id("Badge ID", &badge_id);
+        require!(self.badges.get(&badge_id).is_some(), "Badge does not exist");
+        require!(
+            self.claim_keys
+                .insert(public_key.clone(), badge_id.clone())
+                .is_none(),
+            "Claim key already exists"
+        );
+        self.on_admin_action("create_claim_link", format!("badge {}", badge_id));
+
+        let current_account_id = env::current_account_id();
+
+        Promise::new(current_account_id.clone()).add_access_key_allowance(
+            public_key,
+            Allowance::limited(CLAIM_KEY_ALLOWANCE).unwrap(),
+            current_account_id,
+            CLAIM_KEY_METHOD_NAMES.to_string(),
+        )
+    }
+
+    #[payable]
+    pub fn revoke_claim_link(&mut self, public_key: PublicKey) -> Promise {
+        metered!("revoke_claim_link");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        require!(
+            self.claim_keys.remove(&public_key).is_some(),
+            "Claim key does not exist"
+        );
+        self.on_admin_action("revoke_claim_link", String::from(&public_key));
+
+        Promise::new(env::current_account_id()).delete_key(public_key)
+    }
+
+    /// Called by the holder of a claim link using the contract's own
+    /// restricted access key. The key is deleted after a successful claim.
+    pub fn claim_badge_with_key(&mut self, account_id: AccountId) -> Award {
+        metered!("claim_badge_with_key");
+        let current_account_id = env::current_account_id();
+        require!(
+            env::predecessor_account_id() == current_account_id,
+            "Claim links only"
+        );
+
+        let public_key = env::signer_account_pk();
+        let badge_id = self
+            .claim_keys
+            .remove(&public_key)
+            .unwrap_or_else(|| env::panic_str("Claim key does not exist"));
+
+        let award = self.award_badge(badge_id, account_id);
+
+        Promise::new(current_account_id).delete_key(public_key);
+
+        award
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn award_badge(&mut self, badge_id: String, account_id: AccountId) -> Award {
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        let now = now();
+        require!(
+            badge.is_enabled && badge.hold.is_none() && !badge.is_expired(now),
+            "Badge is not active"
+        );
+        require!(
+            self.badge_streams
+                .get(&badge_id)
+                .cloned()
+                .is_none_or(|stream| stream.is_healthy == Some(true)),
+            "Badge payment stream has not been verified"
+        );
+
+        let award = Award {
+            badge_id: badge_id.clone(),
+            account_id: account_id.clone(),
+            awarded_at: now,
+            expires_at: self
+                .award_validity
+                .get(&badge_id)
+                .cloned()
+                .map(|validity| now.saturating_add(validity)),
+        };
+
+        require!(
+            self.awards
+                .insert((badge_id.clone(), account_id.clone()), award.clone())
+                .is_none(),
+            "Badge already awarded to account"
+        );
+
+        self.account_award_set(&account_id).insert(badge_id);
+
+        emit_standard_event(
+            NEP171_STANDARD,
+            NEP171_STANDARD_VERSION,
+            "nft_mint",
+            [NftEventData {
+                owner_id: &account_id,
+                token_ids: [award.token_id()],
+                authorized_id: None,
+            }],
+        );
+
+        award
+    }
+
+    /// IDs of the badges awarded to the account, in the same order as
+    /// `get_account_badges`. Only reads the requested page.
+    pub(crate) fn account_badge_ids(
+        &self,
+        account_id: &AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<String> {
+        if let Some(badge_ids) = self.account_award_sets.get(account_id) {
+            return badge_ids
+                .iter()
+                .skip(from_index as usize)
+                .take(limit as usize)
+                .cloned()
+                .collect();
+        }
+        if let Some(badge_ids) = self.account_award_sets_v2.get(account_id) {
+            let badge_ids = badge_ids.as_vector();
+            let to_index = from_index.saturating_add(limit).min(badge_ids.len());
+            return (from_index..to_index)
+                .filter_map(|index| badge_ids.get(index))
+                .collect();
+        }
+        self.account_awards
+            .get(account_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// The account's award set, moving over its set or list from before
+    /// state version 3 if there is one
+    fn account_award_set(&mut self, account_id: &AccountId) -> &mut IterableSet<String> {
+        if !self.account_award_sets.contains_key(account_id) {
+            // Sets of state version 2 keep their elements under other keys
+            // below the same prefix, so the two do not overlap
+            let mut badge_ids = IterableSet::new(
+                [
+                    StorageKey::ACCOUNT_AWARD_SET.into_storage_key(),
+                    env::sha256(account_id.as_bytes()),
+                ]
+                .concat(),
+            );
+            if let Some(mut legacy) = self.account_award_sets_v2.remove(account_id) {
+                badge_ids.extend(legacy.iter());
+                legacy.clear();
+            } else if let Some(legacy) = self.account_awards.remove(account_id) {
+                badge_ids.extend(legacy);
+            }
+            self.account_award_sets
+                .insert(account_id.clone(), badge_ids);
+        }
+
+        // .unwrap() is safe because the set was just inserted
+        self.account_award_sets.get_mut(account_id).unwrap()
+    }
+}
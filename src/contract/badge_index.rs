@@ -0,0 +1,214 @@
+use super::*;
+use std::ops::Bound;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum BadgeSort {
+    CreatedAt,
+    StartAt,
+    ExpiresAt,
+    Name,
+}
+
+/// Sort-order indexes over badge IDs, kept in sync by `save_badge` and
+/// `delete_badge`. Badges without an expiry sort last by `expires_at`. The
+/// `id` and `group_id` indexes also back prefix search. Only sponsored badges
+/// are indexed by `sponsor_id`.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeIndexes {
+    created_at: TreeMap<(u64, String), ()>,
+    start_at: TreeMap<(u64, String), ()>,
+    expires_at: TreeMap<(u64, String), ()>,
+    name: TreeMap<(String, String), ()>,
+    id: TreeMap<String, ()>,
+    group_id: TreeMap<(String, String), ()>,
+    sponsor_id: TreeMap<(AccountId, String), ()>,
+}
+
+/// `BadgeIndexes` as stored in state version 2
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct BadgeIndexesV2 {
+    pub created_at: collections::TreeMap<(u64, String), ()>,
+    pub start_at: collections::TreeMap<(u64, String), ()>,
+    pub expires_at: collections::TreeMap<(u64, String), ()>,
+    pub name: collections::TreeMap<(String, String), ()>,
+    pub id: collections::TreeMap<String, ()>,
+    pub group_id: collections::TreeMap<(String, String), ()>,
+    pub sponsor_id: collections::TreeMap<(AccountId, String), ()>,
+}
+
+impl BadgeIndexes {
+    pub fn new<S>(storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            created_at: TreeMap::new(prefix_key(&k, NestedKey::INDEX_CREATED_AT)),
+            start_at: TreeMap::new(prefix_key(&k, NestedKey::INDEX_START_AT)),
+            expires_at: TreeMap::new(prefix_key(&k, NestedKey::INDEX_EXPIRES_AT)),
+            name: TreeMap::new(prefix_key(&k, NestedKey::INDEX_NAME)),
+            id: TreeMap::new(prefix_key(&k, NestedKey::INDEX_ID)),
+            group_id: TreeMap::new(prefix_key(&k, NestedKey::INDEX_GROUP_ID)),
+            sponsor_id: TreeMap::new(prefix_key(&k, NestedKey::INDEX_SPONSOR_ID)),
+        }
+    }
+
+    /// Moves state version 2 into the current layout. `storage_key_prefix`
+    /// must be the one `old` was created with.
+    pub(crate) fn from_v2<S>(old: BadgeIndexesV2, storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+        let key = |nested| prefix_key(&k, nested);
+
+        Self {
+            created_at: migrate_tree_map(old.created_at, key(NestedKey::INDEX_CREATED_AT)),
+            start_at: migrate_tree_map(old.start_at, key(NestedKey::INDEX_START_AT)),
+            expires_at: migrate_tree_map(old.expires_at, key(NestedKey::INDEX_EXPIRES_AT)),
+            name: migrate_tree_map(old.name, key(NestedKey::INDEX_NAME)),
+            id: migrate_tree_map(old.id, key(NestedKey::INDEX_ID)),
+            group_id: migrate_tree_map(old.group_id, key(NestedKey::INDEX_GROUP_ID)),
+            sponsor_id: migrate_tree_map(old.sponsor_id, key(NestedKey::INDEX_SPONSOR_ID)),
+        }
+    }
+
+    pub fn insert(&mut self, badge: &Badge) {
+        let id = badge.id.clone();
+        self.created_at.insert((badge.created_at, id.clone()), ());
+        self.start_at.insert((badge.start_at, id.clone()), ());
+        self.expires_at
+            .insert((badge.expires_at().unwrap_or(u64::MAX), id.clone()), ());
+        self.name.insert((badge.name.clone(), id.clone()), ());
+        self.id.insert(id.clone(), ());
+        self.group_id
+            .insert((badge.group_id.clone(), id.clone()), ());
+        if let Some(sponsor_id) = &badge.sponsor_id {
+            self.sponsor_id.insert((sponsor_id.clone(), id), ());
+        }
+    }
+
+    pub fn remove(&mut self, badge: &Badge) {
+        let id = badge.id.clone();
+        self.created_at.remove(&(badge.created_at, id.clone()));
+        self.start_at.remove(&(badge.start_at, id.clone()));
+        self.expires_at
+            .remove(&(badge.expires_at().unwrap_or(u64::MAX), id.clone()));
+        self.name.remove(&(badge.name.clone(), id.clone()));
+        self.id.remove(&id);
+        self.group_id.remove(&(badge.group_id.clone(), id.clone()));
+        if let Some(sponsor_id) = &badge.sponsor_id {
+            self.sponsor_id.remove(&(sponsor_id.clone(), id));
+        }
+    }
+
+    /// Whether every index has an entry for the badge as given
+    pub fn contains(&self, badge: &Badge) -> bool {
+        let id = badge.id.clone();
+        self.created_at
+            .contains_key(&(badge.created_at, id.clone()))
+            && self.start_at.contains_key(&(badge.start_at, id.clone()))
+            && self
+                .expires_at
+                .contains_key(&(badge.expires_at().unwrap_or(u64::MAX), id.clone()))
+            && self.name.contains_key(&(badge.name.clone(), id.clone()))
+            && self.id.contains_key(&id)
+            && self
+                .group_id
+                .contains_key(&(badge.group_id.clone(), id.clone()))
+            && badge
+                .sponsor_id
+                .as_ref()
+                .is_none_or(|s| self.sponsor_id.contains_key(&(s.clone(), id)))
+    }
+
+    /// Number of badges indexed
+    pub fn len(&self) -> u64 {
+        self.id.len().into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.id.is_empty()
+    }
+
+    /// IDs of badges whose ID starts with `prefix`, followed by those whose
+    /// group ID does. A badge may appear in both.
+    pub fn search<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = String> + 'a {
+        let by_id = self
+            .id
+            .range((Bound::Included(prefix.to_string()), Bound::Unbounded))
+            .map(|(id, _)| id.clone())
+            .take_while(move |id| id.starts_with(prefix));
+
+        let by_group_id = self
+            .group_id
+            .range((
+                Bound::Included((prefix.to_string(), String::new())),
+                Bound::Unbounded,
+            ))
+            .take_while(move |((group_id, _), _)| group_id.starts_with(prefix))
+            .map(|((_, id), _)| id.clone());
+
+        by_id.chain(by_group_id)
+    }
+
+    /// IDs of the badges in a group, in order of ID
+    pub fn ids_in_group<'a>(&'a self, group_id: &'a str) -> impl Iterator<Item = String> + 'a {
+        self.group_id
+            .range((
+                Bound::Included((group_id.to_string(), String::new())),
+                Bound::Unbounded,
+            ))
+            .take_while(move |((g, _), _)| g == group_id)
+            .map(|((_, id), _)| id.clone())
+    }
+
+    /// Number of badges whose window has not ended by `now`, including those
+    /// that never expire. Iterates over those badges only.
+    pub fn count_unexpired(&self, now: u64) -> u64 {
+        self.expires_at
+            .range((Bound::Included((now, String::new())), Bound::Unbounded))
+            .count() as u64
+    }
+
+    /// IDs of the badges an account sponsored, in order of ID
+    pub fn ids_by_sponsor<'a>(
+        &'a self,
+        sponsor_id: &'a AccountId,
+    ) -> impl Iterator<Item = String> + 'a {
+        self.sponsor_id
+            .range((
+                Bound::Included((sponsor_id.clone(), String::new())),
+                Bound::Unbounded,
+            ))
+            .take_while(move |((s, _), _)| s == sponsor_id)
+            .map(|((_, id), _)| id.clone())
+    }
+
+    pub fn ids(&self, sort: BadgeSort, descending: bool) -> Box<dyn Iterator<Item = String> + '_> {
+        fn ids<'a, K>(
+            index: &'a TreeMap<(K, String), ()>,
+            descending: bool,
+        ) -> Box<dyn Iterator<Item = String> + 'a>
+        where
+            K: Ord + Clone + BorshSerialize + BorshDeserialize + 'a,
+        {
+            if descending {
+                Box::new(index.iter().rev().map(|((_, id), _)| id.clone()))
+            } else {
+                Box::new(index.iter().map(|((_, id), _)| id.clone()))
+            }
+        }
+
+        match sort {
+            BadgeSort::CreatedAt => ids(&self.created_at, descending),
+            BadgeSort::StartAt => ids(&self.start_at, descending),
+            BadgeSort::ExpiresAt => ids(&self.expires_at, descending),
+            BadgeSort::Name => ids(&self.name, descending),
+        }
+    }
+}
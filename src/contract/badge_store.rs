@@ -0,0 +1,100 @@
+use super::*;
+
+/// Stored form of a badge. New variants can be added when the `Badge` layout
+/// changes, without rewriting existing records.
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedBadge {
+    V1(Badge),
+}
+
+impl From<VersionedBadge> for Badge {
+    fn from(versioned: VersionedBadge) -> Self {
+        match versioned {
+            VersionedBadge::V1(badge) => badge,
+        }
+    }
+}
+
+impl From<Badge> for VersionedBadge {
+    fn from(badge: Badge) -> Self {
+        VersionedBadge::V1(badge)
+    }
+}
+
+/// Badges keyed by ID, with an explicit ordered index of the keys so that
+/// iteration order is stable and unaffected by removals.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BadgeStore {
+    badges: LookupMap<String, VersionedBadge>,
+    ids: TreeMap<String, ()>,
+}
+
+/// `BadgeStore` as stored in state version 2
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct BadgeStoreV2 {
+    pub badges: LookupMap<String, VersionedBadge>,
+    pub ids: collections::TreeMap<String, ()>,
+}
+
+impl BadgeStore {
+    pub fn new<S>(storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            badges: LookupMap::new(prefix_key(&k, NestedKey::BADGE_RECORDS)),
+            ids: TreeMap::new(prefix_key(&k, NestedKey::BADGE_IDS)),
+        }
+    }
+
+    /// Moves state version 2 into the current layout. `storage_key_prefix`
+    /// must be the one `old` was created with.
+    pub(crate) fn from_v2<S>(old: BadgeStoreV2, storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            badges: old.badges,
+            ids: migrate_tree_map(old.ids, prefix_key(&k, NestedKey::BADGE_IDS)),
+        }
+    }
+
+    pub fn get(&self, badge_id: &String) -> Option<Badge> {
+        self.badges.get(badge_id).cloned().map(Badge::from)
+    }
+
+    /// Returns the badge previously stored under the same ID
+    pub fn insert(&mut self, badge: &Badge) -> Option<Badge> {
+        self.ids.insert(badge.id.clone(), ());
+        self.badges
+            .insert(badge.id.clone(), badge.clone().into())
+            .map(Badge::from)
+    }
+
+    pub fn remove(&mut self, badge_id: &String) -> Option<Badge> {
+        self.ids.remove(badge_id);
+        self.badges.remove(badge_id).map(Badge::from)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.ids.len().into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// All badges, ordered by ID
+    pub fn values(&self) -> impl Iterator<Item = Badge> + '_ {
+        self.ids
+            .iter()
+            .filter_map(move |(badge_id, _)| self.get(badge_id))
+    }
+}
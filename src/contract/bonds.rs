@@ -0,0 +1,128 @@
+use super::*;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeBondEvent<'a> {
+    badge_id: &'a str,
+    sponsor_id: &'a AccountId,
+    amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_badge_creation_bond(&self) -> U128 {
+        self.badge_creation_bond.into()
+    }
+
+    /// Refundable amount that must be deposited on top of the creation price
+    #[payable]
+    pub fn set_badge_creation_bond(&mut self, badge_creation_bond: U128) {
+        metered!("set_badge_creation_bond");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let badge_creation_bond = badge_creation_bond.into();
+        self.on_admin_action(
+            "set_badge_creation_bond",
+            format!("{} -> {}", self.badge_creation_bond, badge_creation_bond),
+        );
+        self.badge_creation_bond = badge_creation_bond;
+    }
+
+    pub fn get_total_bonds_held(&self) -> U128 {
+        self.total_bonds_held.into()
+    }
+
+    /// Forfeits the sponsor's bond and disables the badge
+    #[payable]
+    pub fn slash_badge_bond(&mut self, badge_id: String, reason: String) -> Badge {
+        metered!("slash_badge_bond");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        self.input_limits.check_reason(&reason);
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        require!(badge.bond > 0, "Badge has no bond");
+        // .unwrap() is safe because bonds are only taken from sponsored badges
+        let sponsor_id = badge.sponsor_id.clone().unwrap();
+        let amount = badge.bond;
+
+        self.on_admin_action(
+            "slash_badge_bond",
+            format!("badge {} reason {}", badge_id, reason),
+        );
+
+        self.total_bonds_held -= amount;
+        emit_balance_changed(
+            BalanceChangeKind::KEPT,
+            &sponsor_id,
+            amount,
+            "bond_slashed",
+            BalanceRef::badge(&badge_id),
+        );
+        let badge = Badge { bond: 0, ..badge }.with_is_enabled(false, now());
+        self.save_badge(&badge);
+
+        emit_event(
+            "badge_bond_slashed",
+            [BadgeBondEvent {
+                badge_id: &badge_id,
+                sponsor_id: &sponsor_id,
+                amount: amount.into(),
+                reason: Some(&reason),
+            }],
+        );
+
+        badge
+    }
+
+    /// Returns the bond to the sponsor once the badge has expired
+    #[payable]
+    pub fn claim_badge_bond(&mut self, badge_id: String) -> Promise {
+        metered!("claim_badge_bond");
+        assert_one_yocto();
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        require!(badge.bond > 0, "Badge has no bond");
+        require!(
+            badge.sponsor_id.as_ref() == Some(&env::predecessor_account_id()),
+            "Bond can only be claimed by badge sponsor"
+        );
+        require!(
+            badge.is_expired(now()),
+            "Bond cannot be claimed until the badge expires"
+        );
+
+        // .unwrap() is safe because of the sponsor check
+        let sponsor_id = badge.sponsor_id.clone().unwrap();
+        let amount = badge.bond;
+
+        self.total_bonds_held -= amount;
+        self.save_badge(&Badge { bond: 0, ..badge });
+
+        emit_event(
+            "badge_bond_refunded",
+            [BadgeBondEvent {
+                badge_id: &badge_id,
+                sponsor_id: &sponsor_id,
+                amount: amount.into(),
+                reason: None,
+            }],
+        );
+
+        transfer_out(
+            sponsor_id,
+            amount,
+            "bond_refund",
+            BalanceRef::badge(&badge_id),
+        )
+    }
+}
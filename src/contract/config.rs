@@ -0,0 +1,42 @@
+use super::*;
+
+/// Contract settings in one call, for clients that would otherwise query
+/// each getter separately
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    pub api_version: u32,
+    pub badge_rate_per_day: U128,
+    pub badge_max_active_duration: U64,
+    pub badge_min_creation_deposit: U128,
+    pub badge_creation_bond: U128,
+    pub badge_report_fee: U128,
+    pub badge_report_threshold: Option<U64>,
+    pub sunset: Option<Sunset>,
+    pub streaming: Option<StreamConfig>,
+    pub referral_share_bps: u16,
+    pub max_badges_per_sponsor: Option<u32>,
+    pub max_active_badges: Option<u32>,
+    pub treasury_account_id: Option<AccountId>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_config(&self) -> Config {
+        Config {
+            api_version: self.get_api_version(),
+            badge_rate_per_day: self.get_badge_rate_per_day(),
+            badge_max_active_duration: self.get_badge_max_active_duration(),
+            badge_min_creation_deposit: self.get_badge_min_creation_deposit(),
+            badge_creation_bond: self.get_badge_creation_bond(),
+            badge_report_fee: self.get_badge_report_fee(),
+            badge_report_threshold: self.get_badge_report_threshold(),
+            sunset: self.sunset.clone(),
+            streaming: self.stream_config.clone(),
+            referral_share_bps: self.referral_share_bps,
+            max_badges_per_sponsor: self.max_badges_per_sponsor,
+            max_active_badges: self.max_active_badges,
+            treasury_account_id: self.treasury_account_id.clone(),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use super::*;
+
+const MAX_DEMO_BADGES: u64 = 50;
+const MAX_DEMO_AWARDS_PER_BADGE: u64 = 20;
+const MAX_DEMO_PROPOSALS_PER_STATUS: u64 = 5;
+
+const DEMO_GROUPS: [&str; 5] = ["ref-finance", "paras", "aurora", "mintbase", "sweat"];
+const DEMO_ADJECTIVES: [&str; 6] = ["Early", "Loyal", "Bold", "Curious", "Generous", "Swift"];
+const DEMO_NOUNS: [&str; 6] = [
+    "Swapper",
+    "Collector",
+    "Bridger",
+    "Minter",
+    "Staker",
+    "Voter",
+];
+
+const DEMO_STATUSES: [ProposalStatus; 8] = [
+    ProposalStatus::PENDING,
+    ProposalStatus::ACCEPTED,
+    ProposalStatus::REJECTED,
+    ProposalStatus::RESCINDED,
+    ProposalStatus::EXPIRED,
+    ProposalStatus::DISPUTED,
+    ProposalStatus::SCHEDULED,
+    ProposalStatus::DRAFT,
+];
+
+/// How much demo data `seed_demo_data` generates
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DemoSpec {
+    pub badges: u64,
+    pub awards_per_badge: u64,
+    pub proposals_per_status: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DemoData {
+    pub badge_ids: Vec<String>,
+    pub proposal_ids: Vec<U64>,
+    pub awards: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Fills a staging contract with badges, awards, and proposals in every
+    /// status. Seeded proposals are authored by the owner and hold no
+    /// deposit. Only compiled with the `demo` feature.
+    #[payable]
+    pub fn seed_demo_data(&mut self, spec: DemoSpec) -> DemoData {
+        metered!("seed_demo_data");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            spec.badges <= MAX_DEMO_BADGES,
+            format!("Cannot seed more than {} badges", MAX_DEMO_BADGES)
+        );
+        require!(
+            spec.awards_per_badge <= MAX_DEMO_AWARDS_PER_BADGE,
+            format!(
+                "Cannot seed more than {} awards per badge",
+                MAX_DEMO_AWARDS_PER_BADGE
+            )
+        );
+        require!(
+            spec.proposals_per_status <= MAX_DEMO_PROPOSALS_PER_STATUS,
+            format!(
+                "Cannot seed more than {} proposals per status",
+                MAX_DEMO_PROPOSALS_PER_STATUS
+            )
+        );
+        self.on_admin_action(
+            "seed_demo_data",
+            format!(
+                "badges {} awards_per_badge {} proposals_per_status {}",
+                spec.badges, spec.awards_per_badge, spec.proposals_per_status
+            ),
+        );
+
+        let now = now();
+        let mut badge_ids = vec![];
+        let mut awards = 0;
+        for i in 0..spec.badges {
+            let badge_id = self.next_demo_id("demo-badge");
+            let group_id = DEMO_GROUPS[i as usize % DEMO_GROUPS.len()];
+            let created_at = now.saturating_sub(i * DAY);
+            self.save_badge(&Badge {
+                id: badge_id.clone(),
+                group_id: group_id.to_string(),
+                name: format!(
+                    "{} {}",
+                    DEMO_ADJECTIVES[i as usize % DEMO_ADJECTIVES.len()],
+                    DEMO_NOUNS[i as usize % DEMO_NOUNS.len()]
+                ),
+                description: format!("Awarded for activity on {}", group_id),
+                is_enabled: true,
+                created_at,
+                start_at: created_at,
+                duration: Some((30 + i % 60) * DAY),
+                paused_at: None,
+                sponsor_id: self.get_group_account_id(group_id.to_string()),
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+
+            for j in 0..spec.awards_per_badge {
+                self.award_badge(badge_id.clone(), self.demo_account(j));
+                awards += 1;
+            }
+
+            badge_ids.push(badge_id);
+        }
+
+        let author_id = env::predecessor_account_id();
+        let mut proposal_ids = vec![];
+        for status in DEMO_STATUSES.iter() {
+            for i in 0..spec.proposals_per_status {
+                // Accepted extensions apply to seeded badges; everything
+                // else proposes a badge that does not exist yet
+                let (tag, action) = match (status, badge_ids.get(i as usize)) {
+                    (ProposalStatus::ACCEPTED, Some(badge_id)) => (
+                        TAG_BADGE_EXTEND,
+                        BadgeAction::Extend(BadgeExtend {
+                            id: badge_id.clone(),
+                            duration: 7 * DAY,
+                            stream_id: None,
+                        }),
+                    ),
+                    _ => (
+                        TAG_BADGE_CREATE,
+                        BadgeAction::Create(BadgeCreate {
+                            id: self.next_demo_id("demo-proposed"),
+                            group_id: DEMO_GROUPS[i as usize % DEMO_GROUPS.len()].to_string(),
+                            name: format!("Proposed {}", DEMO_NOUNS[i as usize % DEMO_NOUNS.len()]),
+                            description: "A badge proposed for the demo".to_string(),
+                            start_at: None,
+                            duration: 30 * DAY,
+                            stream_id: None,
+                            preview: false,
+                            publish_at: None,
+                            media_url: None,
+                        }),
+                    ),
+                };
+
+                let proposal = self.sponsorship.insert_demo(
+                    author_id.clone(),
+                    ProposalSubmission {
+                        description: format!("Demo {:?} proposal", status),
+                        tag: tag.to_string(),
+                        msg: Some(action),
+                        deposit: U128(0),
+                        duration: None,
+                        nonce: None,
+                        referrer_id: None,
+                        promo_code: None,
+                        max_storage_fee: None,
+                        previous_proposal_id: None,
+                    },
+                    status.clone(),
+                );
+                if !matches!(
+                    status,
+                    ProposalStatus::ACCEPTED | ProposalStatus::RESCINDED | ProposalStatus::DRAFT
+                ) {
+                    self.proposal_terms
+                        .insert(proposal.id, self.current_terms());
+                }
+                proposal_ids.push(proposal.id.into());
+            }
+        }
+
+        emit_records_changed("seed_demo_data", "badge", &badge_ids);
+        emit_records_changed("seed_demo_data", "proposal", &proposal_ids);
+
+        DemoData {
+            badge_ids,
+            proposal_ids,
+            awards: awards.into(),
+        }
+    }
+}
+
+impl StatsGallery {
+    /// An unused badge ID of the form `{prefix}-{n}`. `n` starts from the
+    /// number of badges and proposals, so repeated seeding does not propose
+    /// the same ID twice.
+    fn next_demo_id(&self, prefix: &str) -> String {
+        let mut n = self.badges.len() + self.sponsorship.len();
+        loop {
+            let id = format!("{}-{}", prefix, n);
+            if self.badges.get(&id).is_none() {
+                return id;
+            }
+            n += 1;
+        }
+    }
+
+    fn demo_account(&self, n: u64) -> AccountId {
+        let current_account_id = env::current_account_id();
+        let top_level = current_account_id.as_str().rsplit('.').next().unwrap();
+
+        format!("demo-user-{}.{}", n, top_level).parse().unwrap()
+    }
+}
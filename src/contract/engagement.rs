@@ -0,0 +1,53 @@
+use super::*;
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_view_relayers(&self) -> Vec<AccountId> {
+        self.view_relayers.iter().cloned().collect()
+    }
+
+    #[payable]
+    pub fn add_view_relayer(&mut self, account_id: AccountId) {
+        metered!("add_view_relayer");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action("add_view_relayer", account_id.to_string());
+        self.view_relayers.insert(account_id);
+    }
+
+    #[payable]
+    pub fn remove_view_relayer(&mut self, account_id: AccountId) {
+        metered!("remove_view_relayer");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(self.view_relayers.remove(&account_id), "Not a view relayer");
+        self.on_admin_action("remove_view_relayer", account_id.to_string());
+    }
+
+    /// Adds `count` views to a badge's counter. Called by the frontend's
+    /// relayer, which aggregates views off-chain and reports them in batches.
+    pub fn record_badge_view(&mut self, badge_id: String, count: U64) -> U64 {
+        metered!("record_badge_view");
+        require!(
+            self.view_relayers.contains(&env::predecessor_account_id()),
+            "View relayer only"
+        );
+        require!(self.badges.get(&badge_id).is_some(), "Badge does not exist");
+
+        let views = self
+            .badge_views
+            .get(&badge_id)
+            .cloned()
+            .unwrap_or(0)
+            .saturating_add(count.0);
+        self.badge_views.insert(badge_id, views);
+
+        views.into()
+    }
+
+    pub fn get_badge_view_count(&self, badge_id: String) -> U64 {
+        self.badge_views.get(&badge_id).cloned().unwrap_or(0).into()
+    }
+}
@@ -0,0 +1,218 @@
+use super::*;
+
+/// Gas an accepted proposal's side effects may need. When less than this
+/// remains while a proposal is accepted, the side effects are left for
+/// `execute_accepted` instead of risking the whole acceptance.
+pub const GAS_FOR_EXECUTION: Gas = Gas::from_tgas(30);
+
+const DEFAULT_GET_PENDING_EXECUTIONS: u64 = 10;
+const MAX_GET_PENDING_EXECUTIONS: u64 = 50;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ProposalExecutionEvent<'a> {
+    proposal_id: U64,
+    tag: &'a str,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Accepted proposals whose side effects (e.g. creating the badge) have
+    /// not run yet
+    pub fn get_pending_executions(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<U64> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_PENDING_EXECUTIONS)
+            .min(MAX_GET_PENDING_EXECUTIONS);
+
+        self.pending_executions
+            .iter()
+            .skip(from_index.map(u64::from).unwrap_or(0) as usize)
+            .take(limit as usize)
+            .map(|id| U64(*id))
+            .collect()
+    }
+
+    pub fn is_execution_pending(&self, proposal_id: U64) -> bool {
+        self.pending_executions.contains(&proposal_id.into())
+    }
+
+    /// Runs the side effects of an accepted proposal that were left pending.
+    /// Anyone may call this. Returns `false`, and does nothing, if the
+    /// proposal has nothing pending, so repeating a call is harmless.
+    pub fn execute_accepted(&mut self, proposal_id: U64) -> bool {
+        metered!("execute_accepted");
+        let proposal_id: u64 = proposal_id.into();
+        if !self.pending_executions.remove(&proposal_id) {
+            return false;
+        }
+
+        // .unwrap() is safe because only accepted proposals are queued, and
+        // those are never removed
+        let proposal = self.sponsorship.get_proposal(proposal_id).unwrap();
+        self.execute(&proposal);
+        self.pay_keeper(KeeperTask::ACCEPTED_EXECUTIONS, 1);
+
+        true
+    }
+}
+
+impl StatsGallery {
+    /// Runs an accepted proposal's side effects now if there is gas to spare,
+    /// or queues them for `execute_accepted`. Anything that moves funds must
+    /// happen before this, at acceptance.
+    pub(crate) fn queue_execution(&mut self, proposal: &Proposal<BadgeAction>) {
+        let remaining = env::prepaid_gas().saturating_sub(env::used_gas());
+        if remaining >= GAS_FOR_EXECUTION {
+            self.execute(proposal);
+            return;
+        }
+
+        self.pending_executions.insert(proposal.id);
+        emit_event(
+            "proposal_execution_queued",
+            [ProposalExecutionEvent {
+                proposal_id: proposal.id.into(),
+                tag: &proposal.tag,
+            }],
+        );
+    }
+
+    /// Whether a queued or waitlisted create proposal will take `badge_id`,
+    /// so that no other proposal may claim it in the meantime
+    pub(crate) fn is_badge_creation_queued(&self, badge_id: &str) -> bool {
+        self.queued_creations().any(|(_, id)| id == badge_id)
+    }
+
+    /// Number of queued or waitlisted create proposals by `author_id`
+    pub(crate) fn queued_creations_by(&self, author_id: &AccountId) -> usize {
+        self.queued_creations()
+            .filter(|(author, _)| author == author_id)
+            .count()
+    }
+
+    fn queued_creations(&self) -> impl Iterator<Item = (AccountId, String)> + '_ {
+        self.pending_creations().chain(self.waitlisted_creations())
+    }
+
+    /// Author and badge ID of each badge that a create or package proposal
+    /// awaiting execution will create
+    pub(crate) fn pending_creations(&self) -> impl Iterator<Item = (AccountId, String)> + '_ {
+        self.pending_executions.iter().flat_map(|id| {
+            let proposal = match self.sponsorship.get_proposal(*id) {
+                Some(proposal) => proposal,
+                None => return vec![],
+            };
+            match proposal.msg {
+                Some(BadgeAction::Create(create_request)) => {
+                    vec![(proposal.author_id, create_request.id)]
+                }
+                Some(BadgeAction::Package(package_request)) => package_request
+                    .badges
+                    .into_iter()
+                    .map(|b| (proposal.author_id.clone(), b.id))
+                    .collect(),
+                _ => vec![],
+            }
+        })
+    }
+
+    fn execute(&mut self, proposal: &Proposal<BadgeAction>) {
+        match proposal.tag.as_str() {
+            TAG_BADGE_CREATE => {
+                let create_request = extract_msg!(proposal, BadgeAction, Create);
+                let terms = self.terms_for(proposal);
+                self.create_sponsored_badge(proposal, create_request, &terms);
+                self.proposal_terms.remove(&proposal.id);
+            }
+            TAG_BADGE_PACKAGE => {
+                let package_request = extract_msg!(proposal, BadgeAction, Package);
+                let terms = self.terms_for(proposal);
+                // .unwrap() is safe because the package is recorded while
+                // the proposal is pending and kept once it is accepted
+                let package = self.proposal_packages.get(&proposal.id).cloned().unwrap();
+                let featured_until = now() + package.featured_duration.0;
+
+                for create_request in package_request.badges.iter() {
+                    self.create_sponsored_badge(proposal, create_request, &terms);
+                    if package.featured_duration.0 > 0 {
+                        self.feature_badge(&create_request.id, featured_until);
+                    }
+                }
+                self.proposal_terms.remove(&proposal.id);
+            }
+            TAG_BADGE_EXTEND => {
+                let extend_request = extract_msg!(proposal, BadgeAction, Extend);
+                let terms = self.terms_for(proposal);
+
+                match self.badges.get(&extend_request.id) {
+                    Some(badge) => {
+                        self.save_badge(&Badge {
+                            duration: badge.duration.map(|d| d + extend_request.duration),
+                            ..badge
+                        });
+                        if let Some(stream_id) = &extend_request.stream_id {
+                            self.link_badge_stream(
+                                &extend_request.id,
+                                stream_id,
+                                &proposal.author_id,
+                                &terms,
+                            );
+                        }
+                    }
+                    // Deleted by the owner after the proposal was accepted
+                    None => log!("Badge {} no longer exists", extend_request.id),
+                }
+                self.proposal_terms.remove(&proposal.id);
+            }
+            TAG_GENERAL_SPONSORSHIP => self.record_supporter(proposal),
+            _ => {}
+        }
+
+        emit_event(
+            "proposal_executed",
+            [ProposalExecutionEvent {
+                proposal_id: proposal.id.into(),
+                tag: &proposal.tag,
+            }],
+        );
+    }
+
+    fn create_sponsored_badge(
+        &mut self,
+        proposal: &Proposal<BadgeAction>,
+        create_request: &BadgeCreate,
+        terms: &BadgeTerms,
+    ) {
+        // Badges created late, e.g. off the waitlist, get their full
+        // duration from when they appear
+        let now = now();
+
+        self.save_badge(&Badge {
+            id: create_request.id.clone(),
+            group_id: create_request.group_id.clone(),
+            name: create_request.name.clone(),
+            description: create_request.description.clone(),
+            created_at: now,
+            start_at: create_request.start_at.unwrap_or(now).max(now),
+            duration: Some(create_request.duration),
+            is_enabled: true,
+            paused_at: None,
+            sponsor_id: Some(proposal.author_id.clone()),
+            bond: terms.creation_bond.0,
+            hold: None,
+            disabled_reason: None,
+            is_community: terms.is_free_tier,
+            is_preview: create_request.preview || create_request.publish_at.is_some(),
+            publish_at: create_request.publish_at,
+            media_url: create_request.media_url.clone(),
+            storage_deposit: terms.metadata_storage(create_request),
+        });
+        self.total_bonds_held += terms.creation_bond.0;
+        self.total_storage_deposits_held += terms.metadata_storage(create_request);
+
+        if let Some(stream_id) = &create_request.stream_id {
+            self.link_badge_stream(&create_request.id, stream_id, &proposal.author_id, terms);
+        }
+    }
+}
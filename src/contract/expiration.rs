@@ -0,0 +1,101 @@
+use super::*;
+
+const DEFAULT_PROCESS_EXPIRATIONS: u64 = 10;
+const MAX_PROCESS_EXPIRATIONS: u64 = 50;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeExpiredEvent<'a> {
+    badge_id: &'a str,
+    expires_at: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Emits `badge_expired` for badges whose active window has ended, in
+    /// `expires_at` order. Anyone may call this, and is paid the expiration
+    /// keeper reward for each event emitted. Returns how many badges expired.
+    pub fn process_expirations(&mut self, limit: Option<U64>) -> U64 {
+        metered!("process_expirations");
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_PROCESS_EXPIRATIONS)
+            .min(MAX_PROCESS_EXPIRATIONS);
+
+        let now = now();
+        let due: Vec<(u64, String)> = self
+            .expiration_queue
+            .iter()
+            .map(|(key, _)| key.clone())
+            .take_while(|(expires_at, _)| *expires_at < now)
+            .take(limit as usize)
+            .collect();
+
+        let mut expired = 0;
+        for (expires_at, badge_id) in due.iter() {
+            self.expiration_queue
+                .remove(&(*expires_at, badge_id.clone()));
+            let badge = match self.badges.get(badge_id) {
+                Some(badge) => badge,
+                None => continue,
+            };
+            self.move_active_badge(Some(&badge.group_id), None);
+            // A paused badge's expiry moves when it is resumed, at which
+            // point it is queued again
+            if badge.paused_at.is_none() {
+                emit_event(
+                    "badge_expired",
+                    [BadgeExpiredEvent {
+                        badge_id,
+                        expires_at: (*expires_at).into(),
+                    }],
+                );
+                expired += 1;
+            }
+        }
+
+        self.pay_keeper(KeeperTask::BADGE_EXPIRATIONS, expired);
+        // Each expiration frees a slot for the waitlist
+        self.promote_waitlisted("process_expirations", expired);
+
+        expired.into()
+    }
+
+    /// Number of queued expirations whose window has already ended
+    pub fn get_due_expirations_count(&self) -> U64 {
+        let now = now();
+
+        (self
+            .expiration_queue
+            .iter()
+            .take_while(|((expires_at, _), _)| *expires_at < now)
+            .count() as u64)
+            .into()
+    }
+}
+
+impl StatsGallery {
+    /// Moves a badge's queue entry when it is saved. A badge is queued while
+    /// its window has yet to end, and stays queued if it already was, so that
+    /// saving it before its expiration is processed does not drop the event.
+    pub(crate) fn requeue_expiration(&mut self, previous: Option<&Badge>, badge: &Badge) {
+        let was_queued = previous.is_some_and(|p| self.dequeue_expiration(p));
+
+        if let Some(expires_at) = badge.expires_at() {
+            if was_queued || expires_at >= now() {
+                self.expiration_queue
+                    .insert((expires_at, badge.id.clone()), ());
+            }
+        }
+    }
+
+    pub(crate) fn dequeue_expiration(&mut self, badge: &Badge) -> bool {
+        match badge.expires_at() {
+            Some(expires_at) => self
+                .expiration_queue
+                .remove(&(expires_at, badge.id.clone()))
+                .is_some(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use super::*;
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Accounts, e.g. of ecosystem public-goods projects, whose badges are
+    /// created without charge
+    pub fn get_free_tier_accounts(&self) -> Vec<AccountId> {
+        self.free_tier_accounts.iter().cloned().collect()
+    }
+
+    pub fn is_free_tier_account(&self, account_id: AccountId) -> bool {
+        self.free_tier_accounts.contains(&account_id)
+    }
+
+    #[payable]
+    pub fn add_free_tier_account(&mut self, account_id: AccountId) {
+        metered!("add_free_tier_account");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action("add_free_tier_account", account_id.to_string());
+        self.free_tier_accounts.insert(account_id);
+    }
+
+    /// Proposals the account has already submitted stay free
+    #[payable]
+    pub fn remove_free_tier_account(&mut self, account_id: AccountId) {
+        metered!("remove_free_tier_account");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            self.free_tier_accounts.remove(&account_id),
+            "Not a free-tier account"
+        );
+        self.on_admin_action("remove_free_tier_account", account_id.to_string());
+    }
+}
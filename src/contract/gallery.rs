@@ -0,0 +1,79 @@
+use super::*;
+
+const MAX_FEATURED_BADGES: usize = 8;
+const DEFAULT_RECENT_BADGES: u64 = 10;
+const MAX_RECENT_BADGES: u64 = 25;
+/// Upper bound on index entries visited while looking for active badges
+const MAX_RECENT_BADGES_SCAN: usize = 100;
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GalleryHome {
+    pub recent: Vec<Badge>,
+    pub featured: Vec<Badge>,
+    pub badge_count: U64,
+    pub proposal_count: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Everything the landing page needs in one bounded call
+    pub fn get_gallery_home(&self, recent_limit: Option<U64>) -> GalleryHome {
+        let now = now();
+        let recent_limit = recent_limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_RECENT_BADGES)
+            .min(MAX_RECENT_BADGES);
+
+        let recent = self
+            .badge_indexes
+            .ids(BadgeSort::CreatedAt, true)
+            .take(MAX_RECENT_BADGES_SCAN)
+            .filter_map(|badge_id| self.badges.get(&badge_id))
+            .filter(|b| BadgeVisibility::PUBLIC.is_visible(b, now))
+            .take(recent_limit as usize)
+            .map(|b| self.with_default_media(b))
+            .collect();
+
+        // Chosen by the owner first, then those featured by a package
+        let mut seen = HashSet::new();
+        let featured = self
+            .featured_badge_ids
+            .iter()
+            .cloned()
+            .chain(self.package_featured_ids(now))
+            .filter(|badge_id| seen.insert(badge_id.clone()))
+            .filter_map(|badge_id| self.badges.get(&badge_id))
+            .filter(|b| BadgeVisibility::PUBLIC.is_visible(b, now))
+            .map(|b| self.with_default_media(b))
+            .collect();
+
+        GalleryHome {
+            recent,
+            featured,
+            badge_count: self.badges.len().into(),
+            proposal_count: self.sponsorship.len().into(),
+        }
+    }
+
+    pub fn get_featured_badge_ids(&self) -> Vec<String> {
+        self.featured_badge_ids.clone()
+    }
+
+    #[payable]
+    pub fn set_featured_badge_ids(&mut self, badge_ids: Vec<String>) {
+        metered!("set_featured_badge_ids");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        require!(
+            badge_ids.len() <= MAX_FEATURED_BADGES,
+            format!("Cannot feature more than {} badges", MAX_FEATURED_BADGES)
+        );
+        for badge_id in badge_ids.iter() {
+            require!(self.badges.get(badge_id).is_some(), "Badge does not exist");
+        }
+
+        self.on_admin_action("set_featured_badge_ids", badge_ids.join(","));
+        self.featured_badge_ids = badge_ids;
+    }
+}
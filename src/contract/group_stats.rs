@@ -0,0 +1,103 @@
+use super::*;
+
+/// Running totals for the badges in a group, kept up to date as badges are
+/// created, extended and expire. Totals start from when they were first
+/// tracked.
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Default, PartialEq, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct GroupStats {
+    /// Badges whose expiration has not been processed yet, including those
+    /// without a duration
+    pub active_badges: u32,
+    /// Days bought by accepted create and extend proposals
+    #[serde(with = "dec_string")]
+    pub sponsored_days: u64,
+    /// What those proposals paid for their days, bonds excluded. Badges paid
+    /// for by a stream add nothing.
+    #[serde(with = "dec_string")]
+    pub revenue: Balance,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_group_stats(&self, group_id: String) -> GroupStats {
+        self.group_stats.get(&group_id).cloned().unwrap_or_default()
+    }
+}
+
+impl StatsGallery {
+    /// Counts as active a badge that is waiting for its expiration to be
+    /// processed, or that never expires
+    pub(crate) fn is_tracked_active(&self, badge: &Badge) -> bool {
+        match badge.expires_at() {
+            Some(expires_at) => self
+                .expiration_queue
+                .contains_key(&(expires_at, badge.id.clone())),
+            None => true,
+        }
+    }
+
+    /// Moves an active badge between groups' counts. `None` on either side
+    /// means the badge is not counted there, e.g. because it was just
+    /// created or has expired.
+    pub(crate) fn move_active_badge(
+        &mut self,
+        from_group_id: Option<&str>,
+        to_group_id: Option<&str>,
+    ) {
+        if from_group_id == to_group_id {
+            return;
+        }
+
+        if let Some(group_id) = from_group_id {
+            self.update_group_stats(group_id, |stats| {
+                stats.active_badges = stats.active_badges.saturating_sub(1)
+            });
+        }
+        if let Some(group_id) = to_group_id {
+            self.update_group_stats(group_id, |stats| stats.active_badges += 1);
+        }
+    }
+
+    pub(crate) fn add_group_sale(&mut self, group_id: &str, duration: u64, revenue: Balance) {
+        self.update_group_stats(group_id, |stats| {
+            stats.sponsored_days += billable_days_in_duration(duration);
+            stats.revenue += revenue;
+        });
+    }
+
+    /// Takes back what `add_group_sale` added, e.g. for an unwound proposal
+    pub(crate) fn remove_group_sale(&mut self, group_id: &str, duration: u64, revenue: Balance) {
+        self.update_group_stats(group_id, |stats| {
+            stats.sponsored_days = stats
+                .sponsored_days
+                .saturating_sub(billable_days_in_duration(duration));
+            stats.revenue = stats.revenue.saturating_sub(revenue);
+        });
+    }
+
+    /// Folds the totals of a group whose badges were all merged into another
+    pub(crate) fn merge_group_stats(&mut self, from_group_id: &str, into_group_id: &str) {
+        if let Some(from) = self.group_stats.remove(&from_group_id.to_string()) {
+            self.update_group_stats(into_group_id, |stats| {
+                stats.active_badges += from.active_badges;
+                stats.sponsored_days += from.sponsored_days;
+                stats.revenue += from.revenue;
+            });
+        }
+    }
+
+    fn update_group_stats(&mut self, group_id: &str, f: impl FnOnce(&mut GroupStats)) {
+        let group_id = group_id.to_string();
+        let mut stats = self.group_stats.get(&group_id).cloned().unwrap_or_default();
+        f(&mut stats);
+        if stats == GroupStats::default() {
+            self.group_stats.remove(&group_id);
+        } else {
+            self.group_stats.insert(group_id, stats);
+        }
+    }
+}
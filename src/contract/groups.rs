@@ -0,0 +1,392 @@
+use super::*;
+
+const MAX_GROUP_SPONSORS: usize = 10;
+
+/// Proof that the account named after a group has claimed it. Only that
+/// account and the sponsors it names may propose badges in the group.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct GroupVerification {
+    pub account_id: AccountId,
+    pub sponsors: Vec<AccountId>,
+    pub verified_at: U64,
+}
+
+impl GroupVerification {
+    pub fn may_sponsor(&self, account_id: &AccountId) -> bool {
+        &self.account_id == account_id || self.sponsors.contains(account_id)
+    }
+}
+
+/// A sponsor's request to hand their badges in a group to another account,
+/// pending the owner's approval
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct GroupTransfer {
+    pub group_id: String,
+    pub from_account_id: AccountId,
+    pub to_account_id: AccountId,
+    pub requested_at: U64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct GroupEvent<'a> {
+    group_id: &'a str,
+    account_id: &'a AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct GroupTransferredEvent<'a> {
+    group_id: &'a str,
+    from_account_id: &'a AccountId,
+    to_account_id: &'a AccountId,
+    badge_ids: &'a [String],
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct GroupMergedEvent<'a> {
+    from_group_id: &'a str,
+    into_group_id: &'a str,
+    badge_ids: &'a [String],
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// The account that may verify `group_id`: the group ID as a subaccount
+    /// of the contract's top-level account, e.g. `ref-finance.near`
+    pub fn get_group_account_id(&self, group_id: String) -> Option<AccountId> {
+        let current_account_id = env::current_account_id();
+        let top_level = current_account_id.as_str().rsplit('.').next().unwrap();
+
+        format!("{}.{}", group_id, top_level).parse().ok()
+    }
+
+    pub fn get_group_verification(&self, group_id: String) -> Option<GroupVerification> {
+        self.group_verifications.get(&group_id).cloned()
+    }
+
+    /// Called by the account named after a group to claim it. Requires a
+    /// full access key, like any call with an attached deposit.
+    #[payable]
+    pub fn verify_group(&mut self, group_id: String) -> GroupVerification {
+        metered!("verify_group");
+        assert_one_yocto();
+
+        self.input_limits.check_id("group_id", &group_id);
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.get_group_account_id(group_id.clone()).as_ref() == Some(&account_id),
+            "Only the account named after the group may verify it"
+        );
+
+        let verification = GroupVerification {
+            account_id,
+            sponsors: self
+                .group_verifications
+                .get(&group_id)
+                .map(|v| v.sponsors.clone())
+                .unwrap_or_default(),
+            verified_at: now().into(),
+        };
+        self.group_verifications
+            .insert(group_id.clone(), verification.clone());
+        self.emit_group_event("group_verified", &group_id, &verification.account_id);
+
+        verification
+    }
+
+    /// Lets the group account name other accounts that may sponsor badges in
+    /// the group
+    #[payable]
+    pub fn set_group_sponsors(
+        &mut self,
+        group_id: String,
+        sponsors: Vec<AccountId>,
+    ) -> GroupVerification {
+        metered!("set_group_sponsors");
+        assert_one_yocto();
+        require!(
+            sponsors.len() <= MAX_GROUP_SPONSORS,
+            format!("Cannot name more than {} sponsors", MAX_GROUP_SPONSORS)
+        );
+
+        let verification = self
+            .group_verifications
+            .get(&group_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Group is not verified"));
+        require!(
+            verification.account_id == env::predecessor_account_id(),
+            "Group account only"
+        );
+
+        let verification = GroupVerification {
+            sponsors,
+            ..verification
+        };
+        self.group_verifications
+            .insert(group_id, verification.clone());
+
+        verification
+    }
+
+    /// Removes a group's verification, opening it to any sponsor again. May
+    /// be called by the group account or the owner.
+    #[payable]
+    pub fn revoke_group_verification(&mut self, group_id: String) {
+        metered!("revoke_group_verification");
+        assert_one_yocto();
+
+        let verification = self
+            .group_verifications
+            .get(&group_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Group is not verified"));
+        let predecessor = env::predecessor_account_id();
+        if verification.account_id != predecessor {
+            self.ownership.assert_owner();
+            self.on_admin_action("revoke_group_verification", format!("group {}", group_id));
+        }
+
+        self.group_verifications.remove(&group_id);
+        self.emit_group_event(
+            "group_verification_revoked",
+            &group_id,
+            &verification.account_id,
+        );
+    }
+
+    /// Shown for badges in the group that have no media of their own
+    pub fn get_group_default_media(&self, group_id: String) -> Option<String> {
+        self.group_media.get(&group_id).cloned()
+    }
+
+    /// May be called by the group account or the owner. `None` clears the
+    /// default. Badges are not rewritten; the default is filled in when they
+    /// are viewed.
+    #[payable]
+    pub fn set_group_default_media(&mut self, group_id: String, media_url: Option<String>) {
+        metered!("set_group_default_media");
+        assert_one_yocto();
+
+        let predecessor = env::predecessor_account_id();
+        let is_group_account = self
+            .group_verifications
+            .get(&group_id)
+            .cloned()
+            .is_some_and(|v| v.account_id == predecessor);
+        if !is_group_account {
+            self.ownership.assert_owner();
+            self.on_admin_action(
+                "set_group_default_media",
+                format!("group {}: {:?}", group_id, media_url),
+            );
+        }
+
+        match &media_url {
+            Some(media_url) => {
+                self.input_limits.check_id("Group ID", &group_id);
+                self.input_limits
+                    .check_media_url("Group media URL", media_url);
+                self.group_media.insert(group_id.clone(), media_url.clone());
+            }
+            None => {
+                self.group_media.remove(&group_id);
+            }
+        }
+        self.emit_group_event("group_media_changed", &group_id, &predecessor);
+    }
+
+    pub fn get_group_transfer(&self, group_id: String) -> Option<GroupTransfer> {
+        self.group_transfers.get(&group_id).cloned()
+    }
+
+    /// Asks the owner to move sponsorship of the caller's badges in a group to
+    /// another account, e.g. after an acquisition or rebrand. Replaces any
+    /// pending request for the group.
+    #[payable]
+    pub fn request_group_transfer(
+        &mut self,
+        group_id: String,
+        to_account_id: AccountId,
+    ) -> GroupTransfer {
+        metered!("request_group_transfer");
+        assert_one_yocto();
+
+        let from_account_id = env::predecessor_account_id();
+        require!(from_account_id != to_account_id, "Cannot transfer to self");
+        require!(
+            self.sponsored_badges_in_group(&group_id, &from_account_id)
+                .next()
+                .is_some(),
+            "Caller sponsors no badges in group"
+        );
+
+        let transfer = GroupTransfer {
+            group_id: group_id.clone(),
+            from_account_id,
+            to_account_id,
+            requested_at: now().into(),
+        };
+        self.group_transfers.insert(group_id, transfer.clone());
+        emit_event("group_transfer_requested", [&transfer]);
+
+        transfer
+    }
+
+    /// Withdraws a pending transfer. May be called by the requester or the
+    /// owner.
+    #[payable]
+    pub fn cancel_group_transfer(&mut self, group_id: String) {
+        metered!("cancel_group_transfer");
+        assert_one_yocto();
+
+        let transfer = self
+            .group_transfers
+            .get(&group_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("No pending transfer for group"));
+        if transfer.from_account_id != env::predecessor_account_id() {
+            self.ownership.assert_owner();
+            self.on_admin_action("cancel_group_transfer", format!("group {}", group_id));
+        }
+
+        self.group_transfers.remove(&group_id);
+    }
+
+    /// Moves the sponsor of every badge in the group sponsored by the
+    /// requester to the new account, along with any bonds. If the group is
+    /// verified, the new account is added to its sponsors.
+    #[payable]
+    pub fn approve_group_transfer(&mut self, group_id: String) -> Vec<String> {
+        metered!("approve_group_transfer");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let transfer = self
+            .group_transfers
+            .remove(&group_id)
+            .unwrap_or_else(|| env::panic_str("No pending transfer for group"));
+        self.on_admin_action(
+            "approve_group_transfer",
+            format!(
+                "group {} {} -> {}",
+                group_id, transfer.from_account_id, transfer.to_account_id
+            ),
+        );
+
+        let badges: Vec<Badge> = self
+            .sponsored_badges_in_group(&group_id, &transfer.from_account_id)
+            .collect();
+        let badge_ids: Vec<String> = badges.iter().map(|b| b.id.clone()).collect();
+        for badge in badges {
+            self.save_badge(&Badge {
+                sponsor_id: Some(transfer.to_account_id.clone()),
+                ..badge
+            });
+        }
+
+        if let Some(mut verification) = self.group_verifications.get(&group_id).cloned() {
+            if !verification.may_sponsor(&transfer.to_account_id) {
+                verification.sponsors.push(transfer.to_account_id.clone());
+                self.group_verifications
+                    .insert(group_id.clone(), verification);
+            }
+        }
+
+        emit_event(
+            "group_transferred",
+            [GroupTransferredEvent {
+                group_id: &group_id,
+                from_account_id: &transfer.from_account_id,
+                to_account_id: &transfer.to_account_id,
+                badge_ids: &badge_ids,
+            }],
+        );
+        emit_records_changed("approve_group_transfer", "badge", &badge_ids);
+
+        badge_ids
+    }
+
+    /// Moves every badge in `from_group_id` into `into_group_id`. Badge IDs,
+    /// sponsors and awards are unchanged.
+    #[payable]
+    pub fn merge_groups(&mut self, from_group_id: String, into_group_id: String) -> Vec<String> {
+        metered!("merge_groups");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.input_limits.check_id("Group ID", &into_group_id);
+        require!(
+            from_group_id != into_group_id,
+            "Cannot merge a group into itself"
+        );
+        let badge_ids: Vec<String> = self.badge_indexes.ids_in_group(&from_group_id).collect();
+        require!(!badge_ids.is_empty(), "Group has no badges");
+        self.on_admin_action(
+            "merge_groups",
+            format!("{} -> {}", from_group_id, into_group_id),
+        );
+
+        for badge_id in badge_ids.iter() {
+            let badge = self.badges.get(badge_id).unwrap();
+            self.save_badge(&Badge {
+                group_id: into_group_id.clone(),
+                ..badge
+            });
+        }
+        self.group_transfers.remove(&from_group_id);
+        self.merge_group_stats(&from_group_id, &into_group_id);
+
+        emit_event(
+            "group_merged",
+            [GroupMergedEvent {
+                from_group_id: &from_group_id,
+                into_group_id: &into_group_id,
+                badge_ids: &badge_ids,
+            }],
+        );
+
+        badge_ids
+    }
+}
+
+impl StatsGallery {
+    /// For views only; the default is never stored on the badge
+    pub(crate) fn with_default_media(&self, badge: Badge) -> Badge {
+        if badge.media_url.is_some() {
+            return badge;
+        }
+
+        Badge {
+            media_url: self.group_media.get(&badge.group_id).cloned(),
+            ..badge
+        }
+    }
+
+    fn sponsored_badges_in_group<'a>(
+        &'a self,
+        group_id: &'a str,
+        sponsor_id: &'a AccountId,
+    ) -> impl Iterator<Item = Badge> + 'a {
+        self.badge_indexes
+            .ids_in_group(group_id)
+            .filter_map(|badge_id| self.badges.get(&badge_id))
+            .filter(move |badge| badge.sponsor_id.as_ref() == Some(sponsor_id))
+    }
+
+    fn emit_group_event(&self, event: &str, group_id: &str, account_id: &AccountId) {
+        emit_event(
+            event,
+            [GroupEvent {
+                group_id,
+                account_id,
+            }],
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+/// Balance and storage figures for monitoring. Amounts are in yoctoNEAR.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Health {
+    pub account_balance: U128,
+    /// Bytes of state the contract account uses
+    pub storage_usage: U64,
+    /// Balance the protocol locks for `storage_usage`
+    pub storage_staking_cost: U128,
+    /// Balance owed to others: escrowed deposits, bonds, storage deposits and
+    /// referral rewards
+    pub liabilities: U128,
+    pub escrowed: U128,
+    pub bonds_held: U128,
+    pub storage_deposits_held: U128,
+    pub referral_rewards_owed: U128,
+    /// Balance left over once storage staking and liabilities are covered,
+    /// i.e. what new writes can be paid with. Writes that need more start
+    /// failing, or are paid for out of funds that are owed.
+    pub headroom: U128,
+    /// `headroom` as bytes of storage
+    pub headroom_bytes: U64,
+    /// Accepted proposals whose side effects have not run yet
+    pub pending_executions: U64,
+    /// Accepted create proposals waiting for a free badge slot
+    pub waitlisted: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Lets monitoring alert before the contract runs short of balance to
+    /// back its storage
+    pub fn get_health(&self) -> Health {
+        let storage_usage = env::storage_usage();
+        let escrowed = self.sponsorship.get_total_escrowed();
+        let headroom = self.available_balance();
+
+        Health {
+            account_balance: env::account_balance().as_yoctonear().into(),
+            storage_usage: storage_usage.into(),
+            storage_staking_cost: (Balance::from(storage_usage)
+                * env::storage_byte_cost().as_yoctonear())
+            .into(),
+            liabilities: (escrowed
+                + self.total_bonds_held
+                + self.total_storage_deposits_held
+                + self.total_referral_rewards_owed)
+                .into(),
+            escrowed: escrowed.into(),
+            bonds_held: self.total_bonds_held.into(),
+            storage_deposits_held: self.total_storage_deposits_held.into(),
+            referral_rewards_owed: self.total_referral_rewards_owed.into(),
+            headroom: headroom.into(),
+            headroom_bytes: ((headroom / env::storage_byte_cost().as_yoctonear()) as u64).into(),
+            pending_executions: u64::from(self.pending_executions.len()).into(),
+            waitlisted: u64::from(self.waitlist.len()).into(),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use super::*;
+
+/// Oldest updates are dropped past this many, so an author who never
+/// acknowledges does not grow contract storage without bound
+const MAX_UNACKED_UPDATES: usize = 50;
+
+/// A status change on one of an author's proposals. `seq` increases across
+/// all authors.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalUpdate {
+    pub seq: U64,
+    pub proposal_id: U64,
+    pub status: ProposalStatus,
+    pub updated_at: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Status changes on the account's proposals that it has not
+    /// acknowledged, oldest first
+    pub fn get_unacked_updates(&self, account_id: AccountId) -> Vec<ProposalUpdate> {
+        self.proposal_updates
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Acknowledges the caller's updates up to and including `seq`. Returns
+    /// how many were acknowledged.
+    pub fn ack_updates(&mut self, seq: U64) -> u32 {
+        metered!("ack_updates");
+        let account_id = env::predecessor_account_id();
+        let updates = match self.proposal_updates.get(&account_id).cloned() {
+            Some(updates) => updates,
+            None => return 0,
+        };
+
+        let (acked, unacked): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .partition(|update| update.seq.0 <= seq.0);
+        if unacked.is_empty() {
+            self.proposal_updates.remove(&account_id);
+        } else {
+            self.proposal_updates.insert(account_id, unacked);
+        }
+
+        acked.len() as u32
+    }
+}
+
+impl StatsGallery {
+    /// Adds a status change to the author's inbox. Submissions are left out,
+    /// as the author made them.
+    pub(crate) fn record_proposal_update(&mut self, proposal: &Proposal<BadgeAction>) {
+        if matches!(
+            proposal.status,
+            ProposalStatus::PENDING | ProposalStatus::DRAFT
+        ) {
+            return;
+        }
+
+        let mut updates = self
+            .proposal_updates
+            .get(&proposal.author_id)
+            .cloned()
+            .unwrap_or_default();
+        updates.push(ProposalUpdate {
+            seq: self.next_update_seq.into(),
+            proposal_id: proposal.id.into(),
+            status: proposal.status.clone(),
+            updated_at: now().into(),
+        });
+        self.next_update_seq += 1;
+        if updates.len() > MAX_UNACKED_UPDATES {
+            updates.remove(0);
+        }
+        self.proposal_updates
+            .insert(proposal.author_id.clone(), updates);
+    }
+}
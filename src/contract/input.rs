@@ -0,0 +1,170 @@
+use super::*;
+
+/// Limits on user-supplied strings, checked before they are stored. Lengths
+/// are in bytes.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct InputLimits {
+    /// Badge and group IDs
+    pub max_id_length: u32,
+    pub max_name_length: u32,
+    /// Badge and proposal descriptions
+    pub max_description_length: u32,
+    /// Reasons given for reports, holds, and other moderation actions
+    pub max_reason_length: u32,
+    /// Badge artwork and group default media
+    #[serde(default = "default_max_media_url_length")]
+    pub max_media_url_length: u32,
+}
+
+fn default_max_media_url_length() -> u32 {
+    512
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_id_length: 64,
+            max_name_length: 128,
+            max_description_length: 2048,
+            max_reason_length: 512,
+            max_media_url_length: default_max_media_url_length(),
+        }
+    }
+}
+
+impl InputLimits {
+    /// IDs are restricted to lowercase ASCII letters, digits, `-` and `_`, so
+    /// that two IDs that look the same are the same
+    pub fn check_id(&self, field: &str, value: &str) {
+        require!(!value.is_empty(), format!("{} cannot be empty", field));
+        self.check_length(field, value, self.max_id_length);
+        require!(
+            value
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_'),
+            format!(
+                "{} may only contain lowercase letters, digits, '-' and '_'",
+                field
+            )
+        );
+    }
+
+    pub fn check_name(&self, field: &str, value: &str) {
+        require!(
+            !value.trim().is_empty(),
+            format!("{} cannot be empty", field)
+        );
+        self.check_length(field, value, self.max_name_length);
+        Self::check_no_control_chars(field, value, false);
+    }
+
+    pub fn check_description(&self, field: &str, value: &str) {
+        self.check_length(field, value, self.max_description_length);
+        Self::check_no_control_chars(field, value, true);
+    }
+
+    pub fn check_reason(&self, value: &str) {
+        self.check_length("Reason", value, self.max_reason_length);
+        Self::check_no_control_chars("Reason", value, false);
+    }
+
+    /// Media URLs are stored as given and not fetched, so only their shape
+    /// is checked
+    pub fn check_media_url(&self, field: &str, value: &str) {
+        require!(!value.is_empty(), format!("{} cannot be empty", field));
+        self.check_length(field, value, self.max_media_url_length);
+        require!(
+            !value.chars().any(|c| c.is_whitespace() || c.is_control()),
+            format!("{} cannot contain whitespace", field)
+        );
+    }
+
+    fn check_length(&self, field: &str, value: &str, max_length: u32) {
+        require!(
+            value.len() <= max_length as usize,
+            format!("{} exceeds maximum length of {} bytes", field, max_length)
+        );
+    }
+
+    fn check_no_control_chars(field: &str, value: &str, multiline: bool) {
+        require!(
+            value
+                .chars()
+                .all(|c| !c.is_control() || (multiline && (c == '\n' || c == '\t'))),
+            format!("{} contains control characters", field)
+        );
+    }
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_input_limits(&self) -> InputLimits {
+        self.input_limits.clone()
+    }
+
+    #[payable]
+    pub fn set_input_limits(&mut self, input_limits: InputLimits) {
+        metered!("set_input_limits");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action("set_input_limits", format!("{:?}", input_limits));
+        self.input_limits = input_limits;
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn check_badge_input(&self, badge: &Badge) {
+        self.input_limits.check_id("Badge ID", &badge.id);
+        self.input_limits.check_id("Group ID", &badge.group_id);
+        self.input_limits.check_name("Badge name", &badge.name);
+        self.input_limits
+            .check_description("Badge description", &badge.description);
+        if let Some(media_url) = &badge.media_url {
+            self.input_limits
+                .check_media_url("Badge media URL", media_url);
+        }
+    }
+
+    pub(crate) fn check_proposal_input(&self, proposal: &Proposal<BadgeAction>) {
+        self.input_limits
+            .check_description("Proposal description", &proposal.description);
+
+        match &proposal.msg {
+            Some(BadgeAction::Create(create_request)) => self.check_create_input(create_request),
+            Some(BadgeAction::Extend(extend_request)) => {
+                self.input_limits.check_id("Badge ID", &extend_request.id);
+                if let Some(stream_id) = &extend_request.stream_id {
+                    self.input_limits.check_id("Stream ID", stream_id);
+                }
+            }
+            Some(BadgeAction::Package(package_request)) => {
+                self.input_limits
+                    .check_id("Package ID", &package_request.package_id);
+                for create_request in package_request.badges.iter() {
+                    self.check_create_input(create_request);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn check_create_input(&self, create_request: &BadgeCreate) {
+        self.input_limits.check_id("Badge ID", &create_request.id);
+        self.input_limits
+            .check_id("Group ID", &create_request.group_id);
+        self.input_limits
+            .check_name("Badge name", &create_request.name);
+        self.input_limits
+            .check_description("Badge description", &create_request.description);
+        if let Some(stream_id) = &create_request.stream_id {
+            self.input_limits.check_id("Stream ID", stream_id);
+        }
+        if let Some(media_url) = &create_request.media_url {
+            self.input_limits
+                .check_media_url("Badge media URL", media_url);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+/// Result of `check_invariants`. `violations` describes each inconsistency
+/// found; an empty list means the state checked is consistent.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InvariantReport {
+    pub ok: bool,
+    pub proposals_checked: U64,
+    pub badges_checked: U64,
+    pub accounts_checked: U64,
+    pub violations: Vec<String>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Verifies the contract's bookkeeping against its records, so that
+    /// monitoring can detect corrupted state, e.g. after an upgrade:
+    ///
+    /// - per-status deposit totals match the proposals and sum to the total
+    ///   deposits held, and every proposal is indexed
+    /// - every stored badge is indexed, and badge bonds and storage deposits
+    ///   sum to the totals held
+    /// - every unresolved badge proposal has its terms recorded (badges do not
+    ///   record their proposals, so the link is checked from this side)
+    /// - the awards of `account_ids` exist and refer to existing (or pruned)
+    ///   badges.
+    ///   Awards are only reachable by account, so only those listed are
+    ///   checked.
+    ///
+    /// Reads every proposal and badge, so it may run out of gas on a large
+    /// contract.
+    pub fn check_invariants(&self, account_ids: Option<Vec<AccountId>>) -> InvariantReport {
+        let mut violations = self.sponsorship.check_invariants();
+
+        let headers = self
+            .sponsorship
+            .get_headers(None, 0, self.sponsorship.len());
+        for header in headers.iter() {
+            let unresolved = matches!(
+                header.status,
+                ProposalStatus::PENDING
+                    | ProposalStatus::REJECTED
+                    | ProposalStatus::EXPIRED
+                    | ProposalStatus::DISPUTED
+                    | ProposalStatus::SCHEDULED
+            );
+            let is_badge_tag = matches!(
+                header.tag.as_str(),
+                TAG_BADGE_CREATE | TAG_BADGE_EXTEND | TAG_BADGE_PACKAGE
+            );
+            let awaits_terms = unresolved
+                || self.pending_executions.contains(&header.id)
+                || self.is_waitlisted(header.id);
+            if awaits_terms
+                && is_badge_tag
+                && self.proposal_terms.get(&header.id).cloned().is_none()
+            {
+                violations.push(format!("Proposal {} has no recorded terms", header.id));
+            }
+        }
+
+        let mut bonds = 0;
+        let mut storage_deposits = 0;
+        let mut badges_checked = 0;
+        for badge in self.badges.values() {
+            if !self.badge_indexes.contains(&badge) {
+                violations.push(format!("Badge {} is not indexed", badge.id));
+            }
+            bonds += badge.bond;
+            storage_deposits += badge.storage_deposit;
+            badges_checked += 1;
+        }
+        if self.badge_indexes.len() != badges_checked {
+            violations.push(format!(
+                "{} badges are indexed but {} are stored",
+                self.badge_indexes.len(),
+                badges_checked
+            ));
+        }
+        if bonds != self.total_bonds_held {
+            violations.push(format!(
+                "Badge bonds sum to {} but {} is held",
+                bonds, self.total_bonds_held
+            ));
+        }
+        if storage_deposits != self.total_storage_deposits_held {
+            violations.push(format!(
+                "Badge storage deposits sum to {} but {} is held",
+                storage_deposits, self.total_storage_deposits_held
+            ));
+        }
+
+        let account_ids = account_ids.unwrap_or_default();
+        for account_id in account_ids.iter() {
+            for badge_id in self.account_badge_ids(account_id, 0, u64::MAX) {
+                if self
+                    .awards
+                    .get(&(badge_id.clone(), account_id.clone()))
+                    .cloned()
+                    .is_none()
+                {
+                    violations.push(format!(
+                        "Award of {} to {} is listed but missing",
+                        badge_id, account_id
+                    ));
+                } else if self.badges.get(&badge_id).is_none()
+                    && !self.pruned_badge_ids.contains(&badge_id)
+                {
+                    violations.push(format!(
+                        "Award of {} to {} refers to a missing badge",
+                        badge_id, account_id
+                    ));
+                }
+            }
+        }
+
+        InvariantReport {
+            ok: violations.is_empty(),
+            proposals_checked: (headers.len() as u64).into(),
+            badges_checked: badges_checked.into(),
+            accounts_checked: (account_ids.len() as u64).into(),
+            violations,
+        }
+    }
+}
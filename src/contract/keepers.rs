@@ -0,0 +1,166 @@
+use super::*;
+
+/// Permissionless maintenance work that callers ("keepers") can be paid for
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum KeeperTask {
+    /// `spo_sweep_expired`, per proposal settled
+    PROPOSAL_SWEEP,
+    /// `process_activations`, per `badge_activated` event
+    BADGE_ACTIVATIONS,
+    /// `process_expirations`, per `badge_expired` event
+    BADGE_EXPIRATIONS,
+    /// `execute_accepted`, per proposal executed
+    ACCEPTED_EXECUTIONS,
+    /// `prune_badges`, per badge pruned
+    BADGE_PRUNING,
+}
+
+/// Reward configuration and payout accounting for one keeper task.
+///
+/// Keepers are paid per item of work done, never per call, so repeating a
+/// call that finds nothing to do earns nothing. Payouts are also drawn from
+/// a budget the owner sets; once it runs out, the work is still done but no
+/// longer paid until the owner tops it up.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeeperTaskState {
+    pub reward_per_item: U128,
+    /// Remaining amount that may be paid out for this task
+    pub budget: U128,
+    pub items_rewarded: U64,
+    pub rewards_paid: U128,
+}
+
+/// 0.001 NEAR per expired badge
+pub const DEFAULT_EXPIRATION_REWARD: Balance = 1_000_000_000_000_000_000_000;
+/// 0.1 NEAR, i.e. 100 expirations at the default reward
+pub const DEFAULT_EXPIRATION_BUDGET: Balance = 100_000_000_000_000_000_000_000;
+
+impl KeeperTaskState {
+    /// State of a task the owner has not configured. Only expirations are
+    /// rewarded out of the box.
+    pub fn default_for(task: KeeperTask) -> Self {
+        let (reward_per_item, budget) = match task {
+            KeeperTask::BADGE_EXPIRATIONS => (DEFAULT_EXPIRATION_REWARD, DEFAULT_EXPIRATION_BUDGET),
+            _ => (0, 0),
+        };
+
+        Self {
+            reward_per_item: reward_per_item.into(),
+            budget: budget.into(),
+            items_rewarded: U64(0),
+            rewards_paid: U128(0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct KeeperPaidEvent<'a> {
+    task: KeeperTask,
+    keeper_id: &'a AccountId,
+    items: U64,
+    reward: U128,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_keeper_task(&self, task: KeeperTask) -> KeeperTaskState {
+        self.keeper_tasks
+            .get(&task)
+            .cloned()
+            .unwrap_or_else(|| KeeperTaskState::default_for(task))
+    }
+
+    /// Sets the reward per item and the remaining budget for a task. Payout
+    /// accounting is kept.
+    #[payable]
+    pub fn set_keeper_reward(&mut self, task: KeeperTask, reward_per_item: U128, budget: U128) {
+        metered!("set_keeper_reward");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_keeper_reward",
+            format!(
+                "{:?} reward_per_item {} budget {}",
+                task, reward_per_item.0, budget.0
+            ),
+        );
+        let state = KeeperTaskState {
+            reward_per_item,
+            budget,
+            ..self.get_keeper_task(task)
+        };
+        self.keeper_tasks.insert(task, state);
+    }
+}
+
+impl StatsGallery {
+    /// Pays the predecessor for `items` units of work on `task`, within the
+    /// task's remaining budget and the contract's available balance, so that
+    /// rewards are never paid out of deposits, bonds or other funds the
+    /// contract holds for someone. Returns the amount paid.
+    pub(crate) fn pay_keeper(&mut self, task: KeeperTask, items: u64) -> Balance {
+        if items == 0 {
+            return 0;
+        }
+
+        let state = self.get_keeper_task(task);
+        let reward = state
+            .reward_per_item
+            .0
+            .saturating_mul(items.into())
+            .min(state.budget.0)
+            .min(self.available_balance());
+        if reward == 0 {
+            return 0;
+        }
+
+        self.keeper_tasks.insert(
+            task,
+            KeeperTaskState {
+                budget: (state.budget.0 - reward).into(),
+                items_rewarded: (state.items_rewarded.0 + items).into(),
+                rewards_paid: (state.rewards_paid.0 + reward).into(),
+                ..state
+            },
+        );
+
+        let keeper_id = env::predecessor_account_id();
+        emit_event(
+            "keeper_paid",
+            [KeeperPaidEvent {
+                task,
+                keeper_id: &keeper_id,
+                items: items.into(),
+                reward: reward.into(),
+            }],
+        );
+        self.assert_available_balance(reward);
+        transfer_out(keeper_id, reward, "keeper_reward", BalanceRef::default());
+
+        reward
+    }
+
+    /// Called by `spo_sweep_expired` with the number of proposals it settled
+    pub(crate) fn on_proposals_swept(&mut self, count: u64) {
+        self.pay_keeper(KeeperTask::PROPOSAL_SWEEP, count);
+    }
+}
@@ -0,0 +1,45 @@
+use super::*;
+
+impl StatsGallery {
+    /// Rejects accepting a create proposal whose badge starts further out than
+    /// the tag's `MAX_LEAD_TIME` allows, so that sponsors' funds are not held
+    /// long before the badge is delivered
+    pub(crate) fn check_lead_time(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        create_request: &BadgeCreate,
+    ) -> Result<(), ProposalValidationError> {
+        let max_lead_time = match self.sponsorship.get_lead_time_policy(&proposal.tag) {
+            LeadTimePolicy::MAX_LEAD_TIME(max_lead_time) => max_lead_time.0,
+            LeadTimePolicy::NONE | LeadTimePolicy::VEST_AT_START => return Ok(()),
+        };
+
+        let lead_time = create_request
+            .start_at
+            .map_or(0, |start_at| start_at.saturating_sub(now()));
+        if lead_time > max_lead_time {
+            return Err(ProposalValidationError::LeadTimeExceeded {
+                lead_time: lead_time.into(),
+                max_lead_time: max_lead_time.into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Under the tag's `VEST_AT_START` policy, keeps the deposit of an
+    /// accepted create proposal in escrow until its badge starts
+    pub(crate) fn vest_at_start(
+        &mut self,
+        proposal: &Proposal<BadgeAction>,
+        create_request: &BadgeCreate,
+    ) {
+        if self.sponsorship.get_lead_time_policy(&proposal.tag) != LeadTimePolicy::VEST_AT_START {
+            return;
+        }
+
+        if let Some(start_at) = create_request.start_at.filter(|&start_at| start_at > now()) {
+            self.sponsorship.vest_escrow_at(proposal.id, start_at);
+        }
+    }
+}
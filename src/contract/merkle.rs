@@ -0,0 +1,127 @@
+use super::*;
+
+/// Deep enough for 2^32 recipients
+const MAX_PROOF_LENGTH: usize = 32;
+const BITMAP_WORD_BITS: u64 = 128;
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// A bulk distribution of a badge. Recipient `i` of the list is the leaf
+/// `sha256(0x00 || i as u64 little-endian || account_id)`, and each parent
+/// node is `sha256(0x01 || left || right)`; the position of a leaf follows
+/// from its index. Claimed indices are tracked in a bitmap per root.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MerkleDrop {
+    pub root: Base64VecU8,
+    pub leaf_count: U64,
+}
+
+pub fn merkle_leaf(index: u64, account_id: &AccountId) -> Vec<u8> {
+    env::sha256(
+        &[
+            &[LEAF_PREFIX],
+            &index.to_le_bytes()[..],
+            account_id.as_bytes(),
+        ]
+        .concat(),
+    )
+}
+
+pub fn merkle_node(left: &[u8], right: &[u8]) -> Vec<u8> {
+    env::sha256(&[&[NODE_PREFIX], left, right].concat())
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_merkle_drop(&self, badge_id: String) -> Option<MerkleDrop> {
+        self.merkle_drops.get(&badge_id).cloned()
+    }
+
+    /// Starts a bulk distribution of a badge, replacing any previous one.
+    /// May be called by the owner or the badge's sponsor.
+    #[payable]
+    pub fn set_merkle_root(&mut self, badge_id: String, root: Base64VecU8, leaf_count: U64) {
+        metered!("set_merkle_root");
+        assert_one_yocto();
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        if badge.sponsor_id != Some(env::predecessor_account_id()) {
+            self.ownership.assert_owner();
+            self.on_admin_action(
+                "set_merkle_root",
+                format!("badge {} leaf_count {}", badge_id, leaf_count.0),
+            );
+        }
+        require!(root.0.len() == 32, "Merkle root must be 32 bytes");
+        require!(leaf_count.0 > 0, "Leaf count must be greater than 0");
+
+        self.merkle_drops
+            .insert(badge_id, MerkleDrop { root, leaf_count });
+    }
+
+    pub fn is_merkle_leaf_claimed(&self, badge_id: String, index: U64) -> bool {
+        match self.merkle_drops.get(&badge_id).cloned() {
+            Some(drop) => {
+                let (word, bit) = self.merkle_bitmap_word(&drop, index.0);
+                word & bit != 0
+            }
+            None => false,
+        }
+    }
+
+    /// Awards a badge to the caller if they are recipient `index` of the
+    /// badge's distribution. `proof` lists the sibling hashes from the leaf
+    /// up to the root.
+    pub fn claim_badge_with_proof(
+        &mut self,
+        badge_id: String,
+        index: U64,
+        proof: Vec<Base64VecU8>,
+    ) -> Award {
+        metered!("claim_badge_with_proof");
+        let drop = self
+            .merkle_drops
+            .get(&badge_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Badge has no distribution"));
+        let index = index.0;
+        require!(index < drop.leaf_count.0, "Index out of range");
+        require!(proof.len() <= MAX_PROOF_LENGTH, "Proof is too long");
+
+        let account_id = env::predecessor_account_id();
+        let mut node = merkle_leaf(index, &account_id);
+        for (level, sibling) in proof.iter().enumerate() {
+            node = if (index >> level) & 1 == 0 {
+                merkle_node(&node, &sibling.0)
+            } else {
+                merkle_node(&sibling.0, &node)
+            };
+        }
+        require!(node == drop.root.0, "Invalid proof");
+
+        let (word, bit) = self.merkle_bitmap_word(&drop, index);
+        require!(word & bit == 0, "Already claimed");
+        self.merkle_claims
+            .insert((drop.root.0.clone(), index / BITMAP_WORD_BITS), word | bit);
+
+        self.award_badge(badge_id, account_id)
+    }
+}
+
+impl StatsGallery {
+    /// The bitmap word holding `index`, and the bit for `index` within it
+    fn merkle_bitmap_word(&self, drop: &MerkleDrop, index: u64) -> (u128, u128) {
+        let word = self
+            .merkle_claims
+            .get(&(drop.root.0.clone(), index / BITMAP_WORD_BITS))
+            .cloned()
+            .unwrap_or(0);
+
+        (word, 1 << (index % BITMAP_WORD_BITS))
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+/// Counters for one call method, accumulated since the last reset
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MethodMetrics {
+    pub method: String,
+    pub calls: U64,
+    pub total_gas_burnt: U64,
+    pub max_gas_burnt: U64,
+    pub last_gas_burnt: U64,
+    /// Net bytes of storage added (or freed, if negative). Collections that
+    /// are only flushed with the contract state at the end of the call
+    /// aren't counted.
+    pub total_storage_delta: I64,
+    pub last_storage_delta: I64,
+}
+
+/// Measures the enclosing method from creation until it is dropped. Created
+/// by the `metered!` macro.
+pub struct Meter {
+    method: &'static str,
+    gas_at_start: u64,
+    storage_at_start: StorageUsage,
+}
+
+impl Meter {
+    pub fn start(method: &'static str) -> Self {
+        Self {
+            method,
+            gas_at_start: env::used_gas().as_gas(),
+            storage_at_start: env::storage_usage(),
+        }
+    }
+}
+
+impl Drop for Meter {
+    fn drop(&mut self) {
+        let gas_burnt = env::used_gas().as_gas().saturating_sub(self.gas_at_start);
+        let storage_delta = env::storage_usage() as i64 - self.storage_at_start as i64;
+
+        let mut store = MetricsStore::new();
+        let metrics = match store.metrics.get(&self.method.to_string()) {
+            Some(m) => MethodMetrics {
+                calls: (m.calls.0 + 1).into(),
+                total_gas_burnt: (m.total_gas_burnt.0 + gas_burnt).into(),
+                max_gas_burnt: u64::max(m.max_gas_burnt.0, gas_burnt).into(),
+                last_gas_burnt: gas_burnt.into(),
+                total_storage_delta: (m.total_storage_delta.0 + storage_delta).into(),
+                last_storage_delta: storage_delta.into(),
+                ..m.clone()
+            },
+            None => {
+                let mut methods = store.methods.get().unwrap_or_default();
+                methods.push(self.method.to_string());
+                store.methods.set(&methods);
+
+                MethodMetrics {
+                    method: self.method.to_string(),
+                    calls: 1.into(),
+                    total_gas_burnt: gas_burnt.into(),
+                    max_gas_burnt: gas_burnt.into(),
+                    last_gas_burnt: gas_burnt.into(),
+                    total_storage_delta: storage_delta.into(),
+                    last_storage_delta: storage_delta.into(),
+                }
+            }
+        };
+        store.metrics.insert(metrics.method.clone(), metrics);
+    }
+}
+
+/// Kept outside of the contract struct so that builds with and without the
+/// `metrics` feature share the same state layout. It is rebuilt on every
+/// call, so the method list uses the legacy `LazyOption`, which unlike
+/// `store::LazyOption` doesn't write its initial value back when dropped.
+struct MetricsStore {
+    methods: collections::LazyOption<Vec<String>>,
+    metrics: LookupMap<String, MethodMetrics>,
+}
+
+impl MetricsStore {
+    fn new() -> Self {
+        let k = StorageKey::METHOD_METRICS.into_storage_key();
+
+        Self {
+            methods: collections::LazyOption::new(prefix_key(&k, NestedKey::METRICS_METHODS), None),
+            metrics: LookupMap::new(prefix_key(&k, NestedKey::METRICS_COUNTERS)),
+        }
+    }
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_method_metrics(&self) -> Vec<MethodMetrics> {
+        let store = MetricsStore::new();
+
+        store
+            .methods
+            .get()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|method| store.metrics.get(method).cloned())
+            .collect()
+    }
+
+    /// Starts a new measurement window
+    #[payable]
+    pub fn reset_method_metrics(&mut self) {
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let mut store = MetricsStore::new();
+        for method in store.methods.get().unwrap_or_default() {
+            store.metrics.remove(&method);
+        }
+        store.methods.remove();
+
+        self.on_admin_action("reset_method_metrics", String::new());
+    }
+}
@@ -0,0 +1,374 @@
+use super::*;
+
+// Layout of state version 1, the first release of the contract. Its
+// collections were keyed with hand-picked ASCII suffixes (`b"p"`, `b"t"`,
+// ...) rather than `NestedKey`, so they do not overlap the current ones.
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct OwnershipV1 {
+    pub owner: Option<AccountId>,
+    pub proposed_owner: collections::LazyOption<AccountId>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct ProposalV1 {
+    pub id: u64,
+    pub description: String,
+    pub tag: String,
+    pub msg: Option<BadgeActionV1>,
+    pub author_id: AccountId,
+    pub deposit: Balance,
+    pub status: ProposalStatus,
+    pub created_at: u64,
+    pub duration: Option<u64>,
+    pub resolved_at: Option<u64>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct SponsorshipV1 {
+    pub tags: collections::UnorderedSet<String>,
+    pub proposals: collections::Vector<ProposalV1>,
+    pub proposal_duration: collections::LazyOption<u64>,
+    pub total_deposits: Balance,
+    pub total_accepted_deposits: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct BadgeV1 {
+    pub id: String,
+    pub group_id: String,
+    pub name: String,
+    pub description: String,
+    pub is_enabled: bool,
+    pub created_at: u64,
+    pub start_at: u64,
+    pub duration: Option<u64>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct StatsGalleryV1 {
+    pub ownership: OwnershipV1,
+    pub sponsorship: SponsorshipV1,
+    pub badges: collections::UnorderedMap<String, BadgeV1>,
+    pub badge_rate_per_day: Balance,
+    pub badge_max_active_duration: u64,
+    pub badge_min_creation_deposit: Balance,
+}
+
+// Layout of state version 2, the last kept in near-sdk 4's collections.
+// Fields whose collection lays out its entries differently in `store` keep
+// the old type here, and are moved over by `migrate`.
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct StatsGalleryV2 {
+    pub ownership: OwnershipV2,
+    pub sponsorship: SponsorshipV2<VersionedBadgeAction>,
+    pub badges: BadgeStoreV2,
+    pub badge_rate_per_day: Balance,
+    pub badge_max_active_duration: u64,
+    pub badge_min_creation_deposit: Balance,
+    pub awards: LookupMap<(String, AccountId), Award>,
+    pub account_awards: LookupMap<AccountId, Vec<String>>,
+    pub account_award_sets: LookupMap<AccountId, collections::UnorderedSet<String>>,
+    pub claim_keys: LookupMap<PublicKey, String>,
+    pub badge_indexes: BadgeIndexesV2,
+    pub featured_badge_ids: Vec<String>,
+    pub audit_log: AuditLogV2,
+    pub badge_creation_bond: Balance,
+    pub total_bonds_held: Balance,
+    pub proposal_terms: LookupMap<u64, BadgeTerms>,
+    pub badge_reports: LookupMap<String, Vec<BadgeReport>>,
+    pub badge_report_fee: Balance,
+    pub badge_report_threshold: Option<u64>,
+    pub input_limits: InputLimits,
+    pub activation_queue: collections::TreeMap<(u64, String), ()>,
+    pub expiration_queue: collections::TreeMap<(u64, String), ()>,
+    pub keeper_tasks: LookupMap<KeeperTask, KeeperTaskState>,
+    pub group_verifications: LookupMap<String, GroupVerification>,
+    pub oracles: collections::UnorderedSet<AccountId>,
+    pub attestations: LookupMap<(String, AccountId), Attestation>,
+    pub merkle_drops: LookupMap<String, MerkleDrop>,
+    pub merkle_claims: LookupMap<(Vec<u8>, u64), u128>,
+    pub award_validity: LookupMap<String, u64>,
+    pub group_transfers: LookupMap<String, GroupTransfer>,
+    pub view_relayers: collections::UnorderedSet<AccountId>,
+    pub badge_views: LookupMap<String, u64>,
+    pub sunset: Option<Sunset>,
+    pub supporters: collections::Vector<Supporter>,
+    pub recent_donations: collections::Vector<Donation>,
+    pub donations_count: u64,
+    pub donor_totals: LookupMap<AccountId, Balance>,
+    pub top_donors: collections::TreeMap<(Balance, AccountId), ()>,
+    pub stream_config: Option<StreamConfig>,
+    pub badge_streams: LookupMap<String, BadgeStream>,
+    pub wrap_tokens: collections::UnorderedSet<AccountId>,
+    pub treasury_positions: LookupMap<AccountId, TreasuryPosition>,
+    pub total_wrapped: Balance,
+    pub total_wrap_pending: Balance,
+    pub referral_share_bps: u16,
+    pub referral_rewards: LookupMap<AccountId, Balance>,
+    pub total_referral_rewards_owed: Balance,
+    pub promo_codes: collections::UnorderedMap<String, PromoCode>,
+    pub free_tier_accounts: collections::UnorderedSet<AccountId>,
+    pub pending_executions: collections::UnorderedSet<u64>,
+    pub max_badges_per_sponsor: Option<u32>,
+    pub max_active_badges: Option<u32>,
+    pub waitlist: collections::TreeMap<u64, WaitlistEntry>,
+    pub next_waitlist_position: u64,
+    pub proposal_updates: LookupMap<AccountId, Vec<ProposalUpdate>>,
+    pub next_update_seq: u64,
+    pub group_stats: LookupMap<String, GroupStats>,
+    pub group_media: LookupMap<String, String>,
+    pub total_storage_deposits_held: Balance,
+    pub badge_prune_after: Option<u64>,
+    pub pruned_badge_ids: LookupSet<String>,
+    pub treasury_account_id: Option<AccountId>,
+    pub total_forwarded: Balance,
+    pub total_forward_pending: Balance,
+    pub terms_offers: LookupMap<u64, TermsOffer>,
+    pub packages: collections::UnorderedMap<String, Package>,
+    pub proposal_packages: LookupMap<u64, Package>,
+    pub featured_until: LookupMap<String, u64>,
+    pub featured_queue: collections::TreeMap<(u64, String), ()>,
+}
+
+/// Key the SDK keeps the contract struct under
+const STATE_KEY: &[u8] = b"STATE";
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Rebuilds state version 1 or 2 in the current layout. Call it in the
+    /// same transaction that deploys this code over an older contract; any
+    /// other state fails to deserialize and the call panics.
+    ///
+    /// From version 1, proposals keep their IDs, and their deposits stay
+    /// where they were: held for pending and rejected proposals, which the
+    /// author may still rescind, and already settled for the rest. Settings
+    /// added since version 1 start at their defaults.
+    ///
+    /// From version 2, every record is kept. Collections whose entries are
+    /// laid out differently since near-sdk 5 are rewritten, except each
+    /// account's award set, which moves over on the account's next award.
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(STATE_KEY)
+            .unwrap_or_else(|| env::panic_str("Contract is not initialized"));
+
+        // A version 1 state is far too short to read as version 2
+        match StatsGalleryV2::try_from_slice(&state) {
+            Ok(old) => Self::migrate_from_v2(old),
+            Err(_) => Self::migrate_from_v1(
+                StatsGalleryV1::try_from_slice(&state)
+                    .unwrap_or_else(|_| env::panic_str("Unknown state version")),
+            ),
+        }
+    }
+}
+
+impl StatsGallery {
+    fn migrate_from_v2(old: StatsGalleryV2) -> Self {
+        Self {
+            ownership: Ownership::from_v2(old.ownership, StorageKey::OWNERSHIP),
+            sponsorship: Sponsorship::from_v2(old.sponsorship, StorageKey::SPONSORSHIP),
+            badges: BadgeStore::from_v2(old.badges, StorageKey::BADGES),
+            badge_rate_per_day: old.badge_rate_per_day,
+            badge_max_active_duration: old.badge_max_active_duration,
+            badge_min_creation_deposit: old.badge_min_creation_deposit,
+            awards: old.awards,
+            account_awards: old.account_awards,
+            account_award_sets_v2: old.account_award_sets,
+            account_award_sets: LookupMap::new(StorageKey::ACCOUNT_AWARD_SETS_V3),
+            claim_keys: old.claim_keys,
+            badge_indexes: BadgeIndexes::from_v2(old.badge_indexes, StorageKey::BADGE_INDEXES),
+            featured_badge_ids: old.featured_badge_ids,
+            audit_log: AuditLog::from_v2(old.audit_log, StorageKey::AUDIT_LOG),
+            badge_creation_bond: old.badge_creation_bond,
+            total_bonds_held: old.total_bonds_held,
+            proposal_terms: old.proposal_terms,
+            badge_reports: old.badge_reports,
+            badge_report_fee: old.badge_report_fee,
+            badge_report_threshold: old.badge_report_threshold,
+            input_limits: old.input_limits,
+            activation_queue: migrate_tree_map(
+                old.activation_queue,
+                StorageKey::ACTIVATION_QUEUE.into_storage_key(),
+            ),
+            expiration_queue: migrate_tree_map(
+                old.expiration_queue,
+                StorageKey::EXPIRATION_QUEUE.into_storage_key(),
+            ),
+            keeper_tasks: old.keeper_tasks,
+            group_verifications: old.group_verifications,
+            oracles: migrate_set(old.oracles, StorageKey::ORACLES.into_storage_key()),
+            attestations: old.attestations,
+            merkle_drops: old.merkle_drops,
+            merkle_claims: old.merkle_claims,
+            award_validity: old.award_validity,
+            group_transfers: old.group_transfers,
+            view_relayers: migrate_set(
+                old.view_relayers,
+                StorageKey::VIEW_RELAYERS.into_storage_key(),
+            ),
+            badge_views: old.badge_views,
+            sunset: old.sunset,
+            supporters: migrate_vector(old.supporters, StorageKey::SUPPORTERS.into_storage_key()),
+            recent_donations: migrate_vector(
+                old.recent_donations,
+                StorageKey::RECENT_DONATIONS.into_storage_key(),
+            ),
+            donations_count: old.donations_count,
+            donor_totals: old.donor_totals,
+            top_donors: migrate_tree_map(old.top_donors, StorageKey::TOP_DONORS.into_storage_key()),
+            stream_config: old.stream_config,
+            badge_streams: old.badge_streams,
+            wrap_tokens: migrate_set(old.wrap_tokens, StorageKey::WRAP_TOKENS.into_storage_key()),
+            treasury_positions: old.treasury_positions,
+            total_wrapped: old.total_wrapped,
+            total_wrap_pending: old.total_wrap_pending,
+            referral_share_bps: old.referral_share_bps,
+            referral_rewards: old.referral_rewards,
+            total_referral_rewards_owed: old.total_referral_rewards_owed,
+            promo_codes: migrate_map(old.promo_codes, StorageKey::PROMO_CODES.into_storage_key()),
+            free_tier_accounts: migrate_set(
+                old.free_tier_accounts,
+                StorageKey::FREE_TIER_ACCOUNTS.into_storage_key(),
+            ),
+            pending_executions: migrate_set(
+                old.pending_executions,
+                StorageKey::PENDING_EXECUTIONS.into_storage_key(),
+            ),
+            max_badges_per_sponsor: old.max_badges_per_sponsor,
+            max_active_badges: old.max_active_badges,
+            waitlist: migrate_tree_map(old.waitlist, StorageKey::WAITLIST.into_storage_key()),
+            next_waitlist_position: old.next_waitlist_position,
+            proposal_updates: old.proposal_updates,
+            next_update_seq: old.next_update_seq,
+            group_stats: old.group_stats,
+            group_media: old.group_media,
+            total_storage_deposits_held: old.total_storage_deposits_held,
+            badge_prune_after: old.badge_prune_after,
+            pruned_badge_ids: old.pruned_badge_ids,
+            treasury_account_id: old.treasury_account_id,
+            total_forwarded: old.total_forwarded,
+            total_forward_pending: old.total_forward_pending,
+            terms_offers: old.terms_offers,
+            packages: migrate_map(old.packages, StorageKey::PACKAGES.into_storage_key()),
+            proposal_packages: old.proposal_packages,
+            featured_until: old.featured_until,
+            featured_queue: migrate_tree_map(
+                old.featured_queue,
+                StorageKey::FEATURED_QUEUE.into_storage_key(),
+            ),
+            claim_issuers: LookupMap::new(StorageKey::CLAIM_ISSUERS),
+            signed_claim_nonces: LookupSet::new(StorageKey::SIGNED_CLAIM_NONCES),
+        }
+    }
+
+    fn migrate_from_v1(old: StatsGalleryV1) -> Self {
+        let StatsGalleryV1 {
+            ownership: mut old_ownership,
+            sponsorship: mut old_sponsorship,
+            badges: mut old_badges,
+            badge_rate_per_day,
+            badge_max_active_duration,
+            badge_min_creation_deposit,
+        } = old;
+
+        let mut contract = Self::new(
+            env::current_account_id(),
+            old_sponsorship.proposal_duration.get().unwrap_or(0).into(),
+            badge_rate_per_day.into(),
+            badge_max_active_duration.into(),
+            badge_min_creation_deposit.into(),
+        );
+
+        contract.ownership.owner = old_ownership.owner.take();
+        if let Some(proposed_owner) = old_ownership.proposed_owner.take() {
+            contract.ownership.proposed_owner.set(Some(proposed_owner));
+        }
+
+        let tags = contract.sponsorship.get_tags();
+        contract.sponsorship.remove_tags(tags);
+        contract.sponsorship.add_tags(old_sponsorship.tags.to_vec());
+        contract
+            .sponsorship
+            .set_duration(old_sponsorship.proposal_duration.take(), false);
+
+        for old_proposal in old_sponsorship.proposals.iter() {
+            let proposal = contract.sponsorship.import(Proposal {
+                id: old_proposal.id,
+                uid: old_proposal.id,
+                description: old_proposal.description,
+                tag: old_proposal.tag,
+                msg: old_proposal.msg.map(BadgeAction::from),
+                author_id: old_proposal.author_id,
+                deposit: old_proposal.deposit,
+                status: old_proposal.status,
+                created_at: old_proposal.created_at,
+                duration: old_proposal.duration,
+                resolved_at: old_proposal.resolved_at,
+                nonce: None,
+                dispute: None,
+                accept_at: None,
+                refunded: 0,
+                approved_by: None,
+                finalized_by: None,
+                storage_fee: 0,
+                referrer_id: None,
+                promo_code: None,
+                previous_proposal_id: None,
+                claimed_by: None,
+                claimed_at: None,
+                // Computed by `import`
+                msg_hash: None,
+                content_hash: Base64VecU8(vec![]),
+                expires_at: None,
+                is_expired: false,
+            });
+            // Version 1 only had badge tags, priced at the rates carried over
+            if matches!(
+                proposal.status,
+                ProposalStatus::PENDING | ProposalStatus::REJECTED
+            ) {
+                let terms = contract.current_terms();
+                contract.proposal_terms.insert(proposal.id, terms);
+            }
+        }
+
+        for old_badge in old_badges.values() {
+            contract.save_badge(&Badge {
+                id: old_badge.id,
+                group_id: old_badge.group_id,
+                name: old_badge.name,
+                description: old_badge.description,
+                is_enabled: old_badge.is_enabled,
+                created_at: old_badge.created_at,
+                start_at: old_badge.start_at,
+                duration: old_badge.duration,
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+        }
+
+        old_sponsorship.tags.clear();
+        old_sponsorship.proposals.clear();
+        old_badges.clear();
+
+        contract
+    }
+}
@@ -0,0 +1,265 @@
+use super::*;
+
+/// 0.01 NEAR, enough to cover storing a report
+pub const DEFAULT_BADGE_REPORT_FEE: Balance = 10_000_000_000_000_000_000_000;
+const COMMUNITY_HOLD_REASON: &str = "Reported by community";
+
+/// A badge under moderator review. Held badges are hidden from public views
+/// and cannot be awarded, and their expiry clock is paused as if disabled.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeHold {
+    pub reason: String,
+    #[serde(with = "dec_string")]
+    pub held_at: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeReport {
+    pub reporter_id: AccountId,
+    pub reason: String,
+    #[serde(with = "dec_string")]
+    pub reported_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeModerationEvent<'a> {
+    badge_id: &'a str,
+    sponsor_id: Option<&'a AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    #[payable]
+    pub fn hold_badge(&mut self, badge_id: String, reason: String) -> Badge {
+        metered!("hold_badge");
+        assert_one_yocto();
+        self.ownership.assert_owner_or_role(Role::MODERATOR);
+
+        self.input_limits.check_reason(&reason);
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+
+        self.on_admin_action(
+            "hold_badge",
+            format!("badge {} reason {}", badge_id, reason),
+        );
+        self.place_on_hold(badge, reason)
+    }
+
+    pub fn get_badge_reports(&self, badge_id: String) -> Vec<BadgeReport> {
+        self.badge_reports
+            .get(&badge_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Lets anyone flag a badge for review. Each account may report a badge
+    /// once, and must attach the report fee. Once the number of reports
+    /// reaches the report threshold, the badge is put on hold automatically.
+    #[payable]
+    pub fn report_badge(&mut self, badge_id: String, reason: String) {
+        metered!("report_badge");
+        let attached_deposit = env::attached_deposit().as_yoctonear();
+        require!(
+            attached_deposit >= self.badge_report_fee,
+            format!(
+                "Insufficient deposit. Required: {} yoctoNEAR Received: {} yoctoNEAR",
+                &self.badge_report_fee, &attached_deposit
+            )
+        );
+
+        self.input_limits.check_reason(&reason);
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        require!(badge.hold.is_none(), "Badge is already on hold");
+
+        let reporter_id = env::predecessor_account_id();
+        let mut reports = self.get_badge_reports(badge_id.clone());
+        require!(
+            reports.iter().all(|r| r.reporter_id != reporter_id),
+            "Badge already reported by account"
+        );
+        reports.push(BadgeReport {
+            reporter_id,
+            reason: reason.clone(),
+            reported_at: now(),
+        });
+        self.badge_reports.insert(badge_id.clone(), reports.clone());
+
+        self.emit_moderation_event("badge_reported", &badge, Some(&reason));
+
+        if let Some(threshold) = self.badge_report_threshold {
+            if reports.len() as u64 >= threshold && badge.is_enabled {
+                self.place_on_hold(badge, COMMUNITY_HOLD_REASON.to_string());
+            }
+        }
+
+        let reporter_id = env::predecessor_account_id();
+        emit_balance_changed(
+            BalanceChangeKind::RECEIVED,
+            &reporter_id,
+            attached_deposit,
+            "report_fee",
+            BalanceRef::badge(&badge_id),
+        );
+        let refund = attached_deposit - self.badge_report_fee;
+        if refund > 0 {
+            transfer_out(
+                reporter_id,
+                refund,
+                "excess_deposit_refund",
+                BalanceRef::badge(&badge_id),
+            );
+        }
+    }
+
+    pub fn get_badge_report_fee(&self) -> U128 {
+        self.badge_report_fee.into()
+    }
+
+    #[payable]
+    pub fn set_badge_report_fee(&mut self, badge_report_fee: U128) {
+        metered!("set_badge_report_fee");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let badge_report_fee = badge_report_fee.into();
+        self.on_admin_action(
+            "set_badge_report_fee",
+            format!("{} -> {}", self.badge_report_fee, badge_report_fee),
+        );
+        self.badge_report_fee = badge_report_fee;
+    }
+
+    pub fn get_badge_report_threshold(&self) -> Option<U64> {
+        self.badge_report_threshold.map(|x| x.into())
+    }
+
+    /// Number of reports that puts a badge on hold. `None` disables
+    /// automatic holds.
+    #[payable]
+    pub fn set_badge_report_threshold(&mut self, badge_report_threshold: Option<U64>) {
+        metered!("set_badge_report_threshold");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let badge_report_threshold = badge_report_threshold.map(u64::from);
+        require!(
+            badge_report_threshold != Some(0),
+            "Report threshold must be greater than 0"
+        );
+        self.on_admin_action(
+            "set_badge_report_threshold",
+            format!(
+                "{:?} -> {:?}",
+                self.badge_report_threshold, badge_report_threshold
+            ),
+        );
+        self.badge_report_threshold = badge_report_threshold;
+    }
+
+    /// Ends the hold and restores the badge to public views. Reports that
+    /// led to the hold are cleared.
+    #[payable]
+    pub fn release_badge(&mut self, badge_id: String) -> Badge {
+        metered!("release_badge");
+        assert_one_yocto();
+        self.ownership.assert_owner_or_role(Role::MODERATOR);
+
+        let badge = self.get_held_badge(&badge_id);
+        // Resumes the clock the same way re-enabling a disabled badge does
+        let badge = Badge {
+            hold: None,
+            is_enabled: false,
+            ..badge
+        }
+        .with_is_enabled(true, now());
+
+        self.on_admin_action("release_badge", format!("badge {}", badge_id));
+        self.badge_reports.remove(&badge_id);
+        self.save_badge(&badge);
+        self.emit_moderation_event("badge_released", &badge, None);
+
+        badge
+    }
+
+    /// Ends the hold by disabling the badge, recording why
+    #[payable]
+    pub fn disable_held_badge(&mut self, badge_id: String, reason: String) -> Badge {
+        metered!("disable_held_badge");
+        assert_one_yocto();
+        self.ownership.assert_owner_or_role(Role::MODERATOR);
+
+        self.input_limits.check_reason(&reason);
+        let badge = self.get_held_badge(&badge_id);
+        // The clock stays paused from when the badge was held
+        let badge = Badge {
+            hold: None,
+            is_enabled: false,
+            disabled_reason: Some(reason.clone()),
+            ..badge
+        };
+
+        self.on_admin_action(
+            "disable_held_badge",
+            format!("badge {} reason {}", badge_id, reason),
+        );
+        self.save_badge(&badge);
+        self.emit_moderation_event("badge_disabled", &badge, Some(&reason));
+
+        badge
+    }
+}
+
+impl StatsGallery {
+    fn place_on_hold(&mut self, badge: Badge, reason: String) -> Badge {
+        require!(badge.hold.is_none(), "Badge is already on hold");
+        require!(badge.is_enabled, "Badge is disabled");
+
+        let now = now();
+        let badge = Badge {
+            hold: Some(BadgeHold {
+                reason: reason.clone(),
+                held_at: now,
+            }),
+            paused_at: Some(now),
+            ..badge
+        };
+
+        self.save_badge(&badge);
+        self.emit_moderation_event("badge_held", &badge, Some(&reason));
+
+        badge
+    }
+
+    fn get_held_badge(&self, badge_id: &String) -> Badge {
+        let badge = self
+            .badges
+            .get(badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        require!(badge.hold.is_some(), "Badge is not on hold");
+        badge
+    }
+
+    fn emit_moderation_event(&self, event: &str, badge: &Badge, reason: Option<&str>) {
+        emit_event(
+            event,
+            [BadgeModerationEvent {
+                badge_id: &badge.id,
+                sponsor_id: badge.sponsor_id.as_ref(),
+                reason,
+            }],
+        );
+    }
+}
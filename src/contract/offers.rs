@@ -0,0 +1,77 @@
+use super::*;
+
+/// Terms the owner offered to accept a proposal on in place of those it was
+/// submitted with. Nothing is charged under them until the author confirms.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TermsOffer {
+    pub proposal_id: U64,
+    pub approved_duration: U64,
+    /// Part of the deposit returned to the author on acceptance
+    pub refund: U128,
+    pub offered_at: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Counter-offer waiting for the author of a pending proposal to confirm
+    pub fn get_terms_offer(&self, proposal_id: U64) -> Option<TermsOffer> {
+        self.terms_offers.get(&proposal_id.into()).cloned()
+    }
+
+    /// Agrees to the terms the owner offered, accepting the proposal on them.
+    /// An author who does not agree can rescind the proposal instead.
+    #[payable]
+    pub fn spo_confirm_terms(&mut self, id: U64) -> Proposal<BadgeAction> {
+        metered!("spo_confirm_terms");
+        assert_one_yocto();
+
+        let offer = self
+            .terms_offers
+            .get(&id.into())
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("No terms are waiting to be confirmed"));
+        let proposal = self
+            .sponsorship
+            .get_proposal(id.into())
+            .unwrap_or_else(|| env::panic_str("Proposal does not exist"));
+        require!(
+            proposal.author_id == env::predecessor_account_id(),
+            "Terms can only be confirmed by the proposal author"
+        );
+        let extend_request = extract_msg!(proposal, BadgeAction, Extend);
+
+        emit_event("terms_confirmed", [&offer]);
+        let proposal = self.sponsorship.accept_amended(
+            proposal.id,
+            Some(BadgeAction::Extend(BadgeExtend {
+                id: extend_request.id.clone(),
+                duration: offer.approved_duration.into(),
+                stream_id: extend_request.stream_id.clone(),
+            })),
+            offer.refund.into(),
+        );
+        self.on_proposal_change(&proposal);
+        self.sponsorship.reload(proposal)
+    }
+}
+
+impl StatsGallery {
+    /// Replaces any earlier offer for the proposal
+    pub(crate) fn offer_terms(
+        &mut self,
+        proposal: &Proposal<BadgeAction>,
+        approved_duration: u64,
+        refund: Balance,
+    ) {
+        let offer = TermsOffer {
+            proposal_id: proposal.id.into(),
+            approved_duration: approved_duration.into(),
+            refund: refund.into(),
+            offered_at: now().into(),
+        };
+        self.terms_offers.insert(proposal.id, offer.clone());
+        emit_event("terms_offered", [&offer]);
+    }
+}
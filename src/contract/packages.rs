@@ -0,0 +1,262 @@
+use super::*;
+use std::ops::Bound;
+
+/// Most badges one package may include, so that creating them all fits in
+/// one execution
+const MAX_PACKAGE_BADGES: u32 = 10;
+const DEFAULT_GET_PACKAGES: u64 = 10;
+const MAX_GET_PACKAGES: u64 = 50;
+/// Upper bound on badges a package may feature on the gallery home at once
+const MAX_PACKAGE_FEATURED_BADGES: usize = 8;
+
+/// A bundle sold at a fixed price, e.g. three badges and 90 days on the
+/// gallery home for 50 NEAR. The sponsor bond and metadata storage deposit of
+/// each badge are charged on top of `price`.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Package {
+    pub id: String,
+    pub name: String,
+    pub badge_count: u32,
+    /// Duration of each badge
+    pub badge_duration: U64,
+    /// How long the badges are featured on the gallery home once created
+    pub featured_duration: U64,
+    pub price: U128,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_package(&self, package_id: String) -> Option<Package> {
+        self.packages.get(&package_id).cloned()
+    }
+
+    pub fn get_packages(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Package> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_PACKAGES)
+            .min(MAX_GET_PACKAGES);
+
+        self.packages
+            .values()
+            .skip(from_index.map(u64::from).unwrap_or(0) as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    /// Package a package proposal was submitted for, as it was then
+    pub fn get_proposal_package(&self, proposal_id: U64) -> Option<Package> {
+        self.proposal_packages.get(&proposal_id.into()).cloned()
+    }
+
+    /// Adds or replaces a package. Proposals already submitted keep the
+    /// package as it was when they were submitted.
+    #[payable]
+    pub fn set_package(&mut self, package: Package) {
+        metered!("set_package");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.input_limits.check_id("Package ID", &package.id);
+        self.input_limits.check_name("Package name", &package.name);
+        require!(
+            package.badge_count > 0 && package.badge_count <= MAX_PACKAGE_BADGES,
+            format!(
+                "Package must include between 1 and {} badges",
+                MAX_PACKAGE_BADGES
+            )
+        );
+        require!(
+            package.badge_duration.0 > 0,
+            "Package badge duration must be greater than 0"
+        );
+
+        self.on_admin_action("set_package", format!("{:?}", package));
+        self.packages.insert(package.id.clone(), package);
+    }
+
+    #[payable]
+    pub fn remove_package(&mut self, package_id: String) {
+        metered!("remove_package");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            self.packages.remove(&package_id).is_some(),
+            "Package does not exist"
+        );
+        self.on_admin_action("remove_package", package_id);
+    }
+
+    /// End of the featured period a package gave the badge, if any
+    pub fn get_badge_featured_until(&self, badge_id: String) -> Option<U64> {
+        self.featured_until.get(&badge_id).cloned().map(U64)
+    }
+}
+
+impl StatsGallery {
+    /// The package recorded for the proposal, or the one it names if it is
+    /// still pending
+    pub(crate) fn package_for(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        package_request: &BadgePackage,
+    ) -> Result<Package, ProposalValidationError> {
+        self.proposal_packages
+            .get(&proposal.id)
+            .cloned()
+            .or_else(|| self.packages.get(&package_request.package_id).cloned())
+            .ok_or_else(|| ProposalValidationError::PackageDoesNotExist {
+                package_id: package_request.package_id.clone(),
+            })
+    }
+
+    pub(crate) fn validate_package_proposal(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        package_request: &BadgePackage,
+        package: &Package,
+        terms: &BadgeTerms,
+    ) -> Result<(), ProposalValidationError> {
+        self.check_sunset_allows_create()?;
+
+        let badge_count = package_request.badges.len() as u32;
+        if badge_count != package.badge_count {
+            return Err(ProposalValidationError::PackageBadgeCountMismatch {
+                badge_count,
+                package_badge_count: package.badge_count,
+            });
+        }
+
+        // .unwrap() is safe because the count matched a package, and
+        // packages are never empty
+        let group_id = &package_request.badges.first().unwrap().group_id;
+        let mut badge_ids = HashSet::new();
+        for create_request in package_request.badges.iter() {
+            if create_request.duration != package.badge_duration.0 {
+                return Err(ProposalValidationError::PackageDurationMismatch {
+                    badge_id: create_request.id.clone(),
+                    duration: create_request.duration.into(),
+                    package_duration: package.badge_duration,
+                });
+            }
+            if &create_request.group_id != group_id {
+                return Err(ProposalValidationError::PackageSpansGroups {
+                    group_id: group_id.clone(),
+                    other_group_id: create_request.group_id.clone(),
+                });
+            }
+            if !badge_ids.insert(&create_request.id) {
+                return Err(ProposalValidationError::BadgeAlreadyExists {
+                    badge_id: create_request.id.clone(),
+                });
+            }
+            self.check_badge_creatable(proposal, create_request, terms)?;
+        }
+
+        let required = package.price.0 + self.package_held(package_request, terms);
+        if proposal.retained_deposit() < required {
+            return Err(ProposalValidationError::InsufficientDeposit {
+                deposit: proposal.retained_deposit().into(),
+                required: required.into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Everything `validate_package_proposal` checks, plus the limits that
+    /// only apply once the badges are about to be created
+    pub(crate) fn check_package_acceptance(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        package_request: &BadgePackage,
+    ) -> Result<(), ProposalValidationError> {
+        let package = self.package_for(proposal, package_request)?;
+        self.validate_package_proposal(
+            proposal,
+            package_request,
+            &package,
+            &self.terms_for(proposal),
+        )?;
+        self.check_sponsor_badge_limit(proposal, package.badge_count)?;
+        for create_request in package_request.badges.iter() {
+            self.check_lead_time(proposal, create_request)?;
+        }
+
+        // Packages are not waitlisted, since their badges are created
+        // together, so they cannot jump the queue either
+        let free = if self.waitlist.is_empty() {
+            self.free_badge_slots()
+        } else {
+            0
+        };
+        if free < u64::from(package.badge_count) {
+            return Err(ProposalValidationError::BadgeSlotsUnavailable {
+                free: free.into(),
+                required: u64::from(package.badge_count).into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Takes payment for an accepted package proposal. Its badges are all
+    /// created in one execution.
+    pub(crate) fn accept_package(&mut self, proposal: &Proposal<BadgeAction>) {
+        let package_request = extract_msg!(proposal, BadgeAction, Package);
+        // .unwrap() is safe because the package is recorded while the
+        // proposal is pending
+        let package = self.proposal_packages.get(&proposal.id).cloned().unwrap();
+        let held = self.package_held(package_request, &self.terms_for(proposal));
+
+        self.refund_surplus(proposal, package.price.0 + held);
+        self.credit_referral(proposal, package.price.0);
+        // .unwrap() is safe because packages are never empty
+        let group_id = &package_request.badges.first().unwrap().group_id;
+        self.add_group_sale(
+            group_id,
+            package.badge_duration.0 * u64::from(package.badge_count),
+            package.price.0,
+        );
+        // Vests once the last of the badges has started
+        if let Some(last) = package_request.badges.iter().max_by_key(|b| b.start_at) {
+            self.vest_at_start(proposal, last);
+        }
+        self.queue_execution(proposal);
+    }
+
+    pub(crate) fn feature_badge(&mut self, badge_id: &str, until: u64) {
+        self.unfeature_badge(badge_id);
+        self.featured_until.insert(badge_id.to_string(), until);
+        self.featured_queue
+            .insert((until, badge_id.to_string()), ());
+    }
+
+    pub(crate) fn unfeature_badge(&mut self, badge_id: &str) {
+        if let Some(until) = self.featured_until.remove(&badge_id.to_string()) {
+            self.featured_queue.remove(&(until, badge_id.to_string()));
+        }
+    }
+
+    /// Badges whose package featured period has not ended, soonest to end
+    /// first
+    pub(crate) fn package_featured_ids(&self, now: u64) -> Vec<String> {
+        self.featured_queue
+            .range((Bound::Excluded((now, String::new())), Bound::Unbounded))
+            .map(|((_, badge_id), _)| badge_id.clone())
+            .take(MAX_PACKAGE_FEATURED_BADGES)
+            .collect()
+    }
+
+    /// Sponsor bonds and metadata storage deposits of a package's badges
+    fn package_held(&self, package_request: &BadgePackage, terms: &BadgeTerms) -> Balance {
+        package_request
+            .badges
+            .iter()
+            .map(|b| terms.creation_held(b))
+            .sum()
+    }
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgePublishedEvent<'a> {
+    badge_id: &'a str,
+    published_by: &'a AccountId,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// The sponsor's badges that have not been published yet, so that it can
+    /// check how they render before anyone else sees them
+    pub fn get_badge_previews(&self, account_id: AccountId) -> Vec<Badge> {
+        let now = now();
+        self.badge_indexes
+            .ids_by_sponsor(&account_id)
+            .filter_map(|id| self.badges.get(&id))
+            .filter(|badge| !badge.is_published(now))
+            .map(|badge| self.with_default_media(badge))
+            .collect()
+    }
+
+    /// Makes a preview badge public. Either the sponsor or the owner may
+    /// publish it.
+    #[payable]
+    pub fn publish_badge(&mut self, badge_id: String) -> Badge {
+        metered!("publish_badge");
+        assert_one_yocto();
+
+        let badge = self
+            .badges
+            .get(&badge_id)
+            .unwrap_or_else(|| env::panic_str("Badge does not exist"));
+        let caller = env::predecessor_account_id();
+        let is_owner = self.ownership.is_owner(&caller);
+        require!(
+            is_owner || badge.sponsor_id.as_ref() == Some(&caller),
+            "Badge can only be published by its sponsor or the owner"
+        );
+        require!(!badge.is_published(now()), "Badge is already published");
+
+        if is_owner {
+            self.on_admin_action("publish_badge", badge_id.clone());
+        }
+        let badge = Badge {
+            is_preview: false,
+            publish_at: None,
+            ..badge
+        };
+        self.save_badge(&badge);
+
+        emit_event(
+            "badge_published",
+            [BadgePublishedEvent {
+                badge_id: &badge_id,
+                published_by: &caller,
+            }],
+        );
+
+        badge
+    }
+}
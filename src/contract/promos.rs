@@ -0,0 +1,184 @@
+use super::*;
+
+const DEFAULT_GET_PROMO_CODES: u64 = 10;
+const MAX_GET_PROMO_CODES: u64 = 50;
+const MAX_DISCOUNT_BPS: u16 = 10_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum PromoDiscount {
+    /// Share of the price taken off, in basis points
+    BasisPoints(u16),
+    /// Amount taken off the price, down to zero
+    Fixed(U128),
+}
+
+impl PromoDiscount {
+    pub fn apply(&self, price: Balance) -> Balance {
+        match self {
+            Self::BasisPoints(bps) => {
+                price - price * Balance::from(*bps) / Balance::from(MAX_DISCOUNT_BPS)
+            }
+            Self::Fixed(amount) => price.saturating_sub(amount.0),
+        }
+    }
+}
+
+/// A discount on badge proposals. The discount is locked in when a proposal
+/// using the code is submitted, and every such submission counts as a use.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PromoCode {
+    pub code: String,
+    pub discount: PromoDiscount,
+    pub max_uses: Option<U64>,
+    pub uses: U64,
+    pub expires_at: Option<U64>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct PromoCodeRedeemedEvent<'a> {
+    code: &'a str,
+    proposal_id: U64,
+    author_id: &'a AccountId,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_promo_code(&self, code: String) -> Option<PromoCode> {
+        self.promo_codes.get(&code).cloned()
+    }
+
+    pub fn get_promo_codes(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<PromoCode> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_PROMO_CODES)
+            .min(MAX_GET_PROMO_CODES);
+
+        self.promo_codes
+            .values()
+            .skip(from_index.map(u64::from).unwrap_or(0) as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    #[payable]
+    pub fn create_promo_code(
+        &mut self,
+        code: String,
+        discount: PromoDiscount,
+        max_uses: Option<U64>,
+        expires_at: Option<U64>,
+    ) -> PromoCode {
+        metered!("create_promo_code");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.input_limits.check_id("Promo code", &code);
+        require!(
+            self.promo_codes.get(&code).cloned().is_none(),
+            "Promo code already exists"
+        );
+        if let PromoDiscount::BasisPoints(bps) = discount {
+            require!(
+                bps <= MAX_DISCOUNT_BPS,
+                format!("Discount cannot exceed {} basis points", MAX_DISCOUNT_BPS)
+            );
+        }
+        self.on_admin_action("create_promo_code", format!("{} {:?}", code, discount));
+
+        let promo_code = PromoCode {
+            code: code.clone(),
+            discount,
+            max_uses,
+            uses: U64(0),
+            expires_at,
+        };
+        self.promo_codes.insert(code, promo_code.clone());
+
+        promo_code
+    }
+
+    /// Proposals already submitted with the code keep their discount
+    #[payable]
+    pub fn delete_promo_code(&mut self, code: String) {
+        metered!("delete_promo_code");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            self.promo_codes.remove(&code).is_some(),
+            "Promo code does not exist"
+        );
+        self.on_admin_action("delete_promo_code", code);
+    }
+}
+
+impl StatsGallery {
+    /// Uses up the code a badge proposal was submitted with, if any, and
+    /// returns its discount
+    /// The discount the proposal's promo code would give, if it may still be
+    /// redeemed
+    pub(crate) fn check_promo_code(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+    ) -> Result<Option<PromoDiscount>, String> {
+        let code = match &proposal.promo_code {
+            Some(code) => code,
+            None => return Ok(None),
+        };
+        let promo_code = self
+            .promo_codes
+            .get(code)
+            .cloned()
+            .ok_or("Promo code does not exist")?;
+        if promo_code
+            .expires_at
+            .is_some_and(|expires_at| now() >= expires_at.0)
+        {
+            return Err("Promo code has expired".to_string());
+        }
+        if promo_code
+            .max_uses
+            .is_some_and(|max_uses| promo_code.uses.0 >= max_uses.0)
+        {
+            return Err("Promo code has reached its usage limit".to_string());
+        }
+
+        Ok(Some(promo_code.discount))
+    }
+
+    pub(crate) fn redeem_promo_code(
+        &mut self,
+        proposal: &Proposal<BadgeAction>,
+    ) -> Option<PromoDiscount> {
+        let discount = self
+            .check_promo_code(proposal)
+            .unwrap_or_else(|e| env::panic_str(&e))?;
+        // .unwrap() is safe because the code was just checked
+        let code = proposal.promo_code.as_ref().unwrap();
+        let promo_code = self.promo_codes.get(code).cloned().unwrap();
+
+        self.promo_codes.insert(
+            code.clone(),
+            PromoCode {
+                uses: (promo_code.uses.0 + 1).into(),
+                ..promo_code.clone()
+            },
+        );
+        emit_event(
+            "promo_code_redeemed",
+            [PromoCodeRedeemedEvent {
+                code,
+                proposal_id: proposal.id.into(),
+                author_id: &proposal.author_id,
+            }],
+        );
+
+        Some(discount)
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+const DEFAULT_PRUNE_BADGES: u64 = 10;
+const MAX_PRUNE_BADGES: u64 = 50;
+/// Upper bound on index entries visited while looking for badges to prune,
+/// so that paused badges at the front of the index cannot exhaust the gas
+const MAX_PRUNE_BADGES_SCAN: usize = 100;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgePrunedEvent<'a> {
+    /// The badge as it was stored, for indexers that keep the history
+    badge: &'a Badge,
+    pruned_by: &'a AccountId,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// How long after expiring a badge may be pruned, or `None` if badges
+    /// are never pruned
+    pub fn get_badge_prune_after(&self) -> Option<U64> {
+        self.badge_prune_after.map(U64)
+    }
+
+    #[payable]
+    pub fn set_badge_prune_after(&mut self, badge_prune_after: Option<U64>) {
+        metered!("set_badge_prune_after");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        let badge_prune_after = badge_prune_after.map(u64::from);
+        self.on_admin_action(
+            "set_badge_prune_after",
+            format!("{:?} -> {:?}", self.badge_prune_after, badge_prune_after),
+        );
+        self.badge_prune_after = badge_prune_after;
+    }
+
+    /// Whether `badge_id` belonged to a pruned badge. Pruned IDs cannot be
+    /// used again, so existing awards never point at a different badge.
+    pub fn is_badge_pruned(&self, badge_id: String) -> bool {
+        self.pruned_badge_ids.contains(&badge_id)
+    }
+
+    /// Deletes badges that expired more than `badge_prune_after` ago, oldest
+    /// first, emitting each one in a `badge_pruned` event beforehand. Any
+    /// bond and storage deposit still held is returned to the sponsor.
+    /// Awards are kept. Anyone may call this, and is paid the pruning keeper
+    /// reward for each badge pruned. Returns how many were pruned.
+    pub fn prune_badges(&mut self, limit: Option<U64>) -> U64 {
+        metered!("prune_badges");
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_PRUNE_BADGES)
+            .min(MAX_PRUNE_BADGES);
+        let cutoff = match self.badge_prune_after {
+            Some(prune_after) => now().saturating_sub(prune_after),
+            None => return U64(0),
+        };
+
+        let due: Vec<Badge> = self
+            .badge_indexes
+            .ids(BadgeSort::ExpiresAt, false)
+            .take(MAX_PRUNE_BADGES_SCAN)
+            .filter_map(|badge_id| self.badges.get(&badge_id))
+            .take_while(|badge| badge.expires_at().is_some_and(|e| e < cutoff))
+            // A paused badge's expiry moves when it is resumed
+            .filter(|badge| badge.paused_at.is_none())
+            .take(limit as usize)
+            .collect();
+
+        let pruned_by = env::predecessor_account_id();
+        let mut badge_ids = vec![];
+        for badge in due {
+            emit_event(
+                "badge_pruned",
+                [BadgePrunedEvent {
+                    badge: &badge,
+                    pruned_by: &pruned_by,
+                }],
+            );
+            self.prune_badge(&badge);
+            badge_ids.push(badge.id);
+        }
+
+        self.pay_keeper(KeeperTask::BADGE_PRUNING, badge_ids.len() as u64);
+        emit_records_changed("prune_badges", "badge", &badge_ids);
+
+        (badge_ids.len() as u64).into()
+    }
+}
+
+impl StatsGallery {
+    fn prune_badge(&mut self, badge: &Badge) {
+        self.delete_badge(&badge.id);
+        self.pruned_badge_ids.insert(badge.id.clone());
+        self.badge_reports.remove(&badge.id);
+        self.award_validity.remove(&badge.id);
+        self.claim_issuers.remove(&badge.id);
+        self.featured_badge_ids.retain(|id| id != &badge.id);
+
+        if badge.bond > 0 {
+            self.total_bonds_held -= badge.bond;
+            // .unwrap() is safe because bonds are only taken from sponsored
+            // badges
+            transfer_out(
+                badge.sponsor_id.clone().unwrap(),
+                badge.bond,
+                "bond_refund",
+                BalanceRef::badge(&badge.id),
+            );
+        }
+        self.refund_storage_deposit(badge, badge.storage_deposit);
+    }
+}
@@ -0,0 +1,100 @@
+use super::*;
+
+/// What a sponsor paid for an accepted proposal, for their records
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Receipt {
+    pub proposal_id: U64,
+    pub payer_id: AccountId,
+    pub tag: String,
+    pub badge_id: String,
+    pub accepted_at: U64,
+    /// Badge rate in effect when the proposal was submitted
+    pub rate_per_day: U128,
+    /// Days charged for, after any partial acceptance
+    pub billable_days: U64,
+    /// Refundable sponsor bond included in `deposit`
+    pub bond: U128,
+    /// Refundable metadata storage deposit included in `deposit`
+    pub storage_deposit: U128,
+    /// Sponsorship deposit declared with the proposal
+    pub deposit: U128,
+    /// Part of `deposit` returned to the payer on acceptance
+    pub refunded: U128,
+    /// `deposit` less `refunded`, including `bond` and `storage_deposit`
+    pub amount_charged: U128,
+    /// Paid on top of `deposit` for storing the proposal
+    pub storage_fee: U128,
+    /// `amount_charged` plus `storage_fee`
+    pub total_paid: U128,
+    /// Discount code the proposal was submitted with
+    pub promo_code: Option<String>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Receipt for an accepted proposal; `None` for any other proposal
+    pub fn get_receipt(&self, proposal_id: U64) -> Option<Receipt> {
+        let proposal = self.sponsorship.get_proposal(proposal_id.into())?;
+        if proposal.status != ProposalStatus::ACCEPTED {
+            return None;
+        }
+
+        let terms = self.terms_for(&proposal);
+        let (badge_id, billable_days, bond, storage_deposit) = match &proposal.msg {
+            Some(BadgeAction::Create(create)) => (
+                create.id.clone(),
+                billable_days_in_duration(create.duration),
+                terms.creation_bond.0,
+                terms.metadata_storage(create),
+            ),
+            Some(BadgeAction::Extend(extend)) => (
+                extend.id.clone(),
+                billable_days_in_duration(extend.duration),
+                0,
+                0,
+            ),
+            // Badge IDs are comma-separated, and days are summed over them
+            Some(BadgeAction::Package(package)) => (
+                package
+                    .badges
+                    .iter()
+                    .map(|b| b.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                package
+                    .badges
+                    .iter()
+                    .map(|b| billable_days_in_duration(b.duration))
+                    .sum(),
+                terms.creation_bond.0 * package.badges.len() as u128,
+                package
+                    .badges
+                    .iter()
+                    .map(|b| terms.metadata_storage(b))
+                    .sum(),
+            ),
+            None => return None,
+        };
+        let amount_charged = proposal.retained_deposit();
+
+        Some(Receipt {
+            proposal_id,
+            payer_id: proposal.author_id,
+            tag: proposal.tag,
+            badge_id,
+            // .unwrap() is safe because accepted proposals are resolved
+            accepted_at: proposal.resolved_at.unwrap().into(),
+            rate_per_day: terms.rate_per_day,
+            billable_days: billable_days.into(),
+            bond: bond.into(),
+            storage_deposit: storage_deposit.into(),
+            deposit: proposal.deposit.into(),
+            refunded: proposal.refunded.into(),
+            amount_charged: amount_charged.into(),
+            storage_fee: proposal.storage_fee.into(),
+            total_paid: (amount_charged + proposal.storage_fee).into(),
+            promo_code: proposal.promo_code,
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use super::*;
+
+const MAX_REFERRAL_SHARE_BPS: u16 = 10_000;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ReferralCreditedEvent<'a> {
+    referrer_id: &'a AccountId,
+    proposal_id: U64,
+    amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ReferralRewardsClaimedEvent<'a> {
+    referrer_id: &'a AccountId,
+    amount: U128,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Share of an accepted proposal's revenue credited to its referrer, in
+    /// basis points
+    pub fn get_referral_share_bps(&self) -> u16 {
+        self.referral_share_bps
+    }
+
+    /// Applies to proposals accepted from then on
+    #[payable]
+    pub fn set_referral_share_bps(&mut self, referral_share_bps: u16) {
+        metered!("set_referral_share_bps");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            referral_share_bps <= MAX_REFERRAL_SHARE_BPS,
+            format!(
+                "Referral share cannot exceed {} basis points",
+                MAX_REFERRAL_SHARE_BPS
+            )
+        );
+        self.on_admin_action(
+            "set_referral_share_bps",
+            format!("{} -> {}", self.referral_share_bps, referral_share_bps),
+        );
+        self.referral_share_bps = referral_share_bps;
+    }
+
+    pub fn get_referral_rewards(&self, account_id: AccountId) -> U128 {
+        self.referral_rewards
+            .get(&account_id)
+            .cloned()
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Pays out everything credited to the caller as a referrer
+    pub fn claim_referral_rewards(&mut self) -> U128 {
+        metered!("claim_referral_rewards");
+        let referrer_id = env::predecessor_account_id();
+        let amount = self
+            .referral_rewards
+            .remove(&referrer_id)
+            .unwrap_or_else(|| env::panic_str("No referral rewards to claim"));
+        self.total_referral_rewards_owed -= amount;
+
+        emit_event(
+            "referral_rewards_claimed",
+            [ReferralRewardsClaimedEvent {
+                referrer_id: &referrer_id,
+                amount: amount.into(),
+            }],
+        );
+        transfer_out(
+            referrer_id,
+            amount,
+            "referral_rewards",
+            BalanceRef::default(),
+        );
+
+        amount.into()
+    }
+}
+
+impl StatsGallery {
+    /// Credits the referrer of an accepted proposal with their share of
+    /// `revenue`, the part of the deposit the contract keeps for good (i.e.
+    /// not a bond or a refund)
+    pub(crate) fn credit_referral(&mut self, proposal: &Proposal<BadgeAction>, revenue: Balance) {
+        let referrer_id = match &proposal.referrer_id {
+            Some(referrer_id) => referrer_id,
+            None => return,
+        };
+        let amount = self.referral_share(revenue);
+        if amount == 0 {
+            return;
+        }
+
+        let rewards = self.referral_rewards.get(referrer_id).cloned().unwrap_or(0);
+        self.referral_rewards
+            .insert(referrer_id.clone(), rewards + amount);
+        self.total_referral_rewards_owed += amount;
+
+        emit_event(
+            "referral_credited",
+            [ReferralCreditedEvent {
+                referrer_id,
+                proposal_id: proposal.id.into(),
+                amount: amount.into(),
+            }],
+        );
+    }
+
+    /// Takes back what `credit_referral` credited for `revenue` when the
+    /// proposal is unwound, as far as the referrer has not claimed it yet
+    pub(crate) fn reverse_referral(&mut self, proposal: &Proposal<BadgeAction>, revenue: Balance) {
+        let referrer_id = match &proposal.referrer_id {
+            Some(referrer_id) => referrer_id,
+            None => return,
+        };
+        let rewards = self.referral_rewards.get(referrer_id).cloned().unwrap_or(0);
+        let amount = self.referral_share(revenue).min(rewards);
+        if amount == 0 {
+            return;
+        }
+
+        if amount == rewards {
+            self.referral_rewards.remove(referrer_id);
+        } else {
+            self.referral_rewards
+                .insert(referrer_id.clone(), rewards - amount);
+        }
+        self.total_referral_rewards_owed -= amount;
+
+        emit_event(
+            "referral_reversed",
+            [ReferralCreditedEvent {
+                referrer_id,
+                proposal_id: proposal.id.into(),
+                amount: amount.into(),
+            }],
+        );
+    }
+
+    fn referral_share(&self, revenue: Balance) -> Balance {
+        revenue * Balance::from(self.referral_share_bps) / Balance::from(MAX_REFERRAL_SHARE_BPS)
+    }
+}
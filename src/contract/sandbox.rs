@@ -0,0 +1,25 @@
+use super::*;
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_time_offset(&self) -> U64 {
+        time_offset().get().unwrap_or(0).into()
+    }
+
+    /// Moves the contract's clock `time_offset` nanoseconds ahead of the
+    /// block timestamp, so expiry, activation and vesting can be exercised
+    /// without producing weeks of blocks. Only compiled with the `sandbox`
+    /// feature.
+    #[payable]
+    pub fn set_time_offset(&mut self, time_offset: U64) {
+        metered!("set_time_offset");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_time_offset",
+            format!("{} -> {}", self.get_time_offset().0, time_offset.0),
+        );
+        crate::clock::time_offset().set(&time_offset.0);
+    }
+}
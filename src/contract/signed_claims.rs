@@ -0,0 +1,100 @@
+use super::*;
+
+/// What a badge's issuer signs to let an account claim the badge. The
+/// signature covers the JSON text of the claim exactly as it is passed to
+/// `claim_signed`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignedClaim {
+    /// Keeps a claim from being used on another deployment that trusts the
+    /// same issuer key
+    pub contract_id: AccountId,
+    pub badge_id: String,
+    pub account_id: AccountId,
+    /// Chosen by the issuer. Each nonce can be used once per badge.
+    pub nonce: U64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_claim_issuer(&self, badge_id: String) -> Option<PublicKey> {
+        self.claim_issuers.get(&badge_id).cloned()
+    }
+
+    /// Registers the ed25519 key whose signatures `claim_signed` accepts for
+    /// the badge, replacing any previous one.
+    #[payable]
+    pub fn set_claim_issuer(&mut self, badge_id: String, public_key: PublicKey) {
+        metered!("set_claim_issuer");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        require!(self.badges.get(&badge_id).is_some(), "Badge does not exist");
+        require!(
+            public_key.curve_type() == CurveType::ED25519,
+            "Issuer key must be an ed25519 key"
+        );
+
+        self.on_admin_action(
+            "set_claim_issuer",
+            format!("badge {} key {}", badge_id, String::from(&public_key)),
+        );
+        self.claim_issuers.insert(badge_id, public_key);
+    }
+
+    #[payable]
+    pub fn remove_claim_issuer(&mut self, badge_id: String) {
+        metered!("remove_claim_issuer");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+        require!(
+            self.claim_issuers.remove(&badge_id).is_some(),
+            "Badge has no claim issuer"
+        );
+        self.on_admin_action("remove_claim_issuer", format!("badge {}", badge_id));
+    }
+
+    pub fn is_signed_claim_used(&self, badge_id: String, nonce: U64) -> bool {
+        self.signed_claim_nonces.contains(&(badge_id, nonce.0))
+    }
+
+    /// Awards a badge to the caller on the strength of a claim signed by the
+    /// badge's issuer. `payload` is the JSON text of a `SignedClaim`, and
+    /// `signature` the issuer's ed25519 signature over it.
+    pub fn claim_signed(
+        &mut self,
+        badge_id: String,
+        payload: String,
+        signature: Base64VecU8,
+    ) -> Award {
+        metered!("claim_signed");
+        let public_key = self
+            .claim_issuers
+            .get(&badge_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Badge has no claim issuer"));
+        require!(
+            verify_ed25519(&public_key, payload.as_bytes(), &signature),
+            "Invalid signature"
+        );
+
+        let claim: SignedClaim = serde_json::from_str(&payload)
+            .unwrap_or_else(|_| env::panic_str("Invalid claim payload"));
+        let account_id = env::predecessor_account_id();
+        require!(
+            claim.contract_id == env::current_account_id(),
+            "Claim is for another contract"
+        );
+        require!(claim.badge_id == badge_id, "Claim is for another badge");
+        require!(
+            claim.account_id == account_id,
+            "Claim is for another account"
+        );
+        require!(
+            self.signed_claim_nonces
+                .insert((badge_id.clone(), claim.nonce.0)),
+            "Claim has already been used"
+        );
+
+        self.award_badge(badge_id, account_id)
+    }
+}
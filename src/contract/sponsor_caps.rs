@@ -0,0 +1,63 @@
+use super::*;
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Most unexpired badges one account may sponsor at a time, if limited
+    pub fn get_max_badges_per_sponsor(&self) -> Option<u32> {
+        self.max_badges_per_sponsor
+    }
+
+    /// Checked when create proposals are accepted. Lowering the limit does
+    /// not affect badges that already exist.
+    #[payable]
+    pub fn set_max_badges_per_sponsor(&mut self, max_badges_per_sponsor: Option<u32>) {
+        metered!("set_max_badges_per_sponsor");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_max_badges_per_sponsor",
+            format!(
+                "{:?} -> {:?}",
+                self.max_badges_per_sponsor, max_badges_per_sponsor
+            ),
+        );
+        self.max_badges_per_sponsor = max_badges_per_sponsor;
+    }
+
+    /// Badges the account sponsors that have not expired, disabled ones
+    /// included, plus accepted creations still waiting to be executed or for
+    /// a free slot
+    pub fn get_sponsor_badge_count(&self, account_id: AccountId) -> u32 {
+        let now = now();
+        let active = self
+            .badge_indexes
+            .ids_by_sponsor(&account_id)
+            .filter_map(|id| self.badges.get(&id))
+            .filter(|badge| !badge.is_expired(now))
+            .count();
+
+        (active + self.queued_creations_by(&account_id)) as u32
+    }
+}
+
+impl StatsGallery {
+    /// Whether the author may sponsor `badge_count` more badges
+    pub(crate) fn check_sponsor_badge_limit(
+        &self,
+        proposal: &Proposal<BadgeAction>,
+        badge_count: u32,
+    ) -> Result<(), ProposalValidationError> {
+        let limit = match self.max_badges_per_sponsor {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        let count = self.get_sponsor_badge_count(proposal.author_id.clone());
+        if count + badge_count > limit {
+            return Err(ProposalValidationError::SponsorBadgeLimitReached { count, limit });
+        }
+
+        Ok(())
+    }
+}
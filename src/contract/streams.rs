@@ -0,0 +1,207 @@
+use super::*;
+
+const GAS_FOR_GET_STREAM: Gas = Gas::from_tgas(10);
+const GAS_FOR_ON_BADGE_STREAM_CHECKED: Gas = Gas::from_tgas(20);
+const SECONDS_PER_DAY: u128 = 60 * 60 * 24;
+pub const STREAM_INACTIVE_REASON: &str = "Payment stream is not active";
+
+/// The payment-streaming contract badges may be funded through, and the
+/// account their streams must pay
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamConfig {
+    pub contract_id: AccountId,
+    pub treasury_id: AccountId,
+}
+
+/// A stream as returned by the streaming contract's `get_stream` view
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamView {
+    pub owner_id: AccountId,
+    pub receiver_id: AccountId,
+    pub tokens_per_sec: U128,
+    pub is_active: bool,
+}
+
+/// The stream paying for a badge in place of an upfront deposit. Its health
+/// is unknown until someone calls `check_badge_stream`.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeStream {
+    pub stream_id: String,
+    pub sponsor_id: AccountId,
+    /// Rate at the time the stream was linked
+    pub rate_per_day: U128,
+    pub is_healthy: Option<bool>,
+    pub checked_at: Option<U64>,
+}
+
+impl BadgeStream {
+    fn is_healthy_stream(&self, stream: &StreamView, config: &StreamConfig) -> bool {
+        stream.is_active
+            && stream.owner_id == self.sponsor_id
+            && stream.receiver_id == config.treasury_id
+            && stream.tokens_per_sec.0.saturating_mul(SECONDS_PER_DAY) >= self.rate_per_day.0
+    }
+}
+
+#[ext_contract(ext_stream)]
+pub trait StreamContract {
+    fn get_stream(&self, stream_id: String) -> Option<StreamView>;
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BadgeStreamCheckedEvent<'a> {
+    badge_id: &'a str,
+    stream_id: &'a str,
+    is_healthy: bool,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_stream_config(&self) -> Option<StreamConfig> {
+        self.stream_config.clone()
+    }
+
+    /// `None` stops new proposals from referencing streams. Badges already
+    /// linked to a stream keep it, but can no longer be checked.
+    #[payable]
+    pub fn set_stream_config(&mut self, stream_config: Option<StreamConfig>) {
+        metered!("set_stream_config");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_stream_config",
+            match &stream_config {
+                Some(config) => format!(
+                    "contract {} treasury {}",
+                    config.contract_id, config.treasury_id
+                ),
+                None => "none".to_string(),
+            },
+        );
+        self.stream_config = stream_config;
+    }
+
+    pub fn get_badge_stream(&self, badge_id: String) -> Option<BadgeStream> {
+        self.badge_streams.get(&badge_id).cloned()
+    }
+
+    /// Asks the streaming contract whether the stream paying for a badge is
+    /// still active and paying the treasury at least the badge rate. Anyone
+    /// may call this. A badge whose stream fails the check is disabled, and
+    /// re-enabled once it passes again.
+    pub fn check_badge_stream(&mut self, badge_id: String) -> Promise {
+        metered!("check_badge_stream");
+        let config = self
+            .stream_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Payment streaming is not configured"));
+        let badge_stream = self
+            .badge_streams
+            .get(&badge_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Badge has no payment stream"));
+
+        ext_stream::ext(config.contract_id)
+            .with_static_gas(GAS_FOR_GET_STREAM)
+            .get_stream(badge_stream.stream_id)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_BADGE_STREAM_CHECKED)
+                    .on_badge_stream_checked(badge_id),
+            )
+    }
+
+    #[private]
+    pub fn on_badge_stream_checked(&mut self, badge_id: String) -> Option<BadgeStream> {
+        let stream = match env::promise_result(0) {
+            PromiseResult::Successful(bytes) => {
+                serde_json::from_slice::<Option<StreamView>>(&bytes).ok()
+            }
+            _ => None,
+        };
+        let (stream, config, badge_stream, badge) = match (
+            stream,
+            self.stream_config.clone(),
+            self.badge_streams.get(&badge_id).cloned(),
+            self.badges.get(&badge_id),
+        ) {
+            (Some(stream), Some(config), Some(badge_stream), Some(badge)) => {
+                (stream, config, badge_stream, badge)
+            }
+            _ => {
+                log!("Stream check for badge {} was inconclusive", badge_id);
+                return None;
+            }
+        };
+
+        // A stream the streaming contract does not know about is unhealthy
+        let is_healthy = stream.is_some_and(|s| badge_stream.is_healthy_stream(&s, &config));
+        let now = now();
+        let badge_stream = BadgeStream {
+            is_healthy: Some(is_healthy),
+            checked_at: Some(now.into()),
+            ..badge_stream
+        };
+        self.badge_streams
+            .insert(badge_id.clone(), badge_stream.clone());
+
+        let was_disabled_by_stream =
+            badge.disabled_reason.as_deref() == Some(STREAM_INACTIVE_REASON);
+        if !is_healthy && badge.is_enabled {
+            self.save_badge(&Badge {
+                disabled_reason: Some(STREAM_INACTIVE_REASON.to_string()),
+                ..badge.with_is_enabled(false, now)
+            });
+        } else if is_healthy && !badge.is_enabled && was_disabled_by_stream {
+            self.save_badge(&badge.with_is_enabled(true, now));
+        }
+
+        emit_event(
+            "badge_stream_checked",
+            [BadgeStreamCheckedEvent {
+                badge_id: &badge_id,
+                stream_id: &badge_stream.stream_id,
+                is_healthy,
+            }],
+        );
+
+        Some(badge_stream)
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn check_streaming_configured(&self) -> Result<(), ProposalValidationError> {
+        match self.stream_config {
+            Some(_) => Ok(()),
+            None => Err(ProposalValidationError::StreamingNotConfigured),
+        }
+    }
+
+    /// Links an accepted proposal's stream to its badge, replacing any
+    /// previous one. The stream is unverified until it is checked.
+    pub(crate) fn link_badge_stream(
+        &mut self,
+        badge_id: &str,
+        stream_id: &str,
+        sponsor_id: &AccountId,
+        terms: &BadgeTerms,
+    ) {
+        self.badge_streams.insert(
+            badge_id.to_string(),
+            BadgeStream {
+                stream_id: stream_id.to_string(),
+                sponsor_id: sponsor_id.clone(),
+                rate_per_day: terms.rate_per_day,
+                is_healthy: None,
+                checked_at: None,
+            },
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use super::*;
+
+/// Wind-down of the contract, e.g. ahead of a migration. Once begun, no new
+/// proposals are submitted and no new badges are created. Authors may still
+/// rescind and be refunded, and existing badges may be extended until the
+/// deadline.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Sunset {
+    #[serde(with = "dec_string")]
+    pub begun_at: u64,
+    #[serde(with = "dec_string")]
+    pub deadline: u64,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Cannot be undone
+    #[payable]
+    pub fn begin_sunset(&mut self, deadline: U64) -> Sunset {
+        metered!("begin_sunset");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(self.sunset.is_none(), "Sunset has already begun");
+        let now = now();
+        require!(deadline.0 > now, "Deadline must be in the future");
+        self.on_admin_action("begin_sunset", format!("deadline {}", deadline.0));
+
+        let sunset = Sunset {
+            begun_at: now,
+            deadline: deadline.0,
+        };
+        self.sunset = Some(sunset.clone());
+        emit_event("sunset_begun", [&sunset]);
+
+        sunset
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn check_sunset_allows_create(&self) -> Result<(), ProposalValidationError> {
+        match &self.sunset {
+            Some(sunset) => Err(ProposalValidationError::SunsetCreationClosed {
+                deadline: sunset.deadline.into(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_sunset_allows_extend(&self) -> Result<(), ProposalValidationError> {
+        let now = now();
+        match &self.sunset {
+            Some(sunset) if sunset.deadline <= now => {
+                Err(ProposalValidationError::SunsetDeadlinePassed {
+                    deadline: sunset.deadline.into(),
+                    now: now.into(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
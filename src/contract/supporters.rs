@@ -0,0 +1,182 @@
+use super::*;
+
+const DEFAULT_GET_SUPPORTERS: u64 = 10;
+const MAX_GET_SUPPORTERS: u64 = 50;
+/// 0.01 NEAR, which covers storing the donation
+pub const MIN_DONATION: Balance = 10_000_000_000_000_000_000_000;
+/// Only the latest donations are kept
+const MAX_RECENT_DONATIONS: u64 = 100;
+/// Donors ranked by their total donations. Donors who fall out of the
+/// ranking keep their total, and re-enter it if they donate enough.
+const MAX_TOP_SUPPORTERS: u64 = 100;
+
+/// An accepted general sponsorship, for the supporters page
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Supporter {
+    pub proposal_id: U64,
+    pub account_id: AccountId,
+    pub description: String,
+    pub amount: U128,
+    pub supported_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Donation {
+    pub donor_id: AccountId,
+    pub amount: U128,
+    pub message: Option<String>,
+    pub donated_at: U64,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TopSupporter {
+    pub account_id: AccountId,
+    pub total_donated: U128,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Donates the attached deposit to stats.gallery, with an optional
+    /// message for the supporters wall
+    #[payable]
+    pub fn donate(&mut self, message: Option<String>) -> Donation {
+        metered!("donate");
+        let amount = env::attached_deposit().as_yoctonear();
+        require!(
+            amount >= MIN_DONATION,
+            format!("Donation must be at least {} yoctoNEAR", MIN_DONATION)
+        );
+        if let Some(message) = &message {
+            self.input_limits.check_name("Donation message", message);
+        }
+
+        let donor_id = env::predecessor_account_id();
+        emit_balance_changed(
+            BalanceChangeKind::RECEIVED,
+            &donor_id,
+            amount,
+            "donation",
+            BalanceRef::default(),
+        );
+
+        let donation = Donation {
+            donor_id,
+            amount: amount.into(),
+            message,
+            donated_at: now().into(),
+        };
+
+        let slot = (self.donations_count % MAX_RECENT_DONATIONS) as u32;
+        if slot < self.recent_donations.len() {
+            self.recent_donations.replace(slot, donation.clone());
+        } else {
+            self.recent_donations.push(donation.clone());
+        }
+        self.donations_count += 1;
+
+        let previous_total = self
+            .donor_totals
+            .get(&donation.donor_id)
+            .copied()
+            .unwrap_or(0);
+        let total = previous_total + amount;
+        self.donor_totals.insert(donation.donor_id.clone(), total);
+        self.top_donors
+            .remove(&(previous_total, donation.donor_id.clone()));
+        self.top_donors
+            .insert((total, donation.donor_id.clone()), ());
+        if u64::from(self.top_donors.len()) > MAX_TOP_SUPPORTERS {
+            // .unwrap() is safe because the ranking is not empty
+            let (lowest, _) = self.top_donors.iter().next().unwrap();
+            let lowest = lowest.clone();
+            self.top_donors.remove(&lowest);
+        }
+
+        emit_event("donation_received", [&donation]);
+
+        donation
+    }
+
+    /// Donors with the highest total donations, highest first
+    pub fn get_top_supporters(&self, limit: Option<U64>) -> Vec<TopSupporter> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_SUPPORTERS)
+            .min(MAX_GET_SUPPORTERS);
+
+        self.top_donors
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .map(|((total_donated, account_id), _)| TopSupporter {
+                account_id: account_id.clone(),
+                total_donated: (*total_donated).into(),
+            })
+            .collect()
+    }
+
+    /// Latest donations, newest first
+    pub fn get_recent_donations(&self, limit: Option<U64>) -> Vec<Donation> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_SUPPORTERS)
+            .min(MAX_GET_SUPPORTERS)
+            .min(self.recent_donations.len().into());
+
+        (1..=limit)
+            .filter_map(|i| {
+                let index = (self.donations_count - i) % MAX_RECENT_DONATIONS;
+                self.recent_donations.get(index as u32).cloned()
+            })
+            .collect()
+    }
+
+    pub fn get_total_donated(&self, account_id: AccountId) -> U128 {
+        self.donor_totals
+            .get(&account_id)
+            .copied()
+            .unwrap_or(0)
+            .into()
+    }
+
+    pub fn get_supporters_count(&self) -> U64 {
+        u64::from(self.supporters.len()).into()
+    }
+
+    /// Supporters in the order their sponsorships were accepted
+    pub fn get_supporters(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Supporter> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_SUPPORTERS)
+            .min(MAX_GET_SUPPORTERS);
+
+        self.supporters
+            .iter()
+            .skip(from_index.map(u64::from).unwrap_or(0) as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+}
+
+impl StatsGallery {
+    /// Called when a `TAG_GENERAL_SPONSORSHIP` proposal is accepted. The
+    /// whole deposit is kept.
+    pub(crate) fn record_supporter(&mut self, proposal: &Proposal<BadgeAction>) {
+        let supporter = Supporter {
+            proposal_id: proposal.id.into(),
+            account_id: proposal.author_id.clone(),
+            description: proposal.description.clone(),
+            amount: proposal.retained_deposit().into(),
+            // .unwrap() is safe because accepted proposals are resolved
+            supported_at: proposal.resolved_at.unwrap().into(),
+        };
+        self.supporters.push(supporter.clone());
+        emit_event("supporter_recorded", [&supporter]);
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+
+/// Badge pricing parameters in effect when a proposal was submitted. The
+/// proposal is validated and charged against these at acceptance, so the
+/// owner changing parameters in the meantime does not affect it.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeTerms {
+    pub rate_per_day: U128,
+    pub min_creation_deposit: U128,
+    pub max_active_duration: U64,
+    pub creation_bond: U128,
+    /// From the promo code the proposal was submitted with. Does not apply
+    /// to the bond.
+    #[serde(default)]
+    pub discount: Option<PromoDiscount>,
+    /// The author was on the free-tier allowlist, so a badge creation is
+    /// charged only the bond
+    #[serde(default)]
+    pub is_free_tier: bool,
+    /// Charged per byte of a created badge's description and media URL, and
+    /// held until the badge is archived
+    #[serde(default, with = "dec_string")]
+    pub metadata_byte_cost: Balance,
+}
+
+impl BadgeTerms {
+    pub fn discounted(&self, price: Balance) -> Balance {
+        match &self.discount {
+            Some(discount) => discount.apply(price),
+            None => price,
+        }
+    }
+
+    pub fn extension_price(&self, duration: u64) -> Balance {
+        self.discounted(u128::from(billable_days_in_duration(duration)) * self.rate_per_day.0)
+    }
+
+    pub fn metadata_storage(&self, create_request: &BadgeCreate) -> Balance {
+        Balance::from(create_request.metadata_bytes()) * self.metadata_byte_cost
+    }
+
+    /// The refundable part of a creation price: the sponsor bond and the
+    /// metadata storage deposit
+    pub fn creation_held(&self, create_request: &BadgeCreate) -> Balance {
+        self.creation_bond.0 + self.metadata_storage(create_request)
+    }
+
+    /// Includes the sponsor bond and metadata storage deposit
+    pub fn creation_price(&self, create_request: &BadgeCreate) -> Balance {
+        if self.is_free_tier {
+            return self.creation_held(create_request);
+        }
+
+        self.creation_held(create_request)
+            + Balance::max(
+                self.discounted(self.min_creation_deposit.0),
+                self.extension_price(create_request.duration),
+            )
+    }
+}
+
+/// What a submission form needs to know about one tag
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TagDetails {
+    pub tag: String,
+    pub config: TagConfig,
+    /// `BadgeAction` variant the tag expects as `msg`, or `None` if it takes
+    /// no message
+    pub msg_variant: Option<&'static str>,
+    /// Review period applied to submissions, if any
+    pub proposal_duration: Option<U64>,
+    /// Pricing a submission would be charged under now, for badge tags
+    pub terms: Option<BadgeTerms>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_proposal_terms(&self, proposal_id: U64) -> Option<BadgeTerms> {
+        self.proposal_terms.get(&proposal_id.into()).cloned()
+    }
+
+    /// Every tag that currently accepts submissions, with its settings and
+    /// pricing, in one call
+    pub fn spo_get_tags_detailed(&self) -> Vec<TagDetails> {
+        let proposal_duration = self.sponsorship.get_duration().map(U64);
+
+        self.sponsorship
+            .get_tags()
+            .into_iter()
+            .map(|tag| {
+                let msg_variant = TAG_BINDINGS
+                    .iter()
+                    .find(|(t, _)| *t == tag)
+                    .and_then(|(_, binding)| binding.map(|(variant, _)| variant));
+                let terms = match tag.as_str() {
+                    TAG_BADGE_CREATE | TAG_BADGE_EXTEND | TAG_BADGE_PACKAGE => {
+                        Some(self.current_terms())
+                    }
+                    _ => None,
+                };
+
+                TagDetails {
+                    config: self.sponsorship.get_tag_config(&tag),
+                    tag,
+                    msg_variant,
+                    proposal_duration,
+                    terms,
+                }
+            })
+            .collect()
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn current_terms(&self) -> BadgeTerms {
+        BadgeTerms {
+            rate_per_day: self.badge_rate_per_day.into(),
+            min_creation_deposit: self.badge_min_creation_deposit.into(),
+            max_active_duration: self.badge_max_active_duration.into(),
+            creation_bond: self.badge_creation_bond.into(),
+            discount: None,
+            is_free_tier: false,
+            metadata_byte_cost: env::storage_byte_cost().as_yoctonear(),
+        }
+    }
+
+    /// Proposals submitted before terms were recorded fall back to the
+    /// current terms
+    pub(crate) fn terms_for(&self, proposal: &Proposal<BadgeAction>) -> BadgeTerms {
+        self.proposal_terms
+            .get(&proposal.id)
+            .cloned()
+            .unwrap_or_else(|| self.current_terms())
+    }
+}
@@ -0,0 +1,286 @@
+use super::*;
+
+const GAS_FOR_NEAR_DEPOSIT: Gas = Gas::from_tgas(10);
+const GAS_FOR_ON_TREASURY_WRAPPED: Gas = Gas::from_tgas(10);
+const GAS_FOR_ON_TREASURY_FORWARDED: Gas = Gas::from_tgas(10);
+
+/// What the treasury holds of one approved fungible token, as NEAR wrapped
+/// into it 1:1
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryPosition {
+    pub token_id: AccountId,
+    pub wrapped: U128,
+    /// Sent to the token contract, awaiting its callback
+    pub pending: U128,
+}
+
+impl TreasuryPosition {
+    fn new(token_id: AccountId) -> Self {
+        Self {
+            token_id,
+            wrapped: U128(0),
+            pending: U128(0),
+        }
+    }
+}
+
+/// The deposit method of a NEAR-wrapping token, e.g. `wrap.near`
+#[ext_contract(ext_wrap)]
+pub trait WrapContract {
+    fn near_deposit(&mut self);
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct TreasuryWrappedEvent<'a> {
+    token_id: &'a AccountId,
+    amount: U128,
+    success: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct TreasuryForwardedEvent<'a> {
+    treasury_account_id: &'a AccountId,
+    amount: U128,
+    success: bool,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Account that settled deposits are forwarded to, if any
+    pub fn get_treasury_account(&self) -> Option<AccountId> {
+        self.treasury_account_id.clone()
+    }
+
+    /// Once set, settling a deposit with `spo_settle` forwards what the
+    /// contract can spend to the treasury account, so that sponsor funds are
+    /// not kept alongside the contract's storage staking balance. `None`
+    /// keeps deposits in the contract.
+    #[payable]
+    pub fn set_treasury_account(&mut self, treasury_account_id: Option<AccountId>) {
+        metered!("set_treasury_account");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_treasury_account",
+            format!(
+                "{:?} -> {:?}",
+                self.treasury_account_id, treasury_account_id
+            ),
+        );
+        self.treasury_account_id = treasury_account_id;
+    }
+
+    /// Accepted deposits that have been forwarded to the treasury account
+    pub fn get_total_forwarded(&self) -> U128 {
+        self.total_forwarded.into()
+    }
+
+    /// Forwards every accepted deposit not yet wrapped or forwarded, limited
+    /// to what the contract can spend, to the treasury account. Anyone may
+    /// call this, e.g. after deposits were released by an unwind or a
+    /// forfeit. The amount is counted as pending until the transfer
+    /// resolves; if it fails, the NEAR comes back and may be forwarded again.
+    pub fn forward_to_treasury(&mut self) -> Promise {
+        metered!("forward_to_treasury");
+        let treasury_account_id = self
+            .treasury_account_id
+            .clone()
+            .unwrap_or_else(|| env::panic_str("No treasury account is set"));
+        let amount = self.wrappable_balance();
+        require!(amount > 0, "Nothing to forward");
+
+        self.forward(treasury_account_id, amount)
+    }
+
+    #[private]
+    pub fn on_treasury_forwarded(&mut self, treasury_account_id: AccountId, amount: U128) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        self.total_forward_pending = self.total_forward_pending.saturating_sub(amount.0);
+        if success {
+            self.total_forwarded += amount.0;
+        } else {
+            emit_balance_changed(
+                BalanceChangeKind::RECEIVED,
+                &treasury_account_id,
+                amount.0,
+                "treasury_forward_returned",
+                BalanceRef::default(),
+            );
+        }
+
+        emit_event(
+            "treasury_forwarded",
+            [TreasuryForwardedEvent {
+                treasury_account_id: &treasury_account_id,
+                amount,
+                success,
+            }],
+        );
+
+        success
+    }
+
+    pub fn get_wrap_tokens(&self) -> Vec<AccountId> {
+        self.wrap_tokens.iter().cloned().collect()
+    }
+
+    /// The contract must already be registered with the token for storage,
+    /// e.g. by calling `storage_deposit` on the token for the contract
+    /// account
+    #[payable]
+    pub fn add_wrap_token(&mut self, token_id: AccountId) {
+        metered!("add_wrap_token");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action("add_wrap_token", token_id.to_string());
+        self.wrap_tokens.insert(token_id);
+    }
+
+    /// The treasury's position in the token is kept
+    #[payable]
+    pub fn remove_wrap_token(&mut self, token_id: AccountId) {
+        metered!("remove_wrap_token");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(self.wrap_tokens.remove(&token_id), "Not an approved token");
+        self.on_admin_action("remove_wrap_token", token_id.to_string());
+    }
+
+    pub fn get_treasury_position(&self, token_id: AccountId) -> TreasuryPosition {
+        self.treasury_positions
+            .get(&token_id)
+            .cloned()
+            .unwrap_or_else(|| TreasuryPosition::new(token_id))
+    }
+
+    /// Accepted deposits that have not been wrapped or forwarded yet, limited
+    /// to what the contract can spend
+    pub fn get_wrappable_balance(&self) -> U128 {
+        self.wrappable_balance().into()
+    }
+
+    /// Converts `amount` of accepted NEAR deposits into an approved token by
+    /// calling its `near_deposit`. The amount is counted as pending until
+    /// the token contract responds; if the call fails, the NEAR comes back
+    /// and the amount may be wrapped again.
+    #[payable]
+    pub fn wrap_treasury(&mut self, token_id: AccountId, amount: U128) -> Promise {
+        metered!("wrap_treasury");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        require!(
+            self.wrap_tokens.contains(&token_id),
+            "Not an approved token"
+        );
+        let amount: Balance = amount.into();
+        require!(amount > 0, "Amount must be greater than 0");
+        require!(
+            amount <= self.wrappable_balance(),
+            "Amount exceeds wrappable balance"
+        );
+        self.on_admin_action("wrap_treasury", format!("{} {}", token_id, amount));
+
+        let position = self.get_treasury_position(token_id.clone());
+        self.treasury_positions.insert(
+            token_id.clone(),
+            TreasuryPosition {
+                pending: (position.pending.0 + amount).into(),
+                ..position
+            },
+        );
+        self.total_wrap_pending += amount;
+
+        ext_wrap::ext(token_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(amount))
+            .with_static_gas(GAS_FOR_NEAR_DEPOSIT)
+            .near_deposit()
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_TREASURY_WRAPPED)
+                    .on_treasury_wrapped(token_id, amount.into()),
+            )
+    }
+
+    #[private]
+    pub fn on_treasury_wrapped(&mut self, token_id: AccountId, amount: U128) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let position = self.get_treasury_position(token_id.clone());
+        let wrapped = if success {
+            position.wrapped.0 + amount.0
+        } else {
+            position.wrapped.0
+        };
+        self.treasury_positions.insert(
+            token_id.clone(),
+            TreasuryPosition {
+                wrapped: wrapped.into(),
+                pending: position.pending.0.saturating_sub(amount.0).into(),
+                ..position
+            },
+        );
+        self.total_wrap_pending = self.total_wrap_pending.saturating_sub(amount.0);
+        if success {
+            self.total_wrapped += amount.0;
+        }
+
+        emit_event(
+            "treasury_wrapped",
+            [TreasuryWrappedEvent {
+                token_id: &token_id,
+                amount,
+                success,
+            }],
+        );
+
+        success
+    }
+}
+
+impl StatsGallery {
+    /// Called by `spo_settle` once a deposit has been released
+    pub(crate) fn on_deposit_settled(&mut self) {
+        if let Some(treasury_account_id) = self.treasury_account_id.clone() {
+            let amount = self.wrappable_balance();
+            if amount > 0 {
+                self.forward(treasury_account_id, amount);
+            }
+        }
+    }
+
+    /// Accepted deposits neither wrapped nor forwarded, limited to what the
+    /// contract can spend
+    fn wrappable_balance(&self) -> Balance {
+        self.sponsorship
+            .get_total_accepted_deposits()
+            .0
+            .saturating_sub(self.total_wrapped)
+            .saturating_sub(self.total_wrap_pending)
+            .saturating_sub(self.total_forwarded)
+            .saturating_sub(self.total_forward_pending)
+            .min(self.available_balance())
+    }
+
+    fn forward(&mut self, treasury_account_id: AccountId, amount: Balance) -> Promise {
+        self.total_forward_pending += amount;
+
+        transfer_out(
+            treasury_account_id.clone(),
+            amount,
+            "treasury_forward",
+            BalanceRef::default(),
+        )
+        .then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_TREASURY_FORWARDED)
+                .on_treasury_forwarded(treasury_account_id, amount.into()),
+        )
+    }
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+impl StatsGallery {
+    /// Undoes what an accepted badge proposal did once its author has unwound
+    /// it: the badge it created is deleted, or the days it added are taken
+    /// off again, and the referral it earned is reversed
+    pub(crate) fn revert_unwound(&mut self, proposal: &Proposal<BadgeAction>) {
+        // .unwrap() is safe because every unwind is recorded
+        let unwind = self.sponsorship.get_unwind(proposal.id).unwrap();
+        // What the proposal retained before it was unwound
+        let retained = unwind.refund.0 + unwind.fee.0;
+        let is_executed =
+            !self.pending_executions.remove(&proposal.id) && !self.remove_waitlisted(proposal.id);
+
+        match proposal.tag.as_str() {
+            TAG_BADGE_CREATE => {
+                let create_request = extract_msg!(proposal, BadgeAction, Create);
+                // The bond and storage deposit are refunded with the rest
+                let held = if is_executed {
+                    match self.delete_badge(&create_request.id) {
+                        Some(badge) => {
+                            self.total_bonds_held -= badge.bond;
+                            self.total_storage_deposits_held -= badge.storage_deposit;
+                            badge.bond + badge.storage_deposit
+                        }
+                        None => 0,
+                    }
+                } else {
+                    self.terms_for(proposal).creation_held(create_request)
+                };
+
+                let revenue = if create_request.stream_id.is_none() {
+                    self.reverse_referral(proposal, retained.saturating_sub(held));
+                    retained.saturating_sub(held)
+                } else {
+                    0
+                };
+                self.remove_group_sale(&create_request.group_id, create_request.duration, revenue);
+            }
+            TAG_BADGE_EXTEND => {
+                let extend_request = extract_msg!(proposal, BadgeAction, Extend);
+                if is_executed {
+                    if let Some(badge) = self.badges.get(&extend_request.id) {
+                        self.save_badge(&Badge {
+                            duration: badge
+                                .duration
+                                .map(|d| d.saturating_sub(extend_request.duration)),
+                            ..badge
+                        });
+                    }
+                }
+
+                let revenue = if extend_request.stream_id.is_none() {
+                    self.reverse_referral(proposal, retained);
+                    retained
+                } else {
+                    0
+                };
+                if let Some(badge) = self.badges.get(&extend_request.id) {
+                    self.remove_group_sale(&badge.group_id, extend_request.duration, revenue);
+                }
+            }
+            _ => env::panic_str("Only badge proposals can be unwound"),
+        }
+
+        self.proposal_terms.remove(&proposal.id);
+    }
+}
@@ -0,0 +1,212 @@
+use super::*;
+
+/// Why a proposal cannot be accepted, with the values involved
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde", tag = "error", rename_all = "snake_case")]
+pub enum ProposalValidationError {
+    ProposalDoesNotExist,
+    AlreadyResolved {
+        status: ProposalStatus,
+    },
+    ProposalExpired {
+        expired_at: U64,
+    },
+    MessageMismatch,
+    RequiresTwoPhaseApproval {
+        deposit: U128,
+        approval_threshold: U128,
+    },
+    SunsetCreationClosed {
+        deadline: U64,
+    },
+    SunsetDeadlinePassed {
+        deadline: U64,
+        now: U64,
+    },
+    BadgeAlreadyExists {
+        badge_id: String,
+    },
+    GroupNotAuthorized {
+        group_id: String,
+        group_account_id: AccountId,
+    },
+    BadgeDoesNotExist {
+        badge_id: String,
+    },
+    BadgeHasNoDuration {
+        badge_id: String,
+    },
+    ActivePeriodEnded {
+        ends_at: U64,
+        now: U64,
+    },
+    ExceedsMaxActiveDuration {
+        active_duration: U64,
+        max_active_duration: U64,
+    },
+    DepositBelowBond {
+        deposit: U128,
+        bond: U128,
+    },
+    DepositBelowStorageDeposit {
+        deposit: U128,
+        bond: U128,
+        storage_deposit: U128,
+    },
+    DepositBelowMinimum {
+        deposit: U128,
+        min_creation_deposit: U128,
+    },
+    InsufficientDeposit {
+        deposit: U128,
+        required: U128,
+    },
+    StreamingNotConfigured,
+    SponsorBadgeLimitReached {
+        count: u32,
+        limit: u32,
+    },
+    LeadTimeExceeded {
+        lead_time: U64,
+        max_lead_time: U64,
+    },
+    PackageDoesNotExist {
+        package_id: String,
+    },
+    PackageBadgeCountMismatch {
+        badge_count: u32,
+        package_badge_count: u32,
+    },
+    PackageDurationMismatch {
+        badge_id: String,
+        duration: U64,
+        package_duration: U64,
+    },
+    PackageSpansGroups {
+        group_id: String,
+        other_group_id: String,
+    },
+    BadgeSlotsUnavailable {
+        free: U64,
+        required: U64,
+    },
+}
+
+impl ProposalValidationError {
+    /// The message the contract panics with when this check fails
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::ProposalDoesNotExist => "Proposal does not exist",
+            Self::AlreadyResolved { .. } => "Proposal has already been resolved",
+            Self::ProposalExpired { .. } => "Proposal is expired",
+            Self::MessageMismatch => "tag mismatch",
+            Self::RequiresTwoPhaseApproval { .. } => "Proposal requires two-phase approval",
+            Self::SunsetCreationClosed { .. } => "Badge creation is closed for sunset",
+            Self::SunsetDeadlinePassed { .. } => "Sunset deadline has passed",
+            Self::BadgeAlreadyExists { .. } => "Badge ID already exists",
+            Self::GroupNotAuthorized { .. } => "Author may not sponsor badges in this group",
+            Self::BadgeDoesNotExist { .. } => "Badge ID does not exist",
+            Self::BadgeHasNoDuration { .. } => {
+                "Cannot extend: Existing badge has no duration (indefinite)"
+            }
+            Self::ActivePeriodEnded { .. } => "Badge active period has already ended",
+            Self::ExceedsMaxActiveDuration { .. } => "Exceeded maximum active duration",
+            Self::DepositBelowBond { .. } => "Deposit does not cover sponsor bond",
+            Self::DepositBelowStorageDeposit { .. } => {
+                "Deposit does not cover sponsor bond and metadata storage"
+            }
+            Self::DepositBelowMinimum { .. } => {
+                "Deposit does not meet minimum creation deposit requirement"
+            }
+            Self::InsufficientDeposit { .. } => "Insufficient deposit for specified duration",
+            Self::StreamingNotConfigured => "Payment streaming is not configured",
+            Self::SponsorBadgeLimitReached { .. } => {
+                "Sponsor has reached the maximum number of active badges"
+            }
+            Self::LeadTimeExceeded { .. } => "Badge starts too long after acceptance",
+            Self::PackageDoesNotExist { .. } => "Package does not exist",
+            Self::PackageBadgeCountMismatch { .. } => "Number of badges does not match the package",
+            Self::PackageDurationMismatch { .. } => "Badge duration does not match the package",
+            Self::PackageSpansGroups { .. } => "All badges of a package must be in one group",
+            Self::BadgeSlotsUnavailable { .. } => "Not enough free badge slots",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AcceptCheck {
+    pub can_accept: bool,
+    pub message: Option<String>,
+    pub error: Option<ProposalValidationError>,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Runs the checks `spo_accept` would, without changing state, so the
+    /// admin UI can explain a failure before sending the transaction
+    pub fn spo_try_accept(&self, id: U64) -> AcceptCheck {
+        match self.check_accept(id.into()) {
+            Ok(()) => AcceptCheck {
+                can_accept: true,
+                message: None,
+                error: None,
+            },
+            Err(error) => AcceptCheck {
+                can_accept: false,
+                message: Some(error.message().to_string()),
+                error: Some(error),
+            },
+        }
+    }
+}
+
+impl StatsGallery {
+    fn check_accept(&self, id: u64) -> Result<(), ProposalValidationError> {
+        let proposal = self
+            .sponsorship
+            .get_proposal(id)
+            .ok_or(ProposalValidationError::ProposalDoesNotExist)?;
+
+        match proposal.status {
+            ProposalStatus::PENDING => {
+                if proposal.is_expired(now()) {
+                    return Err(ProposalValidationError::ProposalExpired {
+                        // .unwrap() is safe because only proposals with a duration expire
+                        expired_at: (proposal.created_at + proposal.duration.unwrap()).into(),
+                    });
+                }
+                if self.sponsorship.requires_two_phase(&proposal) {
+                    return Err(ProposalValidationError::RequiresTwoPhaseApproval {
+                        deposit: proposal.deposit.into(),
+                        // .unwrap() is safe because only a set threshold requires two phases
+                        approval_threshold: self
+                            .sponsorship
+                            .get_approval_threshold()
+                            .unwrap()
+                            .into(),
+                    });
+                }
+            }
+            ProposalStatus::SCHEDULED => {}
+            status => return Err(ProposalValidationError::AlreadyResolved { status }),
+        }
+
+        let terms = self.terms_for(&proposal);
+        match (proposal.tag.as_str(), &proposal.msg) {
+            (TAG_BADGE_CREATE, Some(BadgeAction::Create(create_request))) => {
+                self.validate_create_proposal(&proposal, create_request, &terms)?;
+                self.check_sponsor_badge_limit(&proposal, 1)?;
+                self.check_lead_time(&proposal, create_request)
+            }
+            (TAG_BADGE_EXTEND, Some(BadgeAction::Extend(extend_request))) => self
+                .validate_extend_proposal(&proposal, extend_request, &terms)
+                .map(|_| ()),
+            (TAG_BADGE_PACKAGE, Some(BadgeAction::Package(package_request))) => {
+                self.check_package_acceptance(&proposal, package_request)
+            }
+            (TAG_GENERAL_SPONSORSHIP, None) => Ok(()),
+            _ => Err(ProposalValidationError::MessageMismatch),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::*;
+
+/// Version of the contract's stored state. Bump it whenever the borsh layout
+/// of `StatsGallery` or anything it stores changes, along with a way to
+/// migrate from the previous version. Version 1 is the first release, and
+/// version 2 the last on near-sdk 4's collections; `migrate` upgrades from
+/// either.
+pub const STATE_VERSION: u32 = 3;
+
+/// What was deployed, for matching a user's report to the logic they hit
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractVersion {
+    /// `version` in Cargo.toml, bumped for each release that is deployed
+    pub crate_version: &'static str,
+    pub api_version: u32,
+    pub state_version: u32,
+    pub sdk_version: &'static str,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    pub fn get_version(&self) -> ContractVersion {
+        ContractVersion {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            api_version: API_VERSION,
+            state_version: STATE_VERSION,
+            sdk_version: env!("NEAR_SDK_VERSION"),
+        }
+    }
+}
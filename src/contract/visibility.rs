@@ -0,0 +1,108 @@
+use super::*;
+
+/// Which badges a listing includes. Every badge view filters through one of
+/// these so that they agree on what is publicly visible.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BadgeVisibility {
+    pub include_disabled: bool,
+    /// Badges on hold pending moderator review
+    pub include_held: bool,
+    /// Badges whose active window has ended
+    pub include_archived: bool,
+    /// Badges whose active window has not started yet
+    pub include_upcoming: bool,
+    /// Unpublished previews
+    #[serde(default)]
+    pub include_previews: bool,
+}
+
+impl BadgeVisibility {
+    pub const PUBLIC: BadgeVisibility = BadgeVisibility {
+        include_disabled: false,
+        include_held: false,
+        include_archived: false,
+        include_upcoming: true,
+        include_previews: false,
+    };
+
+    /// Public badges whose active window has begun
+    pub const ACTIVE: BadgeVisibility = BadgeVisibility {
+        include_upcoming: false,
+        ..BadgeVisibility::PUBLIC
+    };
+
+    pub const ALL: BadgeVisibility = BadgeVisibility {
+        include_disabled: true,
+        include_held: true,
+        include_archived: true,
+        include_upcoming: true,
+        include_previews: true,
+    };
+
+    pub fn is_visible(&self, badge: &Badge, now: u64) -> bool {
+        (self.include_disabled || badge.is_enabled)
+            && (self.include_held || badge.hold.is_none())
+            && (self.include_archived || !badge.is_expired(now))
+            && (self.include_upcoming || badge.start_at <= now)
+            && (self.include_previews || badge.is_published(now))
+    }
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Public badges whose active window has begun. A badge appears here
+    /// from its `start_at`, whether or not its activation has been processed.
+    pub fn get_active_badges(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+        sort: Option<BadgeSort>,
+        descending: Option<bool>,
+    ) -> Vec<Badge> {
+        self.list_badges(BadgeVisibility::ACTIVE, from_index, limit, sort, descending)
+    }
+
+    /// Unfiltered listing for the admin UI, including disabled, held,
+    /// archived and preview badges
+    pub fn get_all_badges_admin(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+        sort: Option<BadgeSort>,
+        descending: Option<bool>,
+    ) -> Vec<Badge> {
+        self.list_badges(BadgeVisibility::ALL, from_index, limit, sort, descending)
+    }
+}
+
+impl StatsGallery {
+    pub(crate) fn list_badges(
+        &self,
+        visibility: BadgeVisibility,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+        sort: Option<BadgeSort>,
+        descending: Option<bool>,
+    ) -> Vec<Badge> {
+        let now = now();
+        let from_index = from_index.map(u64::from).unwrap_or(0) as usize;
+        let limit = limit.map(u64::from).unwrap_or(u64::MAX) as usize;
+
+        let badges: Box<dyn Iterator<Item = Badge>> = match sort {
+            Some(sort) => Box::new(
+                self.badge_indexes
+                    .ids(sort, descending.unwrap_or(false))
+                    .filter_map(|badge_id| self.badges.get(&badge_id)),
+            ),
+            None => Box::new(self.badges.values()),
+        };
+
+        badges
+            .filter(|b| visibility.is_visible(b, now))
+            .skip(from_index)
+            .take(limit)
+            .map(|b| self.with_default_media(b))
+            .collect()
+    }
+}
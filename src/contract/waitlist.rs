@@ -0,0 +1,180 @@
+use super::*;
+
+const DEFAULT_GET_WAITLIST: u64 = 10;
+const MAX_GET_WAITLIST: u64 = 50;
+const DEFAULT_PROCESS_WAITLIST: u64 = 10;
+const MAX_PROCESS_WAITLIST: u64 = 50;
+
+/// An accepted create proposal waiting for a free badge slot. Its deposit has
+/// already been taken; only the badge itself is held back.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct WaitlistEntry {
+    pub position: U64,
+    pub proposal_id: U64,
+    pub badge_id: String,
+    pub author_id: AccountId,
+    pub waitlisted_at: U64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct WaitlistEvent<'a> {
+    proposal_id: U64,
+    badge_id: &'a str,
+}
+
+#[near_bindgen]
+impl StatsGallery {
+    /// Most badges that may be active at once, if limited
+    pub fn get_max_active_badges(&self) -> Option<u32> {
+        self.max_active_badges
+    }
+
+    /// Raising the limit does not promote waitlisted proposals by itself;
+    /// call `process_waitlist` for that
+    #[payable]
+    pub fn set_max_active_badges(&mut self, max_active_badges: Option<u32>) {
+        metered!("set_max_active_badges");
+        assert_one_yocto();
+        self.ownership.assert_owner();
+
+        self.on_admin_action(
+            "set_max_active_badges",
+            format!("{:?} -> {:?}", self.max_active_badges, max_active_badges),
+        );
+        self.max_active_badges = max_active_badges;
+    }
+
+    /// Badges that have not expired, plus accepted creations waiting to be
+    /// executed. Waitlisted proposals are not included.
+    pub fn get_active_badge_count(&self) -> U64 {
+        self.active_badge_count().into()
+    }
+
+    /// Waitlisted proposals in the order they will be promoted
+    pub fn get_waitlist(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<WaitlistEntry> {
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_GET_WAITLIST)
+            .min(MAX_GET_WAITLIST);
+
+        self.waitlist
+            .iter()
+            .map(|(_, entry)| entry.clone())
+            .skip(from_index.map(u64::from).unwrap_or(0) as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Promotes waitlisted proposals, oldest first, into any free badge
+    /// slots. Anyone may call this. Returns how many were promoted.
+    pub fn process_waitlist(&mut self, limit: Option<U64>) -> U64 {
+        metered!("process_waitlist");
+        let limit = limit
+            .map(u64::from)
+            .unwrap_or(DEFAULT_PROCESS_WAITLIST)
+            .min(MAX_PROCESS_WAITLIST);
+
+        self.promote_waitlisted("process_waitlist", limit).into()
+    }
+}
+
+impl StatsGallery {
+    /// Sends an accepted create proposal on to execution if there is a free
+    /// slot and nobody is waiting ahead of it, and waitlists it otherwise
+    pub(crate) fn queue_creation(&mut self, proposal: &Proposal<BadgeAction>) {
+        if self.waitlist.is_empty() && self.free_badge_slots() > 0 {
+            self.queue_execution(proposal);
+            return;
+        }
+
+        let create_request = extract_msg!(proposal, BadgeAction, Create);
+        let position = self.next_waitlist_position;
+        self.next_waitlist_position += 1;
+        self.waitlist.insert(
+            position,
+            WaitlistEntry {
+                position: position.into(),
+                proposal_id: proposal.id.into(),
+                badge_id: create_request.id.clone(),
+                author_id: proposal.author_id.clone(),
+                waitlisted_at: now().into(),
+            },
+        );
+        emit_event(
+            "proposal_waitlisted",
+            [WaitlistEvent {
+                proposal_id: proposal.id.into(),
+                badge_id: &create_request.id,
+            }],
+        );
+    }
+
+    /// `method` is the call doing the promoting, for the `records_changed`
+    /// event
+    pub(crate) fn promote_waitlisted(&mut self, method: &str, limit: u64) -> u64 {
+        let mut promoted = vec![];
+        while (promoted.len() as u64) < limit && self.free_badge_slots() > 0 {
+            let entry = match self.waitlist.keys().next().copied() {
+                // .unwrap() is safe because the key was just found
+                Some(position) => self.waitlist.remove(&position).unwrap(),
+                None => break,
+            };
+
+            emit_event(
+                "proposal_promoted",
+                [WaitlistEvent {
+                    proposal_id: entry.proposal_id,
+                    badge_id: &entry.badge_id,
+                }],
+            );
+            // .unwrap() is safe because only accepted proposals are
+            // waitlisted, and those are never removed
+            let proposal = self.sponsorship.get_proposal(entry.proposal_id.0).unwrap();
+            self.queue_execution(&proposal);
+            promoted.push(entry.proposal_id);
+        }
+
+        emit_records_changed(method, "proposal", &promoted);
+        promoted.len() as u64
+    }
+
+    /// Returns whether the proposal was waitlisted
+    pub(crate) fn remove_waitlisted(&mut self, proposal_id: u64) -> bool {
+        let position = self
+            .waitlist
+            .iter()
+            .find(|(_, entry)| entry.proposal_id.0 == proposal_id)
+            .map(|(position, _)| *position);
+        match position {
+            Some(position) => self.waitlist.remove(&position).is_some(),
+            None => false,
+        }
+    }
+
+    pub(crate) fn is_waitlisted(&self, proposal_id: u64) -> bool {
+        self.waitlist
+            .iter()
+            .any(|(_, entry)| entry.proposal_id.0 == proposal_id)
+    }
+
+    /// Author and badge ID of each waitlisted proposal
+    pub(crate) fn waitlisted_creations(&self) -> impl Iterator<Item = (AccountId, String)> + '_ {
+        self.waitlist
+            .iter()
+            .map(|(_, entry)| (entry.author_id.clone(), entry.badge_id.clone()))
+    }
+
+    fn active_badge_count(&self) -> u64 {
+        self.badge_indexes.count_unexpired(now()) + self.pending_creations().count() as u64
+    }
+
+    pub(crate) fn free_badge_slots(&self) -> u64 {
+        match self.max_active_badges {
+            Some(max) => u64::from(max).saturating_sub(self.active_badge_count()),
+            None => u64::MAX,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::*;
+
+pub const EVENT_STANDARD: &str = "stats_gallery";
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a, T: Serialize> {
+    standard: &'a str,
+    version: &'a str,
+    event: &'a str,
+    data: T,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct RecordsChangedEvent<'a, T: Serialize> {
+    method: &'a str,
+    record: &'a str,
+    ids: &'a [T],
+}
+
+/// Logs a `records_changed` event listing every record of one kind that a
+/// bulk call (e.g. a sweep) changed, so that indexers can refresh just
+/// those. Nothing is logged if no records changed.
+pub fn emit_records_changed<T: Serialize>(method: &str, record: &str, ids: &[T]) {
+    if ids.is_empty() {
+        return;
+    }
+
+    emit_event(
+        "records_changed",
+        [RecordsChangedEvent {
+            method,
+            record,
+            ids,
+        }],
+    );
+}
+
+/// Logs an event in the NEP-297 format
+pub fn emit_event<T: Serialize>(event: &str, data: T) {
+    emit_standard_event(EVENT_STANDARD, EVENT_STANDARD_VERSION, event, data);
+}
+
+/// Logs an event under another standard than the contract's own, e.g.
+/// NEP-171 for wallets that already index NFT events
+pub fn emit_standard_event<T: Serialize>(standard: &str, version: &str, event: &str, data: T) {
+    log!(
+        "EVENT_JSON:{}",
+        serde_json::to_string(&EventLog {
+            standard,
+            version,
+            event,
+            data,
+        })
+        .unwrap()
+    );
+}
+
+/// How funds moved, as seen from the contract
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BalanceChangeKind {
+    /// Attached to a call, e.g. a proposal deposit or a donation
+    RECEIVED,
+    /// Moved from funds held for someone to the contract's own, e.g. a
+    /// settled or forfeited deposit
+    KEPT,
+    /// Transferred out of the contract
+    SENT,
+}
+
+/// The record a balance change belongs to, if any
+#[derive(Serialize, Default, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalanceRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposal_id: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub badge_id: Option<&'a str>,
+}
+
+impl<'a> BalanceRef<'a> {
+    pub fn proposal(proposal_id: u64) -> Self {
+        Self {
+            proposal_id: Some(proposal_id.into()),
+            badge_id: None,
+        }
+    }
+
+    pub fn badge(badge_id: &'a str) -> Self {
+        Self {
+            proposal_id: None,
+            badge_id: Some(badge_id),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct BalanceChangedEvent<'a> {
+    kind: BalanceChangeKind,
+    account_id: &'a AccountId,
+    amount: U128,
+    reason: &'a str,
+    #[serde(flatten)]
+    reference: BalanceRef<'a>,
+}
+
+/// Logs a `balance_changed` event, so that the treasury can be reconciled
+/// from logs alone. `account_id` is whoever the funds came from or went to.
+/// Nothing is logged for a zero amount.
+pub fn emit_balance_changed(
+    kind: BalanceChangeKind,
+    account_id: &AccountId,
+    amount: Balance,
+    reason: &str,
+    reference: BalanceRef,
+) {
+    if amount == 0 {
+        return;
+    }
+
+    emit_event(
+        "balance_changed",
+        [BalanceChangedEvent {
+            kind,
+            account_id,
+            amount: amount.into(),
+            reason,
+            reference,
+        }],
+    );
+}
+
+/// Transfers `amount` to `account_id`, logging a `balance_changed` event
+pub fn transfer_out(
+    account_id: AccountId,
+    amount: Balance,
+    reason: &str,
+    reference: BalanceRef,
+) -> Promise {
+    emit_balance_changed(
+        BalanceChangeKind::SENT,
+        &account_id,
+        amount,
+        reason,
+        reference,
+    );
+    Promise::new(account_id).transfer(NearToken::from_yoctonear(amount))
+}
@@ -0,0 +1,270 @@
+use crate::*;
+use std::fmt;
+
+/// Version of the JSON API, returned by `get_api_version`. Version 3 encodes
+/// every 64- and 128-bit integer, including timestamps, durations and IDs,
+/// as a decimal string. Version 2 did so only for balances, and version 1
+/// encoded all of them as JSON numbers, which lose precision in JavaScript.
+pub const API_VERSION: u32 = 3;
+
+/// Integers that `dec_string` can encode
+pub(crate) trait DecInteger: Sized + fmt::Display {
+    fn parse_dec(value: &str) -> Option<Self>;
+    fn from_u64(value: u64) -> Option<Self>;
+    fn from_u128(value: u128) -> Option<Self>;
+}
+
+impl DecInteger for u64 {
+    fn parse_dec(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value)
+    }
+
+    fn from_u128(value: u128) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+impl DecInteger for u128 {
+    fn parse_dec(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value.into())
+    }
+
+    fn from_u128(value: u128) -> Option<Self> {
+        Some(value)
+    }
+}
+
+/// Encodes a `u64` or `u128` field as a decimal string, like `U64` and
+/// `U128`, without changing its borsh representation. Integer JSON numbers
+/// are still accepted on input from older clients, as long as they are exact.
+pub(crate) mod dec_string {
+    use super::*;
+    use near_sdk::serde::de;
+    use std::marker::PhantomData;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: DecInteger,
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: DecInteger,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DecVisitor(PhantomData))
+    }
+
+    struct DecVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: DecInteger> de::Visitor<'de> for DecVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an integer as a decimal string")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+            T::parse_dec(value).ok_or_else(|| E::custom("invalid decimal string"))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+            T::from_u64(value).ok_or_else(|| E::custom("integer out of range"))
+        }
+
+        fn visit_u128<E: de::Error>(self, value: u128) -> Result<T, E> {
+            T::from_u128(value).ok_or_else(|| E::custom("integer out of range"))
+        }
+    }
+}
+
+/// `dec_string` for optional fields. Pair with `#[serde(default)]` so that a
+/// missing field still reads as `None`.
+pub(crate) mod option_dec_string {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct Wrapped<T: DecInteger>(#[serde(with = "dec_string")] T);
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: DecInteger,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => dec_string::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: DecInteger,
+        D: Deserializer<'de>,
+    {
+        Option::<Wrapped<T>>::deserialize(deserializer).map(|x| x.map(|Wrapped(value)| value))
+    }
+}
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const NANOS_PER_MINUTE: u64 = NANOS_PER_SECOND * 60;
+const NANOS_PER_HOUR: u64 = NANOS_PER_MINUTE * 60;
+pub(crate) const NANOS_PER_DAY: u64 = NANOS_PER_HOUR * 24;
+const NANOS_PER_WEEK: u64 = NANOS_PER_DAY * 7;
+
+/// A duration written as calendar units, e.g. `{ "days": 45, "hours": 12 }`
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde", deny_unknown_fields)]
+struct DurationParts {
+    #[serde(default)]
+    days: u64,
+    #[serde(default)]
+    hours: u64,
+}
+
+impl DurationParts {
+    fn to_nanos(&self) -> Option<u64> {
+        self.days
+            .checked_mul(NANOS_PER_DAY)?
+            .checked_add(self.hours.checked_mul(NANOS_PER_HOUR)?)
+    }
+}
+
+/// Parses an ISO-8601 duration with whole weeks, days, hours, minutes and
+/// seconds, e.g. `P45D` or `P1DT12H`. Years and months are rejected because
+/// their length varies.
+pub(crate) fn parse_iso8601_duration(value: &str) -> Result<u64, &'static str> {
+    const INVALID: &str = "invalid ISO-8601 duration";
+
+    let components = value.strip_prefix('P').ok_or(INVALID)?;
+    let mut in_time = false;
+    let mut has_component = false;
+    let mut number = String::new();
+    let mut total: u64 = 0;
+
+    for c in components.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if !in_time && number.is_empty() => in_time = true,
+            'Y' => return Err("years are not supported in durations"),
+            'M' if !in_time => return Err("months are not supported in durations"),
+            unit => {
+                let unit_nanos = match (unit, in_time) {
+                    ('W', false) => NANOS_PER_WEEK,
+                    ('D', false) => NANOS_PER_DAY,
+                    ('H', true) => NANOS_PER_HOUR,
+                    ('M', true) => NANOS_PER_MINUTE,
+                    ('S', true) => NANOS_PER_SECOND,
+                    _ => return Err(INVALID),
+                };
+                let n: u64 = number.parse().map_err(|_| INVALID)?;
+                total = n
+                    .checked_mul(unit_nanos)
+                    .and_then(|x| total.checked_add(x))
+                    .ok_or("duration out of range")?;
+                number.clear();
+                has_component = true;
+            }
+        }
+    }
+
+    if !has_component || !number.is_empty() || (in_time && value.ends_with('T')) {
+        return Err(INVALID);
+    }
+    Ok(total)
+}
+
+/// Accepts a duration as nanoseconds (a decimal string or an integer), as
+/// `{ "days", "hours" }`, or as an ISO-8601 duration string. Always encoded
+/// as a decimal string of nanoseconds.
+pub(crate) mod duration {
+    use super::*;
+    use near_sdk::serde::de::{self, value::MapAccessDeserializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u64>,
+        S: Serializer,
+    {
+        dec_string::serialize(&(*value).into(), serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<u64>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DurationVisitor).map(T::from)
+    }
+
+    struct DurationVisitor;
+
+    impl<'de> de::Visitor<'de> for DurationVisitor {
+        type Value = u64;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str(
+                "a duration in nanoseconds, as { \"days\", \"hours\" }, or as an ISO-8601 duration",
+            )
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+            if value.starts_with('P') {
+                parse_iso8601_duration(value).map_err(E::custom)
+            } else {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+            Ok(value)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<u64, A::Error> {
+            DurationParts::deserialize(MapAccessDeserializer::new(map))?
+                .to_nanos()
+                .ok_or_else(|| de::Error::custom("duration out of range"))
+        }
+    }
+}
+
+/// `duration` for optional fields. Pair with `#[serde(default)]` so that a
+/// missing field still reads as `None`.
+pub(crate) mod option_duration {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct Wrapped(#[serde(with = "duration")] u64);
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u64>,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => duration::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: From<u64>,
+        D: Deserializer<'de>,
+    {
+        Option::<Wrapped>::deserialize(deserializer).map(|x| x.map(|Wrapped(value)| value.into()))
+    }
+}
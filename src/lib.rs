@@ -1,24 +1,77 @@
+// Library builds of a subset of the modules leave parts of the shared
+// helpers below unused
+#![cfg_attr(not(feature = "contract"), allow(unused_imports, dead_code))]
+
 use near_sdk::{
     borsh::{self, *},
-    collections::*,
     json_types::*,
     serde::{self, *},
+    store::*,
     *,
 };
 
-mod utils;
-use utils::*;
+/// An amount of yoctoNEAR. Balances are kept as plain numbers, as they are
+/// stored; `NearToken` only appears where they cross into the SDK.
+pub type Balance = u128;
+
+/// Records the gas burnt and storage used by the enclosing call method when
+/// built with the `metrics` feature, and does nothing otherwise. Not for
+/// view methods, which cannot write to storage.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! metered {
+    ($method: expr) => {
+        #[allow(clippy::let_unit_value)]
+        let _meter = $crate::start_meter($method);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "metrics")]
+pub fn start_meter(method: &'static str) -> Meter {
+    Meter::start(method)
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "metrics"))]
+pub fn start_meter(_method: &'static str) {}
 
-mod ownership;
+mod storage;
+use storage::*;
+
+#[cfg(feature = "ownership")]
+pub mod ownership;
+#[cfg(feature = "ownership")]
 use ownership::*;
 
-mod sponsorship;
+#[cfg(feature = "contract")]
+mod audit;
+#[cfg(feature = "contract")]
+use audit::*;
+
+pub mod events;
+use events::*;
+
+mod clock;
+use clock::*;
+
+mod json;
+use json::*;
+
+mod signatures;
+use signatures::*;
+
+#[cfg(feature = "sponsorship")]
+pub mod sponsorship;
+#[cfg(feature = "sponsorship")]
 use sponsorship::*;
 
+#[cfg(feature = "contract")]
 mod contract;
+#[cfg(feature = "contract")]
 pub use contract::*;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "contract"))]
 mod tests {
     use crate::*;
     use near_sdk::{test_utils::*, testing_env};
@@ -36,10 +89,15 @@ mod tests {
     }
 
     fn sponsorship_tags() -> Vec<String> {
-        vec![contract::TAG_BADGE_CREATE, contract::TAG_BADGE_EXTEND]
-            .iter()
-            .map(|x| x.to_string())
-            .collect()
+        vec![
+            contract::TAG_BADGE_CREATE,
+            contract::TAG_BADGE_EXTEND,
+            contract::TAG_GENERAL_SPONSORSHIP,
+            contract::TAG_BADGE_PACKAGE,
+        ]
+        .iter()
+        .map(|x| x.to_string())
+        .collect()
     }
 
     const ONE_DAY: u64 = 1_000_000_000 * 60 * 60 * 24; // nanoseconds
@@ -54,7 +112,7 @@ mod tests {
         let mut builder = VMContextBuilder::new();
         builder
             .current_account_id(contract_account())
-            .account_balance(15 * ONE_NEAR)
+            .account_balance(NearToken::from_yoctonear(15 * ONE_NEAR))
             .signer_account_id(predecessor_account_id.clone())
             .predecessor_account_id(predecessor_account_id);
         builder
@@ -72,18 +130,28 @@ mod tests {
 
     fn calculate_deposit(action: &BadgeAction) -> Balance {
         match action {
-            BadgeAction::Create(create_request) => Balance::max(
-                BADGE_MIN_CREATION_DEPOSIT,
-                Balance::from(billable_days_in_duration(create_request.duration))
-                    * BADGE_RATE_PER_DAY,
-            ),
+            BadgeAction::Create(create_request) => {
+                Balance::max(
+                    BADGE_MIN_CREATION_DEPOSIT,
+                    Balance::from(billable_days_in_duration(create_request.duration))
+                        * BADGE_RATE_PER_DAY,
+                ) + metadata_storage(create_request)
+            }
             BadgeAction::Extend(extend_request) => {
                 Balance::from(billable_days_in_duration(extend_request.duration))
                     * BADGE_RATE_PER_DAY
             }
+            // Without the package price
+            BadgeAction::Package(package_request) => {
+                package_request.badges.iter().map(metadata_storage).sum()
+            }
         }
     }
 
+    fn metadata_storage(create_request: &BadgeCreate) -> Balance {
+        Balance::from(create_request.metadata_bytes()) * env::storage_byte_cost().as_yoctonear()
+    }
+
     fn badge_create() -> BadgeCreate {
         BadgeCreate {
             id: String::from("my-badge-01"),
@@ -92,6 +160,10 @@ mod tests {
             description: String::from("This is a badge you earn from doing cool stuff"),
             duration: ONE_DAY * 45,
             start_at: None,
+            stream_id: None,
+            preview: false,
+            publish_at: None,
+            media_url: None,
         }
     }
 
@@ -99,6 +171,7 @@ mod tests {
         BadgeExtend {
             id: String::from("my-badge-01"),
             duration: ONE_DAY * 12,
+            stream_id: None,
         }
     }
 
@@ -109,6 +182,11 @@ mod tests {
             duration: Some(U64(ONE_DAY * 45)),
             msg: Some(action),
             tag,
+            nonce: None,
+            referrer_id: None,
+            promo_code: None,
+            max_storage_fee: None,
+            previous_proposal_id: None,
         }
     }
 
@@ -167,7 +245,7 @@ mod tests {
     #[should_panic(expected = "Owner only")]
     fn renounce_owner_not_owner() {
         let mut context = get_context(accounts(1));
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_renounce_owner();
@@ -176,7 +254,7 @@ mod tests {
     #[test]
     fn renounce_owner() {
         let mut context = get_context(owner_account());
-        context.attached_deposit(1u128.into());
+        context.attached_deposit(NearToken::from_yoctonear(1u128.into()));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_renounce_owner();
@@ -205,7 +283,7 @@ mod tests {
     #[should_panic(expected = "Owner only")]
     fn propose_owner_not_owner() {
         let mut context = get_context(accounts(1));
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_propose_owner(Some(proposed_owner_account()));
@@ -214,7 +292,7 @@ mod tests {
     #[test]
     fn propose_owner() {
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_propose_owner(Some(proposed_owner_account()));
@@ -234,7 +312,7 @@ mod tests {
     #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
     fn accept_owner_no_deposit() {
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_propose_owner(Some(proposed_owner_account()));
@@ -248,13 +326,13 @@ mod tests {
     #[should_panic(expected = "Proposed owner only")]
     fn accept_owner_not_proposed() {
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_propose_owner(Some(proposed_owner_account()));
 
         let mut context = get_context(accounts(2));
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         c.own_accept_owner();
     }
@@ -262,13 +340,13 @@ mod tests {
     #[test]
     fn accept_owner() {
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         let mut c = create_instance();
         c.own_propose_owner(Some(proposed_owner_account()));
 
         let mut context = get_context(proposed_owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
         c.own_accept_owner();
         assert_eq!(
@@ -283,6 +361,105 @@ mod tests {
         );
     }
 
+    #[test]
+    fn available_balance() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let storage_reserve =
+            Balance::from(env::storage_usage()) * env::storage_byte_cost().as_yoctonear();
+        assert_eq!(
+            env::account_balance().as_yoctonear() - proposal.deposit - storage_reserve,
+            u128::from(c.get_available_balance()),
+            "Pending deposits and storage should not be available",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount exceeds available balance")]
+    fn withdraw_owner_exceeds_available_balance() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.withdraw_owner(env::account_balance().as_yoctonear().into());
+    }
+
+    #[test]
+    fn admin_actions_are_recorded() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(1_000);
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.set_badge_rate_per_day(U128(BADGE_RATE_PER_DAY * 2));
+        c.own_propose_owner(Some(proposed_owner_account()));
+
+        let actions = c.get_admin_actions(None, None);
+
+        assert_eq!(
+            vec!["spo_accept", "set_badge_rate_per_day", "own_propose_owner"],
+            actions
+                .iter()
+                .map(|a| a.method.as_str())
+                .collect::<Vec<_>>(),
+            "Owner actions should be recorded in order",
+        );
+        assert!(
+            actions
+                .iter()
+                .all(|a| a.actor_id == owner_account() && a.timestamp == 1_000),
+            "Actions should record actor and timestamp",
+        );
+        assert_eq!(
+            "spo_accept",
+            c.get_admin_actions(Some(0.into()), Some(1.into()))[0].method,
+            "Audit log should be paginated",
+        );
+    }
+
     #[test]
     fn serialize_actions() {
         let submission = proposal_submission(
@@ -304,7 +481,9 @@ mod tests {
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         let submission_deposit: u128 = submission.deposit.into();
         testing_env!(context.build());
         let proposal = c.spo_submit(submission);
@@ -344,6 +523,124 @@ mod tests {
             c.spo_get_proposal(proposal.id.into()).unwrap(),
             "Proposal should be indexed by ID",
         );
+        assert_eq!(
+            proposal,
+            c.spo_get_proposal_by_uid(proposal.uid.into()).unwrap(),
+            "Proposal should be indexed by UID",
+        );
+    }
+
+    #[test]
+    fn accept_proposal_by_uid() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept_by_uid(proposal.uid.into());
+
+        assert_eq!(proposal.id, accepted.id, "Should resolve the same proposal");
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            accepted.status,
+            "Proposal status should be accepted",
+        );
+        assert!(
+            c.get_badge(badge_create().id).is_some(),
+            "Badge should be created",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate submission")]
+    fn submit_proposal_duplicate_nonce() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            nonce: Some("retry-01".to_string()),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+
+        let submission = ProposalSubmission {
+            nonce: Some("retry-01".to_string()),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn get_proposals_between() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (i, badge_id) in ["my-badge-01", "my-badge-02", "my-badge-03"]
+            .iter()
+            .enumerate()
+        {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context
+                .attached_deposit(NearToken::from_yoctonear(
+                    u128::from(submission.deposit) + 10u128.pow(22),
+                ))
+                .block_timestamp(1_000 * (i as u64 + 1));
+            testing_env!(context.build());
+            c.spo_submit(submission);
+        }
+
+        let ids = |proposals: Vec<Proposal<BadgeAction>>| {
+            proposals.into_iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![1, 2],
+            ids(c.spo_get_proposals_between(2_000.into(), 4_000.into(), None)),
+            "Range should include start and exclude end",
+        );
+        assert_eq!(
+            vec![0],
+            ids(c.spo_get_proposals_between(0.into(), 4_000.into(), Some(1.into()))),
+            "Range should respect limit",
+        );
+        assert!(
+            c.spo_get_proposals_between(3_000.into(), 3_000.into(), None)
+                .is_empty(),
+            "Empty range should return no proposals",
+        );
     }
 
     #[test]
@@ -376,7 +673,9 @@ mod tests {
             TAG_BADGE_CREATE.to_string(),
         );
         // Missing deposit for storage
-        context.attached_deposit(u128::from(submission.deposit) /* + 10u128.pow(22) */);
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit), /* + 10u128.pow(22) */
+        ));
 
         testing_env!(context.build());
         c.spo_submit(submission);
@@ -393,19 +692,21 @@ mod tests {
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
         let proposal = c.spo_submit(submission);
 
         let mut context = get_context(accounts(1));
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
 
-        let balance_before_rescind = env::account_balance();
+        let balance_before_rescind = env::account_balance().as_yoctonear();
 
         let proposal = c.spo_rescind(proposal.id.into());
 
-        let balance_after_rescind = env::account_balance();
+        let balance_after_rescind = env::account_balance().as_yoctonear();
 
         assert_eq!(
             proposal.deposit,
@@ -429,6 +730,127 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deposit_totals() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let create_submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(create_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let accepted = c.spo_submit(create_submission);
+
+        let create_submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                id: String::from("my-badge-02"),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let rejected = c.spo_submit(create_submission);
+
+        assert_eq!(
+            accepted.deposit + rejected.deposit,
+            c.spo_get_deposit_totals().pending.0,
+            "Submitted deposits should be pending",
+        );
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(accepted.id.into());
+        c.spo_reject(rejected.id.into());
+
+        let totals = c.spo_get_deposit_totals();
+        assert_eq!(0, totals.pending.0, "No deposits should be pending");
+        assert_eq!(
+            accepted.deposit, totals.accepted.0,
+            "Accepted deposit should be tracked",
+        );
+        assert_eq!(
+            rejected.deposit, totals.rejected.0,
+            "Rejected deposit should be tracked until refunded",
+        );
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(rejected.id.into());
+
+        let totals = c.spo_get_deposit_totals();
+        assert_eq!(0, totals.rejected.0, "Rejected deposit should be refunded");
+        assert_eq!(
+            rejected.deposit, totals.rescinded.0,
+            "Refunded deposit should be tracked",
+        );
+        assert_eq!(
+            u128::from(c.spo_get_total_deposits()),
+            totals.pending.0
+                + totals.accepted.0
+                + totals.rejected.0
+                + totals.expired.0
+                + totals.disputed.0
+                + totals.scheduled.0,
+            "Held deposits should sum to total deposits",
+        );
+    }
+
+    #[test]
+    fn author_stats() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(ONE_NEAR * 5));
+        testing_env!(context.build());
+        let mut proposals = vec![];
+        for badge_id in ["my-badge-01", "my-badge-02", "my-badge-03"] {
+            proposals.push(c.spo_submit(proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            )));
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposals[0].id.into());
+        c.spo_reject(proposals[1].id.into());
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(proposals[2].id.into());
+
+        assert_eq!(
+            AuthorStats {
+                submitted: 3,
+                accepted: 1,
+                rejected: 1,
+                rescinded: 1,
+                total_accepted_value: U128(proposals[0].deposit),
+            },
+            c.spo_get_author_stats(accounts(1)),
+            "Author stats should be tracked",
+        );
+        assert_eq!(
+            AuthorStats::default(),
+            c.spo_get_author_stats(accounts(2)),
+            "Unknown authors should have empty stats",
+        );
+    }
+
     #[test]
     #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
     fn rescind_proposal_no_deposit() {
@@ -441,12 +863,14 @@ mod tests {
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
         let proposal = c.spo_submit(submission);
 
         let context = get_context(accounts(1));
-        // context.attached_deposit(1);
+        // context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
 
         c.spo_rescind(proposal.id.into());
@@ -464,12 +888,14 @@ mod tests {
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
         let proposal = c.spo_submit(submission);
 
         let mut context = get_context(accounts(2));
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
 
         c.spo_rescind(proposal.id.into());
@@ -487,7 +913,9 @@ mod tests {
             TAG_BADGE_CREATE.to_string(),
         );
         context
-            .attached_deposit(u128::from(submission.deposit) + 10u128.pow(22))
+            .attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ))
             .block_timestamp(1_000_000_000);
 
         testing_env!(context.build());
@@ -495,191 +923,6443 @@ mod tests {
 
         let mut context = get_context(accounts(1));
         context
-            .attached_deposit(1)
+            .attached_deposit(NearToken::from_yoctonear(1))
             .block_timestamp(1_000_000_000 + PROPOSAL_DURATION + 1);
         testing_env!(context.build());
 
         c.spo_rescind(proposal.id.into());
     }
 
+    fn sign_request(
+        author: &ed25519_dalek::SigningKey,
+        method: &str,
+        nonce: u64,
+        args: serde_json::Value,
+    ) -> (String, Base64VecU8) {
+        use ed25519_dalek::Signer;
+
+        let payload = serde_json::json!({
+            "contract_id": contract_account(),
+            "method": method,
+            "author_id": accounts(1),
+            "nonce": nonce.to_string(),
+            "args": args,
+        })
+        .to_string();
+        let signature = author.sign(payload.as_bytes()).to_bytes().to_vec();
+
+        (payload, Base64VecU8(signature))
+    }
+
     #[test]
-    #[should_panic(expected = "Proposal cannot be rescinded")]
-    fn rescind_proposal_already_resolved() {
+    fn submit_and_rescind_signed() {
         let context = get_context(owner_account());
         testing_env!(context.build());
         let mut c = create_instance();
 
+        let author = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let public_key = PublicKey::from_parts(
+            CurveType::ED25519,
+            author.verifying_key().to_bytes().to_vec(),
+        )
+        .unwrap();
         let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_signing_key(Some(public_key.clone()));
+        assert_eq!(Some(public_key), c.spo_get_signing_key(accounts(1)));
+
+        // accounts(3) relays the author's submission and pays its deposit
         let submission = proposal_submission(
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-        let proposal = c.spo_submit(submission);
+        let (payload, signature) = sign_request(
+            &author,
+            "spo_submit",
+            1,
+            serde_json::json!({ "submission": submission }),
+        );
+        let proposal = c.spo_submit_signed(payload, signature);
 
-        let mut context = get_context(accounts(1));
-        context.attached_deposit(1);
+        assert_eq!(accounts(1), proposal.author_id);
+        assert_eq!(ProposalStatus::PENDING, proposal.status);
+        assert_eq!(1, c.spo_get_signing_nonce(accounts(1)).0);
+
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
+        let (payload, signature) = sign_request(
+            &author,
+            "spo_rescind",
+            2,
+            serde_json::json!({ "id": U64(proposal.id) }),
+        );
+        let proposal = c.spo_rescind_signed(payload, signature);
 
-        c.spo_rescind(proposal.id.into());
-        // Cannot rescind twice
-        c.spo_rescind(proposal.id.into());
+        assert_eq!(ProposalStatus::RESCINDED, proposal.status);
+        assert_eq!(2, c.spo_get_signing_nonce(accounts(1)).0);
     }
 
     #[test]
-    fn create_badge() {
+    #[should_panic(expected = "Nonce has already been used")]
+    fn submit_signed_rejects_replay() {
         let context = get_context(owner_account());
         testing_env!(context.build());
         let mut c = create_instance();
 
-        // Submit badge creation request
+        let author = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
         let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_signing_key(Some(
+            PublicKey::from_parts(
+                CurveType::ED25519,
+                author.verifying_key().to_bytes().to_vec(),
+            )
+            .unwrap(),
+        ));
+
         let submission = proposal_submission(
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
-        testing_env!(context.build());
-        let proposal = c.spo_submit(submission);
-
-        // Accept badge creation request
-        let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-
-        c.spo_accept(proposal.id.into());
-
-        require!(c.get_badges().len() == 1, "There should be one badge",);
-
-        let expected = badge_create();
-        let actual = c.get_badge(expected.id.clone());
-        require!(
-            actual.is_some(),
-            "Badge is activated and accessible by its ID",
-        );
-
-        let actual = actual.unwrap();
-
-        require!(actual.id == expected.id, "IDs match",);
-        require!(
-            actual.name == expected.name
-                && actual.description == expected.description
-                && actual.duration.unwrap() == expected.duration
-                && actual.group_id == expected.group_id,
-            "Metadata match",
+        let (payload, signature) = sign_request(
+            &author,
+            "spo_submit",
+            1,
+            serde_json::json!({ "submission": submission }),
         );
+        c.spo_submit_signed(payload.clone(), signature.clone());
+        c.spo_submit_signed(payload, signature);
     }
 
     #[test]
-    #[should_panic(expected = "tag mismatch")]
-    fn create_badge_tag_mismatch() {
+    fn sweep_expired_proposal_with_policy() {
         let context = get_context(owner_account());
         testing_env!(context.build());
         let mut c = create_instance();
 
-        // Submit badge creation request
         let mut context = get_context(accounts(1));
         let submission = proposal_submission(
             BadgeAction::Create(badge_create()),
-            TAG_BADGE_EXTEND.to_string(),
+            TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-        c.spo_submit(submission);
-    }
+        let proposal = c.spo_submit(submission);
 
-    #[test]
-    fn extend_badge() {
-        let context = get_context(owner_account());
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
-        let mut c = create_instance();
+        c.spo_accept(proposal.id.into());
+        c.spo_set_expiry_policy(TAG_BADGE_CREATE.to_string(), ExpiryPolicy::ACCEPT);
+        c.spo_set_expiry_policy(TAG_BADGE_EXTEND.to_string(), ExpiryPolicy::REFUND);
 
-        // Submit badge creation request
         let mut context = get_context(accounts(1));
-        let create_submission = proposal_submission(
-            BadgeAction::Create(badge_create()),
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                id: String::from("my-badge-02"),
+                ..badge_create()
+            }),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(create_submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-        let create_proposal = c.spo_submit(create_submission);
+        let create = c.spo_submit(submission);
+
+        let submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let extend = c.spo_submit(submission);
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+        c.spo_sweep_expired(0.into(), 10.into());
+
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            c.spo_get_proposal(create.id.into()).unwrap().status,
+            "Unreviewed proposal should be accepted under the accept policy",
+        );
+        assert!(c.get_badge(String::from("my-badge-02")).is_some());
+        assert_eq!(
+            ProposalStatus::RESCINDED,
+            c.spo_get_proposal(extend.id.into()).unwrap().status,
+            "Unreviewed proposal should be refunded under the refund policy",
+        );
+        assert_eq!(
+            proposal.deposit + create.deposit,
+            c.spo_get_total_deposits().0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit is below the minimum for this tag")]
+    fn tag_min_deposit() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
 
-        // Accept badge creation request
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
+        c.spo_set_tag_min_deposit(TAG_BADGE_EXTEND.to_string(), (ONE_NEAR * 5).into());
 
-        c.spo_accept(create_proposal.id.into());
+        assert_eq!(
+            ONE_NEAR * 5,
+            c.spo_get_tag_config(TAG_BADGE_EXTEND.to_string())
+                .min_deposit
+                .0
+        );
 
-        // Submit badge extension request
         let mut context = get_context(accounts(1));
-        let extend_submission = proposal_submission(
+        let submission = proposal_submission(
             BadgeAction::Extend(badge_extend()),
             TAG_BADGE_EXTEND.to_string(),
         );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn sweep_expired_proposal() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context
+            .attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ))
+            .block_timestamp(1_000_000_000);
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
 
-        context.attached_deposit(u128::from(extend_submission.deposit) + 10u128.pow(22));
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(1_000_000_000 + PROPOSAL_DURATION + 1);
         testing_env!(context.build());
-        let extend_proposal = c.spo_submit(extend_submission);
 
-        // Accept badge extension request
+        let swept = c.spo_sweep_expired(0.into(), 10.into());
+
+        assert_eq!(1, swept.len(), "Expired proposal should be swept");
+        assert!(
+            get_logs()
+                .iter()
+                .any(|l| l.contains("\"event\":\"records_changed\"")
+                    && l.contains(&format!(
+                        "\"method\":\"spo_sweep_expired\",\"record\":\"proposal\",\"ids\":[\"{}\"]",
+                        proposal.id
+                    ))),
+            "Swept proposals should be listed for indexers",
+        );
+        assert_eq!(
+            ProposalStatus::EXPIRED,
+            c.spo_get_proposal(proposal.id.into()).unwrap().status,
+            "Proposal status should be expired after sweep",
+        );
+        assert!(
+            c.spo_get_expired_proposals().contains(&swept[0]),
+            "Should be a member of expired proposals",
+        );
+        assert_eq!(
+            0,
+            c.spo_sweep_expired(0.into(), 10.into()).len(),
+            "Expired proposals should only be swept once",
+        );
+
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(1_000_000_000 + PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+
+        let balance_before_rescind = env::account_balance().as_yoctonear();
+        let proposal = c.spo_rescind(proposal.id.into());
+
+        assert_eq!(
+            proposal.deposit,
+            balance_before_rescind - env::account_balance().as_yoctonear(),
+            "Expired deposit should be returned",
+        );
+        assert_eq!(
+            ProposalStatus::RESCINDED,
+            proposal.status,
+            "Proposal status should be rescinded",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal cannot be rescinded")]
+    fn rescind_proposal_already_resolved() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_rescind(proposal.id.into());
+        // Cannot rescind twice
+        c.spo_rescind(proposal.id.into());
+    }
+
+    #[test]
+    fn dispute_rejected_proposal() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
+        c.spo_set_dispute_window(Some(ONE_DAY.into()));
 
-        c.spo_accept(extend_proposal.id.into());
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
 
-        let expected_create = badge_create();
-        let expected = badge_extend();
-        let actual = c.get_badge(expected.id.clone());
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
 
-        require!(actual.is_some(), "Badge exists after extend",);
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        let disputed = c.spo_dispute(proposal.id.into(), "Badge meets policy".to_string());
 
-        let actual = actual.unwrap();
+        assert_eq!(
+            ProposalStatus::DISPUTED,
+            disputed.status,
+            "Proposal status should be disputed",
+        );
+        assert!(
+            c.spo_get_disputed_proposals().contains(&disputed),
+            "Should be a member of disputed proposals",
+        );
 
-        require!(expected.id == actual.id, "IDs match",);
-        require!(
-            actual.duration.unwrap() == expected.duration + expected_create.duration,
-            "Duration after extend should be sum of original and extend request"
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        let resolved = c.spo_resolve_dispute(proposal.id.into(), true);
+
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            resolved.status,
+            "Overturned proposal should be accepted",
+        );
+        assert_eq!(
+            Some(true),
+            resolved.dispute.unwrap().overturned,
+            "Dispute outcome should be recorded",
+        );
+        assert!(
+            c.get_badge(badge_create().id).is_some(),
+            "Badge should be created when rejection is overturned",
         );
     }
 
+    /// Submits a create proposal above the approval threshold, rejects it
+    /// and has its author dispute the rejection
+    fn dispute_two_phase_proposal(c: &mut StatsGallery) -> Proposal<BadgeAction> {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_dispute_window(Some(ONE_DAY.into()));
+        c.spo_set_approval_threshold(Some(ONE_NEAR.into()));
+        c.own_add_reviewer(accounts(2));
+
+        let proposal = submit_badge_create(c);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
+
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        c.spo_dispute(proposal.id.into(), "Badge meets policy".to_string())
+    }
+
     #[test]
-    #[should_panic(expected = "Exceeded maximum active duration")]
-    fn extend_badge_exceeds_max_duration() {
+    #[should_panic(expected = "Proposal has not been approved")]
+    fn overturn_two_phase_dispute_without_approval() {
         let context = get_context(owner_account());
         testing_env!(context.build());
         let mut c = create_instance();
+        let proposal = dispute_two_phase_proposal(&mut c);
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        c.spo_resolve_dispute(proposal.id.into(), true);
+    }
+
+    #[test]
+    fn overturn_two_phase_dispute_after_approval() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = dispute_two_phase_proposal(&mut c);
+
+        let mut context = get_context(accounts(2));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        let approved = c.spo_approve(proposal.id.into());
+        assert_eq!(ProposalStatus::DISPUTED, approved.status);
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        let resolved = c.spo_resolve_dispute(proposal.id.into(), true);
+
+        assert_eq!(ProposalStatus::ACCEPTED, resolved.status);
+        assert_eq!(Some(accounts(2)), resolved.approved_by);
+        assert_eq!(Some(owner_account()), resolved.finalized_by);
+        assert!(c.get_badge(badge_create().id).is_some());
+    }
+
+    #[test]
+    fn uphold_two_phase_dispute_without_approval() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = dispute_two_phase_proposal(&mut c);
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        let resolved = c.spo_resolve_dispute(proposal.id.into(), false);
+
+        assert_eq!(ProposalStatus::REJECTED, resolved.status);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dispute window has closed")]
+    fn dispute_after_window() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_dispute_window(Some(ONE_DAY.into()));
 
-        // Submit badge creation request
         let mut context = get_context(accounts(1));
-        let create_submission = proposal_submission(
+        let submission = proposal_submission(
             BadgeAction::Create(badge_create()),
             TAG_BADGE_CREATE.to_string(),
         );
-        context.attached_deposit(u128::from(create_submission.deposit) + 10u128.pow(22));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-        let create_proposal = c.spo_submit(create_submission);
+        let proposal = c.spo_submit(submission);
 
-        // Accept badge creation request
         let mut context = get_context(owner_account());
-        context.attached_deposit(1);
+        context.attached_deposit(NearToken::from_yoctonear(1));
         testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
 
-        c.spo_accept(create_proposal.id.into());
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY + 1);
+        testing_env!(context.build());
+        c.spo_dispute(proposal.id.into(), "Too late".to_string());
+    }
+
+    #[test]
+    fn trusted_author_auto_accept() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_trusted_author(accounts(1), TAG_BADGE_CREATE.to_string(), true);
 
-        // Submit badge extension request
         let mut context = get_context(accounts(1));
-        let original = BadgeExtend {
-            duration: BADGE_MAX_ACTIVE_DURATION - badge_create().duration + 1, // should exceed max duration by 1
-            ..badge_extend()
-        };
-        let extend_submission =
-            proposal_submission(BadgeAction::Extend(original), TAG_BADGE_EXTEND.to_string());
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            proposal.status,
+            "Trusted author's proposal should be accepted on submission",
+        );
+        assert!(
+            c.get_badge(badge_create().id).is_some(),
+            "Badge should be created on submission",
+        );
+        let logs = get_logs();
+        assert!(
+            logs.iter()
+                .any(|l| l.contains("\"event\":\"proposal_submitted\""))
+                && logs
+                    .iter()
+                    .any(|l| l.contains("\"event\":\"proposal_accepted\"")),
+            "Submission and acceptance events should be emitted",
+        );
 
-        context.attached_deposit(u128::from(extend_submission.deposit) + 10u128.pow(22));
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
         testing_env!(context.build());
-        c.spo_submit(extend_submission);
+        let proposal = c.spo_submit(submission);
+
+        assert_eq!(
+            ProposalStatus::PENDING,
+            proposal.status,
+            "Trust should only apply to the specified tag",
+        );
+    }
+
+    #[test]
+    fn scheduled_acceptance() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let launch_at = PROPOSAL_DURATION + ONE_DAY;
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let scheduled = c.spo_accept_at(proposal.id.into(), launch_at.into());
+
+        assert_eq!(
+            ProposalStatus::SCHEDULED,
+            scheduled.status,
+            "Proposal status should be scheduled",
+        );
+        assert!(
+            c.get_badge(badge_create().id).is_none(),
+            "Badge should not be created before the scheduled time",
+        );
+
+        // Anyone may execute after the scheduled time, even past the proposal duration
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(launch_at);
+        testing_env!(context.build());
+        let accepted = c.spo_execute_scheduled(proposal.id.into());
+
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            accepted.status,
+            "Proposal should be accepted once executed",
+        );
+        assert!(
+            c.get_badge(badge_create().id).is_some(),
+            "Badge should be created once executed",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Scheduled acceptance time has not been reached")]
+    fn scheduled_acceptance_too_early() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept_at(proposal.id.into(), ONE_DAY.into());
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(ONE_DAY - 1);
+        testing_env!(context.build());
+        c.spo_execute_scheduled(proposal.id.into());
+    }
+
+    #[test]
+    fn draft_proposal() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let deposit = u128::from(submission.deposit);
+        context.attached_deposit(NearToken::from_yoctonear(10u128.pow(22)));
+        testing_env!(context.build());
+        let draft = c.spo_submit_draft(submission);
+
+        assert_eq!(
+            ProposalStatus::DRAFT,
+            draft.status,
+            "Proposal should be a draft"
+        );
+        assert_eq!(
+            0,
+            c.spo_get_total_deposits().0,
+            "Drafts should not hold a deposit"
+        );
+
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(PROPOSAL_DURATION * 2);
+        context.attached_deposit(NearToken::from_yoctonear(deposit + 10u128.pow(22)));
+        testing_env!(context.build());
+        let funded = c.spo_fund_draft(draft.id.into());
+
+        assert_eq!(
+            ProposalStatus::PENDING,
+            funded.status,
+            "Funded draft should be pending"
+        );
+        assert_eq!(
+            PROPOSAL_DURATION * 2,
+            funded.created_at,
+            "Review period should start when the draft is funded",
+        );
+        assert_eq!(deposit, c.spo_get_total_deposits().0);
+
+        let mut context = get_context(owner_account());
+        context.block_timestamp(PROPOSAL_DURATION * 2);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept(funded.id.into());
+
+        assert_eq!(ProposalStatus::ACCEPTED, accepted.status);
+    }
+
+    #[test]
+    fn create_badge() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        // Submit badge creation request
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        // Accept badge creation request
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_accept(proposal.id.into());
+
+        require!(
+            c.get_badges(None, None, None, None).len() == 1,
+            "There should be one badge",
+        );
+
+        let expected = badge_create();
+        let actual = c.get_badge(expected.id.clone());
+        require!(
+            actual.is_some(),
+            "Badge is activated and accessible by its ID",
+        );
+
+        let actual = actual.unwrap();
+
+        require!(actual.id == expected.id, "IDs match",);
+        require!(
+            actual.name == expected.name
+                && actual.description == expected.description
+                && actual.duration.unwrap() == expected.duration
+                && actual.group_id == expected.group_id,
+            "Metadata match",
+        );
+    }
+
+    #[test]
+    fn disabled_badge_pauses_expiry() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let badge_id = badge_create().id;
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 5);
+        testing_env!(context.build());
+        let disabled = c.set_badge_is_enabled(badge_id.clone(), false);
+        assert_eq!(
+            Some(ONE_DAY * 5),
+            disabled.paused_at,
+            "Disabling should pause the badge",
+        );
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 15);
+        testing_env!(context.build());
+        let enabled = c.set_badge_is_enabled(badge_id, true);
+
+        assert_eq!(None, enabled.paused_at, "Enabling should resume the badge");
+        assert_eq!(
+            Some(badge_create().duration + ONE_DAY * 10),
+            enabled.duration,
+            "Duration should be extended by the time spent disabled",
+        );
+    }
+
+    #[test]
+    fn disabled_badges_only_visible_to_admin_listing() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.set_badge_is_enabled(badge_create().id, false);
+
+        assert!(
+            c.get_badges(None, None, None, None).is_empty(),
+            "Disabled badges should not be listed publicly",
+        );
+        assert!(
+            c.get_gallery_home(None).recent.is_empty(),
+            "Disabled badges should not be listed publicly",
+        );
+        assert_eq!(
+            1,
+            c.get_all_badges_admin(None, None, None, None).len(),
+            "Admin listing should include disabled badges",
+        );
+    }
+
+    #[test]
+    fn hold_and_release_badge() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        let held = c.hold_badge(badge_create().id, "Reported as phishing".to_string());
+
+        assert!(held.is_enabled, "Held badges are not disabled");
+        assert!(
+            c.get_badges(None, None, None, None).is_empty(),
+            "Held badges should be hidden from public views",
+        );
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY * 3);
+        testing_env!(context.build());
+        let released = c.release_badge(badge_create().id);
+
+        assert_eq!(None, released.hold);
+        assert_eq!(
+            Some(badge_create().duration + ONE_DAY * 2),
+            released.duration,
+            "Time spent on hold should not count against the badge",
+        );
+        assert_eq!(1, c.get_badges(None, None, None, None).len());
+
+        let held = c.hold_badge(badge_create().id, "Reported again".to_string());
+        let disabled = c.disable_held_badge(held.id, "Confirmed phishing".to_string());
+
+        assert!(!disabled.is_enabled);
+        assert_eq!(
+            Some("Confirmed phishing".to_string()),
+            disabled.disabled_reason
+        );
+    }
+
+    #[test]
+    fn community_reports_hold_badge() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.set_badge_report_threshold(Some(2.into()));
+
+        let fee = c.get_badge_report_fee().0;
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(fee));
+        testing_env!(context.build());
+        c.report_badge(badge_create().id, "Scam link".to_string());
+
+        assert_eq!(None, c.get_badge(badge_create().id).unwrap().hold);
+
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(fee));
+        testing_env!(context.build());
+        c.report_badge(badge_create().id, "Impersonation".to_string());
+
+        assert_eq!(2, c.get_badge_reports(badge_create().id).len());
+        assert!(
+            c.get_badge(badge_create().id).unwrap().hold.is_some(),
+            "Badge should be held once the report threshold is reached",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge already reported by account")]
+    fn report_badge_twice() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(c.get_badge_report_fee().0));
+        testing_env!(context.build());
+        c.report_badge(badge_create().id, "Scam link".to_string());
+        c.report_badge(badge_create().id, "Scam link".to_string());
+    }
+
+    #[test]
+    fn claim_badge_with_key() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let public_key: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap();
+        c.create_claim_link(badge_create().id, public_key.clone());
+
+        let mut context = get_context(contract_account());
+        context.signer_account_pk(public_key.clone());
+        testing_env!(context.build());
+        let award = c.claim_badge_with_key(accounts(2));
+
+        assert_eq!(
+            Some(award),
+            c.get_award(badge_create().id, accounts(2)),
+            "Badge should be awarded to claiming account",
+        );
+        assert_eq!(
+            1,
+            c.get_account_badges(accounts(2), None, None, None).len(),
+            "Award should be listed for claiming account",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim key does not exist")]
+    fn claim_badge_with_key_twice() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let public_key: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap();
+        c.create_claim_link(badge_create().id, public_key.clone());
+
+        let mut context = get_context(contract_account());
+        context.signer_account_pk(public_key);
+        testing_env!(context.build());
+        c.claim_badge_with_key(accounts(2));
+        c.claim_badge_with_key(accounts(3));
+    }
+
+    #[test]
+    fn get_badges_sorted() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (i, (badge_id, duration)) in [
+            ("my-badge-01", ONE_DAY * 30),
+            ("my-badge-02", ONE_DAY * 10),
+            ("my-badge-03", ONE_DAY * 20),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    duration: *duration,
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context
+                .attached_deposit(NearToken::from_yoctonear(
+                    u128::from(submission.deposit) + 10u128.pow(22),
+                ))
+                .block_timestamp(ONE_DAY * i as u64);
+            testing_env!(context.build());
+            let proposal = c.spo_submit(submission);
+
+            let mut context = get_context(owner_account());
+            context
+                .attached_deposit(NearToken::from_yoctonear(1))
+                .block_timestamp(ONE_DAY * i as u64);
+            testing_env!(context.build());
+            c.spo_accept(proposal.id.into());
+        }
+
+        let ids = |badges: Vec<Badge>| badges.into_iter().map(|b| b.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["my-badge-03", "my-badge-02", "my-badge-01"],
+            ids(c.get_badges(None, None, Some(BadgeSort::CreatedAt), Some(true))),
+            "Newest badges first",
+        );
+        assert_eq!(
+            vec!["my-badge-02", "my-badge-03", "my-badge-01"],
+            ids(c.get_badges(None, None, Some(BadgeSort::ExpiresAt), None)),
+            "Expiring soonest first",
+        );
+        assert_eq!(
+            vec!["my-badge-03"],
+            ids(c.get_badges(
+                Some(1.into()),
+                Some(1.into()),
+                Some(BadgeSort::ExpiresAt),
+                None
+            )),
+            "Sorted views should be paginated",
+        );
+    }
+
+    #[test]
+    fn get_badges_ordered_by_id() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        for badge_id in ["badge-c", "badge-a", "badge-b"] {
+            c.insert_badge(Badge {
+                id: badge_id.to_string(),
+                group_id: "group".to_string(),
+                name: badge_id.to_string(),
+                description: String::new(),
+                is_enabled: true,
+                created_at: 0,
+                start_at: 0,
+                duration: None,
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+        }
+        c.remove_badge(&"badge-a".to_string());
+
+        let ids = c
+            .get_badges(None, None, None, None)
+            .into_iter()
+            .map(|b| b.id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["badge-b", "badge-c"], ids);
+        assert!(c.get_badge("badge-a".to_string()).is_none());
+    }
+
+    #[test]
+    fn find_badges_by_prefix() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (badge_id, group_id) in [
+            ("my-badge-01", "my-badge"),
+            ("other-01", "my-group"),
+            ("other-02", "other"),
+        ] {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    group_id: group_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            let proposal = c.spo_submit(submission);
+
+            let mut context = get_context(owner_account());
+            context.attached_deposit(NearToken::from_yoctonear(1));
+            testing_env!(context.build());
+            c.spo_accept(proposal.id.into());
+        }
+
+        let ids = |badges: Vec<Badge>| badges.into_iter().map(|b| b.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["my-badge-01", "other-01"],
+            ids(c.find_badges("my".to_string(), None)),
+            "Should match ID prefix, then group ID prefix, without duplicates",
+        );
+        assert_eq!(
+            vec!["other-01"],
+            ids(c.find_badges("other".to_string(), Some(1.into()))),
+            "Should respect limit",
+        );
+    }
+
+    #[test]
+    fn gallery_home() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (i, badge_id) in ["my-badge-01", "my-badge-02"].iter().enumerate() {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context
+                .attached_deposit(NearToken::from_yoctonear(
+                    u128::from(submission.deposit) + 10u128.pow(22),
+                ))
+                .block_timestamp(ONE_DAY * i as u64);
+            testing_env!(context.build());
+            let proposal = c.spo_submit(submission);
+
+            let mut context = get_context(owner_account());
+            context
+                .attached_deposit(NearToken::from_yoctonear(1))
+                .block_timestamp(ONE_DAY * i as u64);
+            testing_env!(context.build());
+            c.spo_accept(proposal.id.into());
+        }
+
+        c.set_featured_badge_ids(vec!["my-badge-01".to_string()]);
+
+        let home = c.get_gallery_home(None);
+
+        assert_eq!(
+            vec!["my-badge-02", "my-badge-01"],
+            home.recent
+                .iter()
+                .map(|b| b.id.as_str())
+                .collect::<Vec<_>>(),
+            "Recent badges should be ordered newest first",
+        );
+        assert_eq!(
+            vec!["my-badge-01"],
+            home.featured
+                .iter()
+                .map(|b| b.id.as_str())
+                .collect::<Vec<_>>(),
+            "Featured badges should be included",
+        );
+        assert_eq!(2, u64::from(home.badge_count), "Badge count");
+        assert_eq!(2, u64::from(home.proposal_count), "Proposal count");
+
+        let home = c.get_gallery_home(Some(1.into()));
+        assert_eq!(1, home.recent.len(), "Recent badges should be limited");
+    }
+
+    #[test]
+    fn slash_badge_bond() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let bond = ONE_NEAR;
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_badge_creation_bond(bond.into());
+
+        let mut context = get_context(accounts(1));
+        let mut submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        submission.deposit = U128(submission.deposit.0 + bond);
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let badge = c.get_badge(badge_create().id).unwrap();
+        assert_eq!(bond, badge.bond, "Bond should be held on the badge");
+        assert_eq!(Some(accounts(1)), badge.sponsor_id, "Sponsor is author");
+        assert_eq!(bond, c.get_total_bonds_held().0, "Bond should be tracked");
+
+        let badge = c.slash_badge_bond(badge_create().id, "Policy violation".to_string());
+
+        assert_eq!(0, badge.bond, "Bond should be slashed");
+        assert!(!badge.is_enabled, "Slashed badge should be disabled");
+        assert_eq!(0, c.get_total_bonds_held().0, "No bonds should be held");
+        assert!(
+            get_logs()
+                .iter()
+                .any(|l| l.starts_with("EVENT_JSON:") && l.contains("badge_bond_slashed")),
+            "Slash event should be emitted",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit does not cover sponsor bond")]
+    fn create_badge_without_bond() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_badge_creation_bond(U128(ONE_NEAR * 10));
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    #[should_panic(expected = "tag mismatch")]
+    fn create_badge_tag_mismatch() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        // Submit badge creation request
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn extend_badge() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        // Submit badge creation request
+        let mut context = get_context(accounts(1));
+        let create_submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(create_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let create_proposal = c.spo_submit(create_submission);
+
+        // Accept badge creation request
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_accept(create_proposal.id.into());
+
+        // Submit badge extension request
+        let mut context = get_context(accounts(1));
+        let extend_submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(extend_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let extend_proposal = c.spo_submit(extend_submission);
+
+        // Accept badge extension request
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_accept(extend_proposal.id.into());
+
+        let expected_create = badge_create();
+        let expected = badge_extend();
+        let actual = c.get_badge(expected.id.clone());
+
+        require!(actual.is_some(), "Badge exists after extend",);
+
+        let actual = actual.unwrap();
+
+        require!(expected.id == actual.id, "IDs match",);
+        require!(
+            actual.duration.unwrap() == expected.duration + expected_create.duration,
+            "Duration after extend should be sum of original and extend request"
+        );
+    }
+
+    #[test]
+    fn try_accept_reports_validation_failure() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let first = c.spo_submit(submission);
+
+        let mut context = get_context(accounts(2));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let second = c.spo_submit(submission);
+
+        assert!(c.spo_try_accept(second.id.into()).can_accept);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(first.id.into());
+
+        let check = c.spo_try_accept(second.id.into());
+
+        assert!(!check.can_accept);
+        assert_eq!(
+            Some(ProposalValidationError::BadgeAlreadyExists {
+                badge_id: badge_create().id,
+            }),
+            check.error,
+        );
+        assert_eq!(Some("Badge ID already exists".to_string()), check.message);
+        assert_eq!(
+            ProposalStatus::PENDING,
+            c.spo_get_proposal(second.id.into()).unwrap().status,
+            "Checking should not change state",
+        );
+    }
+
+    #[test]
+    fn pricing_locked_at_submission() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        assert_eq!(
+            BADGE_RATE_PER_DAY,
+            c.get_proposal_terms(proposal.id.into())
+                .unwrap()
+                .rate_per_day
+                .0,
+        );
+
+        // Raising the rate would make the deposit insufficient
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_badge_rate_per_day((BADGE_RATE_PER_DAY * 2).into());
+        let accepted = c.spo_accept(proposal.id.into());
+
+        assert_eq!(ProposalStatus::ACCEPTED, accepted.status);
+        assert_eq!(0, accepted.refunded);
+        assert!(c.get_badge(badge_create().id).is_some());
+    }
+
+    #[test]
+    fn refund_surplus_deposit_on_accept() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let mut submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let price = submission.deposit.0;
+        submission.deposit = U128(price + ONE_NEAR);
+        context.attached_deposit(NearToken::from_yoctonear(price + ONE_NEAR + 10u128.pow(22)));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept(proposal.id.into());
+
+        assert_eq!(ONE_NEAR, accepted.refunded, "Surplus should be refunded");
+        assert_eq!(price, accepted.retained_deposit());
+        assert_eq!(price, c.spo_get_total_accepted_deposits().0);
+        assert_eq!(ONE_NEAR, c.spo_get_deposit_totals().rescinded.0);
+    }
+
+    #[test]
+    fn extend_badge_partially() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let create_submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(create_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let create_proposal = c.spo_submit(create_submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(create_proposal.id.into());
+
+        let mut context = get_context(accounts(1));
+        let extend_submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(extend_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let extend_proposal = c.spo_submit(extend_submission);
+
+        // Approve 3 of the 12 requested days
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let offered = c.spo_accept_partial(extend_proposal.id.into(), (ONE_DAY * 3).into());
+        assert_eq!(
+            ProposalStatus::PENDING,
+            offered.status,
+            "Acceptance should wait for the author to confirm the new terms",
+        );
+        assert_eq!(
+            U64(ONE_DAY * 3),
+            c.get_terms_offer(extend_proposal.id.into())
+                .unwrap()
+                .approved_duration
+        );
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_confirm_terms(extend_proposal.id.into());
+
+        assert_eq!(ProposalStatus::ACCEPTED, accepted.status);
+        assert_eq!(None, c.get_terms_offer(extend_proposal.id.into()));
+        assert_eq!(
+            extend_proposal.deposit * 9 / 12,
+            accepted.refunded,
+            "Unused portion of the deposit should be refunded",
+        );
+        assert_eq!(
+            badge_create().duration + ONE_DAY * 3,
+            c.get_badge(badge_extend().id).unwrap().duration.unwrap(),
+            "Badge should only be extended by the approved duration",
+        );
+        assert_eq!(
+            create_proposal.deposit + extend_proposal.deposit * 3 / 12,
+            c.spo_get_total_deposits().0,
+        );
+        assert_eq!(
+            content_hash(&accepted.description, accepted.msg.as_ref()),
+            accepted.content_hash,
+            "Content hash should cover the amended msg",
+        );
+        assert_ne!(extend_proposal.content_hash, accepted.content_hash);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeded maximum active duration")]
+    fn extend_badge_exceeds_max_duration() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        // Submit badge creation request
+        let mut context = get_context(accounts(1));
+        let create_submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(create_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let create_proposal = c.spo_submit(create_submission);
+
+        // Accept badge creation request
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_accept(create_proposal.id.into());
+
+        // Submit badge extension request
+        let mut context = get_context(accounts(1));
+        let original = BadgeExtend {
+            duration: BADGE_MAX_ACTIVE_DURATION - badge_create().duration + 1, // should exceed max duration by 1
+            ..badge_extend()
+        };
+        let extend_submission =
+            proposal_submission(BadgeAction::Extend(original), TAG_BADGE_EXTEND.to_string());
+
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(extend_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(extend_submission);
+    }
+
+    #[test]
+    fn two_phase_accept() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_approval_threshold(Some(ONE_NEAR.into()));
+        c.own_add_reviewer(accounts(2));
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert!(!c.spo_try_accept(proposal.id.into()).can_accept);
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let approved = c.spo_approve(proposal.id.into());
+        assert_eq!(ProposalStatus::PENDING, approved.status);
+        assert_eq!(Some(accounts(2)), approved.approved_by);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let finalized = c.spo_finalize(proposal.id.into());
+        assert_eq!(ProposalStatus::ACCEPTED, finalized.status);
+        assert_eq!(Some(accounts(2)), finalized.approved_by);
+        assert_eq!(Some(owner_account()), finalized.finalized_by);
+        assert!(c.get_badge(badge_create().id).is_some());
+    }
+
+    #[test]
+    fn sweep_holds_two_phase_proposal_under_accept_policy() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_approval_threshold(Some(ONE_NEAR.into()));
+        c.spo_set_expiry_policy(TAG_BADGE_CREATE.to_string(), ExpiryPolicy::ACCEPT);
+
+        let proposal = submit_badge_create(&mut c);
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+        c.spo_sweep_expired(0.into(), 10.into());
+
+        assert_eq!(
+            ProposalStatus::EXPIRED,
+            c.spo_get_proposal(proposal.id.into()).unwrap().status,
+            "Sweeping should not accept a proposal that requires two-phase approval",
+        );
+        assert!(c.get_badge(badge_create().id).is_none());
+    }
+
+    #[test]
+    fn sweep_holds_proposal_that_cannot_be_accepted() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_expiry_policy(TAG_BADGE_CREATE.to_string(), ExpiryPolicy::ACCEPT);
+        c.set_max_badges_per_sponsor(Some(1));
+
+        let mut ids = vec![];
+        for badge_id in ["first", "second"] {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+
+        // Each passes on its own, but accepting the first uses up the
+        // sponsor's only slot
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+        let swept = c.spo_sweep_expired(0.into(), 10.into());
+
+        assert_eq!(2, swept.len());
+        assert_eq!(
+            ProposalStatus::ACCEPTED,
+            c.spo_get_proposal(ids[0].into()).unwrap().status,
+        );
+        assert!(c.get_badge("first".to_string()).is_some());
+        assert_eq!(
+            ProposalStatus::EXPIRED,
+            c.spo_get_proposal(ids[1].into()).unwrap().status,
+            "A proposal that fails acceptance should be held, not fail the sweep",
+        );
+        assert!(c.get_badge("second".to_string()).is_none());
+        assert!(c.check_invariants(None).ok);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal must be finalized by a different account")]
+    fn two_phase_accept_same_account() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_approval_threshold(Some(ONE_NEAR.into()));
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_approve(proposal.id.into());
+        c.spo_finalize(proposal.id.into());
+    }
+
+    #[test]
+    fn proposal_expiry_fields() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        let expires_at = proposal.created_at + PROPOSAL_DURATION;
+        assert_eq!(Some(expires_at), proposal.expires_at);
+        assert!(!proposal.is_expired);
+
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(expires_at);
+        testing_env!(context.build());
+        let proposal = c.spo_get_proposal(proposal.id.into()).unwrap();
+        assert!(!proposal.is_expired, "Should not be expired at expires_at");
+
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(expires_at + 1);
+        testing_env!(context.build());
+        let proposal = c.spo_get_proposal(proposal.id.into()).unwrap();
+        assert!(proposal.is_expired, "Should be expired after expires_at");
+        assert_eq!(Some(expires_at), proposal.expires_at);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn method_metrics() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for i in 0..2 {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: format!("my-badge-{}", i),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            c.spo_submit(submission);
+        }
+
+        let metrics = c.get_method_metrics();
+        assert_eq!(1, metrics.len());
+        assert_eq!("spo_submit", metrics[0].method);
+        assert_eq!(2, metrics[0].calls.0);
+        assert!(metrics[0].last_storage_delta.0 > 0);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.reset_method_metrics();
+        assert!(c.get_method_metrics().is_empty());
+    }
+
+    #[test]
+    fn proposal_headers() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut ids = vec![];
+        for badge_id in ["my-badge-01", "my-badge-02"] {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept(ids[1].into());
+
+        let headers = c.spo_get_proposal_headers(None, None, None);
+        assert_eq!(2, headers.len());
+        assert_eq!(accepted.header(), headers[1]);
+
+        let pending = c.spo_get_proposal_headers(Some(ProposalStatus::PENDING), None, None);
+        assert_eq!(1, pending.len());
+        assert_eq!(ids[0], pending[0].id);
+        assert_eq!(
+            c.spo_get_proposal(ids[0].into()).unwrap().msg,
+            Some(BadgeAction::Create(BadgeCreate {
+                id: "my-badge-01".to_string(),
+                ..badge_create()
+            })),
+            "Body should be stored alongside the header",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge name contains control characters")]
+    fn submit_rejects_control_characters() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                name: "My\u{0}Badge".to_string(),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge ID exceeds maximum length of 8 bytes")]
+    fn insert_badge_respects_input_limits() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_input_limits(InputLimits {
+            max_id_length: 8,
+            ..c.get_input_limits()
+        });
+        c.insert_badge(Badge {
+            id: "badge-too-long".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+    }
+
+    #[test]
+    fn balances_serialized_as_strings() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let json = serde_json::to_value(&proposal).unwrap();
+        assert_eq!(
+            serde_json::json!(proposal.deposit.to_string()),
+            json["deposit"]
+        );
+        assert_eq!(serde_json::json!("0"), json["refunded"]);
+        assert_eq!(3, c.get_api_version());
+
+        // Version 1 clients sent balances as numbers
+        let badge: Badge = serde_json::from_str(
+            r#"{"id":"a","group_id":"b","name":"c","description":"d","is_enabled":true,
+            "created_at":0,"start_at":0,"duration":null,"paused_at":null,
+            "sponsor_id":null,"bond":1000}"#,
+        )
+        .unwrap();
+        assert_eq!(1000, badge.bond);
+    }
+
+    #[test]
+    fn timestamps_serialized_as_strings() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let json = serde_json::to_value(&proposal).unwrap();
+        assert_eq!(serde_json::json!(proposal.id.to_string()), json["id"]);
+        assert_eq!(
+            serde_json::json!(proposal.created_at.to_string()),
+            json["created_at"]
+        );
+        assert_eq!(
+            serde_json::json!(PROPOSAL_DURATION.to_string()),
+            json["duration"]
+        );
+        assert_eq!(serde_json::Value::Null, json["resolved_at"]);
+        assert_eq!(
+            serde_json::json!((ONE_DAY * 45).to_string()),
+            json["msg"]["Create"]["duration"]
+        );
+
+        // Numbers and missing optional fields are still accepted
+        let create: BadgeCreate = serde_json::from_str(
+            r#"{"id":"a","group_id":"b","name":"c","description":"d","duration":86400000000000}"#,
+        )
+        .unwrap();
+        assert_eq!(None, create.start_at);
+        assert_eq!(ONE_DAY, create.duration);
+    }
+
+    #[test]
+    fn duration_inputs() {
+        let parse = |duration: &str| -> Option<u64> {
+            serde_json::from_str::<BadgeExtend>(&format!(r#"{{"id":"a","duration":{}}}"#, duration))
+                .ok()
+                .map(|x| x.duration)
+        };
+
+        assert_eq!(Some(ONE_DAY * 45), parse("3888000000000000"));
+        assert_eq!(Some(ONE_DAY * 45), parse(r#""3888000000000000""#));
+        assert_eq!(Some(ONE_DAY * 45), parse(r#"{"days":45}"#));
+        assert_eq!(
+            Some(ONE_DAY + ONE_DAY / 2),
+            parse(r#"{"days":1,"hours":12}"#)
+        );
+        assert_eq!(Some(ONE_DAY * 45), parse(r#""P45D""#));
+        assert_eq!(Some(ONE_DAY * 14), parse(r#""P2W""#));
+        assert_eq!(Some(ONE_DAY + ONE_DAY / 2), parse(r#""P1DT12H""#));
+        assert_eq!(Some(90 * 1_000_000_000), parse(r#""PT1M30S""#));
+        assert_eq!(None, parse(r#"{"days":45,"minutes":1}"#));
+        assert_eq!(None, parse(r#""P1M""#));
+        assert_eq!(None, parse(r#""P1DT""#));
+        assert_eq!(None, parse(r#""P""#));
+        assert_eq!(None, parse(r#""P1.5D""#));
+
+        let submission: ProposalSubmission<BadgeAction> = serde_json::from_str(
+            r#"{"description":"d","tag":"t","msg":null,"duration":"P7D","deposit":"1"}"#,
+        )
+        .unwrap();
+        assert_eq!(Some(U64(PROPOSAL_DURATION)), submission.duration);
+        assert_eq!(
+            serde_json::json!(PROPOSAL_DURATION.to_string()),
+            serde_json::to_value(&submission).unwrap()["duration"]
+        );
+    }
+
+    #[test]
+    fn badge_activation_processed_when_window_begins() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        for (badge_id, start_at) in [("badge-now", 0), ("badge-later", ONE_DAY)] {
+            c.insert_badge(Badge {
+                id: badge_id.to_string(),
+                group_id: "group".to_string(),
+                name: badge_id.to_string(),
+                description: String::new(),
+                is_enabled: true,
+                created_at: 0,
+                start_at,
+                duration: None,
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+        }
+
+        assert_eq!(1, c.get_active_badges(None, None, None, None).len());
+        assert_eq!(2, c.get_badges(None, None, None, None).len());
+        assert_eq!(0, c.process_activations(None).0, "Nothing is due yet");
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        assert_eq!(
+            2,
+            c.get_active_badges(None, None, None, None).len(),
+            "Badge should be active as soon as its window begins",
+        );
+        assert_eq!(1, c.get_due_activations_count().0);
+        assert_eq!(1, c.process_activations(None).0);
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"badge_activated\"")
+                && l.contains("\"badge_id\":\"badge-later\"")));
+        assert_eq!(
+            0,
+            c.process_activations(None).0,
+            "Activation is only emitted once"
+        );
+    }
+
+    #[test]
+    fn badge_expiration_processed_by_keeper() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        for (badge_id, duration) in [("badge-short", Some(ONE_DAY)), ("badge-forever", None)] {
+            c.insert_badge(Badge {
+                id: badge_id.to_string(),
+                group_id: "group".to_string(),
+                name: badge_id.to_string(),
+                description: String::new(),
+                is_enabled: true,
+                created_at: 0,
+                start_at: 0,
+                duration,
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+        }
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        assert_eq!(
+            0,
+            c.process_expirations(None).0,
+            "Window ends after expires_at"
+        );
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(ONE_DAY + 1);
+        testing_env!(context.build());
+        assert_eq!(1, c.get_due_expirations_count().0);
+        assert_eq!(1, c.process_expirations(None).0);
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"badge_expired\"")
+                && l.contains("\"badge_id\":\"badge-short\"")));
+        assert_eq!(
+            0,
+            c.process_expirations(None).0,
+            "Expiration is only emitted once"
+        );
+    }
+
+    #[test]
+    fn keeper_rewards_limited_by_budget() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_keeper_reward(
+            KeeperTask::BADGE_EXPIRATIONS,
+            ONE_NEAR.into(),
+            (ONE_NEAR * 3 / 2).into(),
+        );
+        for badge_id in ["badge-a", "badge-b"] {
+            c.insert_badge(Badge {
+                id: badge_id.to_string(),
+                group_id: "group".to_string(),
+                name: badge_id.to_string(),
+                description: String::new(),
+                is_enabled: true,
+                created_at: 0,
+                start_at: 0,
+                duration: Some(ONE_DAY),
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+        }
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        assert_eq!(2, c.process_expirations(None).0);
+        assert_eq!(0, c.process_expirations(None).0);
+
+        let state = c.get_keeper_task(KeeperTask::BADGE_EXPIRATIONS);
+        assert_eq!(0, state.budget.0, "Payout should be capped by budget");
+        assert_eq!(ONE_NEAR * 3 / 2, state.rewards_paid.0);
+        assert_eq!(2, state.items_rewarded.0);
+        assert_eq!(
+            1,
+            get_logs()
+                .iter()
+                .filter(|l| l.contains("\"event\":\"keeper_paid\""))
+                .count(),
+            "No-op calls should not be paid",
+        );
+
+        assert_eq!(
+            0,
+            c.get_keeper_task(KeeperTask::PROPOSAL_SWEEP)
+                .reward_per_item
+                .0,
+            "Other tasks are unpaid by default",
+        );
+    }
+
+    #[test]
+    fn keeper_paid_for_sweeping() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_keeper_reward(KeeperTask::PROPOSAL_SWEEP, 1000.into(), ONE_NEAR.into());
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+        assert_eq!(1, c.spo_sweep_expired(0.into(), 10.into()).len());
+        assert_eq!(0, c.spo_sweep_expired(0.into(), 10.into()).len());
+
+        let state = c.get_keeper_task(KeeperTask::PROPOSAL_SWEEP);
+        assert_eq!(1000, state.rewards_paid.0);
+        assert_eq!(ONE_NEAR - 1000, state.budget.0);
+    }
+
+    #[test]
+    fn keeper_rewards_limited_by_available_balance() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_keeper_reward(KeeperTask::PROPOSAL_SWEEP, 1000.into(), ONE_NEAR.into());
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        // Everything the contract holds is the author's escrowed deposit
+        let mut context = get_context(accounts(2));
+        context
+            .block_timestamp(PROPOSAL_DURATION + 1)
+            .account_balance(NearToken::from_yoctonear(proposal.deposit));
+        testing_env!(context.build());
+        assert_eq!(1, c.spo_sweep_expired(0.into(), 10.into()).len());
+
+        let state = c.get_keeper_task(KeeperTask::PROPOSAL_SWEEP);
+        assert_eq!(0, state.rewards_paid.0);
+        assert_eq!(ONE_NEAR, state.budget.0);
+        assert_eq!(proposal.deposit, c.spo_get_total_escrowed().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Author may not sponsor badges in this group")]
+    fn verified_group_rejects_other_sponsors() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let group_account_id = c.get_group_account_id(badge_create().group_id).unwrap();
+        assert_eq!("my-badge.contract", group_account_id.as_str());
+        let mut context = get_context(group_account_id);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.verify_group(badge_create().group_id);
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn verified_group_accepts_named_sponsors() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let group_account_id = c.get_group_account_id(badge_create().group_id).unwrap();
+        let mut context = get_context(group_account_id);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.verify_group(badge_create().group_id);
+        c.set_group_sponsors(badge_create().group_id, vec![accounts(1)]);
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert_eq!(ProposalStatus::PENDING, proposal.status);
+    }
+
+    #[test]
+    fn claim_badge_with_attestation() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        c.add_oracle(accounts(3));
+        assert_eq!(vec![accounts(3)], c.get_oracles());
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        c.post_attestation(
+            "badge-a".to_string(),
+            accounts(1),
+            "100 transactions".to_string(),
+        );
+
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let award = c.claim_badge_with_attestation("badge-a".to_string());
+
+        assert_eq!(accounts(1), award.account_id);
+        assert!(c.get_award("badge-a".to_string(), accounts(1)).is_some());
+        assert!(
+            c.get_attestation("badge-a".to_string(), accounts(1))
+                .is_none(),
+            "Attestation should be used up",
+        );
+    }
+
+    #[test]
+    fn claim_badge_with_merkle_proof() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+
+        // Four recipients: accounts 1 through 4
+        let leaves: Vec<Vec<u8>> = (0..4)
+            .map(|i| merkle_leaf(i, &accounts(i as usize + 1)))
+            .collect();
+        let left = merkle_node(&leaves[0], &leaves[1]);
+        let right = merkle_node(&leaves[2], &leaves[3]);
+        let root = merkle_node(&left, &right);
+        c.set_merkle_root("badge-a".to_string(), Base64VecU8(root), 4.into());
+
+        // accounts(3) is leaf 2, a left child whose parent is a right child
+        let proof = vec![Base64VecU8(leaves[3].clone()), Base64VecU8(left)];
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        let award = c.claim_badge_with_proof("badge-a".to_string(), 2.into(), proof);
+
+        assert_eq!(accounts(3), award.account_id);
+        assert!(c.is_merkle_leaf_claimed("badge-a".to_string(), 2.into()));
+        assert!(!c.is_merkle_leaf_claimed("badge-a".to_string(), 3.into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid proof")]
+    fn claim_badge_with_merkle_proof_rejects_other_accounts() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        let leaves = [merkle_leaf(0, &accounts(1)), merkle_leaf(1, &accounts(2))];
+        let root = merkle_node(&leaves[0], &leaves[1]);
+        c.set_merkle_root("badge-a".to_string(), Base64VecU8(root), 2.into());
+
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        c.claim_badge_with_proof(
+            "badge-a".to_string(),
+            0.into(),
+            vec![Base64VecU8(leaves[1].clone())],
+        );
+    }
+
+    fn sign_claim(
+        issuer: &ed25519_dalek::SigningKey,
+        account_id: AccountId,
+        nonce: u64,
+    ) -> (String, Base64VecU8) {
+        use ed25519_dalek::Signer;
+
+        let payload = serde_json::to_string(&SignedClaim {
+            contract_id: contract_account(),
+            badge_id: "badge-a".to_string(),
+            account_id,
+            nonce: nonce.into(),
+        })
+        .unwrap();
+        let signature = issuer.sign(payload.as_bytes()).to_bytes().to_vec();
+
+        (payload, Base64VecU8(signature))
+    }
+
+    #[test]
+    fn claim_signed() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        let issuer = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let public_key = PublicKey::from_parts(
+            CurveType::ED25519,
+            issuer.verifying_key().to_bytes().to_vec(),
+        )
+        .unwrap();
+        c.set_claim_issuer("badge-a".to_string(), public_key.clone());
+        assert_eq!(Some(public_key), c.get_claim_issuer("badge-a".to_string()));
+
+        let (payload, signature) = sign_claim(&issuer, accounts(1), 42);
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let award = c.claim_signed("badge-a".to_string(), payload, signature);
+
+        assert_eq!(accounts(1), award.account_id);
+        assert!(c.is_signed_claim_used("badge-a".to_string(), 42.into()));
+        assert!(!c.is_signed_claim_used("badge-a".to_string(), 43.into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim has already been used")]
+    fn claim_signed_rejects_replay() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        let issuer = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        c.set_claim_issuer(
+            "badge-a".to_string(),
+            PublicKey::from_parts(
+                CurveType::ED25519,
+                issuer.verifying_key().to_bytes().to_vec(),
+            )
+            .unwrap(),
+        );
+
+        let (payload, signature) = sign_claim(&issuer, accounts(1), 42);
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        c.claim_signed("badge-a".to_string(), payload.clone(), signature.clone());
+
+        // Revoking the award doesn't make the claim usable again
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.revoke_award("badge-a".to_string(), accounts(1));
+
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        c.claim_signed("badge-a".to_string(), payload, signature);
+    }
+
+    #[test]
+    fn expired_awards_hidden_from_account_badges() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "season-2023".to_string(),
+            group_id: "group".to_string(),
+            name: "Season 2023".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        c.set_award_validity("season-2023".to_string(), Some(ONE_DAY.into()));
+        c.add_oracle(accounts(3));
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        c.post_attestation(
+            "season-2023".to_string(),
+            accounts(1),
+            "Played in 2023".to_string(),
+        );
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let award = c.claim_badge_with_attestation("season-2023".to_string());
+        assert_eq!(Some(ONE_DAY), award.expires_at);
+        assert_eq!(1, c.get_account_badges(accounts(1), None, None, None).len());
+
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(ONE_DAY + 1);
+        testing_env!(context.build());
+        assert!(
+            c.get_account_badges(accounts(1), None, None, None)
+                .is_empty(),
+            "Expired award should be hidden by default",
+        );
+        assert_eq!(
+            1,
+            c.get_account_badges(accounts(1), Some(true), None, None)
+                .len()
+        );
+        assert!(c
+            .get_award("season-2023".to_string(), accounts(1))
+            .is_some());
+    }
+
+    #[test]
+    fn transfer_and_merge_group() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.request_group_transfer(badge_create().group_id, accounts(2));
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let transferred = c.approve_group_transfer(badge_create().group_id);
+        assert_eq!(vec![badge_create().id], transferred);
+        assert_eq!(
+            Some(accounts(2)),
+            c.get_badge(badge_create().id).unwrap().sponsor_id,
+            "Sponsor attribution should move to the new account",
+        );
+        assert!(c.get_group_transfer(badge_create().group_id).is_none());
+
+        let merged = c.merge_groups(badge_create().group_id, "new-group".to_string());
+        assert_eq!(vec![badge_create().id], merged);
+        let badge = c.get_badge(badge_create().id).unwrap();
+        assert_eq!("new-group", badge.group_id);
+        assert_eq!(
+            vec![badge_create().id],
+            c.find_badges("new-group".to_string(), None)
+                .into_iter()
+                .map(|b| b.id)
+                .collect::<Vec<_>>(),
+            "Group index should follow the merge",
+        );
+    }
+
+    #[test]
+    fn record_badge_views_from_relayer() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.insert_badge(Badge {
+            id: "badge-a".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge A".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        c.add_view_relayer(accounts(3));
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        c.record_badge_view("badge-a".to_string(), 40.into());
+        c.record_badge_view("badge-a".to_string(), 2.into());
+
+        assert_eq!(42, c.get_badge_view_count("badge-a".to_string()).0);
+        assert_eq!(0, c.get_badge_view_count("badge-b".to_string()).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "View relayer only")]
+    fn record_badge_view_requires_relayer() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        c.record_badge_view("badge-a".to_string(), 1.into());
+    }
+
+    #[test]
+    fn receipt_for_accepted_proposal() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let deposit = u128::from(submission.deposit);
+        context.attached_deposit(NearToken::from_yoctonear(deposit + 10u128.pow(22)));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert!(proposal.storage_fee > 0, "Storage fee should be recorded");
+        assert!(
+            c.get_receipt(proposal.id.into()).is_none(),
+            "Pending proposals have no receipt",
+        );
+
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let receipt = c.get_receipt(proposal.id.into()).unwrap();
+        assert_eq!(accounts(1), receipt.payer_id);
+        assert_eq!(badge_create().id, receipt.badge_id);
+        assert_eq!(ONE_DAY, receipt.accepted_at.0);
+        assert_eq!(45, receipt.billable_days.0);
+        assert_eq!(c.get_badge_rate_per_day(), receipt.rate_per_day);
+        assert_eq!(deposit, receipt.deposit.0);
+        assert_eq!(proposal.storage_fee, receipt.storage_fee.0);
+        assert_eq!(
+            receipt.amount_charged.0 + receipt.storage_fee.0,
+            receipt.total_paid.0
+        );
+    }
+
+    #[test]
+    fn emergency_refund_in_batches() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (i, author) in [accounts(1), accounts(2)].into_iter().enumerate() {
+            let mut context = get_context(author);
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: format!("my-badge-0{}", i + 1),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            c.spo_submit(submission);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        let refunded = c.spo_emergency_refund(1.into());
+        assert_eq!(1, refunded.len());
+        assert_eq!(ProposalStatus::RESCINDED, refunded[0].status);
+        assert_eq!(accounts(1), refunded[0].author_id);
+        assert_eq!(U64(1), c.spo_get_emergency_refund_cursor());
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"proposal_emergency_refunded\"")));
+        assert_eq!(1, c.spo_get_pending_proposals().len());
+
+        let refunded = c.spo_emergency_refund(10.into());
+        assert_eq!(1, refunded.len());
+        assert_eq!(accounts(2), refunded[0].author_id);
+        assert_eq!(U64(2), c.spo_get_emergency_refund_cursor());
+        assert_eq!(0, c.spo_get_pending_proposals().len());
+        assert_eq!(U128(0), c.spo_get_total_deposits());
+
+        assert!(c.spo_emergency_refund(10.into()).is_empty());
+        assert_eq!(1, c.spo_get_author_stats(accounts(1)).rescinded);
+        assert_eq!(1, c.spo_get_author_stats(accounts(2)).rescinded);
+    }
+
+    #[test]
+    fn emergency_refund_after_cursor_reset() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let deposit = u128::from(submission.deposit);
+        context.attached_deposit(NearToken::from_yoctonear(10u128.pow(22)));
+        testing_env!(context.build());
+        let draft = c.spo_submit_draft(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        assert!(c.spo_emergency_refund(10.into()).is_empty());
+        assert_eq!(U64(1), c.spo_get_emergency_refund_cursor());
+
+        // Funded after the scan passed it
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(deposit + 10u128.pow(22)));
+        testing_env!(context.build());
+        c.spo_fund_draft(draft.id.into());
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        assert!(c.spo_emergency_refund(10.into()).is_empty());
+
+        c.spo_reset_emergency_refund_cursor();
+        assert_eq!(U64(0), c.spo_get_emergency_refund_cursor());
+        let refunded = c.spo_emergency_refund(10.into());
+        assert_eq!(1, refunded.len());
+        assert_eq!(draft.id, refunded[0].id);
+        assert_eq!(U128(0), c.spo_get_total_deposits());
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn emergency_refund_owner_only() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+
+        c.spo_emergency_refund(10.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge creation is closed for sunset")]
+    fn sunset_blocks_creation() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        c.begin_sunset(U64(ONE_DAY * 30));
+        assert_eq!(
+            Some(ONE_DAY * 30),
+            c.get_config().sunset.map(|s| s.deadline),
+            "Sunset should be exposed in config",
+        );
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn sunset_allows_extension_until_deadline() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        c.insert_badge(Badge {
+            id: "my-badge-01".to_string(),
+            group_id: "my-badge".to_string(),
+            name: "Cool Badge".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: Some(ONE_DAY * 10),
+            paused_at: None,
+            sponsor_id: Some(accounts(1)),
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        c.begin_sunset(U64(ONE_DAY * 5));
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let first = c.spo_submit(submission);
+        let submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        let second = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(first.id.into());
+        assert_eq!(
+            Some(ONE_DAY * 22),
+            c.get_badge("my-badge-01".to_string()).unwrap().duration,
+            "Extension should be accepted before the deadline",
+        );
+
+        let mut context = get_context(owner_account());
+        context.block_timestamp(ONE_DAY * 5);
+        testing_env!(context.build());
+        assert_eq!(
+            Some(ProposalValidationError::SunsetDeadlinePassed {
+                deadline: U64(ONE_DAY * 5),
+                now: U64(ONE_DAY * 5),
+            }),
+            c.spo_try_accept(second.id.into()).error,
+        );
+    }
+
+    #[test]
+    fn invariants_hold_and_detect_orphaned_awards() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for i in 1..=2 {
+            let mut context = get_context(accounts(i));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: format!("my-badge-0{}", i),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            c.spo_submit(submission);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(0.into());
+        c.award_badge("my-badge-01".to_string(), accounts(3));
+
+        let report = c.check_invariants(Some(vec![accounts(3)]));
+        assert!(report.ok, "{:?}", report.violations);
+        assert_eq!(U64(2), report.proposals_checked);
+        assert_eq!(U64(1), report.badges_checked);
+
+        c.remove_badge(&"my-badge-01".to_string());
+
+        let report = c.check_invariants(Some(vec![accounts(3)]));
+        assert!(!report.ok);
+        assert_eq!(
+            vec![format!(
+                "Award of my-badge-01 to {} refers to a missing badge",
+                accounts(3)
+            )],
+            report.violations,
+        );
+    }
+
+    #[cfg(feature = "demo")]
+    #[test]
+    fn seed_demo_data() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let data = c.seed_demo_data(DemoSpec {
+            badges: 3,
+            awards_per_badge: 2,
+            proposals_per_status: 1,
+        });
+
+        assert_eq!(3, data.badge_ids.len());
+        assert_eq!(U64(6), data.awards);
+        assert_eq!(8, data.proposal_ids.len());
+        assert!(c
+            .get_award(
+                data.badge_ids[0].clone(),
+                "demo-user-1.contract".parse().unwrap()
+            )
+            .is_some());
+        for status in [
+            ProposalStatus::ACCEPTED,
+            ProposalStatus::DISPUTED,
+            ProposalStatus::SCHEDULED,
+            ProposalStatus::DRAFT,
+        ] {
+            assert_eq!(
+                1,
+                c.spo_get_proposal_headers(Some(status), None, None).len()
+            );
+        }
+        assert!(c.check_invariants(None).ok);
+
+        let again = c.seed_demo_data(DemoSpec {
+            badges: 1,
+            awards_per_badge: 0,
+            proposals_per_status: 0,
+        });
+        assert!(!data.badge_ids.contains(&again.badge_ids[0]));
+    }
+
+    #[test]
+    fn badge_action_stored_versioned() {
+        let action = BadgeAction::Extend(badge_extend());
+        let stored = borsh::to_vec(&VersionedBadgeAction::from(BadgeAction::Extend(
+            badge_extend(),
+        )))
+        .unwrap();
+
+        assert_eq!(3, stored[0], "Should be tagged with the current version");
+        assert_eq!(borsh::to_vec(&action).unwrap(), stored[1..]);
+        assert_eq!(
+            action,
+            BadgeAction::from(VersionedBadgeAction::try_from_slice(&stored).unwrap()),
+        );
+
+        let legacy = [
+            vec![0],
+            borsh::to_vec(&BadgeActionV1::Extend(BadgeExtendV1 {
+                id: String::from("my-badge-01"),
+                duration: ONE_DAY * 12,
+            }))
+            .unwrap(),
+        ]
+        .concat();
+        assert_eq!(
+            action,
+            BadgeAction::from(VersionedBadgeAction::try_from_slice(&legacy).unwrap()),
+            "First version should decode without a stream",
+        );
+
+        let create = badge_create();
+        let legacy = [
+            vec![1],
+            borsh::to_vec(&BadgeActionV2::Create(BadgeCreateV2 {
+                id: create.id.clone(),
+                group_id: create.group_id.clone(),
+                name: create.name.clone(),
+                description: create.description.clone(),
+                start_at: create.start_at,
+                duration: create.duration,
+                stream_id: None,
+            }))
+            .unwrap(),
+        ]
+        .concat();
+        assert_eq!(
+            BadgeAction::Create(create),
+            BadgeAction::from(VersionedBadgeAction::try_from_slice(&legacy).unwrap()),
+            "Second version should decode as a published badge",
+        );
+
+        let create = badge_create();
+        let legacy = [
+            vec![2],
+            borsh::to_vec(&BadgeActionV3::Create(BadgeCreateV3 {
+                id: create.id.clone(),
+                group_id: create.group_id.clone(),
+                name: create.name.clone(),
+                description: create.description.clone(),
+                start_at: create.start_at,
+                duration: create.duration,
+                stream_id: None,
+                preview: false,
+                publish_at: None,
+            }))
+            .unwrap(),
+        ]
+        .concat();
+        assert_eq!(
+            BadgeAction::Create(create),
+            BadgeAction::from(VersionedBadgeAction::try_from_slice(&legacy).unwrap()),
+            "Third version should decode without media",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tag mismatch: badge_create expects Create")]
+    fn draft_tag_mismatch() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(10u128.pow(22)));
+        testing_env!(context.build());
+        c.spo_submit_draft(submission);
+    }
+
+    #[test]
+    fn tag_bindings() {
+        let create = BadgeAction::Create(badge_create());
+
+        assert_eq!(
+            Ok(()),
+            StatsGallery::validate_msg(TAG_BADGE_CREATE, Some(&create))
+        );
+        assert_eq!(
+            Err("msg value required: badge_extend expects Extend".to_string()),
+            StatsGallery::validate_msg(TAG_BADGE_EXTEND, None),
+        );
+        assert_eq!(Ok(()), StatsGallery::validate_msg("other", None));
+    }
+
+    #[test]
+    fn general_sponsorship() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            description: "Keep up the good work".to_string(),
+            tag: TAG_GENERAL_SPONSORSHIP.to_string(),
+            msg: None,
+            duration: None,
+            deposit: U128(ONE_NEAR),
+            nonce: None,
+            referrer_id: None,
+            promo_code: None,
+            max_storage_fee: None,
+            previous_proposal_id: None,
+        };
+        context.attached_deposit(NearToken::from_yoctonear(ONE_NEAR + 10u128.pow(22)));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        assert!(c.spo_try_accept(proposal.id.into()).can_accept);
+        let proposal = c.spo_accept(proposal.id.into());
+        assert_eq!(ProposalStatus::ACCEPTED, proposal.status);
+
+        assert_eq!(U64(1), c.get_supporters_count());
+        let supporters = c.get_supporters(None, None);
+        assert_eq!(accounts(1), supporters[0].account_id);
+        assert_eq!("Keep up the good work", supporters[0].description);
+        assert_eq!(U128(ONE_NEAR), supporters[0].amount);
+        assert_eq!(U128(ONE_NEAR), c.spo_get_total_accepted_deposits());
+    }
+
+    #[test]
+    #[should_panic(expected = "tag mismatch: general_sponsorship expects no msg")]
+    fn general_sponsorship_with_msg() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_GENERAL_SPONSORSHIP.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn donations() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        for (account, amount, message) in [
+            (accounts(1), ONE_NEAR, Some("Love the badges")),
+            (accounts(2), ONE_NEAR * 2, None),
+            (accounts(1), ONE_NEAR * 3, Some("Again!")),
+        ] {
+            let mut context = get_context(account);
+            context.attached_deposit(NearToken::from_yoctonear(amount));
+            testing_env!(context.build());
+            c.donate(message.map(String::from));
+        }
+
+        assert_eq!(
+            vec![
+                TopSupporter {
+                    account_id: accounts(1),
+                    total_donated: U128(ONE_NEAR * 4),
+                },
+                TopSupporter {
+                    account_id: accounts(2),
+                    total_donated: U128(ONE_NEAR * 2),
+                },
+            ],
+            c.get_top_supporters(None),
+        );
+
+        let recent = c.get_recent_donations(Some(U64(2)));
+        assert_eq!(2, recent.len());
+        assert_eq!(Some("Again!".to_string()), recent[0].message);
+        assert_eq!(accounts(2), recent[1].donor_id);
+
+        // Only the latest 100 donations are kept
+        for i in 0..100 {
+            let mut context = get_context(accounts(3));
+            context.attached_deposit(NearToken::from_yoctonear(ONE_NEAR / 100));
+            testing_env!(context.build());
+            c.donate(Some(format!("Donation {}", i)));
+        }
+        let recent = c.get_recent_donations(Some(U64(50)));
+        assert_eq!(50, recent.len());
+        assert_eq!(Some("Donation 99".to_string()), recent[0].message);
+        assert_eq!(Some("Donation 50".to_string()), recent[49].message);
+    }
+
+    #[test]
+    #[should_panic(expected = "Donation must be at least")]
+    fn donation_below_minimum() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.donate(None);
+    }
+
+    fn stream_config() -> StreamConfig {
+        StreamConfig {
+            contract_id: "streams".parse().unwrap(),
+            treasury_id: "treasury".parse().unwrap(),
+        }
+    }
+
+    fn stream_result(stream: Option<StreamView>) -> Vec<PromiseResult> {
+        vec![PromiseResult::Successful(
+            serde_json::to_vec(&stream).unwrap(),
+        )]
+    }
+
+    fn check_stream(c: &mut StatsGallery, badge_id: &str, stream: Option<StreamView>) {
+        let context = get_context(contract_account());
+        testing_env!(
+            context.build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            stream_result(stream)
+        );
+        c.on_badge_stream_checked(badge_id.to_string());
+    }
+
+    fn create_streamed_badge(c: &mut StatsGallery) -> String {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_stream_config(Some(stream_config()));
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            deposit: U128(metadata_storage(&badge_create())),
+            ..proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    stream_id: Some("stream-1".to_string()),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        badge_create().id
+    }
+
+    #[test]
+    fn streamed_badge_checks() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let badge_id = create_streamed_badge(&mut c);
+
+        let badge_stream = c.get_badge_stream(badge_id.clone()).unwrap();
+        assert_eq!("stream-1", badge_stream.stream_id);
+        assert_eq!(None, badge_stream.is_healthy);
+
+        let healthy = StreamView {
+            owner_id: accounts(1),
+            receiver_id: stream_config().treasury_id,
+            tokens_per_sec: U128(BADGE_RATE_PER_DAY / (60 * 60 * 24) + 1),
+            is_active: true,
+        };
+        check_stream(&mut c, &badge_id, Some(healthy.clone()));
+        assert_eq!(
+            Some(true),
+            c.get_badge_stream(badge_id.clone()).unwrap().is_healthy
+        );
+        c.award_badge(badge_id.clone(), accounts(2));
+
+        // Paying someone other than the treasury disables the badge
+        check_stream(
+            &mut c,
+            &badge_id,
+            Some(StreamView {
+                receiver_id: accounts(3),
+                ..healthy.clone()
+            }),
+        );
+        let badge = c.get_badge(badge_id.clone()).unwrap();
+        assert!(!badge.is_enabled);
+        assert_eq!(
+            Some(STREAM_INACTIVE_REASON.to_string()),
+            badge.disabled_reason
+        );
+
+        check_stream(&mut c, &badge_id, Some(healthy));
+        assert!(c.get_badge(badge_id.clone()).unwrap().is_enabled);
+
+        check_stream(&mut c, &badge_id, None);
+        assert!(!c.get_badge(badge_id).unwrap().is_enabled);
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge payment stream has not been verified")]
+    fn streamed_badge_award_unverified() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let badge_id = create_streamed_badge(&mut c);
+
+        c.award_badge(badge_id, accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Payment streaming is not configured")]
+    fn streamed_badge_not_configured() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                stream_id: Some("stream-1".to_string()),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    fn wrap_token() -> AccountId {
+        "wrap.near".parse().unwrap()
+    }
+
+    fn accept_badge_and_approve_wrap_token(c: &mut StatsGallery) {
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.add_wrap_token(wrap_token());
+    }
+
+    fn wrap_callback(c: &mut StatsGallery, amount: u128, result: PromiseResult) -> bool {
+        let context = get_context(contract_account());
+        testing_env!(
+            context.build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        c.on_treasury_wrapped(wrap_token(), U128(amount))
+    }
+
+    #[test]
+    fn wrap_treasury() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        accept_badge_and_approve_wrap_token(&mut c);
+
+        let wrappable = c.get_wrappable_balance().0;
+        assert_eq!(c.spo_get_total_accepted_deposits().0, wrappable);
+
+        c.wrap_treasury(wrap_token(), U128(ONE_NEAR));
+        assert_eq!(
+            ONE_NEAR,
+            c.get_treasury_position(wrap_token()).pending.0,
+            "Amount should be pending until the callback"
+        );
+        assert_eq!(wrappable - ONE_NEAR, c.get_wrappable_balance().0);
+
+        assert!(wrap_callback(
+            &mut c,
+            ONE_NEAR,
+            PromiseResult::Successful(vec![])
+        ));
+        let position = c.get_treasury_position(wrap_token());
+        assert_eq!(ONE_NEAR, position.wrapped.0);
+        assert_eq!(0, position.pending.0);
+
+        // A failed wrap releases the amount again
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.wrap_treasury(wrap_token(), U128(ONE_NEAR));
+        assert!(!wrap_callback(&mut c, ONE_NEAR, PromiseResult::Failed));
+        let position = c.get_treasury_position(wrap_token());
+        assert_eq!(ONE_NEAR, position.wrapped.0);
+        assert_eq!(0, position.pending.0);
+        assert_eq!(wrappable - ONE_NEAR, c.get_wrappable_balance().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount exceeds wrappable balance")]
+    fn wrap_treasury_exceeds_accepted() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        accept_badge_and_approve_wrap_token(&mut c);
+
+        let wrappable = c.get_wrappable_balance().0;
+        c.wrap_treasury(wrap_token(), U128(wrappable + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not an approved token")]
+    fn wrap_treasury_unapproved_token() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        accept_badge_and_approve_wrap_token(&mut c);
+
+        c.wrap_treasury("other.near".parse().unwrap(), U128(ONE_NEAR));
+    }
+
+    #[test]
+    fn referral_rewards() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_referral_share_bps(1_000);
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            referrer_id: Some(accounts(2)),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        let deposit = u128::from(submission.deposit);
+        // The metadata storage deposit is held, not earned
+        let price = deposit - metadata_storage(&badge_create());
+        context.attached_deposit(NearToken::from_yoctonear(deposit + 10u128.pow(22)));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert_eq!(Some(accounts(2)), proposal.referrer_id);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let available = c.get_available_balance().0;
+        c.spo_accept(proposal.id.into());
+
+        assert_eq!(price / 10, c.get_referral_rewards(accounts(2)).0);
+        assert!(
+            c.get_available_balance().0 <= available + price - price / 10,
+            "Credited rewards should not be available to the owner"
+        );
+
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(price / 10, c.claim_referral_rewards().0);
+        assert_eq!(0, c.get_referral_rewards(accounts(2)).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Author cannot refer themselves")]
+    fn referral_self() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            referrer_id: Some(accounts(1)),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    fn create_promo_code(c: &mut StatsGallery, max_uses: Option<U64>, expires_at: Option<U64>) {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.create_promo_code(
+            "launch".to_string(),
+            PromoDiscount::BasisPoints(2_500),
+            max_uses,
+            expires_at,
+        );
+    }
+
+    fn submit_with_promo_code(c: &mut StatsGallery, id: &str) -> Proposal<BadgeAction> {
+        let action = BadgeAction::Create(BadgeCreate {
+            id: id.to_string(),
+            ..badge_create()
+        });
+        let mut context = get_context(accounts(1));
+        let storage = metadata_storage(&badge_create());
+        let submission = ProposalSubmission {
+            deposit: U128((calculate_deposit(&action) - storage) * 3 / 4 + storage),
+            promo_code: Some("launch".to_string()),
+            ..proposal_submission(action, TAG_BADGE_CREATE.to_string())
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission)
+    }
+
+    #[test]
+    fn promo_code_discount() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        create_promo_code(&mut c, Some(U64(2)), None);
+
+        let proposal = submit_with_promo_code(&mut c, "my-badge-01");
+        assert_eq!(Some("launch".to_string()), proposal.promo_code);
+        assert_eq!(
+            Some(PromoDiscount::BasisPoints(2_500)),
+            c.get_proposal_terms(proposal.id.into()).unwrap().discount
+        );
+        assert_eq!(1, c.get_promo_code("launch".to_string()).unwrap().uses.0);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept(proposal.id.into());
+        assert_eq!(0, accepted.refunded);
+        assert!(c.get_badge(badge_create().id).is_some());
+        assert_eq!(
+            Some("launch".to_string()),
+            c.get_receipt(proposal.id.into()).unwrap().promo_code
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Promo code has reached its usage limit")]
+    fn promo_code_usage_limit() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        create_promo_code(&mut c, Some(U64(1)), None);
+
+        submit_with_promo_code(&mut c, "my-badge-01");
+        submit_with_promo_code(&mut c, "my-badge-02");
+    }
+
+    #[test]
+    #[should_panic(expected = "Promo code has expired")]
+    fn promo_code_expired() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        create_promo_code(&mut c, None, Some(U64(0)));
+
+        submit_with_promo_code(&mut c, "my-badge-01");
+    }
+
+    #[test]
+    fn free_tier_badge() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.add_free_tier_account(accounts(1));
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            deposit: U128(ONE_NEAR / 10),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert!(
+            c.get_proposal_terms(proposal.id.into())
+                .unwrap()
+                .is_free_tier
+        );
+
+        // Removing the account does not affect proposals already submitted
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.remove_free_tier_account(accounts(1));
+        let accepted = c.spo_accept(proposal.id.into());
+
+        assert_eq!(
+            ONE_NEAR / 10 - metadata_storage(&badge_create()),
+            accepted.refunded,
+            "Only the metadata storage deposit should be held"
+        );
+        assert!(c.get_badge(badge_create().id).unwrap().is_community);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit does not meet minimum creation deposit requirement")]
+    fn free_tier_not_allowlisted() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            deposit: U128(metadata_storage(&badge_create())),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    fn submit_badge_create(c: &mut StatsGallery) -> Proposal<BadgeAction> {
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission)
+    }
+
+    #[test]
+    fn escrow_settle() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = submit_badge_create(&mut c);
+
+        let escrow = c.spo_get_escrow(proposal.id.into()).unwrap();
+        assert_eq!(EscrowState::HELD, escrow.state);
+        assert_eq!(proposal.deposit, escrow.amount.0);
+        assert_eq!(proposal.deposit, c.spo_get_total_escrowed().0);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        assert_eq!(
+            proposal.deposit,
+            c.spo_get_total_escrowed().0,
+            "Accepted deposits should stay escrowed until settled"
+        );
+        let available = c.get_available_balance().0;
+        let storage_usage = env::storage_usage();
+
+        let escrow = c.spo_settle(proposal.id.into());
+        assert_eq!(EscrowState::RELEASED, escrow.state);
+        assert_eq!(0, c.spo_get_total_escrowed().0);
+        let storage_cost = Balance::from(env::storage_usage() - storage_usage)
+            * env::storage_byte_cost().as_yoctonear();
+        assert_eq!(
+            available + proposal.deposit - storage_cost,
+            c.get_available_balance().0
+        );
+        assert!(c.check_invariants(None).ok);
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow has already been settled")]
+    fn escrow_settle_twice() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = submit_badge_create(&mut c);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.spo_settle(proposal.id.into());
+        c.spo_settle(proposal.id.into());
+    }
+
+    #[test]
+    fn escrow_rescind_refunds() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = submit_badge_create(&mut c);
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(proposal.id.into());
+
+        let escrow = c.spo_get_escrow(proposal.id.into()).unwrap();
+        assert_eq!(EscrowState::REFUNDED, escrow.state);
+        assert_eq!(proposal.deposit, escrow.refunded.0);
+        assert_eq!(0, c.spo_get_total_escrowed().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Escrow has already been settled")]
+    fn escrow_forfeit() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let proposal = submit_badge_create(&mut c);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
+        let escrow = c.spo_forfeit(proposal.id.into());
+        assert_eq!(EscrowState::FORFEITED, escrow.state);
+        assert_eq!(0, c.spo_get_total_escrowed().0);
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(proposal.id.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit has been forfeited")]
+    fn dispute_forfeited_proposal() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_dispute_window(Some(ONE_DAY.into()));
+        let proposal = submit_badge_create(&mut c);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
+        c.spo_forfeit(proposal.id.into());
+
+        let totals = c.spo_get_deposit_totals();
+        assert_eq!(
+            0, totals.rejected.0,
+            "Forfeited deposit is no longer rejected"
+        );
+        assert_eq!(proposal.deposit, totals.forfeited.0);
+        assert!(c.check_invariants(None).ok);
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_dispute(proposal.id.into(), "Badge meets policy".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Tag has no handler: custom")]
+    fn add_tag_without_handler() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        c.spo_add_tags(vec!["custom".to_string()]);
+    }
+
+    #[test]
+    fn add_tag_with_handler() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        c.spo_remove_tags(vec![TAG_GENERAL_SPONSORSHIP.to_string()]);
+        c.spo_add_tags(vec![TAG_GENERAL_SPONSORSHIP.to_string()]);
+        assert!(c
+            .spo_get_tags()
+            .contains(&TAG_GENERAL_SPONSORSHIP.to_string()));
+    }
+
+    fn accept_with_little_gas(c: &mut StatsGallery, proposal_id: u64) {
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .prepaid_gas(GAS_FOR_EXECUTION.saturating_sub(Gas::from_gas(1)));
+        testing_env!(context.build());
+        c.spo_accept(proposal_id.into());
+    }
+
+    #[test]
+    fn execute_accepted_after_queueing() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        accept_with_little_gas(&mut c, proposal.id);
+
+        assert!(
+            c.get_badge(badge_create().id).is_none(),
+            "Badge should not be created yet",
+        );
+        assert!(c.is_execution_pending(proposal.id.into()));
+        assert_eq!(vec![U64(proposal.id)], c.get_pending_executions(None, None));
+        assert!(c.check_invariants(None).violations.is_empty());
+
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        assert!(c.execute_accepted(proposal.id.into()));
+
+        let badge = c.get_badge(badge_create().id).unwrap();
+        assert_eq!(Some(accounts(1)), badge.sponsor_id);
+        assert!(!c.is_execution_pending(proposal.id.into()));
+        assert!(c.check_invariants(None).violations.is_empty());
+        assert!(
+            !c.execute_accepted(proposal.id.into()),
+            "Executing twice should do nothing",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge ID already exists")]
+    fn queued_creation_reserves_badge_id() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        accept_with_little_gas(&mut c, proposal.id);
+
+        let mut context = get_context(accounts(2));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    #[should_panic(expected = "Submission exceeds maximum size of 1024 bytes")]
+    fn reject_oversized_submission() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_max_submission_bytes(1024);
+
+        // Nested deeper than the JSON parser allows, so this only fails on
+        // size if the size is checked first
+        let mut context = get_context(accounts(1));
+        context.context.input = format!("{{\"submission\":{}", "[".repeat(1024)).into_bytes();
+        testing_env!(context.build());
+        parse_submission_input::<BadgeAction>(c.spo_get_max_submission_bytes());
+    }
+
+    #[test]
+    fn parse_submission_within_size() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        context.context.input = near_sdk::serde_json::to_vec(
+            &near_sdk::serde_json::json!({ "submission": submission }),
+        )
+        .unwrap();
+        testing_env!(context.build());
+        let submission = parse_submission_input::<BadgeAction>(c.spo_get_max_submission_bytes());
+        let proposal = c.spo_submit(submission);
+
+        assert_eq!(proposal.tag, TAG_BADGE_CREATE);
+        assert_eq!(proposal.status, ProposalStatus::PENDING);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sponsor has reached the maximum number of active badges")]
+    fn enforce_badge_cap_per_sponsor() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_max_badges_per_sponsor(Some(1));
+
+        let mut ids = vec![];
+        for badge_id in ["first", "second"] {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(ids[0].into());
+        assert_eq!(1, c.get_sponsor_badge_count(accounts(1)));
+        assert_eq!(0, c.get_sponsor_badge_count(accounts(2)));
+        assert_eq!(
+            Some(ProposalValidationError::SponsorBadgeLimitReached { count: 1, limit: 1 }),
+            c.spo_try_accept(ids[1].into()).error,
+        );
+
+        c.spo_accept(ids[1].into());
+    }
+
+    #[test]
+    fn waitlist_create_proposals_when_full() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_max_active_badges(Some(1));
+
+        let mut ids = vec![];
+        for (account, badge_id) in [(accounts(1), "first"), (accounts(2), "second")] {
+            let mut context = get_context(account);
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(ids[0].into());
+        c.spo_accept(ids[1].into());
+
+        assert!(c.get_badge("first".to_string()).is_some());
+        assert!(
+            c.get_badge("second".to_string()).is_none(),
+            "Badge should wait for a free slot",
+        );
+        let waitlist = c.get_waitlist(None, None);
+        assert_eq!(1, waitlist.len());
+        assert_eq!(U64(ids[1]), waitlist[0].proposal_id);
+        assert_eq!(1, c.get_active_badge_count().0);
+        assert_eq!(1, c.get_sponsor_badge_count(accounts(2)));
+        assert!(c.check_invariants(None).violations.is_empty());
+        assert_eq!(0, c.process_waitlist(None).0, "No slot is free yet");
+
+        let mut context = get_context(accounts(3));
+        context.block_timestamp(ONE_DAY * 46);
+        testing_env!(context.build());
+        c.process_expirations(None);
+
+        let badge = c.get_badge("second".to_string()).unwrap();
+        assert_eq!(ONE_DAY * 46, badge.created_at);
+        assert!(c.get_waitlist(None, None).is_empty());
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"proposal_promoted\"")));
+    }
+
+    #[test]
+    fn badge_json_includes_computed_fields() {
+        let mut context = get_context(owner_account());
+        context.block_timestamp(ONE_DAY * 12 + 1);
+        testing_env!(context.build());
+
+        let badge = Badge {
+            id: "badge".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: ONE_DAY * 10,
+            duration: Some(ONE_DAY * 30),
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        };
+        let json = serde_json::to_value(&badge).unwrap();
+        assert_eq!(json["expires_at"], (ONE_DAY * 40).to_string());
+        assert_eq!(json["active_days_total"], 30);
+        assert_eq!(json["active_days_remaining"], 28);
+        assert_eq!(json["start_at"], (ONE_DAY * 10).to_string());
+
+        let parsed: Badge = serde_json::from_value(json).unwrap();
+        assert_eq!(badge.start_at, parsed.start_at);
+
+        let indefinite = serde_json::to_value(&Badge {
+            duration: None,
+            ..badge
+        })
+        .unwrap();
+        assert!(indefinite["expires_at"].is_null());
+        assert!(indefinite["active_days_remaining"].is_null());
+    }
+
+    #[test]
+    fn badge_with_future_start_keeps_full_window() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                start_at: Some(ONE_DAY * 30),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let badge = c.get_badge(badge_create().id).unwrap();
+        assert_eq!(
+            Some(ONE_DAY * 75),
+            badge.expires_at(),
+            "Window should run for the full duration from start_at",
+        );
+        assert_eq!(Some(45), badge.active_days_total());
+        assert_eq!(Some(45), badge.active_days_remaining(0));
+    }
+
+    #[test]
+    fn proposal_updates_until_acked() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut ids = vec![];
+        for badge_id in ["first", "second"] {
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(
+                BadgeAction::Create(BadgeCreate {
+                    id: badge_id.to_string(),
+                    ..badge_create()
+                }),
+                TAG_BADGE_CREATE.to_string(),
+            );
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+        assert!(
+            c.get_unacked_updates(accounts(1)).is_empty(),
+            "Submissions are not updates",
+        );
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(ids[0].into());
+        c.spo_reject(ids[1].into());
+
+        let updates = c.get_unacked_updates(accounts(1));
+        assert_eq!(2, updates.len());
+        assert_eq!(U64(ids[0]), updates[0].proposal_id);
+        assert_eq!(ProposalStatus::ACCEPTED, updates[0].status);
+        assert_eq!(ProposalStatus::REJECTED, updates[1].status);
+        assert!(c.get_unacked_updates(accounts(2)).is_empty());
+
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        assert_eq!(1, c.ack_updates(updates[0].seq));
+        assert_eq!(vec![updates[1].clone()], c.get_unacked_updates(accounts(1)));
+        assert_eq!(1, c.ack_updates(updates[1].seq));
+        assert!(c.get_unacked_updates(accounts(1)).is_empty());
+        assert_eq!(0, c.ack_updates(updates[1].seq));
+    }
+
+    #[test]
+    fn tags_detailed() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_tag_min_deposit(TAG_BADGE_EXTEND.to_string(), ONE_NEAR.into());
+
+        let details = c.spo_get_tags_detailed();
+        assert_eq!(c.spo_get_tags().len(), details.len());
+
+        let create = details.iter().find(|d| d.tag == TAG_BADGE_CREATE).unwrap();
+        assert_eq!(Some("Create"), create.msg_variant);
+        assert_eq!(Some(U64(PROPOSAL_DURATION)), create.proposal_duration);
+        assert_eq!(
+            U128(BADGE_RATE_PER_DAY),
+            create.terms.as_ref().unwrap().rate_per_day
+        );
+
+        let extend = details.iter().find(|d| d.tag == TAG_BADGE_EXTEND).unwrap();
+        assert_eq!(U128(ONE_NEAR), extend.config.min_deposit);
+
+        let general = details
+            .iter()
+            .find(|d| d.tag == TAG_GENERAL_SPONSORSHIP)
+            .unwrap();
+        assert_eq!(None, general.msg_variant);
+        assert!(general.terms.is_none());
+    }
+
+    #[test]
+    fn version() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let c = create_instance();
+
+        let version = c.get_version();
+        assert_eq!(env!("CARGO_PKG_VERSION"), version.crate_version);
+        assert_eq!(c.get_api_version(), version.api_version);
+        assert_eq!(STATE_VERSION, version.state_version);
+        assert!(version.sdk_version.starts_with("5."));
+    }
+
+    #[cfg(feature = "sandbox")]
+    #[test]
+    fn time_offset_moves_clock() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.insert_badge(Badge {
+            id: "badge".to_string(),
+            group_id: "group".to_string(),
+            name: "Badge".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: Some(ONE_DAY),
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+        assert_eq!(0, c.get_due_expirations_count().0);
+
+        c.set_time_offset(U64(ONE_DAY + 1));
+
+        assert_eq!(ONE_DAY + 1, c.get_time_offset().0);
+        assert_eq!(1, c.get_due_expirations_count().0);
+    }
+
+    #[test]
+    fn quote_covers_submission() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let submission = || {
+            proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        let quote = c.spo_quote_submission(accounts(1), submission());
+        assert_eq!(quote.deposit, submission().deposit);
+        assert_eq!(
+            quote.total.0,
+            quote.deposit.0 + quote.storage_fee.0,
+            "Total should be the deposit plus the storage fee",
+        );
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(quote.total.0));
+        testing_env!(context.build());
+        c.spo_submit(submission());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds max_storage_fee")]
+    fn reject_storage_fee_above_max() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = ProposalSubmission {
+            max_storage_fee: Some(U128(1)),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    #[test]
+    fn extend_pending_proposals_with_duration() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut ids = vec![];
+        for (account, duration) in [(accounts(1), None), (accounts(2), Some(U64(ONE_DAY)))] {
+            let mut context = get_context(account);
+            let submission = ProposalSubmission {
+                duration,
+                ..proposal_submission(
+                    BadgeAction::Create(badge_create()),
+                    TAG_BADGE_CREATE.to_string(),
+                )
+            };
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            ids.push(c.spo_submit(submission).id);
+        }
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let extended = c.spo_set_duration(Some(U64(PROPOSAL_DURATION * 2)), Some(true));
+        assert_eq!(
+            extended,
+            vec![U64(ids[0])],
+            "Only the capped proposal should be extended"
+        );
+
+        let mut context = get_context(owner_account());
+        context.block_timestamp(PROPOSAL_DURATION + 1);
+        testing_env!(context.build());
+        assert!(!c.spo_get_proposal(ids[0].into()).unwrap().is_expired);
+        assert!(c.spo_get_proposal(ids[1].into()).unwrap().is_expired);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only a longer duration can extend pending proposals")]
+    fn extend_pending_requires_longer_duration() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_duration(Some(U64(ONE_DAY)), Some(true));
+    }
+
+    #[test]
+    fn publish_preview_badge() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                preview: true,
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let badge_id = badge_create().id;
+        assert!(
+            c.get_badges(None, None, None, None).is_empty(),
+            "Preview should not be listed publicly",
+        );
+        assert_eq!(1, c.get_all_badges_admin(None, None, None, None).len());
+        assert_eq!(1, c.get_badge_previews(accounts(1)).len());
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.publish_badge(badge_id.clone());
+
+        assert_eq!(1, c.get_badges(None, None, None, None).len());
+        assert!(c.get_badge_previews(accounts(1)).is_empty());
+        assert!(c.get_badge(badge_id).unwrap().is_published(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge can only be published by its sponsor or the owner")]
+    fn publish_badge_only_by_sponsor_or_owner() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.insert_badge(Badge {
+            id: "preview".to_string(),
+            group_id: "group".to_string(),
+            name: "Preview".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: Some(accounts(1)),
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: true,
+            publish_at: Some(1_000),
+            media_url: None,
+            storage_deposit: 0,
+        });
+        assert!(c.get_badges(None, None, None, None).is_empty());
+
+        let mut context = get_context(accounts(2));
+        context.block_timestamp(1_000);
+        testing_env!(context.build());
+        assert_eq!(
+            1,
+            c.get_badges(None, None, None, None).len(),
+            "Preview should publish itself at publish_at",
+        );
+
+        context
+            .block_timestamp(0)
+            .attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.publish_badge("preview".to_string());
+    }
+
+    #[test]
+    fn unwind_accepted_badge() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_unwind_window(Some(U64(ONE_DAY)));
+        c.spo_set_unwind_fee_bps(1_000);
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let accepted = c.spo_accept(proposal.id.into());
+        assert!(c.get_badge(badge_create().id).is_some());
+
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY);
+        testing_env!(context.build());
+        let unwound = c.spo_unwind(proposal.id.into());
+
+        assert_eq!(ProposalStatus::UNWOUND, unwound.status);
+        assert!(
+            c.get_badge(badge_create().id).is_none(),
+            "Badge should be deleted"
+        );
+        let unwind = c.spo_get_unwind(proposal.id.into()).unwrap();
+        let retained = accepted.deposit - accepted.refunded;
+        assert_eq!(retained / 10, unwind.fee.0);
+        assert_eq!(retained - unwind.fee.0, unwind.refund.0);
+        assert_eq!(
+            EscrowState::RELEASED,
+            c.spo_get_escrow(proposal.id.into()).unwrap().state,
+            "Fee should be released to the treasury",
+        );
+        let report = c.check_invariants(None);
+        assert!(report.ok, "{:?}", report.violations);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unwind window has closed")]
+    fn unwind_after_window() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.spo_set_unwind_window(Some(U64(ONE_DAY)));
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(ONE_DAY + 1);
+        testing_env!(context.build());
+        c.spo_unwind(proposal.id.into());
+    }
+
+    #[test]
+    fn proposal_handler_validates_without_panicking() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(ONE_NEAR + 10u128.pow(22)));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(ProposalSubmission {
+            description: "Keep up the good work".to_string(),
+            tag: TAG_GENERAL_SPONSORSHIP.to_string(),
+            msg: None,
+            duration: None,
+            deposit: U128(ONE_NEAR),
+            nonce: None,
+            referrer_id: None,
+            promo_code: None,
+            max_storage_fee: None,
+            previous_proposal_id: None,
+        });
+
+        assert_eq!(Ok(()), c.validate(&proposal));
+        assert_eq!(
+            Err("Only badge proposals can be unwound".to_string()),
+            c.validate(&Proposal {
+                status: ProposalStatus::UNWOUND,
+                ..proposal
+            }),
+        );
+    }
+
+    fn moderated_instance() -> StatsGallery {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.own_grant_role(accounts(2), Role::MODERATOR);
+        c.own_grant_role(accounts(3), Role::REVIEWER);
+
+        c
+    }
+
+    #[test]
+    fn moderator_holds_badge() {
+        let mut c = moderated_instance();
+        assert_eq!(vec![accounts(2)], c.own_get_role_members(Role::MODERATOR));
+        assert_eq!(vec![accounts(3)], c.own_get_reviewers());
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let held = c.hold_badge(badge_create().id, "Reported as phishing".to_string());
+        assert!(held.hold.is_some());
+        let released = c.release_badge(badge_create().id);
+        assert!(released.hold.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner or moderator only")]
+    fn reviewer_cannot_hold_badge() {
+        let mut c = moderated_instance();
+
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.hold_badge(badge_create().id, "Reported as phishing".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn set_duration_owner_only() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_duration(Some(ONE_DAY.into()), None);
+    }
+
+    #[test]
+    fn balance_changes_logged() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let attached = u128::from(submission.deposit) + 10u128.pow(22);
+        context.attached_deposit(NearToken::from_yoctonear(attached));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"balance_changed\"")
+                && l.contains("\"kind\":\"RECEIVED\"")
+                && l.contains(&format!("\"amount\":\"{}\"", attached))
+                && l.contains("\"reason\":\"proposal_submission\"")
+                && l.contains(&format!("\"proposal_id\":\"{}\"", proposal.id))));
+        assert!(get_logs().iter().any(|l| l.contains("\"kind\":\"SENT\"")
+            && l.contains("\"reason\":\"excess_deposit_refund\"")));
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(proposal.id.into());
+        assert!(get_logs().iter().any(|l| l.contains("\"kind\":\"SENT\"")
+            && l.contains(&format!("\"amount\":\"{}\"", proposal.deposit))
+            && l.contains("\"reason\":\"deposit_refund\"")
+            && l.contains(&format!("\"account_id\":\"{}\"", accounts(1)))));
+    }
+
+    fn future_create_proposal(policy: LeadTimePolicy) -> (StatsGallery, u64) {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_tag_lead_time_policy(TAG_BADGE_CREATE.to_string(), policy);
+
+        let mut context = get_context(accounts(1));
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                start_at: Some(ONE_DAY * 10),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+
+        (c, proposal.id)
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge starts too long after acceptance")]
+    fn accept_beyond_max_lead_time() {
+        let (mut c, id) = future_create_proposal(LeadTimePolicy::MAX_LEAD_TIME(U64(ONE_DAY * 7)));
+
+        let check = c.spo_try_accept(id.into());
+        assert!(!check.can_accept);
+        assert_eq!(
+            Some(ProposalValidationError::LeadTimeExceeded {
+                lead_time: U64(ONE_DAY * 10),
+                max_lead_time: U64(ONE_DAY * 7),
+            }),
+            check.error,
+        );
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(id.into());
+    }
+
+    #[test]
+    fn deposit_vests_at_start() {
+        let (mut c, id) = future_create_proposal(LeadTimePolicy::VEST_AT_START);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(id.into());
+        let escrow = c.spo_get_escrow(id.into()).unwrap();
+        assert_eq!(Some(U64(ONE_DAY * 10)), escrow.vests_at);
+
+        let context = get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 10)
+            .clone();
+        testing_env!(context.build());
+        let escrow = c.spo_settle(id.into());
+        assert_eq!(EscrowState::RELEASED, escrow.state);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit cannot be settled before it vests")]
+    fn settle_before_vesting() {
+        let (mut c, id) = future_create_proposal(LeadTimePolicy::VEST_AT_START);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(id.into());
+
+        let context = get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 9)
+            .clone();
+        testing_env!(context.build());
+        c.spo_settle(id.into());
+    }
+
+    #[test]
+    fn group_stats_rollup() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let rate = c.get_badge_rate_per_day().0;
+
+        for action in [
+            BadgeAction::Create(badge_create()),
+            BadgeAction::Extend(badge_extend()),
+        ] {
+            let tag = match action {
+                BadgeAction::Create(_) => TAG_BADGE_CREATE,
+                _ => TAG_BADGE_EXTEND,
+            };
+            let mut context = get_context(accounts(1));
+            let submission = proposal_submission(action, tag.to_string());
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            let proposal = c.spo_submit(submission);
+
+            let mut context = get_context(owner_account());
+            context.attached_deposit(NearToken::from_yoctonear(1));
+            testing_env!(context.build());
+            c.spo_accept(proposal.id.into());
+        }
+
+        assert_eq!(
+            GroupStats {
+                active_badges: 1,
+                sponsored_days: 57,
+                revenue: rate * 57,
+            },
+            c.get_group_stats(badge_create().group_id),
+        );
+
+        let context = get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 58)
+            .clone();
+        testing_env!(context.build());
+        assert_eq!(1, c.process_expirations(None).0);
+        let stats = c.get_group_stats(badge_create().group_id);
+        assert_eq!(0, stats.active_badges);
+        assert_eq!(57, stats.sponsored_days);
+    }
+
+    fn capped_instance() -> (StatsGallery, Proposal<BadgeAction>) {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        let submission = || {
+            proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_tag_max_pending_deposit(
+            TAG_BADGE_CREATE.to_string(),
+            Some(U128(u128::from(submission().deposit) * 3 / 2)),
+        );
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission().deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission());
+
+        (c, proposal)
+    }
+
+    #[test]
+    fn pending_deposit_tracked_per_tag() {
+        let (mut c, proposal) = capped_instance();
+        assert_eq!(
+            proposal.deposit,
+            c.spo_get_pending_deposit(TAG_BADGE_CREATE.to_string()).0
+        );
+        assert_eq!(0, c.spo_get_pending_deposit(TAG_BADGE_EXTEND.to_string()).0);
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(proposal.id.into());
+        assert_eq!(0, c.spo_get_pending_deposit(TAG_BADGE_CREATE.to_string()).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Pending deposits for tag badge_create would exceed the cap")]
+    fn pending_deposit_cap_reached() {
+        let (mut c, proposal) = capped_instance();
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(proposal.deposit + 10u128.pow(22)));
+        testing_env!(context.build());
+        c.spo_submit(proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                id: "my-badge-02".to_string(),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        ));
+    }
+
+    #[test]
+    fn account_badges_paginated() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.add_oracle(accounts(3));
+        let badge_ids: Vec<String> = (0..3).map(|i| format!("badge-{}", i)).collect();
+        for badge_id in badge_ids.iter() {
+            let mut context = get_context(owner_account());
+            context.attached_deposit(NearToken::from_yoctonear(1));
+            testing_env!(context.build());
+            c.insert_badge(Badge {
+                id: badge_id.clone(),
+                group_id: "group".to_string(),
+                name: badge_id.clone(),
+                description: String::new(),
+                is_enabled: true,
+                created_at: 0,
+                start_at: 0,
+                duration: None,
+                paused_at: None,
+                sponsor_id: None,
+                bond: 0,
+                hold: None,
+                disabled_reason: None,
+                is_community: false,
+                is_preview: false,
+                publish_at: None,
+                media_url: None,
+                storage_deposit: 0,
+            });
+
+            let context = get_context(accounts(3));
+            testing_env!(context.build());
+            c.post_attestation(badge_id.clone(), accounts(1), "Earned".to_string());
+            let context = get_context(accounts(1));
+            testing_env!(context.build());
+            c.claim_badge_with_attestation(badge_id.clone());
+        }
+
+        assert_eq!(3, c.get_account_badge_count(accounts(1)).0);
+        assert_eq!(0, c.get_account_badge_count(accounts(2)).0);
+        let page = |from_index: u64| {
+            c.get_account_badges(accounts(1), None, Some(from_index.into()), Some(U64(2)))
+                .into_iter()
+                .map(|award| award.badge_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(badge_ids[..2].to_vec(), page(0));
+        assert_eq!(badge_ids[2..].to_vec(), page(2));
+        assert!(page(3).is_empty());
+    }
+
+    #[test]
+    fn award_nft_events() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.add_oracle(accounts(3));
+        c.insert_badge(Badge {
+            id: "season-2023".to_string(),
+            group_id: "group".to_string(),
+            name: "Season 2023".to_string(),
+            description: String::new(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: None,
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        });
+
+        let context = get_context(accounts(3));
+        testing_env!(context.build());
+        c.post_attestation(
+            "season-2023".to_string(),
+            accounts(1),
+            "Played in 2023".to_string(),
+        );
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        c.claim_badge_with_attestation("season-2023".to_string());
+        assert!(get_logs().contains(&format!(
+            "EVENT_JSON:{{\"standard\":\"nep171\",\"version\":\"1.0.0\",\"event\":\"nft_mint\",\"data\":[{{\"owner_id\":\"{0}\",\"token_ids\":[\"season-2023:{0}\"]}}]}}",
+            accounts(1)
+        )));
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.revoke_award("season-2023".to_string(), accounts(1));
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"nft_burn\"")
+                && l.contains(&format!("\"authorized_id\":\"{}\"", owner_account()))));
+        assert!(c
+            .get_award("season-2023".to_string(), accounts(1))
+            .is_none());
+        assert_eq!(0, c.get_account_badge_count(accounts(1)).0);
+    }
+
+    #[test]
+    fn group_default_media_fills_badges_without_media() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let group_id = badge_create().group_id;
+        let group_account_id = c.get_group_account_id(group_id.clone()).unwrap();
+        let mut context = get_context(group_account_id.clone());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.verify_group(group_id.clone());
+        c.set_group_default_media(group_id.clone(), Some("ipfs://default-art".to_string()));
+
+        for create in [
+            badge_create(),
+            BadgeCreate {
+                id: "my-badge-02".to_string(),
+                media_url: Some("ipfs://own-art".to_string()),
+                ..badge_create()
+            },
+        ] {
+            let mut context = get_context(group_account_id.clone());
+            let submission =
+                proposal_submission(BadgeAction::Create(create), TAG_BADGE_CREATE.to_string());
+            context.attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ));
+            testing_env!(context.build());
+            let proposal = c.spo_submit(submission);
+
+            let mut context = get_context(owner_account());
+            context.attached_deposit(NearToken::from_yoctonear(1));
+            testing_env!(context.build());
+            c.spo_accept(proposal.id.into());
+        }
+
+        let badge_id = badge_create().id;
+        assert_eq!(
+            Some("ipfs://default-art".to_string()),
+            c.get_badge(badge_id.clone()).unwrap().media_url,
+        );
+        assert_eq!(
+            Some("ipfs://own-art".to_string()),
+            c.get_badge("my-badge-02".to_string()).unwrap().media_url,
+        );
+        assert!(c
+            .get_badges(None, None, None, None)
+            .iter()
+            .all(|b| b.media_url.is_some()));
+
+        // The owner may change it too, and badges follow since the default
+        // was never stored on them
+        c.set_group_default_media(group_id, None);
+        assert_eq!(None, c.get_badge(badge_id).unwrap().media_url);
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner only")]
+    fn group_default_media_group_account_only() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.set_group_default_media(
+            badge_create().group_id,
+            Some("ipfs://default-art".to_string()),
+        );
+    }
+
+    #[test]
+    fn archive_badge_refunds_storage_deposit() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let storage_deposit = metadata_storage(&badge_create());
+        assert_eq!(
+            storage_deposit,
+            c.get_badge(badge_create().id).unwrap().storage_deposit
+        );
+        assert_eq!(storage_deposit, c.get_total_storage_deposits_held().0);
+        assert_eq!(
+            storage_deposit,
+            c.get_receipt(proposal.id.into()).unwrap().storage_deposit.0
+        );
+
+        let mut context = get_context(accounts(1))
+            .block_timestamp(ONE_DAY * 46)
+            .clone();
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let badge = c.archive_badge(badge_create().id);
+
+        assert_eq!("", badge.description);
+        assert_eq!(0, badge.storage_deposit);
+        assert_eq!(0, c.get_total_storage_deposits_held().0);
+        assert!(get_logs().iter().any(|l| l.contains("\"kind\":\"SENT\"")
+            && l.contains(&format!("\"amount\":\"{}\"", storage_deposit))
+            && l.contains("\"reason\":\"storage_deposit_refund\"")));
+        assert!(c.check_invariants(None).ok);
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge cannot be archived until it expires")]
+    fn archive_badge_before_expiry() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.archive_badge(badge_create().id);
+    }
+
+    #[test]
+    fn prune_long_expired_badges() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.award_badge(badge_create().id, accounts(3));
+        c.set_badge_prune_after(Some(U64(ONE_DAY * 30)));
+
+        // Expired after 45 days, but not for long enough
+        testing_env!(get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 70)
+            .build());
+        assert_eq!(0, c.prune_badges(None).0);
+
+        testing_env!(get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 76)
+            .build());
+        assert_eq!(1, c.prune_badges(None).0);
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"event\":\"badge_pruned\"")
+                && l.contains(&format!("\"id\":\"{}\"", badge_create().id))
+                && l.contains(&format!(
+                    "\"description\":\"{}\"",
+                    badge_create().description
+                ))));
+        assert!(get_logs()
+            .iter()
+            .any(|l| l.contains("\"reason\":\"storage_deposit_refund\"")));
+        assert!(c.get_badge(badge_create().id).is_none());
+        assert!(c.is_badge_pruned(badge_create().id));
+        assert_eq!(0, c.get_total_storage_deposits_held().0);
+        assert!(c.get_award(badge_create().id, accounts(3)).is_some());
+        let report = c.check_invariants(Some(vec![accounts(3)]));
+        assert!(report.ok, "{:?}", report.violations);
+    }
+
+    #[test]
+    #[should_panic(expected = "Badge ID already exists")]
+    fn pruned_badge_id_not_reused() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        c.set_badge_prune_after(Some(U64(0)));
+
+        testing_env!(get_context(accounts(2))
+            .block_timestamp(ONE_DAY * 46)
+            .build());
+        assert_eq!(1, c.prune_badges(None).0);
+
+        let mut context = get_context(accounts(1))
+            .block_timestamp(ONE_DAY * 46)
+            .clone();
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    fn forward_callback(c: &mut StatsGallery, amount: u128, result: PromiseResult) -> bool {
+        let context = get_context(contract_account());
+        testing_env!(
+            context.build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        c.on_treasury_forwarded(accounts(4), U128(amount))
+    }
+
+    #[test]
+    fn settled_deposits_forwarded_to_treasury() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+        accept_badge_and_approve_wrap_token(&mut c);
+        c.set_treasury_account(Some(accounts(4)));
+        assert_eq!(Some(accounts(4)), c.get_config().treasury_account_id);
+
+        let forwardable = c.get_wrappable_balance().0;
+        testing_env!(get_context(accounts(2)).build());
+        c.spo_settle(0.into());
+        assert!(get_logs().iter().any(|l| l.contains("\"kind\":\"SENT\"")
+            && l.contains(&format!("\"account_id\":\"{}\"", accounts(4)))
+            && l.contains(&format!("\"amount\":\"{}\"", forwardable))
+            && l.contains("\"reason\":\"treasury_forward\"")));
+        assert_eq!(
+            0,
+            c.get_wrappable_balance().0,
+            "Amount should be pending until the callback"
+        );
+
+        // A failed transfer releases the amount again
+        assert!(!forward_callback(
+            &mut c,
+            forwardable,
+            PromiseResult::Failed
+        ));
+        assert_eq!(0, c.get_total_forwarded().0);
+        assert_eq!(forwardable, c.get_wrappable_balance().0);
+
+        testing_env!(get_context(accounts(2)).build());
+        c.forward_to_treasury();
+        assert!(forward_callback(
+            &mut c,
+            forwardable,
+            PromiseResult::Successful(vec![])
+        ));
+        assert_eq!(forwardable, c.get_total_forwarded().0);
+        assert_eq!(0, c.get_wrappable_balance().0);
+    }
+
+    #[test]
+    fn health_reports_liabilities_and_headroom() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+
+        let health = c.get_health();
+        assert_eq!(c.spo_get_total_escrowed().0, health.escrowed.0);
+        assert_eq!(
+            metadata_storage(&badge_create()),
+            health.storage_deposits_held.0
+        );
+        assert_eq!(
+            health.escrowed.0 + health.storage_deposits_held.0,
+            health.liabilities.0
+        );
+        assert_eq!(
+            u128::from(health.storage_usage.0) * env::storage_byte_cost().as_yoctonear(),
+            health.storage_staking_cost.0
+        );
+        assert_eq!(c.get_available_balance(), health.headroom);
+        assert_eq!(
+            (health.headroom.0 / env::storage_byte_cost().as_yoctonear()) as u64,
+            health.headroom_bytes.0
+        );
+        assert_eq!(0, health.pending_executions.0);
+    }
+
+    fn queue_capped_instance() -> (StatsGallery, Proposal<BadgeAction>) {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_max_pending_proposals(Some(U64(1)));
+
+        let proposal = submit_badge_create(&mut c);
+
+        (c, proposal)
+    }
+
+    #[test]
+    fn pending_count_tracked() {
+        let (mut c, proposal) = queue_capped_instance();
+        assert_eq!(1, c.spo_get_pending_count().0);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(proposal.id.into());
+        assert_eq!(0, c.spo_get_pending_count().0);
+
+        // The freed slot can be used again
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                id: "badge_2".to_string(),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+        assert_eq!(1, c.spo_get_pending_count().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal queue is full")]
+    fn pending_proposal_cap_reached() {
+        let (mut c, _) = queue_capped_instance();
+
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                id: "badge_2".to_string(),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission);
+    }
+
+    fn resubmit_badge_create(
+        c: &mut StatsGallery,
+        previous_proposal_id: u64,
+    ) -> Proposal<BadgeAction> {
+        let submission = ProposalSubmission {
+            previous_proposal_id: Some(previous_proposal_id.into()),
+            ..proposal_submission(
+                BadgeAction::Create(badge_create()),
+                TAG_BADGE_CREATE.to_string(),
+            )
+        };
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        c.spo_submit(submission)
+    }
+
+    #[test]
+    fn resubmission_linked_to_rejected_proposal() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let first = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(first.id.into());
+
+        let second = resubmit_badge_create(&mut c, first.id);
+        assert_eq!(Some(first.id), second.previous_proposal_id);
+        assert_eq!(
+            Some(U64(second.id)),
+            c.spo_get_resubmission(first.id.into())
+        );
+        assert_eq!(None, c.spo_get_resubmission(second.id.into()));
+
+        let chain = c.spo_get_proposal_chain(second.id.into(), None);
+        assert_eq!(
+            vec![second.id, first.id],
+            chain.iter().map(|p| p.id).collect::<Vec<_>>()
+        );
+        assert_eq!(ProposalStatus::REJECTED, chain[1].status);
+        assert_eq!(
+            1,
+            c.spo_get_proposal_chain(second.id.into(), Some(U64(1)))
+                .len()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Previous proposal has already been resubmitted")]
+    fn rejected_proposal_resubmitted_once() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let first = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(first.id.into());
+
+        let second = resubmit_badge_create(&mut c, first.id);
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(second.id.into());
+
+        resubmit_badge_create(&mut c, first.id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Previous proposal was not rejected")]
+    fn pending_proposal_cannot_be_resubmitted() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let first = submit_badge_create(&mut c);
+        resubmit_badge_create(&mut c, first.id);
+    }
+
+    fn claimed_instance() -> (StatsGallery, Proposal<BadgeAction>) {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.own_add_reviewer(accounts(2));
+        c.own_add_reviewer(accounts(3));
+
+        let proposal = submit_badge_create(&mut c);
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let proposal = c.spo_claim_review(proposal.id.into());
+
+        (c, proposal)
+    }
+
+    #[test]
+    fn review_claim_recorded_and_released() {
+        let (mut c, proposal) = claimed_instance();
+        assert_eq!(Some(accounts(2)), proposal.claimed_by);
+        let headers = c.spo_get_proposal_headers(Some(ProposalStatus::PENDING), None, None);
+        assert_eq!(Some(accounts(2)), headers[0].claimed_by);
+
+        let mut context = get_context(accounts(2));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let proposal = c.spo_release_review(proposal.id.into());
+        assert_eq!(None, proposal.claimed_by);
+
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_claim_review(proposal.id.into());
+
+        // The owner can release anyone's claim
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_release_review(proposal.id.into());
+        let proposal = c.spo_accept(proposal.id.into());
+        assert_eq!(ProposalStatus::ACCEPTED, proposal.status);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal is claimed for review by")]
+    fn claimed_proposal_not_resolved_by_others() {
+        let (mut c, proposal) = claimed_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
+    }
+
+    #[test]
+    fn review_claim_taken_over_from_removed_reviewer() {
+        let (mut c, proposal) = claimed_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.own_remove_reviewer(accounts(2));
+
+        let mut context = get_context(accounts(3));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let proposal = c.spo_claim_review(proposal.id.into());
+        assert_eq!(Some(accounts(3)), proposal.claimed_by);
+    }
+
+    fn rejected_with_cooldown() -> StatsGallery {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_set_rejection_cooldown(Some(U64(ONE_DAY * 7)));
+
+        let proposal = submit_badge_create(&mut c);
+        assert!(proposal.msg_hash.is_some());
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_reject(proposal.id.into());
+
+        c
+    }
+
+    #[test]
+    #[should_panic(expected = "An identical proposal was rejected recently")]
+    fn rejection_cooldown_blocks_identical_resubmission() {
+        let mut c = rejected_with_cooldown();
+        submit_badge_create(&mut c);
+    }
+
+    #[test]
+    fn rejection_cooldown_allows_changes_and_expires() {
+        let mut c = rejected_with_cooldown();
+
+        // A changed msg is not held back
+        let submission = proposal_submission(
+            BadgeAction::Create(BadgeCreate {
+                description: "Now with a better description".to_string(),
+                ..badge_create()
+            }),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let changed = c.spo_submit(submission);
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_rescind(changed.id.into());
+
+        // Nor is the same msg once the cooldown is over
+        let submission = proposal_submission(
+            BadgeAction::Create(badge_create()),
+            TAG_BADGE_CREATE.to_string(),
+        );
+        let mut context = get_context(accounts(1));
+        context
+            .attached_deposit(NearToken::from_yoctonear(
+                u128::from(submission.deposit) + 10u128.pow(22),
+            ))
+            .block_timestamp(ONE_DAY * 7);
+        testing_env!(context.build());
+        let proposal = c.spo_submit(submission);
+        assert_eq!(ProposalStatus::PENDING, proposal.status);
+    }
+
+    #[test]
+    fn proposal_content_hash() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let proposal = submit_badge_create(&mut c);
+        let msg = BadgeAction::Create(badge_create());
+        let content = [
+            borsh::to_vec(&proposal.description).unwrap(),
+            borsh::to_vec(&Some(&msg)).unwrap(),
+        ]
+        .concat();
+        assert_eq!(env::sha256(&content), proposal.content_hash.0);
+        assert_eq!(
+            proposal.content_hash,
+            c.spo_get_proposal_headers(None, None, None)[0].content_hash
+        );
+        assert_ne!(
+            proposal.content_hash,
+            content_hash("Something else", Some(&msg))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Terms can only be confirmed by the proposal author")]
+    fn terms_confirmed_only_by_author() {
+        let context = get_context(owner_account());
+        testing_env!(context.build());
+        let mut c = create_instance();
+
+        let create_proposal = submit_badge_create(&mut c);
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept(create_proposal.id.into());
+
+        let mut context = get_context(accounts(1));
+        let extend_submission = proposal_submission(
+            BadgeAction::Extend(badge_extend()),
+            TAG_BADGE_EXTEND.to_string(),
+        );
+        context.attached_deposit(NearToken::from_yoctonear(
+            u128::from(extend_submission.deposit) + 10u128.pow(22),
+        ));
+        testing_env!(context.build());
+        let extend_proposal = c.spo_submit(extend_submission);
+
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.spo_accept_partial(extend_proposal.id.into(), (ONE_DAY * 3).into());
+        c.spo_confirm_terms(extend_proposal.id.into());
+    }
+
+    fn gold_package() -> Package {
+        Package {
+            id: "gold".to_string(),
+            name: "Gold".to_string(),
+            badge_count: 2,
+            badge_duration: U64(ONE_DAY * 30),
+            featured_duration: U64(ONE_DAY * 90),
+            price: U128(50 * ONE_NEAR),
+        }
+    }
+
+    fn package_badges(ids: &[&str]) -> BadgeAction {
+        BadgeAction::Package(BadgePackage {
+            package_id: "gold".to_string(),
+            badges: ids
+                .iter()
+                .map(|id| BadgeCreate {
+                    id: id.to_string(),
+                    duration: ONE_DAY * 30,
+                    ..badge_create()
+                })
+                .collect(),
+        })
+    }
+
+    fn submit_package(c: &mut StatsGallery, action: BadgeAction) -> Proposal<BadgeAction> {
+        let mut context = get_context(accounts(1));
+        let mut submission = proposal_submission(action, TAG_BADGE_PACKAGE.to_string());
+        submission.deposit = U128(submission.deposit.0 + 50 * ONE_NEAR);
+        context
+            .attached_deposit(NearToken::from_yoctonear(
+                submission.deposit.0 + 10u128.pow(22),
+            ))
+            .account_balance(NearToken::from_yoctonear(100 * ONE_NEAR));
+        testing_env!(context.build());
+        c.spo_submit(submission)
+    }
+
+    #[test]
+    fn package_creates_and_features_all_badges() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_package(gold_package());
+        c.set_featured_badge_ids(vec![]);
+
+        let proposal = submit_package(&mut c, package_badges(&["gold-01", "gold-02"]));
+        assert_eq!(
+            Some(gold_package()),
+            c.get_proposal_package(proposal.id.into()),
+            "Package should be recorded at submission",
+        );
+        // Later changes do not affect the proposal
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .account_balance(NearToken::from_yoctonear(100 * ONE_NEAR))
+            .block_timestamp(1_000);
+        testing_env!(context.build());
+        c.set_package(Package {
+            price: U128(80 * ONE_NEAR),
+            ..gold_package()
+        });
+        c.spo_accept(proposal.id.into());
+
+        for id in ["gold-01", "gold-02"] {
+            let badge = c.get_badge(id.to_string()).unwrap();
+            assert_eq!(Some(ONE_DAY * 30), badge.duration, "Package duration");
+            assert_eq!(Some(accounts(1)), badge.sponsor_id, "Sponsor");
+            assert_eq!(
+                Some(U64(1_000 + ONE_DAY * 90)),
+                c.get_badge_featured_until(id.to_string()),
+                "Badges should be featured for the package period",
+            );
+        }
+        assert_eq!(
+            vec!["gold-01", "gold-02"],
+            c.get_gallery_home(None)
+                .featured
+                .iter()
+                .map(|b| b.id.as_str())
+                .collect::<Vec<_>>(),
+            "Package badges should be featured on the gallery home",
+        );
+        assert_eq!(
+            50 * ONE_NEAR,
+            c.get_group_stats("my-badge".to_string()).revenue,
+            "Package price should count as group revenue",
+        );
+
+        let mut context = get_context(owner_account());
+        context
+            .account_balance(NearToken::from_yoctonear(100 * ONE_NEAR))
+            .block_timestamp(1_000 + ONE_DAY * 91);
+        testing_env!(context.build());
+        assert!(
+            c.get_gallery_home(None).featured.is_empty(),
+            "Featuring should end after the package period",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Number of badges does not match the package")]
+    fn package_badge_count_must_match() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_package(gold_package());
+
+        submit_package(&mut c, package_badges(&["gold-01"]));
+    }
+
+    #[test]
+    fn package_needs_slots_for_all_badges() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_package(gold_package());
+
+        let proposal = submit_package(&mut c, package_badges(&["gold-01", "gold-02"]));
+        let mut context = get_context(owner_account());
+        context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .account_balance(NearToken::from_yoctonear(100 * ONE_NEAR));
+        testing_env!(context.build());
+        c.set_max_active_badges(Some(1));
+
+        assert_eq!(
+            Some(ProposalValidationError::BadgeSlotsUnavailable {
+                free: U64(1),
+                required: U64(2),
+            }),
+            c.spo_try_accept(proposal.id.into()).error,
+            "Every badge of the package should need a free slot",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Package does not exist")]
+    fn package_must_exist() {
+        let mut context = get_context(owner_account());
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let mut c = create_instance();
+        c.set_package(gold_package());
+        c.remove_package("gold".to_string());
+
+        submit_package(&mut c, package_badges(&["gold-01", "gold-02"]));
+    }
+
+    /// Writes state as the first release of the contract stored it: one
+    /// proposal in each status it had, and one badge
+    fn write_v1_state() {
+        let mut tags = collections::UnorderedSet::new(vec![0x01, b't']);
+        tags.extend(sponsorship_tags().into_iter().take(2));
+
+        let mut proposals = collections::Vector::new(vec![0x01, b'p']);
+        let statuses = [
+            ProposalStatus::PENDING,
+            ProposalStatus::REJECTED,
+            ProposalStatus::ACCEPTED,
+            ProposalStatus::RESCINDED,
+        ];
+        for (id, status) in statuses.into_iter().enumerate() {
+            let create_request = badge_create();
+            proposals.push(&ProposalV1 {
+                id: id as u64,
+                description: "This is a sponsorship proposal".to_string(),
+                tag: TAG_BADGE_CREATE.to_string(),
+                msg: Some(BadgeActionV1::Create(BadgeCreateV1 {
+                    id: format!("{}-{}", create_request.id, id),
+                    group_id: create_request.group_id,
+                    name: create_request.name,
+                    description: create_request.description,
+                    start_at: None,
+                    duration: create_request.duration,
+                })),
+                author_id: accounts(1),
+                deposit: ONE_NEAR * 2,
+                resolved_at: if status == ProposalStatus::PENDING {
+                    None
+                } else {
+                    Some(ONE_DAY)
+                },
+                status,
+                created_at: 0,
+                duration: Some(PROPOSAL_DURATION),
+            });
+        }
+
+        let mut badges = collections::UnorderedMap::new(vec![0x02]);
+        badges.insert(
+            &"old-badge".to_string(),
+            &BadgeV1 {
+                id: "old-badge".to_string(),
+                group_id: "old".to_string(),
+                name: "Old Badge".to_string(),
+                description: "Created before the migration".to_string(),
+                is_enabled: true,
+                created_at: 0,
+                start_at: 0,
+                duration: Some(ONE_DAY * 30),
+            },
+        );
+
+        env::state_write(&StatsGalleryV1 {
+            ownership: OwnershipV1 {
+                owner: Some(owner_account()),
+                proposed_owner: collections::LazyOption::new(
+                    vec![0x00, b'p'],
+                    Some(&proposed_owner_account()),
+                ),
+            },
+            sponsorship: SponsorshipV1 {
+                tags,
+                proposals,
+                proposal_duration: collections::LazyOption::new(
+                    vec![0x01, b'd'],
+                    Some(&PROPOSAL_DURATION),
+                ),
+                total_deposits: ONE_NEAR * 6,
+                total_accepted_deposits: ONE_NEAR * 2,
+            },
+            badges,
+            badge_rate_per_day: BADGE_RATE_PER_DAY,
+            badge_max_active_duration: BADGE_MAX_ACTIVE_DURATION,
+            badge_min_creation_deposit: BADGE_MIN_CREATION_DEPOSIT,
+        });
+    }
+
+    #[test]
+    fn migrate_from_v1() {
+        let mut context = get_context(contract_account());
+        context.block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        write_v1_state();
+
+        let mut c = StatsGallery::migrate();
+
+        assert_eq!(c.own_get_owner(), Some(owner_account()));
+        assert_eq!(c.own_get_proposed_owner(), Some(proposed_owner_account()));
+        let mut tags = c.spo_get_tags();
+        tags.sort();
+        assert_eq!(tags, vec![TAG_BADGE_CREATE, TAG_BADGE_EXTEND]);
+        assert_eq!(c.spo_get_duration(), Some(PROPOSAL_DURATION.into()));
+        assert_eq!(c.get_badge_rate_per_day(), BADGE_RATE_PER_DAY.into());
+
+        let accepted = c.spo_get_proposal(2.into()).unwrap();
+        assert_eq!(accepted.status, ProposalStatus::ACCEPTED);
+        assert_eq!(accepted.author_id, accounts(1));
+        assert!(matches!(
+            accepted.msg,
+            Some(BadgeAction::Create(BadgeCreate { ref id, .. })) if id == "my-badge-01-2"
+        ));
+
+        let totals = c.spo_get_deposit_totals();
+        assert_eq!(totals.pending, U128(ONE_NEAR * 2));
+        assert_eq!(totals.rejected, U128(ONE_NEAR * 2));
+        assert_eq!(totals.accepted, U128(ONE_NEAR * 2));
+        assert_eq!(totals.rescinded, U128(ONE_NEAR * 2));
+        assert_eq!(c.spo_get_total_deposits(), U128(ONE_NEAR * 6));
+        assert_eq!(c.spo_get_total_escrowed(), U128(ONE_NEAR * 4));
+
+        let badge = c.get_badge("old-badge".to_string()).unwrap();
+        assert_eq!(badge.expires_at(), Some(ONE_DAY * 30));
+        assert!(c.check_invariants(None).ok);
+
+        // The old collections are gone
+        let first_proposal_key = [&[0x01, b'p'][..], &0u64.to_le_bytes()].concat();
+        assert!(!env::storage_has_key(&first_proposal_key));
+
+        // Rejected deposits can still be taken back
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(ONE_DAY * 2);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let rescinded = c.spo_rescind(1.into());
+        assert_eq!(rescinded.status, ProposalStatus::RESCINDED);
+        assert_eq!(c.spo_get_total_escrowed(), U128(ONE_NEAR * 2));
+        assert!(c.check_invariants(None).ok);
+    }
+
+    /// Writes state as the last release on near-sdk 4 stored it, with
+    /// entries in each kind of collection `migrate` has to move: a pending
+    /// proposal, a badge and its indexes, an award, an admin action, a
+    /// reviewer, an oracle, a promo code and a supporter
+    fn write_v2_state() {
+        let key = |key: StorageKey| key.into_storage_key();
+        let nested =
+            |parent: StorageKey, key: NestedKey| prefix_key(&parent.into_storage_key(), key);
+
+        let mut reviewers =
+            collections::UnorderedSet::new(nested(StorageKey::OWNERSHIP, NestedKey::REVIEWERS));
+        reviewers.insert(&accounts(2));
+
+        let mut tags =
+            collections::UnorderedSet::new(nested(StorageKey::SPONSORSHIP, NestedKey::TAGS));
+        tags.extend(sponsorship_tags());
+        let mut proposals =
+            collections::Vector::new(nested(StorageKey::SPONSORSHIP, NestedKey::PROPOSALS));
+        proposals.push(&ProposalHeader {
+            id: 0,
+            uid: 0,
+            tag: TAG_BADGE_CREATE.to_string(),
+            author_id: accounts(1),
+            deposit: ONE_NEAR * 2,
+            status: ProposalStatus::PENDING,
+            created_at: ONE_DAY,
+            duration: Some(PROPOSAL_DURATION),
+            resolved_at: None,
+            nonce: None,
+            dispute: None,
+            accept_at: None,
+            refunded: 0,
+            approved_by: None,
+            finalized_by: None,
+            storage_fee: 0,
+            referrer_id: None,
+            promo_code: None,
+            previous_proposal_id: None,
+            claimed_by: None,
+            claimed_at: None,
+            msg_hash: None,
+            content_hash: Base64VecU8(vec![]),
+            expires_at: None,
+            is_expired: false,
+        });
+        let mut proposal_bodies =
+            LookupMap::new(nested(StorageKey::SPONSORSHIP, NestedKey::PROPOSAL_BODIES));
+        proposal_bodies.insert(
+            0,
+            ProposalBody {
+                description: "This is a sponsorship proposal".to_string(),
+                msg: Some(BadgeAction::Create(badge_create()).into()),
+            },
+        );
+        let mut proposal_ids_by_uid = LookupMap::new(nested(
+            StorageKey::SPONSORSHIP,
+            NestedKey::PROPOSAL_IDS_BY_UID,
+        ));
+        proposal_ids_by_uid.insert(0, 0);
+        let mut proposal_ids_by_created_at = collections::TreeMap::new(nested(
+            StorageKey::SPONSORSHIP,
+            NestedKey::PROPOSAL_IDS_BY_CREATED_AT,
+        ));
+        proposal_ids_by_created_at.insert(&ONE_DAY, &vec![0]);
+        let mut pending_deposits_by_tag = LookupMap::new(nested(
+            StorageKey::SPONSORSHIP,
+            NestedKey::PENDING_DEPOSITS_BY_TAG,
+        ));
+        pending_deposits_by_tag.insert(TAG_BADGE_CREATE.to_string(), ONE_NEAR * 2);
+        let mut escrows = LookupMap::new(nested(StorageKey::SPONSORSHIP, NestedKey::ESCROWS));
+        escrows.insert(
+            0,
+            Escrow {
+                proposal_id: U64(0),
+                amount: U128(ONE_NEAR * 2),
+                refunded: U128(0),
+                state: EscrowState::HELD,
+                settled_at: None,
+                vests_at: None,
+            },
+        );
+
+        let badge = Badge {
+            id: "old-badge".to_string(),
+            group_id: "old".to_string(),
+            name: "Old Badge".to_string(),
+            description: "Created before the migration".to_string(),
+            is_enabled: true,
+            created_at: 0,
+            start_at: 0,
+            duration: Some(ONE_DAY * 30),
+            paused_at: None,
+            sponsor_id: None,
+            bond: 0,
+            hold: None,
+            disabled_reason: None,
+            is_community: false,
+            is_preview: false,
+            publish_at: None,
+            media_url: None,
+            storage_deposit: 0,
+        };
+        let mut badge_records =
+            LookupMap::new(nested(StorageKey::BADGES, NestedKey::BADGE_RECORDS));
+        badge_records.insert(badge.id.clone(), VersionedBadge::from(badge.clone()));
+        let index =
+            |key: NestedKey| collections::TreeMap::new(nested(StorageKey::BADGE_INDEXES, key));
+        let mut badge_ids =
+            collections::TreeMap::new(nested(StorageKey::BADGES, NestedKey::BADGE_IDS));
+        badge_ids.insert(&badge.id, &());
+        let (mut created_at, mut start_at, mut expires_at) = (
+            index(NestedKey::INDEX_CREATED_AT),
+            index(NestedKey::INDEX_START_AT),
+            index(NestedKey::INDEX_EXPIRES_AT),
+        );
+        created_at.insert(&(badge.created_at, badge.id.clone()), &());
+        start_at.insert(&(badge.start_at, badge.id.clone()), &());
+        expires_at.insert(&(ONE_DAY * 30, badge.id.clone()), &());
+        let mut name =
+            collections::TreeMap::new(nested(StorageKey::BADGE_INDEXES, NestedKey::INDEX_NAME));
+        name.insert(&(badge.name.clone(), badge.id.clone()), &());
+        let mut id =
+            collections::TreeMap::new(nested(StorageKey::BADGE_INDEXES, NestedKey::INDEX_ID));
+        id.insert(&badge.id, &());
+        let mut group_id =
+            collections::TreeMap::new(nested(StorageKey::BADGE_INDEXES, NestedKey::INDEX_GROUP_ID));
+        group_id.insert(&(badge.group_id.clone(), badge.id.clone()), &());
+
+        let mut awards = LookupMap::new(key(StorageKey::AWARDS));
+        awards.insert(
+            (badge.id.clone(), accounts(4)),
+            Award {
+                badge_id: badge.id.clone(),
+                account_id: accounts(4),
+                awarded_at: ONE_DAY,
+                expires_at: None,
+            },
+        );
+        let mut award_set = collections::UnorderedSet::new(
+            [
+                key(StorageKey::ACCOUNT_AWARD_SET),
+                env::sha256(accounts(4).as_bytes()),
+            ]
+            .concat(),
+        );
+        award_set.insert(&badge.id);
+        let mut account_award_sets = LookupMap::new(key(StorageKey::ACCOUNT_AWARD_SETS));
+        account_award_sets.insert(accounts(4), award_set);
+
+        let mut actions =
+            collections::Vector::new(nested(StorageKey::AUDIT_LOG, NestedKey::ADMIN_ACTIONS));
+        actions.push(&AdminAction {
+            actor_id: owner_account(),
+            method: "insert_badge".to_string(),
+            summary: "badge old-badge".to_string(),
+            timestamp: 0,
+        });
+
+        let mut oracles = collections::UnorderedSet::new(key(StorageKey::ORACLES));
+        oracles.insert(&accounts(3));
+        let mut promo_codes = collections::UnorderedMap::new(key(StorageKey::PROMO_CODES));
+        promo_codes.insert(
+            &"LAUNCH".to_string(),
+            &PromoCode {
+                code: "LAUNCH".to_string(),
+                discount: PromoDiscount::BasisPoints(1_000),
+                max_uses: None,
+                uses: U64(0),
+                expires_at: None,
+            },
+        );
+        let mut supporters = collections::Vector::new(key(StorageKey::SUPPORTERS));
+        supporters.push(&Supporter {
+            proposal_id: U64(7),
+            account_id: accounts(1),
+            description: "Early supporter".to_string(),
+            amount: U128(ONE_NEAR),
+            supported_at: U64(0),
+        });
+
+        env::state_write(&StatsGalleryV2 {
+            ownership: OwnershipV2 {
+                owner: Some(owner_account()),
+                proposed_owner: LazyOption::new(
+                    nested(StorageKey::OWNERSHIP, NestedKey::PROPOSED_OWNER),
+                    None,
+                ),
+                reviewers,
+                moderators: collections::UnorderedSet::new(nested(
+                    StorageKey::OWNERSHIP,
+                    NestedKey::MODERATORS,
+                )),
+            },
+            sponsorship: SponsorshipV2 {
+                tags,
+                proposals,
+                proposal_bodies,
+                proposal_ids_by_uid,
+                next_uid: 1,
+                proposal_ids_by_nonce: LookupMap::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::PROPOSAL_IDS_BY_NONCE,
+                )),
+                proposal_ids_by_created_at,
+                author_stats: LookupMap::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::AUTHOR_STATS,
+                )),
+                trusted_authors: LookupSet::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::TRUSTED_AUTHORS,
+                )),
+                tag_configs: LookupMap::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::TAG_CONFIGS,
+                )),
+                proposal_duration: LazyOption::new(
+                    nested(StorageKey::SPONSORSHIP, NestedKey::PROPOSAL_DURATION),
+                    Some(PROPOSAL_DURATION),
+                ),
+                dispute_window: None,
+                approval_threshold: None,
+                total_deposits: ONE_NEAR * 2,
+                total_accepted_deposits: 0,
+                deposit_totals: DepositTotals {
+                    pending: U128(ONE_NEAR * 2),
+                    ..Default::default()
+                },
+                pending_deposits_by_tag,
+                pending_count: 1,
+                max_pending_proposals: None,
+                emergency_refund_cursor: 0,
+                escrows,
+                total_escrowed: ONE_NEAR * 2,
+                max_submission_bytes: DEFAULT_MAX_SUBMISSION_BYTES,
+                unwind_window: None,
+                unwind_fee_bps: 0,
+                unwinds: LookupMap::new(nested(StorageKey::SPONSORSHIP, NestedKey::UNWINDS)),
+                resubmissions: LookupMap::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::RESUBMISSIONS,
+                )),
+                rejection_cooldown: None,
+                rejected_msg_hashes: LookupMap::new(nested(
+                    StorageKey::SPONSORSHIP,
+                    NestedKey::REJECTED_MSG_HASHES,
+                )),
+                key_prefix_len: 2,
+            },
+            badges: BadgeStoreV2 {
+                badges: badge_records,
+                ids: badge_ids,
+            },
+            badge_rate_per_day: BADGE_RATE_PER_DAY,
+            badge_max_active_duration: BADGE_MAX_ACTIVE_DURATION,
+            badge_min_creation_deposit: BADGE_MIN_CREATION_DEPOSIT,
+            awards,
+            account_awards: LookupMap::new(key(StorageKey::ACCOUNT_AWARDS)),
+            account_award_sets,
+            claim_keys: LookupMap::new(key(StorageKey::CLAIM_KEYS)),
+            badge_indexes: BadgeIndexesV2 {
+                created_at,
+                start_at,
+                expires_at,
+                name,
+                id,
+                group_id,
+                sponsor_id: collections::TreeMap::new(nested(
+                    StorageKey::BADGE_INDEXES,
+                    NestedKey::INDEX_SPONSOR_ID,
+                )),
+            },
+            featured_badge_ids: vec![],
+            audit_log: AuditLogV2 { actions },
+            badge_creation_bond: 0,
+            total_bonds_held: 0,
+            proposal_terms: LookupMap::new(key(StorageKey::PROPOSAL_TERMS)),
+            badge_reports: LookupMap::new(key(StorageKey::BADGE_REPORTS)),
+            badge_report_fee: DEFAULT_BADGE_REPORT_FEE,
+            badge_report_threshold: None,
+            input_limits: InputLimits::default(),
+            activation_queue: collections::TreeMap::new(key(StorageKey::ACTIVATION_QUEUE)),
+            expiration_queue: collections::TreeMap::new(key(StorageKey::EXPIRATION_QUEUE)),
+            keeper_tasks: LookupMap::new(key(StorageKey::KEEPER_TASKS)),
+            group_verifications: LookupMap::new(key(StorageKey::GROUP_VERIFICATIONS)),
+            oracles,
+            attestations: LookupMap::new(key(StorageKey::ATTESTATIONS)),
+            merkle_drops: LookupMap::new(key(StorageKey::MERKLE_DROPS)),
+            merkle_claims: LookupMap::new(key(StorageKey::MERKLE_CLAIMS)),
+            award_validity: LookupMap::new(key(StorageKey::AWARD_VALIDITY)),
+            group_transfers: LookupMap::new(key(StorageKey::GROUP_TRANSFERS)),
+            view_relayers: collections::UnorderedSet::new(key(StorageKey::VIEW_RELAYERS)),
+            badge_views: LookupMap::new(key(StorageKey::BADGE_VIEWS)),
+            sunset: None,
+            supporters,
+            recent_donations: collections::Vector::new(key(StorageKey::RECENT_DONATIONS)),
+            donations_count: 0,
+            donor_totals: LookupMap::new(key(StorageKey::DONOR_TOTALS)),
+            top_donors: collections::TreeMap::new(key(StorageKey::TOP_DONORS)),
+            stream_config: None,
+            badge_streams: LookupMap::new(key(StorageKey::BADGE_STREAMS)),
+            wrap_tokens: collections::UnorderedSet::new(key(StorageKey::WRAP_TOKENS)),
+            treasury_positions: LookupMap::new(key(StorageKey::TREASURY_POSITIONS)),
+            total_wrapped: 0,
+            total_wrap_pending: 0,
+            referral_share_bps: 0,
+            referral_rewards: LookupMap::new(key(StorageKey::REFERRAL_REWARDS)),
+            total_referral_rewards_owed: 0,
+            promo_codes,
+            free_tier_accounts: collections::UnorderedSet::new(key(StorageKey::FREE_TIER_ACCOUNTS)),
+            pending_executions: collections::UnorderedSet::new(key(StorageKey::PENDING_EXECUTIONS)),
+            max_badges_per_sponsor: None,
+            max_active_badges: None,
+            waitlist: collections::TreeMap::new(key(StorageKey::WAITLIST)),
+            next_waitlist_position: 0,
+            proposal_updates: LookupMap::new(key(StorageKey::PROPOSAL_UPDATES)),
+            next_update_seq: 0,
+            group_stats: LookupMap::new(key(StorageKey::GROUP_STATS)),
+            group_media: LookupMap::new(key(StorageKey::GROUP_MEDIA)),
+            total_storage_deposits_held: 0,
+            badge_prune_after: None,
+            pruned_badge_ids: LookupSet::new(key(StorageKey::PRUNED_BADGE_IDS)),
+            treasury_account_id: None,
+            total_forwarded: 0,
+            total_forward_pending: 0,
+            terms_offers: LookupMap::new(key(StorageKey::TERMS_OFFERS)),
+            packages: collections::UnorderedMap::new(key(StorageKey::PACKAGES)),
+            proposal_packages: LookupMap::new(key(StorageKey::PROPOSAL_PACKAGES)),
+            featured_until: LookupMap::new(key(StorageKey::FEATURED_UNTIL)),
+            featured_queue: collections::TreeMap::new(key(StorageKey::FEATURED_QUEUE)),
+        });
+    }
+
+    #[test]
+    fn migrate_from_v2() {
+        let mut context = get_context(contract_account());
+        context.block_timestamp(ONE_DAY * 2);
+        testing_env!(context.build());
+        write_v2_state();
+
+        let mut c = StatsGallery::migrate();
+
+        assert_eq!(c.own_get_owner(), Some(owner_account()));
+        assert_eq!(c.own_get_reviewers(), vec![accounts(2)]);
+        let mut tags = c.spo_get_tags();
+        tags.sort();
+        let mut expected_tags = sponsorship_tags();
+        expected_tags.sort();
+        assert_eq!(tags, expected_tags);
+        assert_eq!(c.spo_get_duration(), Some(PROPOSAL_DURATION.into()));
+
+        let proposal = c.spo_get_proposal(0.into()).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::PENDING);
+        assert_eq!(proposal.msg, Some(BadgeAction::Create(badge_create())));
+        let between = c.spo_get_proposals_between(0.into(), (ONE_DAY * 2).into(), None);
+        assert_eq!(between.len(), 1);
+
+        let badges = c.get_badges(None, None, Some(BadgeSort::Name), None);
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].id, "old-badge");
+        assert_eq!(c.get_admin_actions(None, None).len(), 1);
+        assert_eq!(c.get_oracles(), vec![accounts(3)]);
+        assert!(c.get_promo_code("LAUNCH".to_string()).is_some());
+        assert_eq!(c.get_supporters(None, None).len(), 1);
+        let awards = c.get_account_badges(accounts(4), None, None, None);
+        assert_eq!(awards.len(), 1);
+
+        // The old collections are gone
+        let first_proposal_key = [
+            prefix_key(
+                &StorageKey::SPONSORSHIP.into_storage_key(),
+                NestedKey::PROPOSALS,
+            ),
+            0u64.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        assert!(!env::storage_has_key(&first_proposal_key));
+
+        // Proposals resolve as before
+        let mut context = get_context(accounts(1));
+        context.block_timestamp(ONE_DAY * 2);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        let rescinded = c.spo_rescind(0.into());
+        assert_eq!(rescinded.status, ProposalStatus::RESCINDED);
+        assert_eq!(c.spo_get_total_escrowed(), U128(0));
+
+        // An account's award set moves over on its next change
+        let mut context = get_context(owner_account());
+        context.block_timestamp(ONE_DAY * 2);
+        context.attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        c.revoke_award("old-badge".to_string(), accounts(4));
+        assert!(c
+            .get_account_badges(accounts(4), None, None, None)
+            .is_empty());
+        assert_eq!(c.get_account_badge_count(accounts(4)), U64(0));
     }
 }
@@ -1,9 +1,37 @@
 use crate::*;
 
+/// Work the owner can hand off to other accounts. The owner may always act
+/// in any role.
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, PartialEq, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Approves and finalizes high-value proposals
+    REVIEWER,
+    /// Puts badges on hold and resolves holds
+    MODERATOR,
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
 pub struct Ownership {
     pub owner: Option<AccountId>,
     pub proposed_owner: LazyOption<AccountId>,
+    /// Accounts besides the owner that may approve high-value proposals
+    pub reviewers: IterableSet<AccountId>,
+    pub moderators: IterableSet<AccountId>,
+}
+
+/// `Ownership` as stored in state version 2
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct OwnershipV2 {
+    pub owner: Option<AccountId>,
+    pub proposed_owner: LazyOption<AccountId>,
+    pub reviewers: collections::UnorderedSet<AccountId>,
+    pub moderators: collections::UnorderedSet<AccountId>,
 }
 
 impl Ownership {
@@ -15,7 +43,25 @@ impl Ownership {
 
         Self {
             owner: Some(owner_id),
-            proposed_owner: LazyOption::new(prefix_key(&k, b"p"), None),
+            proposed_owner: LazyOption::new(prefix_key(&k, NestedKey::PROPOSED_OWNER), None),
+            reviewers: IterableSet::new(prefix_key(&k, NestedKey::REVIEWERS)),
+            moderators: IterableSet::new(prefix_key(&k, NestedKey::MODERATORS)),
+        }
+    }
+
+    /// Moves state version 2 into the current layout. `storage_key_prefix`
+    /// must be the one `old` was created with.
+    pub(crate) fn from_v2<S>(old: OwnershipV2, storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            owner: old.owner,
+            proposed_owner: old.proposed_owner,
+            reviewers: migrate_set(old.reviewers, prefix_key(&k, NestedKey::REVIEWERS)),
+            moderators: migrate_set(old.moderators, prefix_key(&k, NestedKey::MODERATORS)),
         }
     }
 
@@ -30,24 +76,73 @@ impl Ownership {
         );
     }
 
+    pub fn is_owner(&self, account_id: &AccountId) -> bool {
+        self.owner.as_ref() == Some(account_id)
+    }
+
+    pub fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.role_members(role).contains(account_id)
+    }
+
+    pub fn is_owner_or_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.is_owner(account_id) || self.has_role(account_id, role)
+    }
+
+    pub fn assert_owner_or_role(&self, role: Role) {
+        require!(
+            self.is_owner_or_role(&env::predecessor_account_id(), role),
+            match role {
+                Role::REVIEWER => "Owner or reviewer only",
+                Role::MODERATOR => "Owner or moderator only",
+            }
+        );
+    }
+
+    pub fn role_members(&self, role: Role) -> &IterableSet<AccountId> {
+        match role {
+            Role::REVIEWER => &self.reviewers,
+            Role::MODERATOR => &self.moderators,
+        }
+    }
+
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_owner();
+        match role {
+            Role::REVIEWER => self.reviewers.insert(account_id),
+            Role::MODERATOR => self.moderators.insert(account_id),
+        };
+    }
+
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_owner();
+        let removed = match role {
+            Role::REVIEWER => self.reviewers.remove(&account_id),
+            Role::MODERATOR => self.moderators.remove(&account_id),
+        };
+        require!(
+            removed,
+            match role {
+                Role::REVIEWER => "Not a reviewer",
+                Role::MODERATOR => "Not a moderator",
+            }
+        );
+    }
+
     pub fn renounce_owner(&mut self) {
         self.assert_owner();
         self.owner = None;
-        self.proposed_owner.remove();
+        self.proposed_owner.set(None);
     }
 
     pub fn propose_owner(&mut self, account_id: Option<AccountId>) {
         self.assert_owner();
-        if let Some(a) = account_id {
-            self.proposed_owner.set(&a);
-        } else {
-            self.proposed_owner.remove();
-        }
+        self.proposed_owner.set(account_id);
     }
 
     pub fn accept_owner(&mut self) {
         let proposed_owner = self
             .proposed_owner
+            .get_mut()
             .take()
             .unwrap_or_else(|| env::panic_str("No proposed owner"));
         require!(
@@ -64,37 +159,92 @@ pub trait Ownable {
     fn own_renounce_owner(&mut self);
     fn own_propose_owner(&mut self, account_id: Option<AccountId>);
     fn own_accept_owner(&mut self);
+    fn own_get_reviewers(&self) -> Vec<AccountId>;
+    fn own_add_reviewer(&mut self, account_id: AccountId);
+    fn own_remove_reviewer(&mut self, account_id: AccountId);
+    fn own_get_role_members(&self, role: Role) -> Vec<AccountId>;
+    fn own_grant_role(&mut self, account_id: AccountId, role: Role);
+    fn own_revoke_role(&mut self, account_id: AccountId, role: Role);
 }
 
 #[macro_export]
 macro_rules! impl_ownership {
-    ($contract: ident, $ownership: ident) => {
+    ($contract: ident, $ownership: ident $(, $on_admin_action: ident)? $(,)?) => {
         #[near_bindgen]
-        impl Ownable for $contract {
+        impl $crate::ownership::Ownable for $contract {
             fn own_get_owner(&self) -> Option<AccountId> {
                 self.$ownership.owner.clone()
             }
 
             fn own_get_proposed_owner(&self) -> Option<AccountId> {
-                self.$ownership.proposed_owner.get()
+                self.$ownership.proposed_owner.get().clone()
             }
 
             #[payable]
             fn own_renounce_owner(&mut self) {
-                assert_one_yocto();
-                self.$ownership.renounce_owner()
+                $crate::metered!("own_renounce_owner");
+                near_sdk::assert_one_yocto();
+                self.$ownership.renounce_owner();
+                $(self.$on_admin_action("own_renounce_owner", String::new());)?
             }
 
             #[payable]
             fn own_propose_owner(&mut self, account_id: Option<AccountId>) {
-                assert_one_yocto();
+                $crate::metered!("own_propose_owner");
+                near_sdk::assert_one_yocto();
+                $(self.$on_admin_action(
+                    "own_propose_owner",
+                    format!("{:?}", account_id.as_ref().map(|a| a.as_str())),
+                );)?
                 self.$ownership.propose_owner(account_id);
             }
 
             #[payable]
             fn own_accept_owner(&mut self) {
-                assert_one_yocto();
+                $crate::metered!("own_accept_owner");
+                near_sdk::assert_one_yocto();
                 self.$ownership.accept_owner();
+                $(self.$on_admin_action("own_accept_owner", String::new());)?
+            }
+
+            fn own_get_reviewers(&self) -> Vec<AccountId> {
+                self.$ownership.role_members($crate::ownership::Role::REVIEWER).iter().cloned().collect()
+            }
+
+            #[payable]
+            fn own_add_reviewer(&mut self, account_id: AccountId) {
+                $crate::metered!("own_add_reviewer");
+                near_sdk::assert_one_yocto();
+                self.$ownership.grant_role(account_id.clone(), $crate::ownership::Role::REVIEWER);
+                $(self.$on_admin_action("own_add_reviewer", account_id.to_string());)?
+            }
+
+            #[payable]
+            fn own_remove_reviewer(&mut self, account_id: AccountId) {
+                $crate::metered!("own_remove_reviewer");
+                near_sdk::assert_one_yocto();
+                self.$ownership.revoke_role(account_id.clone(), $crate::ownership::Role::REVIEWER);
+                $(self.$on_admin_action("own_remove_reviewer", account_id.to_string());)?
+            }
+
+            fn own_get_role_members(&self, role: $crate::ownership::Role) -> Vec<AccountId> {
+                self.$ownership.role_members(role).iter().cloned().collect()
+            }
+
+            #[payable]
+            fn own_grant_role(&mut self, account_id: AccountId, role: $crate::ownership::Role) {
+                $crate::metered!("own_grant_role");
+                near_sdk::assert_one_yocto();
+                self.$ownership.grant_role(account_id.clone(), role);
+                $(self.$on_admin_action("own_grant_role", format!("{} {:?}", account_id, role));)?
+            }
+
+            #[payable]
+            fn own_revoke_role(&mut self, account_id: AccountId, role: $crate::ownership::Role) {
+                $crate::metered!("own_revoke_role");
+                near_sdk::assert_one_yocto();
+                self.$ownership.revoke_role(account_id.clone(), role);
+                $(self.$on_admin_action("own_revoke_role", format!("{} {:?}", account_id, role));)?
             }
         }
     };
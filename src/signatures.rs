@@ -0,0 +1,23 @@
+use crate::*;
+
+/// Whether `signature` is `public_key`'s ed25519 signature over `message`.
+/// Keys of other curves never verify.
+pub(crate) fn verify_ed25519(
+    public_key: &PublicKey,
+    message: &[u8],
+    signature: &Base64VecU8,
+) -> bool {
+    if public_key.curve_type() != CurveType::ED25519 {
+        return false;
+    }
+    let signature: [u8; 64] = signature
+        .0
+        .as_slice()
+        .try_into()
+        .unwrap_or_else(|_| env::panic_str("Signature must be 64 bytes"));
+    // .unwrap() is safe because an ed25519 key is a curve type byte followed
+    // by 32 bytes
+    let public_key: [u8; 32] = public_key.as_bytes()[1..].try_into().unwrap();
+
+    env::ed25519_verify(&signature, message, &public_key)
+}
@@ -1,14 +1,112 @@
 use crate::*;
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+/// Default limit on the raw size of a submission's call arguments
+pub const DEFAULT_MAX_SUBMISSION_BYTES: u32 = 16_384;
+/// Denominator of `unwind_fee_bps`
+pub const MAX_UNWIND_FEE_BPS: u16 = 10_000;
+/// Bytes NEAR charges for every stored record on top of its key and value
+const STORAGE_BYTES_PER_RECORD: u64 = 40;
+/// Stored size of a `TreeMap` node: slot tag, key, both child links and
+/// height
+const TREE_NODE_BYTES: u64 = 1 + 8 + 5 + 5 + 4;
+/// Length of the SHA-256 keys a `TreeMap` stores its values under
+const HASHED_KEY_BYTES: u64 = 32;
 
 #[derive(
-    BorshStorageKey, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Debug,
+    BorshStorageKey,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    PartialEq,
+    Debug,
 )]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub enum ProposalStatus {
     PENDING,
     REJECTED,
     ACCEPTED,
     RESCINDED,
+    EXPIRED,
+    DISPUTED,
+    SCHEDULED,
+    DRAFT,
+    /// Accepted, then reverted by its author during the unwind window
+    UNWOUND,
+}
+
+/// What `sweep_expired` does with a pending proposal once its duration has
+/// elapsed without review.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum ExpiryPolicy {
+    /// Mark the proposal as expired and hold the deposit until the author rescinds it.
+    HOLD,
+    /// Refund the deposit to the author immediately.
+    REFUND,
+    /// Accept the proposal. It has already passed validation at submission.
+    /// Proposals that require two-phase approval, or that can no longer be
+    /// accepted (see `MessageValidator::check_acceptance`), are held instead.
+    ACCEPT,
+}
+
+/// When an accepted proposal's deposit becomes the contract's, for proposals
+/// that deliver something later than they are accepted (e.g. a badge that
+/// starts in the future)
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum LeadTimePolicy {
+    /// The deposit can be settled as soon as the proposal is accepted
+    NONE,
+    /// Proposals that start more than this many nanoseconds after
+    /// acceptance cannot be accepted
+    MAX_LEAD_TIME(U64),
+    /// The deposit stays in escrow until the proposal starts
+    VEST_AT_START,
+}
+
+/// Per-tag settings. Tags without a stored config use the default.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TagConfig {
+    pub expiry_policy: ExpiryPolicy,
+    /// Smallest deposit a submission with this tag may declare
+    pub min_deposit: U128,
+    pub lead_time_policy: LeadTimePolicy,
+    /// Most deposit value that pending proposals with this tag may hold in
+    /// total, if limited
+    pub max_pending_deposit: Option<U128>,
+}
+
+impl Default for TagConfig {
+    fn default() -> Self {
+        Self {
+            expiry_policy: ExpiryPolicy::HOLD,
+            min_deposit: U128(0),
+            lead_time_policy: LeadTimePolicy::NONE,
+            max_pending_deposit: None,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Dispute {
+    pub reason: String,
+    #[serde(with = "dec_string")]
+    pub disputed_at: u64,
+    #[serde(default, with = "option_dec_string")]
+    pub resolved_at: Option<u64>,
+    pub overturned: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -17,26 +115,115 @@ pub struct ProposalSubmission<T> {
     pub description: String,
     pub tag: String,
     pub msg: Option<T>,
+    /// Nanoseconds, `{ "days", "hours" }` or an ISO-8601 duration
+    #[serde(default, with = "option_duration")]
     pub duration: Option<U64>,
     pub deposit: U128,
+    /// Client-supplied value used to detect retried submissions. A second
+    /// submission from the same author with the same nonce is rejected.
+    pub nonce: Option<String>,
+    /// Account that brought the author here, credited with a share of the
+    /// deposit if the proposal is accepted. May not be the author.
+    #[serde(default)]
+    pub referrer_id: Option<AccountId>,
+    /// Discount code to apply, recorded on the proposal
+    #[serde(default)]
+    pub promo_code: Option<String>,
+    /// Most the author is willing to pay for storage. The submission fails
+    /// rather than charge more, whatever the attached deposit.
+    #[serde(default)]
+    pub max_storage_fee: Option<U128>,
+    /// Earlier proposal from the same author, with the same tag, that this one
+    /// is another attempt at. It must have been rejected, or have expired or
+    /// been rescinded, and may only be resubmitted once.
+    #[serde(default)]
+    pub previous_proposal_id: Option<U64>,
+}
+
+/// What a submission will cost, from `spo_quote_submission`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubmissionQuote {
+    /// The most storage the submission can add. It may add slightly less,
+    /// e.g. if another proposal was created in the same block, and the
+    /// difference is refunded.
+    pub storage_bytes: U64,
+    pub storage_fee: U128,
+    pub deposit: U128,
+    /// Deposit to attach to `spo_submit`
+    pub total: U128,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Proposal<T>
 where
     T: BorshDeserialize + BorshSerialize,
 {
+    #[serde(with = "dec_string")]
     pub id: u64,
+    #[serde(with = "dec_string")]
+    pub uid: u64,
     pub description: String,
     pub tag: String,
     pub msg: Option<T>,
     pub author_id: AccountId,
+    #[serde(with = "dec_string")]
     pub deposit: Balance,
     pub status: ProposalStatus,
+    #[serde(with = "dec_string")]
     pub created_at: u64,
+    #[serde(default, with = "option_dec_string")]
     pub duration: Option<u64>,
+    #[serde(default, with = "option_dec_string")]
     pub resolved_at: Option<u64>,
+    pub nonce: Option<String>,
+    pub dispute: Option<Dispute>,
+    #[serde(default, with = "option_dec_string")]
+    pub accept_at: Option<u64>,
+    /// Portion of `deposit` returned to the author on acceptance, either
+    /// because less was approved than requested or because the deposit
+    /// exceeded the price. The rest is retained by the contract.
+    #[serde(with = "dec_string")]
+    pub refunded: Balance,
+    /// First reviewer of a proposal that needs two-phase approval
+    pub approved_by: Option<AccountId>,
+    /// Second reviewer, who accepted the proposal
+    pub finalized_by: Option<AccountId>,
+    /// Paid by the author on top of `deposit` to cover the proposal's storage
+    #[serde(with = "dec_string")]
+    pub storage_fee: Balance,
+    pub referrer_id: Option<AccountId>,
+    pub promo_code: Option<String>,
+    /// The proposal this one was resubmitted from
+    #[serde(default, with = "option_dec_string")]
+    pub previous_proposal_id: Option<u64>,
+    /// Reviewer handling the proposal. While it is pending, nobody else may
+    /// resolve it. Kept once resolved, as a record of who handled it.
+    pub claimed_by: Option<AccountId>,
+    #[serde(default, with = "option_dec_string")]
+    pub claimed_at: Option<u64>,
+    /// SHA-256 of the Borsh-serialized `msg`, for spotting identical
+    /// resubmissions without reading the body
+    pub msg_hash: Option<Base64VecU8>,
+    /// SHA-256 of the Borsh-serialized `description` followed by the
+    /// Borsh-serialized `msg` (an `Option`), so that reviewers and the author
+    /// can check the payload is the one they agreed on. Updated if the
+    /// owner amends `msg` on acceptance.
+    pub content_hash: Base64VecU8,
+    /// When the review period ends, if the proposal has a duration. Computed
+    /// when the proposal is read; not stored.
+    #[borsh(skip)]
+    #[serde(default, with = "option_dec_string")]
+    pub expires_at: Option<u64>,
+    /// Whether the review period has ended as of the current block, i.e. the
+    /// block timestamp is strictly greater than `expires_at`. Only meaningful
+    /// while the proposal is pending. Computed when the proposal is read; not
+    /// stored.
+    #[borsh(skip)]
+    #[serde(default)]
+    pub is_expired: bool,
 }
 
 impl<T> Proposal<T>
@@ -49,23 +236,460 @@ where
             None => false,
         }
     }
+
+    /// Fills in the computed `expires_at` and `is_expired` fields
+    pub fn with_expiry(self, now: u64) -> Self {
+        Self {
+            expires_at: self.duration.map(|duration| self.created_at + duration),
+            is_expired: self.is_expired(now),
+            ..self
+        }
+    }
+
+    /// The part of the deposit kept by the contract
+    pub fn retained_deposit(&self) -> Balance {
+        self.deposit - self.refunded
+    }
+
+    pub fn header(&self) -> ProposalHeader {
+        ProposalHeader {
+            id: self.id,
+            uid: self.uid,
+            tag: self.tag.clone(),
+            author_id: self.author_id.clone(),
+            deposit: self.deposit,
+            status: self.status.clone(),
+            created_at: self.created_at,
+            duration: self.duration,
+            resolved_at: self.resolved_at,
+            nonce: self.nonce.clone(),
+            dispute: self.dispute.clone(),
+            accept_at: self.accept_at,
+            refunded: self.refunded,
+            approved_by: self.approved_by.clone(),
+            finalized_by: self.finalized_by.clone(),
+            storage_fee: self.storage_fee,
+            referrer_id: self.referrer_id.clone(),
+            promo_code: self.promo_code.clone(),
+            previous_proposal_id: self.previous_proposal_id,
+            claimed_by: self.claimed_by.clone(),
+            claimed_at: self.claimed_at,
+            msg_hash: self.msg_hash.clone(),
+            content_hash: self.content_hash.clone(),
+            expires_at: self.expires_at,
+            is_expired: self.is_expired,
+        }
+    }
+
+    fn into_parts<M: From<T>>(self) -> (ProposalHeader, ProposalBody<M>) {
+        let header = self.header();
+        (
+            header,
+            ProposalBody {
+                description: self.description,
+                msg: self.msg.map(M::from),
+            },
+        )
+    }
+
+    fn from_parts<M>(header: ProposalHeader, body: ProposalBody<M>) -> Self
+    where
+        T: From<M>,
+    {
+        Self {
+            id: header.id,
+            uid: header.uid,
+            description: body.description,
+            tag: header.tag,
+            msg: body.msg.map(T::from),
+            author_id: header.author_id,
+            deposit: header.deposit,
+            status: header.status,
+            created_at: header.created_at,
+            duration: header.duration,
+            resolved_at: header.resolved_at,
+            nonce: header.nonce,
+            dispute: header.dispute,
+            accept_at: header.accept_at,
+            refunded: header.refunded,
+            approved_by: header.approved_by,
+            finalized_by: header.finalized_by,
+            storage_fee: header.storage_fee,
+            referrer_id: header.referrer_id,
+            promo_code: header.promo_code,
+            previous_proposal_id: header.previous_proposal_id,
+            claimed_by: header.claimed_by,
+            claimed_at: header.claimed_at,
+            msg_hash: header.msg_hash,
+            content_hash: header.content_hash,
+            expires_at: header.expires_at,
+            is_expired: header.is_expired,
+        }
+    }
+}
+
+/// SHA-256 of the Borsh-serialized `msg`
+fn msg_hash<T: BorshSerialize>(msg: Option<&T>) -> Option<Base64VecU8> {
+    msg.map(|msg| Base64VecU8(env::sha256(&borsh::to_vec(msg).unwrap())))
+}
+
+/// SHA-256 of the Borsh-serialized description followed by the
+/// Borsh-serialized `Option` of `msg`
+pub fn content_hash<T: BorshSerialize>(description: &str, msg: Option<&T>) -> Base64VecU8 {
+    let content = [
+        borsh::to_vec(description).unwrap(),
+        borsh::to_vec(&msg).unwrap(),
+    ]
+    .concat();
+    Base64VecU8(env::sha256(&content))
+}
+
+/// Everything about a proposal except its description and `msg`. Headers are
+/// stored apart from the body so that listing and filtering proposals does
+/// not deserialize potentially large payloads.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalHeader {
+    #[serde(with = "dec_string")]
+    pub id: u64,
+    #[serde(with = "dec_string")]
+    pub uid: u64,
+    pub tag: String,
+    pub author_id: AccountId,
+    #[serde(with = "dec_string")]
+    pub deposit: Balance,
+    pub status: ProposalStatus,
+    #[serde(with = "dec_string")]
+    pub created_at: u64,
+    #[serde(default, with = "option_dec_string")]
+    pub duration: Option<u64>,
+    #[serde(default, with = "option_dec_string")]
+    pub resolved_at: Option<u64>,
+    pub nonce: Option<String>,
+    pub dispute: Option<Dispute>,
+    #[serde(default, with = "option_dec_string")]
+    pub accept_at: Option<u64>,
+    #[serde(with = "dec_string")]
+    pub refunded: Balance,
+    pub approved_by: Option<AccountId>,
+    pub finalized_by: Option<AccountId>,
+    #[serde(with = "dec_string")]
+    pub storage_fee: Balance,
+    pub referrer_id: Option<AccountId>,
+    pub promo_code: Option<String>,
+    #[serde(default, with = "option_dec_string")]
+    pub previous_proposal_id: Option<u64>,
+    pub claimed_by: Option<AccountId>,
+    #[serde(default, with = "option_dec_string")]
+    pub claimed_at: Option<u64>,
+    pub msg_hash: Option<Base64VecU8>,
+    pub content_hash: Base64VecU8,
+    /// See `Proposal::expires_at`
+    #[borsh(skip)]
+    #[serde(default, with = "option_dec_string")]
+    pub expires_at: Option<u64>,
+    /// See `Proposal::is_expired`
+    #[borsh(skip)]
+    #[serde(default)]
+    pub is_expired: bool,
+}
+
+impl ProposalHeader {
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self.duration {
+            Some(duration) => self.created_at + duration < now,
+            None => false,
+        }
+    }
+
+    /// Fills in the computed `expires_at` and `is_expired` fields
+    pub fn with_expiry(self, now: u64) -> Self {
+        Self {
+            expires_at: self.duration.map(|duration| self.created_at + duration),
+            is_expired: self.is_expired(now),
+            ..self
+        }
+    }
+}
+
+/// `M` is the stored form of the message, which may be a versioned wrapper
+/// around `T` (see `Sponsorship`)
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct ProposalBody<M> {
+    pub description: String,
+    pub msg: Option<M>,
+}
+
+/// Sum of proposal deposits currently in each status. `rescinded` is the
+/// running total of deposits refunded to authors, and `forfeited` of those
+/// kept from rejected proposals.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositTotals {
+    pub pending: U128,
+    pub accepted: U128,
+    pub rejected: U128,
+    pub rescinded: U128,
+    pub expired: U128,
+    pub disputed: U128,
+    pub scheduled: U128,
+    pub unwound: U128,
+    pub forfeited: U128,
+}
+
+impl Default for DepositTotals {
+    fn default() -> Self {
+        Self {
+            pending: U128(0),
+            accepted: U128(0),
+            rejected: U128(0),
+            rescinded: U128(0),
+            expired: U128(0),
+            disputed: U128(0),
+            scheduled: U128(0),
+            unwound: U128(0),
+            forfeited: U128(0),
+        }
+    }
+}
+
+impl DepositTotals {
+    fn get_mut(&mut self, status: &ProposalStatus) -> &mut Balance {
+        match status {
+            ProposalStatus::PENDING => &mut self.pending.0,
+            ProposalStatus::ACCEPTED => &mut self.accepted.0,
+            ProposalStatus::REJECTED => &mut self.rejected.0,
+            ProposalStatus::RESCINDED => &mut self.rescinded.0,
+            ProposalStatus::EXPIRED => &mut self.expired.0,
+            ProposalStatus::DISPUTED => &mut self.disputed.0,
+            ProposalStatus::SCHEDULED => &mut self.scheduled.0,
+            ProposalStatus::UNWOUND => &mut self.unwound.0,
+            ProposalStatus::DRAFT => unreachable!("Drafts do not hold a deposit"),
+        }
+    }
+
+    fn get(&self, status: &ProposalStatus) -> Balance {
+        match status {
+            ProposalStatus::PENDING => self.pending.0,
+            ProposalStatus::ACCEPTED => self.accepted.0,
+            ProposalStatus::REJECTED => self.rejected.0,
+            ProposalStatus::RESCINDED => self.rescinded.0,
+            ProposalStatus::EXPIRED => self.expired.0,
+            ProposalStatus::DISPUTED => self.disputed.0,
+            ProposalStatus::SCHEDULED => self.scheduled.0,
+            ProposalStatus::UNWOUND => self.unwound.0,
+            ProposalStatus::DRAFT => 0,
+        }
+    }
+
+    fn transfer(&mut self, from: &ProposalStatus, to: &ProposalStatus, amount: Balance) {
+        *self.get_mut(from) -= amount;
+        *self.get_mut(to) += amount;
+    }
+}
+
+/// Where a proposal's deposit is. Every deposit starts out `HELD` and ends in
+/// exactly one of the other states, so it cannot be both paid out and kept.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum EscrowState {
+    HELD,
+    /// Released to the contract's treasury once the proposal was accepted
+    RELEASED,
+    /// Returned to the author in full
+    REFUNDED,
+    /// Kept by the contract after the proposal was rejected
+    FORFEITED,
+}
+
+/// How the retained deposit of an unwound proposal was split
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Unwind {
+    pub proposal_id: U64,
+    /// Returned to the author
+    pub refund: U128,
+    /// Kept, and released to the treasury
+    pub fee: U128,
+    pub unwound_at: U64,
+}
+
+/// A proposal's deposit from the moment it is paid until it is settled.
+/// Parts of it may be refunded while it is held, e.g. the surplus of an
+/// accepted proposal.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Escrow {
+    pub proposal_id: U64,
+    pub amount: U128,
+    pub refunded: U128,
+    pub state: EscrowState,
+    pub settled_at: Option<U64>,
+    /// Held until at least this time, even once the proposal is accepted
+    pub vests_at: Option<U64>,
+}
+
+impl Escrow {
+    fn new(proposal_id: u64, amount: Balance) -> Self {
+        Self {
+            proposal_id: proposal_id.into(),
+            amount: amount.into(),
+            refunded: U128(0),
+            state: EscrowState::HELD,
+            settled_at: None,
+            vests_at: None,
+        }
+    }
+
+    /// Part of the deposit still held for the proposal
+    pub fn held(&self) -> Balance {
+        match self.state {
+            EscrowState::HELD => self.amount.0 - self.refunded.0,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorStats {
+    #[serde(with = "dec_string")]
+    pub submitted: u64,
+    #[serde(with = "dec_string")]
+    pub accepted: u64,
+    #[serde(with = "dec_string")]
+    pub rejected: u64,
+    #[serde(with = "dec_string")]
+    pub rescinded: u64,
+    pub total_accepted_value: U128,
+}
+
+impl Default for AuthorStats {
+    fn default() -> Self {
+        Self {
+            submitted: 0,
+            accepted: 0,
+            rejected: 0,
+            rescinded: 0,
+            total_accepted_value: U128(0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ProposalEvent<'a> {
+    id: U64,
+    uid: U64,
+    author_id: &'a AccountId,
+    tag: &'a str,
+    status: &'a ProposalStatus,
+    deposit: U128,
 }
 
+/// Proposals with messages of type `T`. Messages are stored as `M`, which
+/// defaults to `T`; a contract whose message type may change can store a
+/// versioned enum instead, converted to the latest `T` whenever a proposal
+/// is read.
 #[derive(BorshDeserialize, BorshSerialize)]
-pub struct Sponsorship<T>
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Sponsorship<T, M = T>
 where
     T: BorshDeserialize + BorshSerialize,
+    M: BorshDeserialize + BorshSerialize,
 {
-    tags: UnorderedSet<String>,
-    proposals: Vector<Proposal<T>>,
+    tags: IterableSet<String>,
+    proposals: Vector<ProposalHeader>,
+    proposal_bodies: LookupMap<u64, ProposalBody<M>>,
+    proposal_ids_by_uid: LookupMap<u64, u64>,
+    next_uid: u64,
+    proposal_ids_by_nonce: LookupMap<(AccountId, String), u64>,
+    proposal_ids_by_created_at: TreeMap<u64, Vec<u64>>,
+    author_stats: LookupMap<AccountId, AuthorStats>,
+    trusted_authors: LookupSet<(AccountId, String)>,
+    tag_configs: LookupMap<String, TagConfig>,
     proposal_duration: LazyOption<u64>,
+    dispute_window: Option<u64>,
+    approval_threshold: Option<Balance>,
     total_deposits: Balance,
     total_accepted_deposits: Balance,
+    deposit_totals: DepositTotals,
+    pending_deposits_by_tag: LookupMap<String, Balance>,
+    pending_count: u64,
+    max_pending_proposals: Option<u64>,
+    emergency_refund_cursor: u64,
+    escrows: LookupMap<u64, Escrow>,
+    total_escrowed: Balance,
+    max_submission_bytes: u32,
+    unwind_window: Option<u64>,
+    unwind_fee_bps: u16,
+    unwinds: LookupMap<u64, Unwind>,
+    /// ID of the proposal each resubmitted proposal was resubmitted as
+    resubmissions: LookupMap<u64, u64>,
+    rejection_cooldown: Option<u64>,
+    /// When each author last had a proposal with the given `msg_hash`
+    /// rejected. Only recorded while a cooldown is set.
+    rejected_msg_hashes: LookupMap<(AccountId, Vec<u8>), u64>,
+    /// Keys whose signatures count as the author's own in signed requests
+    signing_keys: LookupMap<AccountId, PublicKey>,
+    /// Last nonce each author used in a signed request
+    signing_nonces: LookupMap<AccountId, u64>,
+    /// Length of the storage prefix every collection above is keyed under
+    key_prefix_len: u64,
+    #[borsh(skip)]
+    message_type: PhantomData<T>,
 }
 
-impl<T> Sponsorship<T>
+/// `Sponsorship` as stored in state version 2
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct SponsorshipV2<M>
 where
-    T: BorshDeserialize + BorshSerialize,
+    M: BorshDeserialize + BorshSerialize,
+{
+    pub tags: collections::UnorderedSet<String>,
+    pub proposals: collections::Vector<ProposalHeader>,
+    pub proposal_bodies: LookupMap<u64, ProposalBody<M>>,
+    pub proposal_ids_by_uid: LookupMap<u64, u64>,
+    pub next_uid: u64,
+    pub proposal_ids_by_nonce: LookupMap<(AccountId, String), u64>,
+    pub proposal_ids_by_created_at: collections::TreeMap<u64, Vec<u64>>,
+    pub author_stats: LookupMap<AccountId, AuthorStats>,
+    pub trusted_authors: LookupSet<(AccountId, String)>,
+    pub tag_configs: LookupMap<String, TagConfig>,
+    pub proposal_duration: LazyOption<u64>,
+    pub dispute_window: Option<u64>,
+    pub approval_threshold: Option<Balance>,
+    pub total_deposits: Balance,
+    pub total_accepted_deposits: Balance,
+    pub deposit_totals: DepositTotals,
+    pub pending_deposits_by_tag: LookupMap<String, Balance>,
+    pub pending_count: u64,
+    pub max_pending_proposals: Option<u64>,
+    pub emergency_refund_cursor: u64,
+    pub escrows: LookupMap<u64, Escrow>,
+    pub total_escrowed: Balance,
+    pub max_submission_bytes: u32,
+    pub unwind_window: Option<u64>,
+    pub unwind_fee_bps: u16,
+    pub unwinds: LookupMap<u64, Unwind>,
+    pub resubmissions: LookupMap<u64, u64>,
+    pub rejection_cooldown: Option<u64>,
+    pub rejected_msg_hashes: LookupMap<(AccountId, Vec<u8>), u64>,
+    pub key_prefix_len: u64,
+}
+
+impl<T, M> Sponsorship<T, M>
+where
+    T: BorshDeserialize + BorshSerialize + From<M>,
+    M: BorshDeserialize + BorshSerialize + From<T> + Clone,
 {
     pub fn new<S>(storage_key_prefix: S, tags: Vec<String>, proposal_duration: Option<u64>) -> Self
     where
@@ -73,21 +697,106 @@ where
     {
         let k = storage_key_prefix.into_storage_key();
 
-        let mut tags_set = UnorderedSet::new(prefix_key(&k, b"t"));
+        let mut tags_set = IterableSet::new(prefix_key(&k, NestedKey::TAGS));
 
         tags_set.extend(tags);
 
         Self {
             tags: tags_set,
-            proposals: Vector::new(prefix_key(&k, b"p")),
-            proposal_duration: LazyOption::new(prefix_key(&k, b"d"), proposal_duration.as_ref()),
+            proposals: Vector::new(prefix_key(&k, NestedKey::PROPOSALS)),
+            proposal_bodies: LookupMap::new(prefix_key(&k, NestedKey::PROPOSAL_BODIES)),
+            proposal_ids_by_uid: LookupMap::new(prefix_key(&k, NestedKey::PROPOSAL_IDS_BY_UID)),
+            next_uid: 0,
+            proposal_ids_by_nonce: LookupMap::new(prefix_key(&k, NestedKey::PROPOSAL_IDS_BY_NONCE)),
+            proposal_ids_by_created_at: TreeMap::new(prefix_key(
+                &k,
+                NestedKey::PROPOSAL_IDS_BY_CREATED_AT,
+            )),
+            author_stats: LookupMap::new(prefix_key(&k, NestedKey::AUTHOR_STATS)),
+            trusted_authors: LookupSet::new(prefix_key(&k, NestedKey::TRUSTED_AUTHORS)),
+            tag_configs: LookupMap::new(prefix_key(&k, NestedKey::TAG_CONFIGS)),
+            proposal_duration: LazyOption::new(
+                prefix_key(&k, NestedKey::PROPOSAL_DURATION),
+                proposal_duration,
+            ),
+            dispute_window: None,
+            approval_threshold: None,
             total_deposits: 0,
             total_accepted_deposits: 0,
+            deposit_totals: DepositTotals::default(),
+            pending_deposits_by_tag: LookupMap::new(prefix_key(
+                &k,
+                NestedKey::PENDING_DEPOSITS_BY_TAG,
+            )),
+            pending_count: 0,
+            max_pending_proposals: None,
+            emergency_refund_cursor: 0,
+            escrows: LookupMap::new(prefix_key(&k, NestedKey::ESCROWS)),
+            total_escrowed: 0,
+            max_submission_bytes: DEFAULT_MAX_SUBMISSION_BYTES,
+            unwind_window: None,
+            unwind_fee_bps: 0,
+            unwinds: LookupMap::new(prefix_key(&k, NestedKey::UNWINDS)),
+            resubmissions: LookupMap::new(prefix_key(&k, NestedKey::RESUBMISSIONS)),
+            rejection_cooldown: None,
+            rejected_msg_hashes: LookupMap::new(prefix_key(&k, NestedKey::REJECTED_MSG_HASHES)),
+            signing_keys: LookupMap::new(prefix_key(&k, NestedKey::SIGNING_KEYS)),
+            signing_nonces: LookupMap::new(prefix_key(&k, NestedKey::SIGNING_NONCES)),
+            key_prefix_len: prefix_key(&k, NestedKey::TAGS).len() as u64,
+            message_type: PhantomData,
+        }
+    }
+
+    /// Moves state version 2 into the current layout. `storage_key_prefix`
+    /// must be the one `old` was created with.
+    pub(crate) fn from_v2<S>(old: SponsorshipV2<M>, storage_key_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let k = storage_key_prefix.into_storage_key();
+
+        Self {
+            tags: migrate_set(old.tags, prefix_key(&k, NestedKey::TAGS)),
+            proposals: migrate_vector(old.proposals, prefix_key(&k, NestedKey::PROPOSALS)),
+            proposal_bodies: old.proposal_bodies,
+            proposal_ids_by_uid: old.proposal_ids_by_uid,
+            next_uid: old.next_uid,
+            proposal_ids_by_nonce: old.proposal_ids_by_nonce,
+            proposal_ids_by_created_at: migrate_tree_map(
+                old.proposal_ids_by_created_at,
+                prefix_key(&k, NestedKey::PROPOSAL_IDS_BY_CREATED_AT),
+            ),
+            author_stats: old.author_stats,
+            trusted_authors: old.trusted_authors,
+            tag_configs: old.tag_configs,
+            proposal_duration: old.proposal_duration,
+            dispute_window: old.dispute_window,
+            approval_threshold: old.approval_threshold,
+            total_deposits: old.total_deposits,
+            total_accepted_deposits: old.total_accepted_deposits,
+            deposit_totals: old.deposit_totals,
+            pending_deposits_by_tag: old.pending_deposits_by_tag,
+            pending_count: old.pending_count,
+            max_pending_proposals: old.max_pending_proposals,
+            emergency_refund_cursor: old.emergency_refund_cursor,
+            escrows: old.escrows,
+            total_escrowed: old.total_escrowed,
+            max_submission_bytes: old.max_submission_bytes,
+            unwind_window: old.unwind_window,
+            unwind_fee_bps: old.unwind_fee_bps,
+            unwinds: old.unwinds,
+            resubmissions: old.resubmissions,
+            rejection_cooldown: old.rejection_cooldown,
+            rejected_msg_hashes: old.rejected_msg_hashes,
+            signing_keys: LookupMap::new(prefix_key(&k, NestedKey::SIGNING_KEYS)),
+            signing_nonces: LookupMap::new(prefix_key(&k, NestedKey::SIGNING_NONCES)),
+            key_prefix_len: old.key_prefix_len,
+            message_type: PhantomData,
         }
     }
 
     pub fn get_tags(&self) -> Vec<String> {
-        self.tags.to_vec()
+        self.tags.iter().cloned().collect()
     }
 
     pub fn add_tags(&mut self, tags: Vec<String>) {
@@ -97,6 +806,7 @@ where
     pub fn remove_tags(&mut self, tags: Vec<String>) {
         for tag in tags {
             self.tags.remove(&tag);
+            self.tag_configs.remove(&tag);
         }
     }
 
@@ -108,198 +818,2321 @@ where
         self.total_accepted_deposits.into()
     }
 
-    pub fn get_all(&self) -> Vec<Proposal<T>> {
-        self.proposals.to_vec()
+    pub fn get_deposit_totals(&self) -> DepositTotals {
+        self.deposit_totals.clone()
     }
 
-    pub fn get_accepted(&self) -> Vec<Proposal<T>> {
-        self.proposals
-            .iter()
-            .filter(|x| x.status == ProposalStatus::ACCEPTED)
-            .collect()
+    /// Sum of the deposits of pending proposals with the tag
+    pub fn get_pending_deposit(&self, tag: &String) -> Balance {
+        self.pending_deposits_by_tag.get(tag).cloned().unwrap_or(0)
     }
 
-    pub fn get_rejected(&self) -> Vec<Proposal<T>> {
-        self.proposals
-            .iter()
-            .filter(|x| x.status == ProposalStatus::REJECTED)
-            .collect()
+    fn check_pending_deposit_cap(&self, tag: &String, deposit: Balance) {
+        if let Some(max_pending_deposit) = self.get_tag_config(tag).max_pending_deposit {
+            let pending = self.get_pending_deposit(tag);
+            require!(
+                pending + deposit <= max_pending_deposit.0,
+                format!(
+                    "Pending deposits for tag {} would exceed the cap. Pending: {} yoctoNEAR Cap: {} yoctoNEAR",
+                    tag, pending, max_pending_deposit.0
+                )
+            );
+        }
     }
 
-    pub fn get_rescinded(&self) -> Vec<Proposal<T>> {
-        self.proposals
-            .iter()
-            .filter(|x| x.status == ProposalStatus::RESCINDED)
-            .collect()
+    /// Number of proposals awaiting review
+    pub fn get_pending_count(&self) -> u64 {
+        self.pending_count
     }
 
-    pub fn get_pending(&self) -> Vec<Proposal<T>> {
-        let now = env::block_timestamp();
-        self.proposals
-            .iter()
-            .filter(|x| x.status == ProposalStatus::PENDING && !x.is_expired(now))
-            .collect()
+    pub fn get_max_pending_proposals(&self) -> Option<u64> {
+        self.max_pending_proposals
     }
 
-    pub fn get_expired(&self) -> Vec<Proposal<T>> {
-        let now = env::block_timestamp();
-        self.proposals
-            .iter()
-            .filter(|x| x.status == ProposalStatus::PENDING && x.is_expired(now))
-            .collect()
+    /// Applies to submissions and funded drafts from then on. Lowering the cap
+    /// below the current count does not affect proposals already pending.
+    pub fn set_max_pending_proposals(&mut self, max_pending_proposals: Option<u64>) {
+        self.max_pending_proposals = max_pending_proposals;
     }
 
-    pub fn get_proposal(&self, id: u64) -> Option<Proposal<T>> {
-        self.proposals.get(id)
+    fn check_pending_count_cap(&self) {
+        if let Some(max_pending_proposals) = self.max_pending_proposals {
+            require!(
+                self.pending_count < max_pending_proposals,
+                format!(
+                    "Proposal queue is full. Pending: {} Cap: {}",
+                    self.pending_count, max_pending_proposals
+                )
+            );
+        }
     }
 
-    pub fn set_duration(&mut self, duration: Option<u64>) {
-        if let Some(duration) = duration {
-            self.proposal_duration.set(&duration);
-        } else {
-            self.proposal_duration.remove();
+    /// Adds a new deposit to the totals for `status`
+    fn add_deposit(&mut self, tag: &String, status: &ProposalStatus, amount: Balance) {
+        *self.deposit_totals.get_mut(status) += amount;
+        if *status == ProposalStatus::PENDING {
+            self.pending_count += 1;
+            let pending = self.get_pending_deposit(tag);
+            self.pending_deposits_by_tag
+                .insert(tag.clone(), pending + amount);
         }
     }
 
-    pub fn get_duration(&self) -> Option<u64> {
-        self.proposal_duration.get()
+    /// Moves a deposit between status totals, keeping the pending total of
+    /// its tag in step
+    fn transfer_deposit(
+        &mut self,
+        tag: &String,
+        from: &ProposalStatus,
+        to: &ProposalStatus,
+        amount: Balance,
+    ) {
+        self.deposit_totals.transfer(from, to, amount);
+        if *from == ProposalStatus::PENDING {
+            self.pending_count -= 1;
+            let pending = self.get_pending_deposit(tag) - amount;
+            if pending == 0 {
+                self.pending_deposits_by_tag.remove(tag);
+            } else {
+                self.pending_deposits_by_tag.insert(tag.clone(), pending);
+            }
+        }
+        if *to == ProposalStatus::PENDING {
+            self.pending_count += 1;
+            let pending = self.get_pending_deposit(tag);
+            self.pending_deposits_by_tag
+                .insert(tag.clone(), pending + amount);
+        }
     }
 
-    pub fn rescind(&mut self, id: u64) -> Proposal<T> {
-        let proposal = self.proposals.get(id);
-        require!(proposal.is_some(), "Proposal does not exist");
-        let proposal = proposal.unwrap();
-        require!(
-            proposal.status == ProposalStatus::PENDING
-                || proposal.status == ProposalStatus::REJECTED,
-            "Proposal cannot be rescinded"
-        );
-        require!(
-            proposal.author_id == env::predecessor_account_id(),
-            "Proposal can only be rescinded by original author"
-        );
-        let now = env::block_timestamp();
+    pub fn len(&self) -> u64 {
+        self.proposals.len().into()
+    }
 
-        let resolved = Proposal {
-            resolved_at: Some(now),
-            status: ProposalStatus::RESCINDED,
-            ..proposal
-        };
+    pub fn is_empty(&self) -> bool {
+        self.proposals.is_empty()
+    }
 
-        self.proposals.replace(id, &resolved);
+    /// Checks the deposit totals and proposal indexes against the stored
+    /// proposals. Returns a description of each inconsistency found.
+    pub fn check_invariants(&self) -> Vec<String> {
+        let mut violations = vec![];
+        let mut held = DepositTotals::default();
+        let mut escrowed = 0;
 
-        self.total_deposits -= proposal.deposit;
+        for header in self.proposals.iter() {
+            if self.proposal_ids_by_uid.get(&header.uid).cloned() != Some(header.id) {
+                violations.push(format!("Proposal {} is not indexed by its uid", header.id));
+            }
+            if !self
+                .proposal_ids_by_created_at
+                .get(&header.created_at)
+                .cloned()
+                .is_some_and(|ids| ids.contains(&header.id))
+            {
+                violations.push(format!(
+                    "Proposal {} is not indexed by its creation time",
+                    header.id
+                ));
+            }
+            if !self.proposal_bodies.contains_key(&header.id) {
+                violations.push(format!("Proposal {} has no body", header.id));
+            }
+            match (self.escrows.get(&header.id).cloned(), &header.status) {
+                (None, ProposalStatus::DRAFT) => {}
+                (None, _) => violations.push(format!("Proposal {} has no escrow", header.id)),
+                (Some(escrow), _) if escrow.state == EscrowState::FORFEITED => {
+                    // Counted in `forfeited` rather than under its status
+                    continue;
+                }
+                (Some(escrow), status) => {
+                    if *status == ProposalStatus::RESCINDED && escrow.state != EscrowState::REFUNDED
+                    {
+                        violations.push(format!(
+                            "Proposal {} is rescinded but its escrow is {:?}",
+                            header.id, escrow.state
+                        ));
+                    }
+                    escrowed += escrow.held();
+                }
+            }
+            if !matches!(
+                header.status,
+                ProposalStatus::DRAFT | ProposalStatus::RESCINDED
+            ) {
+                *held.get_mut(&header.status) += header.deposit - header.refunded;
+            }
+        }
 
-        let author_id = resolved.author_id.clone();
-        log!(
-            "Refunding rescinded deposit to {}: {}",
-            &author_id,
-            &resolved.deposit
-        );
-        Promise::new(author_id).transfer(resolved.deposit);
+        let mut total_held = 0;
+        for status in [
+            ProposalStatus::PENDING,
+            ProposalStatus::ACCEPTED,
+            ProposalStatus::REJECTED,
+            ProposalStatus::EXPIRED,
+            ProposalStatus::DISPUTED,
+            ProposalStatus::SCHEDULED,
+            ProposalStatus::UNWOUND,
+        ] {
+            let (recorded, actual) = (self.deposit_totals.get(&status), held.get(&status));
+            if recorded != actual {
+                violations.push(format!(
+                    "{:?} deposit total is {} but proposals hold {}",
+                    status, recorded, actual
+                ));
+            }
+            total_held += recorded;
+        }
 
-        resolved
-    }
+        if total_held != self.total_deposits {
+            violations.push(format!(
+                "Deposit totals sum to {} but total deposits are {}",
+                total_held, self.total_deposits
+            ));
+        }
+        if self.deposit_totals.accepted.0 != self.total_accepted_deposits {
+            violations.push(format!(
+                "Accepted deposit total is {} but total accepted deposits are {}",
+                self.deposit_totals.accepted.0, self.total_accepted_deposits
+            ));
+        }
+        if escrowed != self.total_escrowed {
+            violations.push(format!(
+                "Escrows hold {} but total escrowed is {}",
+                escrowed, self.total_escrowed
+            ));
+        }
 
-    fn resolve(&mut self, id: u64, accepted: bool) -> Proposal<T> {
-        let proposal = self.proposals.get(id);
-        require!(proposal.is_some(), "Proposal does not exist");
-        let proposal = proposal.unwrap();
-        require!(
-            proposal.status == ProposalStatus::PENDING,
-            "Proposal has already been resolved"
-        );
-        let now = env::block_timestamp();
-        require!(!proposal.is_expired(now), "Proposal is expired");
+        violations
+    }
 
-        let resolved = Proposal {
-            resolved_at: Some(now),
-            status: if accepted {
-                ProposalStatus::ACCEPTED
-            } else {
-                ProposalStatus::REJECTED
-            },
-            ..proposal
-        };
+    pub fn get_author_stats(&self, author_id: &AccountId) -> AuthorStats {
+        self.author_stats
+            .get(author_id)
+            .cloned()
+            .unwrap_or_default()
+    }
 
-        self.proposals.replace(id, &resolved);
+    fn update_author_stats(&mut self, author_id: &AccountId, f: impl FnOnce(&mut AuthorStats)) {
+        let mut stats = self.get_author_stats(author_id);
+        f(&mut stats);
+        self.author_stats.insert(author_id.clone(), stats);
+    }
 
-        if accepted {
-            self.total_accepted_deposits += proposal.deposit;
+    /// Submissions from trusted authors are accepted without manual review
+    pub fn set_trusted(&mut self, author_id: AccountId, tag: String, trusted: bool) {
+        if trusted {
+            require!(self.tags.contains(&tag), "Tag does not exist");
+            self.trusted_authors.insert((author_id, tag));
+        } else {
+            self.trusted_authors.remove(&(author_id, tag));
         }
+    }
 
-        resolved
+    pub fn is_trusted(&self, author_id: AccountId, tag: String) -> bool {
+        self.trusted_authors.contains(&(author_id, tag))
     }
 
-    pub fn accept(&mut self, id: u64) -> Proposal<T> {
-        self.resolve(id, true)
+    pub fn get_signing_key(&self, author_id: &AccountId) -> Option<PublicKey> {
+        self.signing_keys.get(author_id).cloned()
     }
 
-    pub fn reject(&mut self, id: u64) -> Proposal<T> {
-        self.resolve(id, false)
+    /// The last nonce the author used in a signed request, or 0 if they have
+    /// not sent one
+    pub fn get_signing_nonce(&self, author_id: &AccountId) -> u64 {
+        self.signing_nonces.get(author_id).copied().unwrap_or(0)
     }
 
-    pub fn submit(&mut self, submission: ProposalSubmission<T>) -> Proposal<T> {
-        let attached_deposit = env::attached_deposit();
-        require!(attached_deposit >= 1, "Deposit required");
+    /// Registers the ed25519 key whose signatures count as the author's own
+    /// in signed requests, or removes it with `None`. Nonces the author has
+    /// used stay used when the key changes.
+    pub fn set_signing_key(&mut self, author_id: AccountId, public_key: Option<PublicKey>) {
+        match public_key {
+            Some(public_key) => {
+                require!(
+                    public_key.curve_type() == CurveType::ED25519,
+                    "Signing key must be an ed25519 key"
+                );
+                self.signing_keys.insert(author_id, public_key);
+            }
+            None => {
+                self.signing_keys.remove(&author_id);
+            }
+        }
+    }
 
-        let storage_usage_start = env::storage_usage();
+    /// Parses a signed request for `method` and checks its signature against
+    /// the author's registered key. The request's nonce is used up.
+    fn verify_signed<A>(
+        &mut self,
+        method: &str,
+        payload: &str,
+        signature: &Base64VecU8,
+    ) -> SignedRequest<A>
+    where
+        A: near_sdk::serde::de::DeserializeOwned,
+    {
+        let request: SignedRequest<A> = near_sdk::serde_json::from_str(payload)
+            .unwrap_or_else(|_| env::panic_str("Invalid signed request"));
+        require!(
+            request.contract_id == env::current_account_id(),
+            "Request is for another contract"
+        );
+        require!(request.method == method, "Request is for another method");
 
-        require!(self.tags.contains(&submission.tag), "Tag does not exist");
+        let public_key = self
+            .get_signing_key(&request.author_id)
+            .unwrap_or_else(|| env::panic_str("Author has no signing key"));
+        require!(
+            verify_ed25519(&public_key, payload.as_bytes(), signature),
+            "Invalid signature"
+        );
+        require!(
+            request.nonce.0 > self.get_signing_nonce(&request.author_id),
+            "Nonce has already been used"
+        );
+        self.signing_nonces
+            .insert(request.author_id.clone(), request.nonce.0);
 
-        let id = self.proposals.len();
+        request
+    }
 
-        let duration = match (
-            self.proposal_duration.get(),
-            submission.duration.map(|x| x.into()),
-        ) {
-            (Some(contract_duration), Some(submission_duration)) => {
-                Some(u64::min(contract_duration, submission_duration))
-            }
-            (Some(d), _) | (_, Some(d)) => Some(d),
-            _ => None,
-        };
+    /// Reads a proposal from storage with its computed fields filled in
+    fn load(&self, id: u64) -> Option<Proposal<T>> {
+        self.load_header(id).map(|header| self.with_body(header))
+    }
 
-        let submission_deposit = submission.deposit.into();
+    fn load_header(&self, id: u64) -> Option<ProposalHeader> {
+        // IDs are indexes into `proposals`, so none is past `u32::MAX`
+        let index = u32::try_from(id).ok()?;
+        self.proposals
+            .get(index)
+            .cloned()
+            .map(|header| header.with_expiry(now()))
+    }
 
-        let proposal = Proposal {
+    fn with_body(&self, header: ProposalHeader) -> Proposal<T> {
+        // .unwrap() is safe because every header is stored with a body
+        let body = self.proposal_bodies.get(&header.id).cloned().unwrap();
+        Proposal::from_parts(header, body)
+    }
+
+    fn headers(&self) -> impl Iterator<Item = ProposalHeader> + '_ {
+        let now = now();
+        self.proposals
+            .iter()
+            .map(move |header| header.clone().with_expiry(now))
+    }
+
+    /// Proposals whose header matches `f`. Bodies are only read for matches.
+    fn filter(&self, f: impl Fn(&ProposalHeader) -> bool) -> Vec<Proposal<T>> {
+        self.headers()
+            .filter(f)
+            .map(|header| self.with_body(header))
+            .collect()
+    }
+
+    /// Writes a proposal's header. Its body does not change after creation
+    /// except through `accept_amended`.
+    fn update(&mut self, proposal: &Proposal<T>) {
+        self.proposals
+            .replace(proposal.id as u32, proposal.header());
+        Self::emit_proposal_event(proposal);
+    }
+
+    fn emit_proposal_event(proposal: &Proposal<T>) {
+        let event = match proposal.status {
+            ProposalStatus::PENDING => "proposal_submitted",
+            ProposalStatus::ACCEPTED => "proposal_accepted",
+            ProposalStatus::REJECTED => "proposal_rejected",
+            ProposalStatus::RESCINDED => "proposal_rescinded",
+            ProposalStatus::EXPIRED => "proposal_expired",
+            ProposalStatus::DISPUTED => "proposal_disputed",
+            ProposalStatus::SCHEDULED => "proposal_scheduled",
+            ProposalStatus::DRAFT => "proposal_drafted",
+            ProposalStatus::UNWOUND => "proposal_unwound",
+        };
+
+        Self::emit_named_proposal_event(event, proposal);
+    }
+
+    fn emit_named_proposal_event(event: &str, proposal: &Proposal<T>) {
+        emit_event(
+            event,
+            [ProposalEvent {
+                id: proposal.id.into(),
+                uid: proposal.uid.into(),
+                author_id: &proposal.author_id,
+                tag: &proposal.tag,
+                status: &proposal.status,
+                deposit: proposal.deposit.into(),
+            }],
+        );
+    }
+
+    pub fn get_all(&self) -> Vec<Proposal<T>> {
+        self.filter(|_| true)
+    }
+
+    pub fn get_headers(
+        &self,
+        status: Option<ProposalStatus>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<ProposalHeader> {
+        self.headers()
+            .filter(|x| status.is_none() || status.as_ref() == Some(&x.status))
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn get_accepted(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::ACCEPTED)
+    }
+
+    pub fn get_rejected(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::REJECTED)
+    }
+
+    pub fn get_rescinded(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::RESCINDED)
+    }
+
+    pub fn get_disputed(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::DISPUTED)
+    }
+
+    pub fn get_scheduled(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::SCHEDULED)
+    }
+
+    pub fn get_drafts(&self) -> Vec<Proposal<T>> {
+        self.filter(|x| x.status == ProposalStatus::DRAFT)
+    }
+
+    pub fn get_pending(&self) -> Vec<Proposal<T>> {
+        let now = now();
+        self.filter(|x| x.status == ProposalStatus::PENDING && !x.is_expired(now))
+    }
+
+    pub fn get_expired(&self) -> Vec<Proposal<T>> {
+        let now = now();
+        self.filter(|x| {
+            x.status == ProposalStatus::EXPIRED
+                || (x.status == ProposalStatus::PENDING && x.is_expired(now))
+        })
+    }
+
+    /// Proposals created in `[from, to)`, oldest first
+    pub fn get_between(&self, from: u64, to: u64, limit: u64) -> Vec<Proposal<T>> {
+        if from >= to {
+            return vec![];
+        }
+
+        self.proposal_ids_by_created_at
+            .range((Bound::Included(from), Bound::Excluded(to)))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .take(limit as usize)
+            .filter_map(|id| self.load(id))
+            .collect()
+    }
+
+    pub fn get_proposal(&self, id: u64) -> Option<Proposal<T>> {
+        self.load(id)
+    }
+
+    /// Unlike `id`, `uid` does not depend on the proposal's position in storage
+    pub fn get_id_by_uid(&self, uid: u64) -> Option<u64> {
+        self.proposal_ids_by_uid.get(&uid).cloned()
+    }
+
+    pub fn get_proposal_by_uid(&self, uid: u64) -> Option<Proposal<T>> {
+        self.get_id_by_uid(uid).and_then(|id| self.get_proposal(id))
+    }
+
+    fn require_id_by_uid(&self, uid: u64) -> u64 {
+        self.get_id_by_uid(uid)
+            .unwrap_or_else(|| env::panic_str("Proposal does not exist"))
+    }
+
+    pub fn accept_by_uid(&mut self, uid: u64) -> Proposal<T> {
+        self.accept(self.require_id_by_uid(uid))
+    }
+
+    pub fn reject_by_uid(&mut self, uid: u64) -> Proposal<T> {
+        self.reject(self.require_id_by_uid(uid))
+    }
+
+    pub fn rescind_by_uid(&mut self, uid: u64) -> Proposal<T> {
+        self.rescind(self.require_id_by_uid(uid))
+    }
+
+    /// With `extend_pending`, pending proposals that have not expired and
+    /// whose duration was capped at the old value get the new one, so that
+    /// reviews already under way are not cut short by the old limit. Returns
+    /// the IDs of the extended proposals.
+    pub fn set_duration(&mut self, duration: Option<u64>, extend_pending: bool) -> Vec<u64> {
+        let old_duration = *self.proposal_duration.get();
+        self.proposal_duration.set(duration);
+
+        if !extend_pending {
+            return vec![];
+        }
+        let (old_duration, new_duration) = match (old_duration, duration) {
+            (Some(old), Some(new)) if new > old => (old, new),
+            _ => env::panic_str("Only a longer duration can extend pending proposals"),
+        };
+
+        // Only proposals created within the old duration can still be open
+        let now = now();
+        let ids = self
+            .proposal_ids_by_created_at
+            .range((
+                Bound::Included(now.saturating_sub(old_duration)),
+                Bound::Unbounded,
+            ))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect::<Vec<_>>();
+        let mut extended = vec![];
+        for id in ids {
+            // .unwrap() is safe because indexed proposals are never removed
+            let header = self.load_header(id).unwrap();
+            if header.status != ProposalStatus::PENDING
+                || header.duration != Some(old_duration)
+                || header.is_expired(now)
+            {
+                continue;
+            }
+            self.proposals.replace(
+                id as u32,
+                ProposalHeader {
+                    duration: Some(new_duration),
+                    ..header
+                },
+            );
+            extended.push(id);
+        }
+
+        extended
+    }
+
+    pub fn get_duration(&self) -> Option<u64> {
+        *self.proposal_duration.get()
+    }
+
+    /// How long after rejection an author may dispute it. `None` disables disputes.
+    pub fn set_dispute_window(&mut self, dispute_window: Option<u64>) {
+        self.dispute_window = dispute_window;
+    }
+
+    pub fn get_dispute_window(&self) -> Option<u64> {
+        self.dispute_window
+    }
+
+    /// How long after a rejection its author may not submit the same `msg`
+    /// again. `None` disables the cooldown. Only rejections made while a
+    /// cooldown is set count towards it.
+    pub fn set_rejection_cooldown(&mut self, rejection_cooldown: Option<u64>) {
+        self.rejection_cooldown = rejection_cooldown;
+    }
+
+    pub fn get_rejection_cooldown(&self) -> Option<u64> {
+        self.rejection_cooldown
+    }
+
+    fn check_rejection_cooldown(&self, author_id: &AccountId, msg_hash: &Option<Base64VecU8>) {
+        let (rejection_cooldown, msg_hash) = match (self.rejection_cooldown, msg_hash) {
+            (Some(rejection_cooldown), Some(msg_hash)) => (rejection_cooldown, msg_hash),
+            _ => return,
+        };
+        if let Some(rejected_at) = self
+            .rejected_msg_hashes
+            .get(&(author_id.clone(), msg_hash.0.clone()))
+            .cloned()
+        {
+            let cooldown_ends_at = rejected_at + rejection_cooldown;
+            require!(
+                now() >= cooldown_ends_at,
+                format!(
+                    "An identical proposal was rejected recently. It may be resubmitted after {}",
+                    cooldown_ends_at
+                )
+            );
+        }
+    }
+
+    /// How long after acceptance an author may unwind it. `None` disables
+    /// unwinding.
+    pub fn set_unwind_window(&mut self, unwind_window: Option<u64>) {
+        self.unwind_window = unwind_window;
+    }
+
+    pub fn get_unwind_window(&self) -> Option<u64> {
+        self.unwind_window
+    }
+
+    /// Share of the retained deposit kept when a proposal is unwound, in
+    /// basis points
+    pub fn set_unwind_fee_bps(&mut self, unwind_fee_bps: u16) {
+        require!(
+            unwind_fee_bps <= MAX_UNWIND_FEE_BPS,
+            format!(
+                "Unwind fee cannot exceed {} basis points",
+                MAX_UNWIND_FEE_BPS
+            )
+        );
+        self.unwind_fee_bps = unwind_fee_bps;
+    }
+
+    pub fn get_unwind_fee_bps(&self) -> u16 {
+        self.unwind_fee_bps
+    }
+
+    pub fn get_unwind(&self, id: u64) -> Option<Unwind> {
+        self.unwinds.get(&id).cloned()
+    }
+
+    /// Reverts an accepted proposal at its author's request, within the
+    /// unwind window. The retained deposit is refunded less the unwind fee,
+    /// which is released to the treasury. Reverting what the proposal did is
+    /// left to the status change hook.
+    pub fn unwind(&mut self, id: u64) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::ACCEPTED,
+            "Only accepted proposals can be unwound"
+        );
+        require!(
+            proposal.author_id == env::predecessor_account_id(),
+            "Proposal can only be unwound by original author"
+        );
+        let now = now();
+        require!(
+            self.in_unwind_window(proposal.resolved_at, now),
+            "Unwind window has closed"
+        );
+        require!(
+            self.escrows
+                .get(&id)
+                .cloned()
+                .is_some_and(|escrow| escrow.state == EscrowState::HELD),
+            "Deposit has already been settled"
+        );
+
+        let retained = proposal.retained_deposit();
+        let fee = retained * Balance::from(self.unwind_fee_bps) / Balance::from(MAX_UNWIND_FEE_BPS);
+        let refund = retained - fee;
+
+        let unwound = Proposal {
+            status: ProposalStatus::UNWOUND,
+            refunded: proposal.refunded + refund,
+            ..proposal
+        };
+        self.update(&unwound);
+
+        self.total_accepted_deposits -= retained;
+        self.update_author_stats(&unwound.author_id, |stats| {
+            stats.accepted -= 1;
+            stats.total_accepted_value.0 -= retained;
+        });
+        self.deposit_totals
+            .transfer(&ProposalStatus::ACCEPTED, &unwound.status, fee);
+        if refund > 0 {
+            self.refund_escrow(id, refund);
+            self.total_deposits -= refund;
+            self.deposit_totals.transfer(
+                &ProposalStatus::ACCEPTED,
+                &ProposalStatus::RESCINDED,
+                refund,
+            );
+
+            log!(
+                "Refunding unwound deposit to {}: {}",
+                unwound.author_id,
+                &refund
+            );
+            transfer_out(
+                unwound.author_id.clone(),
+                refund,
+                "unwind_refund",
+                BalanceRef::proposal(id),
+            );
+        }
+        if fee > 0 {
+            let escrow = self.load_held_escrow(id);
+            self.close_escrow(escrow, EscrowState::RELEASED);
+        }
+
+        self.unwinds.insert(
+            id,
+            Unwind {
+                proposal_id: id.into(),
+                refund: refund.into(),
+                fee: fee.into(),
+                unwound_at: now.into(),
+            },
+        );
+
+        unwound
+    }
+
+    /// `resolved_at` is that of an accepted proposal
+    fn in_unwind_window(&self, resolved_at: Option<u64>, now: u64) -> bool {
+        match (self.unwind_window, resolved_at) {
+            (Some(unwind_window), Some(resolved_at)) => now <= resolved_at + unwind_window,
+            _ => false,
+        }
+    }
+
+    /// Applies to submissions and drafts made from then on
+    pub fn set_max_submission_bytes(&mut self, max_submission_bytes: u32) {
+        self.max_submission_bytes = max_submission_bytes;
+    }
+
+    pub fn get_max_submission_bytes(&self) -> u32 {
+        self.max_submission_bytes
+    }
+
+    /// Proposals with a deposit above this amount must be approved and then
+    /// finalized by two different accounts. `None` disables two-phase approval.
+    pub fn set_approval_threshold(&mut self, approval_threshold: Option<Balance>) {
+        self.approval_threshold = approval_threshold;
+    }
+
+    pub fn get_approval_threshold(&self) -> Option<Balance> {
+        self.approval_threshold
+    }
+
+    pub fn requires_two_phase(&self, proposal: &Proposal<T>) -> bool {
+        matches!(self.approval_threshold, Some(threshold) if proposal.deposit > threshold)
+    }
+
+    pub fn get_tag_config(&self, tag: &String) -> TagConfig {
+        self.tag_configs.get(tag).cloned().unwrap_or_default()
+    }
+
+    fn update_tag_config(&mut self, tag: &String, f: impl FnOnce(&mut TagConfig)) {
+        require!(self.tags.contains(tag), "Tag does not exist");
+        let mut config = self.get_tag_config(tag);
+        f(&mut config);
+        if config == TagConfig::default() {
+            self.tag_configs.remove(tag);
+        } else {
+            self.tag_configs.insert(tag.clone(), config);
+        }
+    }
+
+    pub fn get_expiry_policy(&self, tag: &String) -> ExpiryPolicy {
+        self.get_tag_config(tag).expiry_policy
+    }
+
+    pub fn set_expiry_policy(&mut self, tag: &String, policy: ExpiryPolicy) {
+        self.update_tag_config(tag, |config| config.expiry_policy = policy);
+    }
+
+    pub fn set_min_deposit(&mut self, tag: &String, min_deposit: Balance) {
+        self.update_tag_config(tag, |config| config.min_deposit = min_deposit.into());
+    }
+
+    pub fn get_lead_time_policy(&self, tag: &String) -> LeadTimePolicy {
+        self.get_tag_config(tag).lead_time_policy
+    }
+
+    /// Proposals already pending are unaffected, even if they exceed a
+    /// lowered cap
+    pub fn set_max_pending_deposit(&mut self, tag: &String, max_pending_deposit: Option<Balance>) {
+        self.update_tag_config(tag, |config| {
+            config.max_pending_deposit = max_pending_deposit.map(U128)
+        });
+    }
+
+    /// Applies to proposals accepted from then on
+    pub fn set_lead_time_policy(&mut self, tag: &String, policy: LeadTimePolicy) {
+        self.update_tag_config(tag, |config| config.lead_time_policy = policy);
+    }
+
+    pub fn dispute(&mut self, id: u64, reason: String) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::REJECTED && proposal.dispute.is_none(),
+            "Proposal cannot be disputed"
+        );
+        self.require_deposit_held(id);
+        require!(
+            proposal.author_id == env::predecessor_account_id(),
+            "Proposal can only be disputed by original author"
+        );
+        let now = now();
+        let dispute_window = self
+            .dispute_window
+            .unwrap_or_else(|| env::panic_str("Disputes are disabled"));
+        // .unwrap() is safe because rejected proposals are always resolved
+        require!(
+            now <= proposal.resolved_at.unwrap() + dispute_window,
+            "Dispute window has closed"
+        );
+
+        let disputed = Proposal {
+            status: ProposalStatus::DISPUTED,
+            // An approval given before the rejection does not count towards
+            // overturning it
+            approved_by: None,
+            dispute: Some(Dispute {
+                reason,
+                disputed_at: now,
+                resolved_at: None,
+                overturned: None,
+            }),
+            ..proposal
+        };
+
+        self.update(&disputed);
+
+        self.deposit_totals.transfer(
+            &ProposalStatus::REJECTED,
+            &disputed.status,
+            disputed.deposit,
+        );
+
+        disputed
+    }
+
+    /// Overturning the rejection accepts the proposal; otherwise it returns
+    /// to REJECTED and may be rescinded by its author as usual. Overturning
+    /// a proposal that requires two-phase approval takes an approval from
+    /// another account first, as accepting it would (see `approve`);
+    /// `resolver_id` then finalizes it. `is_reviewer` is checked against the
+    /// approver, as in `finalize`.
+    pub fn resolve_dispute(
+        &mut self,
+        id: u64,
+        overturned: bool,
+        resolver_id: AccountId,
+        is_reviewer: impl Fn(&AccountId) -> bool,
+    ) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::DISPUTED,
+            "Proposal is not disputed"
+        );
+        self.require_deposit_held(id);
+        let now = now();
+
+        let proposal = if overturned && self.requires_two_phase(&proposal) {
+            require!(
+                proposal.approved_by.is_some(),
+                "Proposal has not been approved"
+            );
+            let approved_by = proposal.approved_by.as_ref().unwrap();
+            require!(is_reviewer(approved_by), "Approver is no longer a reviewer");
+            require!(
+                approved_by != &resolver_id,
+                "Proposal must be finalized by a different account"
+            );
+            Proposal {
+                finalized_by: Some(resolver_id),
+                ..proposal
+            }
+        } else {
+            proposal
+        };
+
+        // .unwrap() is safe because disputed proposals always have a dispute
+        let dispute = proposal.dispute.unwrap();
+
+        let resolved = Proposal {
+            resolved_at: Some(now),
+            status: if overturned {
+                ProposalStatus::ACCEPTED
+            } else {
+                ProposalStatus::REJECTED
+            },
+            dispute: Some(Dispute {
+                resolved_at: Some(now),
+                overturned: Some(overturned),
+                ..dispute
+            }),
+            ..proposal
+        };
+
+        self.update(&resolved);
+
+        if overturned {
+            self.total_accepted_deposits += resolved.deposit;
+            let deposit = resolved.deposit;
+            self.update_author_stats(&resolved.author_id, |stats| {
+                stats.rejected -= 1;
+                stats.accepted += 1;
+                stats.total_accepted_value.0 += deposit;
+            });
+        }
+
+        self.deposit_totals.transfer(
+            &ProposalStatus::DISPUTED,
+            &resolved.status,
+            resolved.deposit,
+        );
+
+        resolved
+    }
+
+    pub fn rescind(&mut self, id: u64) -> Proposal<T> {
+        self.rescind_as(id, &env::predecessor_account_id())
+    }
+
+    /// Rescinds a proposal on behalf of the author of a signed request
+    pub fn rescind_signed(&mut self, payload: &str, signature: &Base64VecU8) -> Proposal<T> {
+        let request: SignedRequest<RescindArgs> =
+            self.verify_signed("spo_rescind", payload, signature);
+        self.rescind_as(request.args.id.into(), &request.author_id)
+    }
+
+    fn rescind_as(&mut self, id: u64, author_id: &AccountId) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::PENDING
+                || proposal.status == ProposalStatus::REJECTED
+                || proposal.status == ProposalStatus::EXPIRED
+                || proposal.status == ProposalStatus::DRAFT,
+            "Proposal cannot be rescinded"
+        );
+        require!(
+            &proposal.author_id == author_id,
+            "Proposal can only be rescinded by original author"
+        );
+        let now = now();
+
+        let resolved = Proposal {
+            resolved_at: Some(now),
+            status: ProposalStatus::RESCINDED,
+            ..proposal
+        };
+
+        self.update(&resolved);
+
+        if proposal.status == ProposalStatus::DRAFT {
+            // Nothing to refund: the deposit was never paid
+            return resolved;
+        }
+
+        self.refund(&proposal.status, &resolved);
+        self.update_author_stats(&resolved.author_id, |stats| stats.rescinded += 1);
+
+        resolved
+    }
+
+    fn refund(&mut self, from_status: &ProposalStatus, resolved: &Proposal<T>) {
+        self.refund_escrow(resolved.id, resolved.deposit);
+        self.total_deposits -= resolved.deposit;
+        self.transfer_deposit(
+            &resolved.tag,
+            from_status,
+            &resolved.status,
+            resolved.deposit,
+        );
+
+        let author_id = resolved.author_id.clone();
+        log!(
+            "Refunding rescinded deposit to {}: {}",
+            &author_id,
+            &resolved.deposit
+        );
+        transfer_out(
+            author_id,
+            resolved.deposit,
+            "deposit_refund",
+            BalanceRef::proposal(resolved.id),
+        );
+    }
+
+    /// IDs of the pending proposals whose duration has elapsed, scanning at
+    /// most `limit` proposals starting at `from_index`
+    pub fn get_expired_ids(&self, from_index: u64, limit: u64) -> Vec<u64> {
+        let now = now();
+        let to_index = u64::min(
+            from_index.saturating_add(limit),
+            self.proposals.len().into(),
+        );
+
+        (from_index..to_index)
+            .filter(|id| {
+                let header = self.load_header(*id).unwrap();
+                header.status == ProposalStatus::PENDING && header.is_expired(now)
+            })
+            .collect()
+    }
+
+    /// The proposal as `sweep_expired` would accept it, or `None` if its
+    /// tag's expiry policy would not accept it. Lets the caller check the
+    /// acceptance before it happens.
+    pub fn get_expiry_acceptance(&self, id: u64) -> Option<Proposal<T>> {
+        let proposal = self.get_proposal(id)?;
+        if self.get_expiry_policy(&proposal.tag) != ExpiryPolicy::ACCEPT
+            // Acceptance above the approval threshold takes two accounts,
+            // which a permissionless sweep cannot stand in for
+            || self.requires_two_phase(&proposal)
+        {
+            return None;
+        }
+
+        Some(Proposal {
+            resolved_at: Some(now()),
+            status: ProposalStatus::ACCEPTED,
+            ..proposal
+        })
+    }
+
+    /// Settles a pending proposal whose duration has elapsed according to
+    /// the expiry policy of its tag (see `ExpiryPolicy`). `acceptable` says
+    /// whether the caller checked that it can be accepted; if not, a proposal
+    /// the policy would accept is held instead.
+    pub fn sweep_expired(&mut self, id: u64, acceptable: bool) -> Proposal<T> {
+        let now = now();
+        let header = self
+            .load_header(id)
+            .unwrap_or_else(|| env::panic_str("Proposal does not exist"));
+        require!(
+            header.status == ProposalStatus::PENDING && header.is_expired(now),
+            "Proposal is not expired"
+        );
+        let proposal = self.with_body(header);
+
+        let policy = match self.get_expiry_policy(&proposal.tag) {
+            ExpiryPolicy::ACCEPT if !acceptable || self.requires_two_phase(&proposal) => {
+                ExpiryPolicy::HOLD
+            }
+            policy => policy,
+        };
+        match policy {
+            ExpiryPolicy::HOLD => {
+                let expired = Proposal {
+                    resolved_at: Some(now),
+                    status: ProposalStatus::EXPIRED,
+                    ..proposal
+                };
+                self.update(&expired);
+                self.transfer_deposit(
+                    &expired.tag,
+                    &ProposalStatus::PENDING,
+                    &expired.status,
+                    expired.deposit,
+                );
+                expired
+            }
+            ExpiryPolicy::REFUND => {
+                let refunded = Proposal {
+                    resolved_at: Some(now),
+                    status: ProposalStatus::RESCINDED,
+                    ..proposal
+                };
+                self.update(&refunded);
+                self.refund(&ProposalStatus::PENDING, &refunded);
+                refunded
+            }
+            ExpiryPolicy::ACCEPT => self.apply_resolution(proposal, true),
+        }
+    }
+
+    /// Index of the next proposal `emergency_refund` will look at
+    pub fn get_emergency_refund_cursor(&self) -> u64 {
+        self.emergency_refund_cursor
+    }
+
+    /// Makes the next `emergency_refund` scan start from the first proposal
+    pub fn reset_emergency_refund_cursor(&mut self) {
+        self.emergency_refund_cursor = 0;
+    }
+
+    /// Rescinds every unresolved proposal holding a deposit and refunds its
+    /// author, scanning at most `limit` proposals per call. The scan resumes
+    /// where the previous call stopped, so a refund of many proposals can be
+    /// split over as many calls as gas requires, and paused between them.
+    ///
+    /// The cursor only moves forward, so a proposal behind it that becomes
+    /// refundable later, e.g. a draft that gets funded, is not picked up
+    /// until the cursor is reset with `reset_emergency_refund_cursor`.
+    pub fn emergency_refund(&mut self, limit: u64) -> Vec<Proposal<T>> {
+        let now = now();
+        let from_index = self.emergency_refund_cursor;
+        let to_index = u64::min(
+            from_index.saturating_add(limit),
+            self.proposals.len().into(),
+        );
+        let mut refunded = vec![];
+
+        for id in from_index..to_index {
+            let header = self.load_header(id).unwrap();
+            let from_status = header.status.clone();
+            if !matches!(
+                from_status,
+                ProposalStatus::PENDING
+                    | ProposalStatus::REJECTED
+                    | ProposalStatus::EXPIRED
+                    | ProposalStatus::DISPUTED
+                    | ProposalStatus::SCHEDULED
+            ) {
+                continue;
+            }
+            // e.g. forfeited: nothing is left to refund
+            if self
+                .escrows
+                .get(&id)
+                .cloned()
+                .is_some_and(|escrow| escrow.state != EscrowState::HELD)
+            {
+                continue;
+            }
+
+            let resolved = Proposal {
+                resolved_at: Some(now),
+                status: ProposalStatus::RESCINDED,
+                ..self.with_body(header)
+            };
+            self.proposals.replace(id as u32, resolved.header());
+            Self::emit_named_proposal_event("proposal_emergency_refunded", &resolved);
+            self.refund(&from_status, &resolved);
+            self.update_author_stats(&resolved.author_id, |stats| stats.rescinded += 1);
+
+            refunded.push(resolved);
+        }
+
+        self.emergency_refund_cursor = to_index;
+
+        refunded
+    }
+
+    fn get_resolvable(&self, id: u64) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        let now = now();
+        match proposal.status {
+            ProposalStatus::PENDING => {
+                require!(!proposal.is_expired(now), "Proposal is expired");
+                Self::check_claim(&proposal);
+            }
+            // Scheduled proposals no longer expire; they may be resolved early
+            ProposalStatus::SCHEDULED => {}
+            _ => env::panic_str("Proposal has already been resolved"),
+        }
+        proposal
+    }
+
+    /// Panics if another account has claimed the proposal for review
+    fn check_claim(proposal: &Proposal<T>) {
+        if let Some(claimed_by) = &proposal.claimed_by {
+            require!(
+                claimed_by == &env::predecessor_account_id(),
+                format!("Proposal is claimed for review by {}", claimed_by)
+            );
+        }
+    }
+
+    /// Marks a pending proposal as being reviewed by `reviewer_id`, so that
+    /// nobody else resolves it meanwhile. A claim held by an account that
+    /// `is_reviewer` no longer accepts may be taken over.
+    pub fn claim_review(
+        &mut self,
+        id: u64,
+        reviewer_id: AccountId,
+        is_reviewer: impl Fn(&AccountId) -> bool,
+    ) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::PENDING,
+            "Proposal is not pending"
+        );
+        require!(!proposal.is_expired(now()), "Proposal is expired");
+        if let Some(claimed_by) = &proposal.claimed_by {
+            require!(
+                claimed_by != &reviewer_id,
+                "Proposal is already claimed by you"
+            );
+            require!(
+                !is_reviewer(claimed_by),
+                format!("Proposal is claimed for review by {}", claimed_by)
+            );
+        }
+
+        let claimed = Proposal {
+            claimed_by: Some(reviewer_id),
+            claimed_at: Some(now()),
+            ..proposal
+        };
+        self.proposals.replace(id as u32, claimed.header());
+        Self::emit_named_proposal_event("proposal_review_claimed", &claimed);
+
+        claimed
+    }
+
+    /// Gives up a review claim. `may_release_any` allows releasing a claim
+    /// held by someone else, e.g. for the owner.
+    pub fn release_review(&mut self, id: u64, may_release_any: bool) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::PENDING,
+            "Proposal is not pending"
+        );
+        let claimed_by = proposal
+            .claimed_by
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("Proposal is not claimed"));
+        require!(
+            may_release_any || claimed_by == &env::predecessor_account_id(),
+            "Review can only be released by the account that claimed it"
+        );
+
+        let released = Self::without_claim(proposal);
+        self.proposals.replace(id as u32, released.header());
+        Self::emit_named_proposal_event("proposal_review_released", &released);
+
+        released
+    }
+
+    /// Ends any review claim on a pending proposal the caller may resolve,
+    /// so that its author can act on it next, e.g. to confirm a counter-offer
+    pub fn hand_off_review(&mut self, id: u64) -> Proposal<T> {
+        let proposal = self.get_resolvable(id);
+        require!(
+            proposal.status == ProposalStatus::PENDING,
+            "Proposal is not pending"
+        );
+        if proposal.claimed_by.is_none() {
+            return proposal;
+        }
+
+        let released = Self::without_claim(proposal);
+        self.proposals.replace(id as u32, released.header());
+        Self::emit_named_proposal_event("proposal_review_released", &released);
+
+        released
+    }
+
+    fn without_claim(proposal: Proposal<T>) -> Proposal<T> {
+        Proposal {
+            claimed_by: None,
+            claimed_at: None,
+            ..proposal
+        }
+    }
+
+    fn resolve(&mut self, id: u64, accepted: bool) -> Proposal<T> {
+        let proposal = self.get_resolvable(id);
+        // Scheduled proposals were approved before they were scheduled
+        if accepted && proposal.status == ProposalStatus::PENDING {
+            require!(
+                !self.requires_two_phase(&proposal),
+                "Proposal requires two-phase approval"
+            );
+        }
+
+        self.apply_resolution(proposal, accepted)
+    }
+
+    /// First phase of two-phase approval, either of a pending proposal or of
+    /// overturning a disputed rejection. The proposal stays as it is until a
+    /// different account finalizes it or resolves the dispute.
+    pub fn approve(&mut self, id: u64, approver_id: AccountId) -> Proposal<T> {
+        let proposal = match self.load(id) {
+            // First phase of overturning the rejection (see `resolve_dispute`)
+            Some(proposal) if proposal.status == ProposalStatus::DISPUTED => {
+                self.require_deposit_held(id);
+                proposal
+            }
+            _ => self.get_resolvable(id),
+        };
+        require!(
+            matches!(
+                proposal.status,
+                ProposalStatus::PENDING | ProposalStatus::DISPUTED
+            ),
+            "Proposal is not pending"
+        );
+        require!(
+            self.requires_two_phase(&proposal),
+            "Proposal does not require two-phase approval"
+        );
+        require!(
+            proposal.approved_by.is_none(),
+            "Proposal has already been approved"
+        );
+
+        // The claim ends with the first phase, so that another reviewer can
+        // finalize
+        let approved = Proposal {
+            approved_by: Some(approver_id),
+            ..Self::without_claim(proposal)
+        };
+        self.proposals.replace(id as u32, approved.header());
+        Self::emit_named_proposal_event("proposal_approved", &approved);
+
+        approved
+    }
+
+    /// Second phase of two-phase approval. `is_reviewer` is checked against
+    /// the first approver, so removing a reviewer voids their approvals.
+    pub fn finalize(
+        &mut self,
+        id: u64,
+        finalizer_id: AccountId,
+        is_reviewer: impl Fn(&AccountId) -> bool,
+    ) -> Proposal<T> {
+        let proposal = self.get_resolvable(id);
+        let approved_by = proposal
+            .approved_by
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("Proposal has not been approved"));
+        require!(is_reviewer(approved_by), "Approver is no longer a reviewer");
+        require!(
+            approved_by != &finalizer_id,
+            "Proposal must be finalized by a different account"
+        );
+
+        self.apply_resolution(
+            Proposal {
+                finalized_by: Some(finalizer_id),
+                ..proposal
+            },
+            true,
+        )
+    }
+
+    fn apply_resolution(&mut self, proposal: Proposal<T>, accepted: bool) -> Proposal<T> {
+        let now = now();
+        let previous_status = proposal.status;
+
+        let resolved = Proposal {
+            resolved_at: Some(now),
+            status: if accepted {
+                ProposalStatus::ACCEPTED
+            } else {
+                ProposalStatus::REJECTED
+            },
+            ..proposal
+        };
+
+        self.update(&resolved);
+
+        if accepted {
+            self.total_accepted_deposits += resolved.retained_deposit();
+        } else if let (Some(_), Some(msg_hash)) = (self.rejection_cooldown, &resolved.msg_hash) {
+            self.rejected_msg_hashes
+                .insert((resolved.author_id.clone(), msg_hash.0.clone()), now);
+        }
+
+        let deposit = resolved.retained_deposit();
+        self.update_author_stats(&resolved.author_id, |stats| {
+            if accepted {
+                stats.accepted += 1;
+                stats.total_accepted_value.0 += deposit;
+            } else {
+                stats.rejected += 1;
+            }
+        });
+
+        self.transfer_deposit(
+            &resolved.tag,
+            &previous_status,
+            &resolved.status,
+            resolved.deposit,
+        );
+
+        if resolved.refunded > 0 {
+            self.refund_accepted(resolved.id, &resolved.author_id, resolved.refunded);
+        }
+
+        resolved
+    }
+
+    /// Returns `amount` of an accepted proposal's retained deposit to its
+    /// author, e.g. when the deposit exceeds the price of what was approved.
+    pub fn refund_surplus(&mut self, id: u64, amount: Balance) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::ACCEPTED,
+            "Proposal is not accepted"
+        );
+        require!(
+            amount <= proposal.retained_deposit(),
+            "Refund exceeds deposit"
+        );
+
+        let refunded = Proposal {
+            refunded: proposal.refunded + amount,
+            ..proposal
+        };
+        self.proposals.replace(id as u32, refunded.header());
+
+        self.total_accepted_deposits -= amount;
+        self.update_author_stats(&refunded.author_id, |stats| {
+            stats.total_accepted_value.0 -= amount;
+        });
+        self.refund_accepted(id, &refunded.author_id, amount);
+
+        refunded
+    }
+
+    fn refund_accepted(&mut self, id: u64, author_id: &AccountId, amount: Balance) {
+        self.refund_escrow(id, amount);
+        self.total_deposits -= amount;
+        self.deposit_totals.transfer(
+            &ProposalStatus::ACCEPTED,
+            &ProposalStatus::RESCINDED,
+            amount,
+        );
+
+        log!("Refunding unused deposit to {}: {}", author_id, &amount);
+        transfer_out(
+            author_id.clone(),
+            amount,
+            "unused_deposit_refund",
+            BalanceRef::proposal(id),
+        );
+    }
+
+    pub fn get_escrow(&self, id: u64) -> Option<Escrow> {
+        self.escrows.get(&id).cloned()
+    }
+
+    /// Deposits held in escrow, including those of accepted proposals that
+    /// have not been settled yet. None of it may be spent by the contract.
+    pub fn get_total_escrowed(&self) -> Balance {
+        self.total_escrowed
+    }
+
+    fn hold_escrow(&mut self, id: u64, amount: Balance) {
+        self.escrows.insert(id, Escrow::new(id, amount));
+        self.total_escrowed += amount;
+    }
+
+    fn load_held_escrow(&self, id: u64) -> Escrow {
+        let escrow = self
+            .escrows
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Proposal has no escrow"));
+        require!(
+            escrow.state == EscrowState::HELD,
+            "Escrow has already been settled"
+        );
+        escrow
+    }
+
+    /// Moves `amount` out of a held escrow to be returned to the author. The
+    /// escrow is settled as refunded once nothing is left in it.
+    fn refund_escrow(&mut self, id: u64, amount: Balance) {
+        let escrow = self.load_held_escrow(id);
+        require!(amount <= escrow.held(), "Refund exceeds escrowed amount");
+
+        let refunded = escrow.refunded.0 + amount;
+        let escrow = if refunded == escrow.amount.0 {
+            Escrow {
+                refunded: refunded.into(),
+                state: EscrowState::REFUNDED,
+                settled_at: Some(now().into()),
+                ..escrow
+            }
+        } else {
+            Escrow {
+                refunded: refunded.into(),
+                ..escrow
+            }
+        };
+        self.escrows.insert(id, escrow);
+        self.total_escrowed -= amount;
+    }
+
+    fn close_escrow(&mut self, escrow: Escrow, state: EscrowState) -> Escrow {
+        let held = escrow.held();
+        let escrow = Escrow {
+            state,
+            settled_at: Some(now().into()),
+            ..escrow
+        };
+        self.escrows.insert(escrow.proposal_id.0, escrow.clone());
+        self.total_escrowed -= held;
+
+        let reason = match escrow.state {
+            EscrowState::RELEASED => Some("deposit_released"),
+            EscrowState::FORFEITED => Some("deposit_forfeited"),
+            // Refunds log their own transfer
+            EscrowState::HELD | EscrowState::REFUNDED => None,
+        };
+        if let Some(reason) = reason {
+            // .unwrap() is safe because escrows are only opened for
+            // existing proposals, and those are never removed
+            let author_id = self.load_header(escrow.proposal_id.0).unwrap().author_id;
+            emit_balance_changed(
+                BalanceChangeKind::KEPT,
+                &author_id,
+                held,
+                reason,
+                BalanceRef::proposal(escrow.proposal_id.0),
+            );
+        }
+
+        escrow
+    }
+
+    /// Releases what is left of an accepted proposal's deposit to the
+    /// treasury, making it available to the contract
+    pub fn settle(&mut self, id: u64) -> Escrow {
+        let proposal = self.load_header(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::ACCEPTED,
+            "Only accepted proposals can be settled"
+        );
+        require!(
+            !self.in_unwind_window(proposal.resolved_at, now()),
+            "Deposit cannot be settled during the unwind window"
+        );
+
+        let escrow = self.load_held_escrow(id);
+        require!(
+            escrow.vests_at.is_none_or(|vests_at| now() >= vests_at.0),
+            "Deposit cannot be settled before it vests"
+        );
+        self.close_escrow(escrow, EscrowState::RELEASED)
+    }
+
+    /// Keeps an accepted proposal's deposit in escrow until `vests_at`. Does
+    /// nothing if none of the deposit is held any more.
+    pub fn vest_escrow_at(&mut self, id: u64, vests_at: u64) {
+        let escrow = self.escrows.get(&id).cloned();
+        if let Some(escrow) = escrow.filter(|escrow| escrow.state == EscrowState::HELD) {
+            let escrow = Escrow {
+                vests_at: Some(vests_at.into()),
+                ..escrow
+            };
+            self.escrows.insert(id, escrow);
+        }
+    }
+
+    /// Keeps the deposit of a rejected proposal, e.g. spam, instead of
+    /// leaving it for the author to reclaim by rescinding
+    pub fn forfeit(&mut self, id: u64) -> Escrow {
+        let proposal = self.load_header(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        require!(
+            proposal.unwrap().status == ProposalStatus::REJECTED,
+            "Only rejected proposals can be forfeited"
+        );
+
+        let escrow = self.load_held_escrow(id);
+        let held = escrow.held();
+        self.deposit_totals.rejected.0 -= held;
+        self.deposit_totals.forfeited.0 += held;
+        self.total_deposits -= held;
+        self.close_escrow(escrow, EscrowState::FORFEITED)
+    }
+
+    /// A forfeited deposit already belongs to the contract, so the proposal
+    /// can no longer be disputed into acceptance
+    fn require_deposit_held(&self, id: u64) {
+        require!(
+            self.escrows
+                .get(&id)
+                .cloned()
+                .is_some_and(|escrow| escrow.state == EscrowState::HELD),
+            "Deposit has been forfeited"
+        );
+    }
+
+    /// Re-reads a proposal to pick up changes made by a status change hook
+    pub fn reload(&self, proposal: Proposal<T>) -> Proposal<T> {
+        self.load(proposal.id).unwrap()
+    }
+
+    pub fn accept(&mut self, id: u64) -> Proposal<T> {
+        self.resolve(id, true)
+    }
+
+    /// Accepts a proposal after replacing its `msg`, returning `refund` of
+    /// the deposit to the author. Used when the owner approves less than
+    /// was requested.
+    pub fn accept_amended(&mut self, id: u64, msg: Option<T>, refund: Balance) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(refund <= proposal.deposit, "Refund exceeds deposit");
+
+        let msg_hash = msg_hash(msg.as_ref());
+        let content_hash = content_hash(&proposal.description, msg.as_ref());
+        self.proposal_bodies.insert(
+            id,
+            ProposalBody {
+                description: proposal.description.clone(),
+                msg: msg.map(M::from),
+            },
+        );
+        self.proposals.replace(
+            id as u32,
+            ProposalHeader {
+                refunded: refund,
+                msg_hash,
+                content_hash,
+                ..proposal.header()
+            },
+        );
+
+        self.resolve(id, true)
+    }
+
+    /// Records an approval that takes effect once `accept_at` has passed and
+    /// `execute_scheduled` is called.
+    pub fn schedule_accept(&mut self, id: u64, accept_at: u64) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::PENDING,
+            "Proposal has already been resolved"
+        );
+        let now = now();
+        require!(!proposal.is_expired(now), "Proposal is expired");
+        Self::check_claim(&proposal);
+        require!(accept_at > now, "Acceptance time must be in the future");
+        require!(
+            !self.requires_two_phase(&proposal),
+            "Proposal requires two-phase approval"
+        );
+
+        let scheduled = Proposal {
+            status: ProposalStatus::SCHEDULED,
+            accept_at: Some(accept_at),
+            ..proposal
+        };
+
+        self.update(&scheduled);
+
+        self.transfer_deposit(
+            &scheduled.tag,
+            &ProposalStatus::PENDING,
+            &scheduled.status,
+            scheduled.deposit,
+        );
+
+        scheduled
+    }
+
+    pub fn execute_scheduled(&mut self, id: u64) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::SCHEDULED,
+            "Proposal is not scheduled"
+        );
+        // .unwrap() is safe because scheduled proposals always have accept_at
+        require!(
+            now() >= proposal.accept_at.unwrap(),
+            "Scheduled acceptance time has not been reached"
+        );
+
+        self.resolve(id, true)
+    }
+
+    pub fn reject(&mut self, id: u64) -> Proposal<T> {
+        self.resolve(id, false)
+    }
+
+    /// `validate_msg` checks the message against the tag before any deposit
+    /// or storage work is done (see `MessageValidator`)
+    pub fn submit(
+        &mut self,
+        submission: ProposalSubmission<T>,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> Proposal<T> {
+        self.create(
+            env::predecessor_account_id(),
+            submission,
+            ProposalStatus::PENDING,
+            validate_msg,
+        )
+    }
+
+    /// Submits a proposal on behalf of the author of a signed request. The
+    /// caller attaches the deposit, but the proposal and any refund of its
+    /// deposit are the author's.
+    pub fn submit_signed(
+        &mut self,
+        payload: &str,
+        signature: &Base64VecU8,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> Proposal<T>
+    where
+        T: near_sdk::serde::de::DeserializeOwned,
+    {
+        let request: SignedRequest<SubmissionArgs<T>> =
+            self.verify_signed("spo_submit", payload, signature);
+        self.create(
+            request.author_id,
+            request.args.submission,
+            ProposalStatus::PENDING,
+            validate_msg,
+        )
+    }
+
+    /// Stores a proposal without taking its deposit. The author only pays for
+    /// storage, and may later call `fund_draft` to submit it for review.
+    pub fn submit_draft(
+        &mut self,
+        submission: ProposalSubmission<T>,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> Proposal<T> {
+        self.create(
+            env::predecessor_account_id(),
+            submission,
+            ProposalStatus::DRAFT,
+            validate_msg,
+        )
+    }
+
+    pub fn fund_draft(&mut self, id: u64) -> Proposal<T> {
+        let proposal = self.load(id);
+        require!(proposal.is_some(), "Proposal does not exist");
+        let proposal = proposal.unwrap();
+        require!(
+            proposal.status == ProposalStatus::DRAFT,
+            "Proposal is not a draft"
+        );
+        require!(
+            proposal.author_id == env::predecessor_account_id(),
+            "Draft can only be funded by original author"
+        );
+        self.check_pending_count_cap();
+        self.check_pending_deposit_cap(&proposal.tag, proposal.deposit);
+
+        let attached_deposit = env::attached_deposit().as_yoctonear();
+        self.flush();
+        let storage_usage_start = env::storage_usage();
+
+        // The review period starts when the draft is funded
+        let now = now();
+        self.remove_created_at_index(proposal.created_at, id);
+        self.insert_created_at_index(now, id);
+
+        let funded = Proposal {
+            status: ProposalStatus::PENDING,
+            created_at: now,
+            ..proposal
+        }
+        .with_expiry(now);
+
+        self.update(&funded);
+        self.update_author_stats(&funded.author_id, |stats| stats.submitted += 1);
+        self.hold_escrow(id, funded.deposit);
+
+        self.flush();
+        let storage_usage_end = env::storage_usage();
+        let storage_fee = Balance::from(storage_usage_end.saturating_sub(storage_usage_start))
+            * env::storage_byte_cost().as_yoctonear();
+        let total_required_deposit = storage_fee + funded.deposit;
+        require!(
+            attached_deposit >= total_required_deposit,
+            format!(
+                "Insufficient deposit. Required: {} yoctoNEAR Received: {} yoctoNEAR",
+                &total_required_deposit, &attached_deposit
+            )
+        );
+
+        let refund = attached_deposit - total_required_deposit;
+
+        emit_balance_changed(
+            BalanceChangeKind::RECEIVED,
+            &funded.author_id,
+            attached_deposit,
+            "draft_funding",
+            BalanceRef::proposal(id),
+        );
+        if refund > 0 {
+            transfer_out(
+                env::predecessor_account_id(),
+                refund,
+                "excess_deposit_refund",
+                BalanceRef::proposal(id),
+            );
+        }
+
+        let funded = Proposal {
+            storage_fee: funded.storage_fee + storage_fee,
+            ..funded
+        };
+        self.proposals.replace(id as u32, funded.header());
+
+        self.total_deposits += funded.deposit;
+        self.add_deposit(&funded.tag, &ProposalStatus::PENDING, funded.deposit);
+
+        funded
+    }
+
+    fn insert_created_at_index(&mut self, created_at: u64, id: u64) {
+        let mut ids = self
+            .proposal_ids_by_created_at
+            .get(&created_at)
+            .cloned()
+            .unwrap_or_default();
+        ids.push(id);
+        self.proposal_ids_by_created_at.insert(created_at, ids);
+    }
+
+    fn remove_created_at_index(&mut self, created_at: u64, id: u64) {
+        let mut ids = self
+            .proposal_ids_by_created_at
+            .get(&created_at)
+            .cloned()
+            .unwrap_or_default();
+        ids.retain(|x| *x != id);
+        if ids.is_empty() {
+            self.proposal_ids_by_created_at.remove(&created_at);
+        } else {
+            self.proposal_ids_by_created_at.insert(created_at, ids);
+        }
+    }
+
+    /// Stores a new proposal and indexes it. `proposal.id` and
+    /// `proposal.uid` must be the next ones available.
+    fn push(&mut self, proposal: Proposal<T>) -> Proposal<T> {
+        let (id, uid) = (proposal.id, proposal.uid);
+        let (header, body) = proposal.into_parts();
+        self.proposals.push(header.clone());
+        self.proposal_bodies.insert(id, body.clone());
+        let proposal = Proposal::from_parts(header, body);
+        self.proposal_ids_by_uid.insert(uid, id);
+        self.next_uid += 1;
+        self.insert_created_at_index(proposal.created_at, id);
+        if let Some(nonce) = &proposal.nonce {
+            self.proposal_ids_by_nonce
+                .insert((proposal.author_id.clone(), nonce.clone()), id);
+        }
+        if let Some(previous_proposal_id) = proposal.previous_proposal_id {
+            self.resubmissions.insert(previous_proposal_id, id);
+        }
+
+        proposal
+    }
+
+    /// Stores a proposal carried over from an earlier state layout, along
+    /// with the escrow and totals its status implies. No funds move: the
+    /// deposit stays held while the author may still rescind the proposal,
+    /// and is recorded as released or refunded if it was accepted or
+    /// rescinded. `msg_hash` and `content_hash` are computed here.
+    pub fn import(&mut self, proposal: Proposal<T>) -> Proposal<T> {
+        require!(
+            proposal.id == u64::from(self.proposals.len()) && proposal.uid == self.next_uid,
+            "Proposals must be imported in order"
+        );
+        let proposal = self.push(Proposal {
+            msg_hash: msg_hash(proposal.msg.as_ref()),
+            content_hash: content_hash(&proposal.description, proposal.msg.as_ref()),
+            ..proposal
+        });
+        let (id, deposit) = (proposal.id, proposal.deposit);
+        let settled_at = proposal.resolved_at.map(U64);
+
+        let escrow = match proposal.status {
+            ProposalStatus::PENDING | ProposalStatus::REJECTED => {
+                self.total_escrowed += deposit;
+                Escrow::new(id, deposit)
+            }
+            ProposalStatus::ACCEPTED => {
+                self.total_accepted_deposits += deposit;
+                Escrow {
+                    state: EscrowState::RELEASED,
+                    settled_at,
+                    ..Escrow::new(id, deposit)
+                }
+            }
+            ProposalStatus::RESCINDED => Escrow {
+                refunded: deposit.into(),
+                state: EscrowState::REFUNDED,
+                settled_at,
+                ..Escrow::new(id, deposit)
+            },
+            _ => env::panic_str("Proposal status cannot be imported"),
+        };
+        self.escrows.insert(id, escrow);
+
+        if proposal.status == ProposalStatus::RESCINDED {
+            self.deposit_totals.rescinded.0 += deposit;
+        } else {
+            self.total_deposits += deposit;
+            self.add_deposit(&proposal.tag, &proposal.status, deposit);
+        }
+        self.update_author_stats(&proposal.author_id, |stats| {
+            stats.submitted += 1;
+            match proposal.status {
+                ProposalStatus::ACCEPTED => {
+                    stats.accepted += 1;
+                    stats.total_accepted_value.0 += deposit;
+                }
+                ProposalStatus::REJECTED => stats.rejected += 1,
+                ProposalStatus::RESCINDED => stats.rescinded += 1,
+                _ => {}
+            }
+        });
+
+        proposal
+    }
+
+    /// Stores a proposal in any status without taking a deposit, for seeding
+    /// demo environments. The deposit is recorded as 0 so that deposit totals
+    /// still match the funds held.
+    #[cfg(feature = "demo")]
+    pub fn insert_demo(
+        &mut self,
+        author_id: AccountId,
+        submission: ProposalSubmission<T>,
+        status: ProposalStatus,
+    ) -> Proposal<T> {
+        require!(self.tags.contains(&submission.tag), "Tag does not exist");
+        let now = now();
+        let is_resolved = !matches!(
+            status,
+            ProposalStatus::PENDING | ProposalStatus::SCHEDULED | ProposalStatus::DRAFT
+        );
+
+        let msg_hash = msg_hash(submission.msg.as_ref());
+        let content_hash = content_hash(&submission.description, submission.msg.as_ref());
+
+        let proposal = self.push(Proposal {
+            id: u64::from(self.proposals.len()),
+            uid: self.next_uid,
+            author_id,
+            description: submission.description,
+            tag: submission.tag,
+            msg: submission.msg,
+            deposit: 0,
+            created_at: now,
+            duration: submission
+                .duration
+                .map(u64::from)
+                .or_else(|| *self.proposal_duration.get()),
+            resolved_at: if is_resolved { Some(now) } else { None },
+            dispute: if status == ProposalStatus::DISPUTED {
+                Some(Dispute {
+                    reason: "Demo dispute".to_string(),
+                    disputed_at: now,
+                    resolved_at: None,
+                    overturned: None,
+                })
+            } else {
+                None
+            },
+            accept_at: if status == ProposalStatus::SCHEDULED {
+                Some(now + NANOS_PER_DAY)
+            } else {
+                None
+            },
+            status,
+            nonce: None,
+            refunded: 0,
+            approved_by: None,
+            finalized_by: None,
+            storage_fee: 0,
+            referrer_id: None,
+            promo_code: None,
+            previous_proposal_id: None,
+            claimed_by: None,
+            claimed_at: None,
+            msg_hash,
+            content_hash,
+            expires_at: None,
+            is_expired: false,
+        });
+        match proposal.status {
+            ProposalStatus::DRAFT => {}
+            ProposalStatus::RESCINDED => {
+                self.hold_escrow(proposal.id, 0);
+                self.refund_escrow(proposal.id, 0);
+            }
+            _ => self.hold_escrow(proposal.id, 0),
+        }
+        Self::emit_proposal_event(&proposal);
+
+        proposal.with_expiry(now)
+    }
+
+    /// Checks a submission and builds the proposal it would create, without
+    /// storing anything
+    fn build_proposal(
+        &self,
+        author_id: AccountId,
+        submission: ProposalSubmission<T>,
+        status: ProposalStatus,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> Proposal<T> {
+        require!(self.tags.contains(&submission.tag), "Tag does not exist");
+        validate_msg(&submission.tag, submission.msg.as_ref())
+            .unwrap_or_else(|e| env::panic_str(&e));
+
+        require!(
+            submission.referrer_id.as_ref() != Some(&author_id),
+            "Author cannot refer themselves"
+        );
+
+        if let Some(nonce) = &submission.nonce {
+            require!(
+                !self
+                    .proposal_ids_by_nonce
+                    .contains_key(&(author_id.clone(), nonce.clone())),
+                "Duplicate submission"
+            );
+        }
+
+        let previous_proposal_id = submission.previous_proposal_id.map(u64::from);
+        if let Some(previous_proposal_id) = previous_proposal_id {
+            self.check_resubmission(previous_proposal_id, &author_id, &submission.tag);
+        }
+
+        let id = u64::from(self.proposals.len());
+        let uid = self.next_uid;
+
+        let duration = match (
+            *self.proposal_duration.get(),
+            submission.duration.map(|x| x.into()),
+        ) {
+            (Some(contract_duration), Some(submission_duration)) => {
+                Some(u64::min(contract_duration, submission_duration))
+            }
+            (Some(d), _) | (_, Some(d)) => Some(d),
+            _ => None,
+        };
+
+        let submission_deposit = submission.deposit.into();
+        require!(
+            submission_deposit >= self.get_tag_config(&submission.tag).min_deposit.0,
+            "Deposit is below the minimum for this tag"
+        );
+        if status == ProposalStatus::PENDING {
+            self.check_pending_count_cap();
+            self.check_pending_deposit_cap(&submission.tag, submission_deposit);
+        }
+
+        let msg_hash = msg_hash(submission.msg.as_ref());
+        self.check_rejection_cooldown(&author_id, &msg_hash);
+        let content_hash = content_hash(&submission.description, submission.msg.as_ref());
+
+        Proposal {
             id,
-            author_id: env::predecessor_account_id(),
+            uid,
+            author_id,
             description: submission.description,
             tag: submission.tag,
             msg: submission.msg,
             deposit: submission_deposit,
-            created_at: env::block_timestamp(),
+            created_at: now(),
             duration,
             resolved_at: None,
-            status: ProposalStatus::PENDING,
+            status,
+            nonce: submission.nonce,
+            dispute: None,
+            accept_at: None,
+            refunded: 0,
+            approved_by: None,
+            finalized_by: None,
+            storage_fee: 0,
+            referrer_id: submission.referrer_id,
+            promo_code: submission.promo_code,
+            previous_proposal_id,
+            claimed_by: None,
+            claimed_at: None,
+            msg_hash,
+            content_hash,
+            expires_at: None,
+            is_expired: false,
+        }
+    }
+
+    fn check_resubmission(&self, previous_proposal_id: u64, author_id: &AccountId, tag: &String) {
+        let previous = self
+            .load_header(previous_proposal_id)
+            .unwrap_or_else(|| env::panic_str("Previous proposal does not exist"));
+        require!(
+            &previous.author_id == author_id,
+            "Previous proposal has a different author"
+        );
+        require!(
+            &previous.tag == tag,
+            "Previous proposal has a different tag"
+        );
+        require!(
+            matches!(
+                previous.status,
+                ProposalStatus::REJECTED | ProposalStatus::EXPIRED | ProposalStatus::RESCINDED
+            ),
+            "Previous proposal was not rejected"
+        );
+        require!(
+            !self.resubmissions.contains_key(&previous_proposal_id),
+            "Previous proposal has already been resubmitted"
+        );
+    }
+
+    /// The proposal `id` was resubmitted as, if any
+    pub fn get_resubmission(&self, id: u64) -> Option<u64> {
+        self.resubmissions.get(&id).cloned()
+    }
+
+    /// The proposal followed by the proposals it was resubmitted from, newest
+    /// first, at most `limit` in all
+    pub fn get_proposal_chain(&self, id: u64, limit: u64) -> Vec<Proposal<T>> {
+        let mut chain = vec![];
+        let mut next_id = Some(id);
+        while let Some(id) = next_id {
+            if chain.len() as u64 >= limit {
+                break;
+            }
+            let proposal = match self.load(id) {
+                Some(proposal) => proposal,
+                None => break,
+            };
+            next_id = proposal.previous_proposal_id;
+            chain.push(proposal);
+        }
+        chain
+    }
+
+    /// The most storage, and so the deposit, `submit` would need for the
+    /// submission from `author_id` right now
+    pub fn quote_submission(
+        &self,
+        author_id: AccountId,
+        submission: ProposalSubmission<T>,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> SubmissionQuote {
+        let proposal = self.build_proposal(
+            author_id.clone(),
+            submission,
+            ProposalStatus::PENDING,
+            validate_msg,
+        );
+        let nonce_key_bytes = proposal.nonce.as_ref().map(|nonce| {
+            borsh::to_vec(&(author_id.clone(), nonce.clone()))
+                .unwrap()
+                .len() as u64
+        });
+        let deposit = proposal.deposit;
+        let previous_proposal_id = proposal.previous_proposal_id;
+        let (header, body) = proposal.into_parts::<M>();
+
+        let record = |key_bytes: u64, value_bytes: u64| {
+            self.key_prefix_len + key_bytes + value_bytes + STORAGE_BYTES_PER_RECORD
+        };
+        let len = |bytes: Vec<u8>| bytes.len() as u64;
+
+        let mut storage_bytes = record(4, len(borsh::to_vec(&header).unwrap()))
+            + record(8, len(borsh::to_vec(&body).unwrap()))
+            // Index by UID
+            + record(8, 8)
+            // Index by creation time, assuming a new key: a value under a
+            // hashed key, a tree node under the tree's own prefix, and one
+            // more link to the node from its parent
+            + HASHED_KEY_BYTES + 4 + 8 + STORAGE_BYTES_PER_RECORD
+            + record(5, TREE_NODE_BYTES)
+            + 4
+            + record(8, len(borsh::to_vec(&Escrow::new(0, deposit)).unwrap()));
+        if let Some(nonce_key_bytes) = nonce_key_bytes {
+            storage_bytes += record(nonce_key_bytes, 8);
+        }
+        if previous_proposal_id.is_some() {
+            storage_bytes += record(8, 8);
+        }
+        if !self.author_stats.contains_key(&author_id) {
+            storage_bytes += record(
+                len(borsh::to_vec(&author_id).unwrap()),
+                len(borsh::to_vec(&AuthorStats::default()).unwrap()),
+            );
+        }
+
+        let storage_fee = Balance::from(storage_bytes) * env::storage_byte_cost().as_yoctonear();
+        SubmissionQuote {
+            storage_bytes: storage_bytes.into(),
+            storage_fee: storage_fee.into(),
+            deposit: deposit.into(),
+            total: (storage_fee + deposit).into(),
+        }
+    }
+
+    /// Writes out every pending change. `store` collections otherwise only
+    /// write when the contract state is saved, after the storage they use
+    /// can be measured.
+    fn flush(&mut self) {
+        self.tags.flush();
+        self.proposals.flush();
+        self.proposal_bodies.flush();
+        self.proposal_ids_by_uid.flush();
+        self.proposal_ids_by_nonce.flush();
+        self.proposal_ids_by_created_at.flush();
+        self.author_stats.flush();
+        self.tag_configs.flush();
+        self.proposal_duration.flush();
+        self.pending_deposits_by_tag.flush();
+        self.escrows.flush();
+        self.unwinds.flush();
+        self.resubmissions.flush();
+        self.rejected_msg_hashes.flush();
+        self.signing_keys.flush();
+        self.signing_nonces.flush();
+    }
+
+    fn create(
+        &mut self,
+        author_id: AccountId,
+        submission: ProposalSubmission<T>,
+        status: ProposalStatus,
+        validate_msg: impl FnOnce(&str, Option<&T>) -> Result<(), String>,
+    ) -> Proposal<T> {
+        let attached_deposit = env::attached_deposit().as_yoctonear();
+        require!(attached_deposit >= 1, "Deposit required");
+
+        self.flush();
+        let storage_usage_start = env::storage_usage();
+
+        let max_storage_fee = submission.max_storage_fee;
+        let proposal = self.build_proposal(author_id.clone(), submission, status, validate_msg);
+        let id = proposal.id;
+        let submission_deposit = proposal.deposit;
+
+        let proposal = self.push(proposal);
+        let is_draft = proposal.status == ProposalStatus::DRAFT;
+        if !is_draft {
+            self.update_author_stats(&author_id, |stats| stats.submitted += 1);
+            self.hold_escrow(id, proposal.deposit);
+        }
+
+        self.flush();
+        let storage_usage_end = env::storage_usage();
+        let storage_fee = Balance::from(storage_usage_end.saturating_sub(storage_usage_start))
+            * env::storage_byte_cost().as_yoctonear();
+        if let Some(max_storage_fee) = max_storage_fee {
+            require!(
+                storage_fee <= max_storage_fee.0,
+                format!(
+                    "Storage fee of {} yoctoNEAR exceeds max_storage_fee",
+                    storage_fee
+                )
+            );
+        }
+        let held_deposit = if is_draft { 0 } else { submission_deposit };
+        let total_required_deposit = storage_fee + held_deposit;
+        require!(
+            attached_deposit >= total_required_deposit,
+            format!(
+                "Insufficient deposit. Required: {} yoctoNEAR Received: {} yoctoNEAR",
+                &total_required_deposit, &attached_deposit
+            )
+        );
+
+        let refund = attached_deposit - total_required_deposit;
+
+        log!("Storage fee: {} Refund: {}", &storage_fee, &refund);
+
+        // Same size as the header already stored, so costs no more storage
+        let proposal = Proposal {
+            storage_fee,
+            ..proposal
         };
+        self.proposals.replace(id as u32, proposal.header());
+
+        // The caller pays, which for a signed request is the relayer
+        emit_balance_changed(
+            BalanceChangeKind::RECEIVED,
+            &env::predecessor_account_id(),
+            attached_deposit,
+            "proposal_submission",
+            BalanceRef::proposal(id),
+        );
+        if refund > 0 {
+            transfer_out(
+                env::predecessor_account_id(),
+                refund,
+                "excess_deposit_refund",
+                BalanceRef::proposal(id),
+            );
+        }
+
+        if !is_draft {
+            self.total_deposits += proposal.deposit;
+            self.add_deposit(&proposal.tag, &proposal.status, proposal.deposit);
+        }
+
+        Self::emit_proposal_event(&proposal);
+
+        proposal.with_expiry(now())
+    }
+}
 
-        self.proposals.push(&proposal);
+/// Implemented by the host contract to declare which tags it handles and
+/// which message each expects. Submissions are checked against it before
+/// their deposit is taken.
+pub trait MessageValidator<T> {
+    fn validate_msg(tag: &str, msg: Option<&T>) -> Result<(), String>;
 
-        let storage_usage_end = env::storage_usage();
-        let storage_fee = Balance::from(storage_usage_end.saturating_sub(storage_usage_start))
-            * env::storage_byte_cost();
-        let total_required_deposit = storage_fee + submission_deposit;
-        require!(
-            attached_deposit >= total_required_deposit,
-            format!(
-                "Insufficient deposit. Required: {} yoctoNEAR Received: {} yoctoNEAR",
-                &total_required_deposit, &attached_deposit
-            )
-        );
+    /// Whether the contract acts on accepted proposals with `tag`. Tags it
+    /// does not handle cannot be added, since accepting such a proposal
+    /// would take the deposit for nothing.
+    fn handles_tag(tag: &str) -> bool;
 
-        let refund = attached_deposit - total_required_deposit;
+    /// Checks that a proposal can be accepted when no reviewer decides, i.e.
+    /// when `spo_sweep_expired` accepts it under `ExpiryPolicy::ACCEPT`.
+    /// `proposal` is as it would be once accepted. Those that fail are held
+    /// as expired instead.
+    fn check_acceptance(&self, _proposal: &Proposal<T>) -> Result<(), String>
+    where
+        T: BorshDeserialize + BorshSerialize,
+    {
+        Ok(())
+    }
+}
 
-        log!("Storage fee: {} Refund: {}", &storage_fee, &refund);
+/// Implemented by the host contract to act on proposals as their status
+/// changes. Each change is first validated and only then acted on, so a
+/// handler can refuse a change without having done anything to undo.
+pub trait ProposalHandler<T>
+where
+    T: BorshDeserialize + BorshSerialize,
+{
+    /// Checks a proposal in its new status. An error aborts the call that
+    /// changed the status, deposit and all, with the error as the message.
+    fn validate(&self, _proposal: &Proposal<T>) -> Result<(), String> {
+        Ok(())
+    }
 
-        if refund > 0 {
-            Promise::new(env::predecessor_account_id()).transfer(refund);
-        }
+    /// Acts on a proposal that passed `validate`, e.g. carries out an
+    /// accepted proposal's request. Runs on every change.
+    fn execute(&mut self, proposal: &Proposal<T>);
 
-        self.total_deposits += proposal.deposit;
+    /// Undoes what `execute` did for an accepted proposal that has since been
+    /// unwound. Runs before `execute` is called with the unwound proposal.
+    fn rollback(&mut self, _proposal: &Proposal<T>) {}
 
-        proposal
+    /// Runs the phases in order. This is the hook to pass to
+    /// `impl_sponsorship!`.
+    fn on_proposal_change(&mut self, proposal: &Proposal<T>) {
+        self.validate(proposal)
+            .unwrap_or_else(|e| env::panic_str(&e));
+        if proposal.status == ProposalStatus::UNWOUND {
+            self.rollback(proposal);
+        }
+        self.execute(proposal);
     }
 }
 
@@ -312,41 +3145,213 @@ where
     fn spo_remove_tags(&mut self, tags: Vec<String>);
     fn spo_get_total_deposits(&self) -> U128;
     fn spo_get_total_accepted_deposits(&self) -> U128;
+    fn spo_get_deposit_totals(&self) -> DepositTotals;
+    fn spo_get_author_stats(&self, account_id: AccountId) -> AuthorStats;
+    fn spo_is_trusted_author(&self, account_id: AccountId, tag: String) -> bool;
+    fn spo_set_trusted_author(&mut self, account_id: AccountId, tag: String, trusted: bool);
     fn spo_get_all_proposals(&self) -> Vec<Proposal<T>>;
     fn spo_get_pending_proposals(&self) -> Vec<Proposal<T>>;
     fn spo_get_accepted_proposals(&self) -> Vec<Proposal<T>>;
     fn spo_get_rejected_proposals(&self) -> Vec<Proposal<T>>;
     fn spo_get_rescinded_proposals(&self) -> Vec<Proposal<T>>;
     fn spo_get_expired_proposals(&self) -> Vec<Proposal<T>>;
+    fn spo_get_disputed_proposals(&self) -> Vec<Proposal<T>>;
+    fn spo_get_scheduled_proposals(&self) -> Vec<Proposal<T>>;
+    fn spo_get_draft_proposals(&self) -> Vec<Proposal<T>>;
+    fn spo_get_proposals_between(
+        &self,
+        from_ts: U64,
+        to_ts: U64,
+        limit: Option<U64>,
+    ) -> Vec<Proposal<T>>;
+    fn spo_get_proposal_headers(
+        &self,
+        status: Option<ProposalStatus>,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<ProposalHeader>;
     fn spo_get_proposal(&self, id: U64) -> Option<Proposal<T>>;
+    fn spo_get_proposal_by_uid(&self, uid: U64) -> Option<Proposal<T>>;
+    fn spo_get_proposal_chain(&self, id: U64, limit: Option<U64>) -> Vec<Proposal<T>>;
+    fn spo_get_resubmission(&self, id: U64) -> Option<U64>;
+    fn spo_sweep_expired(&mut self, from_index: U64, limit: U64) -> Vec<Proposal<T>>;
+    fn spo_get_emergency_refund_cursor(&self) -> U64;
+    fn spo_get_escrow(&self, id: U64) -> Option<Escrow>;
+    fn spo_get_total_escrowed(&self) -> U128;
+    fn spo_settle(&mut self, id: U64) -> Escrow;
+    fn spo_forfeit(&mut self, id: U64) -> Escrow;
+    fn spo_emergency_refund(&mut self, limit: U64) -> Vec<Proposal<T>>;
+    fn spo_reset_emergency_refund_cursor(&mut self);
     fn spo_get_duration(&self) -> Option<U64>;
-    fn spo_set_duration(&mut self, duration: Option<U64>);
-    fn spo_submit(&mut self, submission: ProposalSubmission<T>) -> Proposal<T>;
+    fn spo_set_duration(&mut self, duration: Option<U64>, extend_pending: Option<bool>)
+        -> Vec<U64>;
+    fn spo_get_dispute_window(&self) -> Option<U64>;
+    fn spo_set_dispute_window(&mut self, dispute_window: Option<U64>);
+    fn spo_get_rejection_cooldown(&self) -> Option<U64>;
+    fn spo_set_rejection_cooldown(&mut self, rejection_cooldown: Option<U64>);
+    fn spo_get_unwind_window(&self) -> Option<U64>;
+    fn spo_set_unwind_window(&mut self, unwind_window: Option<U64>);
+    fn spo_get_unwind_fee_bps(&self) -> u16;
+    fn spo_set_unwind_fee_bps(&mut self, unwind_fee_bps: u16);
+    fn spo_get_unwind(&self, id: U64) -> Option<Unwind>;
+    fn spo_get_max_submission_bytes(&self) -> u32;
+    fn spo_set_max_submission_bytes(&mut self, max_submission_bytes: u32);
+    fn spo_get_approval_threshold(&self) -> Option<U128>;
+    fn spo_set_approval_threshold(&mut self, approval_threshold: Option<U128>);
+    fn spo_get_expiry_policy(&self, tag: String) -> ExpiryPolicy;
+    fn spo_set_expiry_policy(&mut self, tag: String, policy: ExpiryPolicy);
+    fn spo_get_tag_config(&self, tag: String) -> TagConfig;
+    fn spo_set_tag_min_deposit(&mut self, tag: String, min_deposit: U128);
+    fn spo_set_tag_lead_time_policy(&mut self, tag: String, policy: LeadTimePolicy);
+    fn spo_set_tag_max_pending_deposit(&mut self, tag: String, max_pending_deposit: Option<U128>);
+    fn spo_get_pending_deposit(&self, tag: String) -> U128;
+    fn spo_get_pending_count(&self) -> U64;
+    fn spo_get_max_pending_proposals(&self) -> Option<U64>;
+    fn spo_set_max_pending_proposals(&mut self, max_pending_proposals: Option<U64>);
+    fn spo_dispute(&mut self, id: U64, reason: String) -> Proposal<T>;
+    fn spo_unwind(&mut self, id: U64) -> Proposal<T>;
+    fn spo_resolve_dispute(&mut self, id: U64, overturned: bool) -> Proposal<T>;
+    fn spo_quote_submission(
+        &self,
+        author_id: AccountId,
+        submission: ProposalSubmission<T>,
+    ) -> SubmissionQuote;
+    fn spo_fund_draft(&mut self, id: U64) -> Proposal<T>;
     fn spo_accept(&mut self, id: U64) -> Proposal<T>;
+    fn spo_approve(&mut self, id: U64) -> Proposal<T>;
+    fn spo_claim_review(&mut self, id: U64) -> Proposal<T>;
+    fn spo_release_review(&mut self, id: U64) -> Proposal<T>;
+    fn spo_finalize(&mut self, id: U64) -> Proposal<T>;
     fn spo_reject(&mut self, id: U64) -> Proposal<T>;
     fn spo_rescind(&mut self, id: U64) -> Proposal<T>;
+    fn spo_accept_at(&mut self, id: U64, timestamp: U64) -> Proposal<T>;
+    fn spo_execute_scheduled(&mut self, id: U64) -> Proposal<T>;
+    fn spo_accept_by_uid(&mut self, uid: U64) -> Proposal<T>;
+    fn spo_reject_by_uid(&mut self, uid: U64) -> Proposal<T>;
+    fn spo_rescind_by_uid(&mut self, uid: U64) -> Proposal<T>;
+    fn spo_get_signing_key(&self, account_id: AccountId) -> Option<PublicKey>;
+    fn spo_get_signing_nonce(&self, account_id: AccountId) -> U64;
+    fn spo_set_signing_key(&mut self, public_key: Option<PublicKey>);
+    fn spo_rescind_signed(&mut self, payload: String, signature: Base64VecU8) -> Proposal<T>;
+}
+
+/// Submission methods. These are exported by hand rather than through
+/// `#[near_bindgen]`, whose generated wrapper parses the arguments before the
+/// method body runs: the exported functions check the size of the raw
+/// arguments first, then parse them with `parse_submission_input`.
+pub trait Submittable<T>
+where
+    T: BorshDeserialize + BorshSerialize,
+{
+    fn spo_submit(&mut self, submission: ProposalSubmission<T>) -> Proposal<T>;
+    fn spo_submit_draft(&mut self, submission: ProposalSubmission<T>) -> Proposal<T>;
+    fn spo_submit_signed(&mut self, payload: String, signature: Base64VecU8) -> Proposal<T>;
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct SubmissionArgs<T> {
+    submission: ProposalSubmission<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct RescindArgs {
+    id: U64,
+}
+
+/// A call an author signed for a relayer to send on their behalf, e.g. with
+/// `spo_submit_signed`. `args` are the arguments the author would have passed
+/// to `method` directly. The signature covers the JSON text of the request
+/// exactly as it is passed to the contract.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct SignedRequest<A> {
+    /// Keeps a request from being sent to another deployment
+    contract_id: AccountId,
+    method: String,
+    author_id: AccountId,
+    /// Must be greater than the last nonce the author used
+    nonce: U64,
+    args: A,
+}
+
+/// Reads the `submission` argument from the raw call input. Input over
+/// `max_submission_bytes` is rejected before any of it is parsed, so an
+/// oversized or deeply nested payload is turned away for the cost of reading
+/// its length.
+pub fn parse_submission_input<T>(max_submission_bytes: u32) -> ProposalSubmission<T>
+where
+    T: near_sdk::serde::de::DeserializeOwned,
+{
+    near_sdk::serde_json::from_slice::<SubmissionArgs<T>>(&read_submission_input(
+        max_submission_bytes,
+    ))
+    .unwrap_or_else(|_| env::panic_str("Failed to deserialize input from JSON."))
+    .submission
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct SignedSubmissionArgs {
+    payload: String,
+    signature: Base64VecU8,
+}
+
+/// Reads the `payload` and `signature` arguments of `spo_submit_signed`
+/// from the raw call input, which is held to the same size limit as
+/// `parse_submission_input`'s.
+pub fn parse_signed_submission_input(max_submission_bytes: u32) -> (String, Base64VecU8) {
+    let args = near_sdk::serde_json::from_slice::<SignedSubmissionArgs>(&read_submission_input(
+        max_submission_bytes,
+    ))
+    .unwrap_or_else(|_| env::panic_str("Failed to deserialize input from JSON."));
+
+    (args.payload, args.signature)
+}
+
+fn read_submission_input(max_submission_bytes: u32) -> Vec<u8> {
+    let input = env::input().unwrap_or_default();
+    require!(
+        input.len() <= max_submission_bytes as usize,
+        format!(
+            "Submission exceeds maximum size of {} bytes",
+            max_submission_bytes
+        )
+    );
+    input
 }
 
 #[macro_export]
 macro_rules! impl_sponsorship {
-    ($contract: ident, $sponsorship: ident, $sponsorship_type: ident, $ownership: ident $(, $on_status_change: ident)? $(,)?) => {
+    ($contract: ident, $sponsorship: ident, $sponsorship_type: ident, $ownership: ident $(, $on_status_change: ident $(, $on_admin_action: ident $(, $on_swept: ident $(, $on_settled: ident)?)?)?)? $(,)?) => {
         #[near_bindgen]
-        impl Sponsorable<$sponsorship_type> for $contract {
+        impl $crate::sponsorship::Sponsorable<$sponsorship_type> for $contract {
             fn spo_get_tags(&self) -> Vec<String> {
                 self.$sponsorship.get_tags()
             }
 
             #[payable]
             fn spo_add_tags(&mut self, tags: Vec<String>) {
-                assert_one_yocto();
+                $crate::metered!("spo_add_tags");
+                near_sdk::assert_one_yocto();
                 self.$ownership.assert_owner();
+                for tag in tags.iter() {
+                    require!(
+                        <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::handles_tag(tag),
+                        format!("Tag has no handler: {}", tag)
+                    );
+                }
+                $($(self.$on_admin_action("spo_add_tags", tags.join(","));)?)?
                 self.$sponsorship.add_tags(tags)
             }
 
             #[payable]
             fn spo_remove_tags(&mut self, tags: Vec<String>) {
-                assert_one_yocto();
+                $crate::metered!("spo_remove_tags");
+                near_sdk::assert_one_yocto();
                 self.$ownership.assert_owner();
+                $($(self.$on_admin_action("spo_remove_tags", tags.join(","));)?)?
                 self.$sponsorship.remove_tags(tags)
             }
 
@@ -358,6 +3363,30 @@ macro_rules! impl_sponsorship {
                 self.$sponsorship.get_total_accepted_deposits()
             }
 
+            fn spo_get_deposit_totals(&self) -> DepositTotals {
+                self.$sponsorship.get_deposit_totals()
+            }
+
+            fn spo_get_author_stats(&self, account_id: AccountId) -> AuthorStats {
+                self.$sponsorship.get_author_stats(&account_id)
+            }
+
+            fn spo_is_trusted_author(&self, account_id: AccountId, tag: String) -> bool {
+                self.$sponsorship.is_trusted(account_id, tag)
+            }
+
+            #[payable]
+            fn spo_set_trusted_author(&mut self, account_id: AccountId, tag: String, trusted: bool) {
+                $crate::metered!("spo_set_trusted_author");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_trusted_author",
+                    format!("{} {} {}", account_id, tag, trusted),
+                );)?)?
+                self.$sponsorship.set_trusted(account_id, tag, trusted)
+            }
+
             fn spo_get_all_proposals(&self) -> Vec<Proposal<$sponsorship_type>> {
                 self.$sponsorship.get_all()
             }
@@ -382,53 +3411,687 @@ macro_rules! impl_sponsorship {
                 self.$sponsorship.get_expired()
             }
 
+            fn spo_get_disputed_proposals(&self) -> Vec<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_disputed()
+            }
+
+            fn spo_get_scheduled_proposals(&self) -> Vec<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_scheduled()
+            }
+
+            fn spo_get_draft_proposals(&self) -> Vec<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_drafts()
+            }
+
+            fn spo_get_proposals_between(
+                &self,
+                from_ts: U64,
+                to_ts: U64,
+                limit: Option<U64>,
+            ) -> Vec<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_between(
+                    from_ts.into(),
+                    to_ts.into(),
+                    limit.map(|x| x.into()).unwrap_or(u64::MAX),
+                )
+            }
+
+            fn spo_get_proposal_headers(
+                &self,
+                status: Option<ProposalStatus>,
+                from_index: Option<U64>,
+                limit: Option<U64>,
+            ) -> Vec<ProposalHeader> {
+                self.$sponsorship.get_headers(
+                    status,
+                    from_index.map(|x| x.into()).unwrap_or(0),
+                    limit.map(|x| x.into()).unwrap_or(u64::MAX),
+                )
+            }
+
             fn spo_get_proposal(&self, id: U64) -> Option<Proposal<$sponsorship_type>> {
                 self.$sponsorship.get_proposal(id.into())
             }
 
+            /// The proposal followed by the earlier attempts it was
+            /// resubmitted from, newest first, so reviewers can see how the
+            /// previous ones were handled
+            fn spo_get_proposal_chain(&self, id: U64, limit: Option<U64>) -> Vec<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_proposal_chain(
+                    id.into(),
+                    limit.map(|x| x.into()).unwrap_or(u64::MAX),
+                )
+            }
+
+            /// ID of the proposal that `id` was resubmitted as, if any
+            fn spo_get_resubmission(&self, id: U64) -> Option<U64> {
+                self.$sponsorship.get_resubmission(id.into()).map(U64)
+            }
+
+            fn spo_get_proposal_by_uid(&self, uid: U64) -> Option<Proposal<$sponsorship_type>> {
+                self.$sponsorship.get_proposal_by_uid(uid.into())
+            }
+
+            fn spo_sweep_expired(&mut self, from_index: U64, limit: U64) -> Vec<Proposal<$sponsorship_type>> {
+                $crate::metered!("spo_sweep_expired");
+                // Permissionless: only settles proposals that have already expired
+                let mut swept = vec![];
+                for id in self.$sponsorship.get_expired_ids(from_index.into(), limit.into()) {
+                    // Checked one at a time, after the acceptances before it
+                    // took effect, so that one proposal that can no longer be
+                    // accepted is held rather than failing the whole sweep
+                    let acceptable = self
+                        .$sponsorship
+                        .get_expiry_acceptance(id)
+                        .map_or(false, |accepted| {
+                            <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::check_acceptance(self, &accepted).is_ok()
+                        });
+                    let proposal = self.$sponsorship.sweep_expired(id, acceptable);
+                    $(self.$on_status_change(&proposal);)?
+                    swept.push(proposal);
+                }
+                $($($(self.$on_swept(swept.len() as u64);)?)?)?
+                $crate::events::emit_records_changed(
+                    "spo_sweep_expired",
+                    "proposal",
+                    &swept.iter().map(|p| U64(p.id)).collect::<Vec<_>>(),
+                );
+                swept.into_iter().map(|x| self.$sponsorship.reload(x)).collect()
+            }
+
+            fn spo_get_emergency_refund_cursor(&self) -> U64 {
+                self.$sponsorship.get_emergency_refund_cursor().into()
+            }
+
+            fn spo_get_escrow(&self, id: U64) -> Option<Escrow> {
+                self.$sponsorship.get_escrow(id.into())
+            }
+
+            fn spo_get_total_escrowed(&self) -> U128 {
+                self.$sponsorship.get_total_escrowed().into()
+            }
+
+            fn spo_settle(&mut self, id: U64) -> Escrow {
+                $crate::metered!("spo_settle");
+                // Permissionless: only releases deposits of accepted proposals
+                let escrow = self.$sponsorship.settle(id.into());
+                $($($($(self.$on_settled();)?)?)?)?
+                escrow
+            }
+
+            #[payable]
+            fn spo_forfeit(&mut self, id: U64) -> Escrow {
+                $crate::metered!("spo_forfeit");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action("spo_forfeit", format!("proposal {}", id.0));)?)?
+                self.$sponsorship.forfeit(id.into())
+            }
+
+            #[payable]
+            fn spo_emergency_refund(&mut self, limit: U64) -> Vec<Proposal<$sponsorship_type>> {
+                $crate::metered!("spo_emergency_refund");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_emergency_refund",
+                    format!("limit {}", limit.0),
+                );)?)?
+                let refunded = self.$sponsorship.emergency_refund(limit.into());
+                $(for proposal in refunded.iter() {
+                    self.$on_status_change(proposal);
+                })?
+                $crate::events::emit_records_changed(
+                    "spo_emergency_refund",
+                    "proposal",
+                    &refunded.iter().map(|p| U64(p.id)).collect::<Vec<_>>(),
+                );
+                refunded.into_iter().map(|x| self.$sponsorship.reload(x)).collect()
+            }
+
+            #[payable]
+            fn spo_reset_emergency_refund_cursor(&mut self) {
+                $crate::metered!("spo_reset_emergency_refund_cursor");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_reset_emergency_refund_cursor",
+                    String::new(),
+                );)?)?
+                self.$sponsorship.reset_emergency_refund_cursor();
+            }
+
             fn spo_get_duration(&self) -> Option<U64> {
                 self.$sponsorship.get_duration().map(|x| x.into())
             }
 
             #[payable]
-            fn spo_set_duration(&mut self, duration: Option<U64>) {
-                assert_one_yocto();
-                self.$sponsorship.set_duration(duration.map(|x| x.into()))
+            fn spo_set_duration(&mut self, duration: Option<U64>, extend_pending: Option<bool>) -> Vec<U64> {
+                $crate::metered!("spo_set_duration");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_duration",
+                    format!("{:?}", duration.map(u64::from)),
+                );)?)?
+                let extended: Vec<U64> = self
+                    .$sponsorship
+                    .set_duration(duration.map(|x| x.into()), extend_pending.unwrap_or(false))
+                    .into_iter()
+                    .map(U64)
+                    .collect();
+                $crate::events::emit_records_changed("spo_set_duration", "proposal", &extended);
+                extended
+            }
+
+            fn spo_get_dispute_window(&self) -> Option<U64> {
+                self.$sponsorship.get_dispute_window().map(|x| x.into())
+            }
+
+            #[payable]
+            fn spo_set_dispute_window(&mut self, dispute_window: Option<U64>) {
+                $crate::metered!("spo_set_dispute_window");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_dispute_window",
+                    format!("{:?}", dispute_window.map(u64::from)),
+                );)?)?
+                self.$sponsorship.set_dispute_window(dispute_window.map(|x| x.into()))
+            }
+
+            fn spo_get_rejection_cooldown(&self) -> Option<U64> {
+                self.$sponsorship.get_rejection_cooldown().map(|x| x.into())
             }
 
             #[payable]
-            fn spo_submit(&mut self, submission: ProposalSubmission<$sponsorship_type>) -> Proposal<$sponsorship_type> {
-                // submit manages its own deposit requirements
-                let proposal = self.$sponsorship.submit(submission);
+            fn spo_set_rejection_cooldown(&mut self, rejection_cooldown: Option<U64>) {
+                $crate::metered!("spo_set_rejection_cooldown");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_rejection_cooldown",
+                    format!(
+                        "{:?} -> {:?}",
+                        self.$sponsorship.get_rejection_cooldown(),
+                        rejection_cooldown.map(u64::from),
+                    ),
+                );)?)?
+                self.$sponsorship.set_rejection_cooldown(rejection_cooldown.map(|x| x.into()))
+            }
+
+            fn spo_get_unwind_window(&self) -> Option<U64> {
+                self.$sponsorship.get_unwind_window().map(|x| x.into())
+            }
+
+            #[payable]
+            fn spo_set_unwind_window(&mut self, unwind_window: Option<U64>) {
+                $crate::metered!("spo_set_unwind_window");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_unwind_window",
+                    format!("{:?}", unwind_window.map(u64::from)),
+                );)?)?
+                self.$sponsorship.set_unwind_window(unwind_window.map(|x| x.into()))
+            }
+
+            fn spo_get_unwind_fee_bps(&self) -> u16 {
+                self.$sponsorship.get_unwind_fee_bps()
+            }
+
+            #[payable]
+            fn spo_set_unwind_fee_bps(&mut self, unwind_fee_bps: u16) {
+                $crate::metered!("spo_set_unwind_fee_bps");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_unwind_fee_bps",
+                    unwind_fee_bps.to_string(),
+                );)?)?
+                self.$sponsorship.set_unwind_fee_bps(unwind_fee_bps)
+            }
+
+            fn spo_get_unwind(&self, id: U64) -> Option<Unwind> {
+                self.$sponsorship.get_unwind(id.into())
+            }
+
+            fn spo_get_max_submission_bytes(&self) -> u32 {
+                self.$sponsorship.get_max_submission_bytes()
+            }
+
+            #[payable]
+            fn spo_set_max_submission_bytes(&mut self, max_submission_bytes: u32) {
+                $crate::metered!("spo_set_max_submission_bytes");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_max_submission_bytes",
+                    format!("{} -> {}", self.$sponsorship.get_max_submission_bytes(), max_submission_bytes),
+                );)?)?
+                self.$sponsorship.set_max_submission_bytes(max_submission_bytes)
+            }
+
+            fn spo_get_approval_threshold(&self) -> Option<U128> {
+                self.$sponsorship.get_approval_threshold().map(|x| x.into())
+            }
+
+            #[payable]
+            fn spo_set_approval_threshold(&mut self, approval_threshold: Option<U128>) {
+                $crate::metered!("spo_set_approval_threshold");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_approval_threshold",
+                    format!("{:?}", approval_threshold.map(u128::from)),
+                );)?)?
+                self.$sponsorship.set_approval_threshold(approval_threshold.map(|x| x.into()))
+            }
+
+            fn spo_get_expiry_policy(&self, tag: String) -> ExpiryPolicy {
+                self.$sponsorship.get_expiry_policy(&tag)
+            }
+
+            #[payable]
+            fn spo_set_expiry_policy(&mut self, tag: String, policy: ExpiryPolicy) {
+                $crate::metered!("spo_set_expiry_policy");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_expiry_policy",
+                    format!("{} {:?}", tag, policy),
+                );)?)?
+                self.$sponsorship.set_expiry_policy(&tag, policy)
+            }
+
+            fn spo_get_tag_config(&self, tag: String) -> TagConfig {
+                self.$sponsorship.get_tag_config(&tag)
+            }
+
+            #[payable]
+            fn spo_set_tag_min_deposit(&mut self, tag: String, min_deposit: U128) {
+                $crate::metered!("spo_set_tag_min_deposit");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_tag_min_deposit",
+                    format!("{} {}", tag, min_deposit.0),
+                );)?)?
+                self.$sponsorship.set_min_deposit(&tag, min_deposit.into())
+            }
+
+            #[payable]
+            fn spo_set_tag_lead_time_policy(&mut self, tag: String, policy: LeadTimePolicy) {
+                $crate::metered!("spo_set_tag_lead_time_policy");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_tag_lead_time_policy",
+                    format!("{} {:?}", tag, policy),
+                );)?)?
+                self.$sponsorship.set_lead_time_policy(&tag, policy)
+            }
+
+            #[payable]
+            fn spo_set_tag_max_pending_deposit(&mut self, tag: String, max_pending_deposit: Option<U128>) {
+                $crate::metered!("spo_set_tag_max_pending_deposit");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                $($(self.$on_admin_action(
+                    "spo_set_tag_max_pending_deposit",
+                    format!("{} {:?}", tag, max_pending_deposit.map(u128::from)),
+                );)?)?
+                self.$sponsorship
+                    .set_max_pending_deposit(&tag, max_pending_deposit.map(u128::from))
+            }
+
+            fn spo_get_pending_deposit(&self, tag: String) -> U128 {
+                self.$sponsorship.get_pending_deposit(&tag).into()
+            }
+
+            fn spo_get_pending_count(&self) -> U64 {
+                self.$sponsorship.get_pending_count().into()
+            }
+
+            fn spo_get_max_pending_proposals(&self) -> Option<U64> {
+                self.$sponsorship.get_max_pending_proposals().map(U64)
+            }
+
+            #[payable]
+            fn spo_set_max_pending_proposals(&mut self, max_pending_proposals: Option<U64>) {
+                $crate::metered!("spo_set_max_pending_proposals");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                let max_pending_proposals = max_pending_proposals.map(u64::from);
+                $($(self.$on_admin_action(
+                    "spo_set_max_pending_proposals",
+                    format!("{:?} -> {:?}", self.$sponsorship.get_max_pending_proposals(), max_pending_proposals),
+                );)?)?
+                self.$sponsorship.set_max_pending_proposals(max_pending_proposals)
+            }
+
+            #[payable]
+            fn spo_dispute(&mut self, id: U64, reason: String) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_dispute");
+                near_sdk::assert_one_yocto();
+                let proposal = self.$sponsorship.dispute(id.into(), reason);
+                $(self.$on_status_change(&proposal);)?
+                proposal
+            }
+
+            #[payable]
+            fn spo_unwind(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_unwind");
+                near_sdk::assert_one_yocto();
+                let proposal = self.$sponsorship.unwind(id.into());
                 $(self.$on_status_change(&proposal);)?
                 proposal
             }
 
+            #[payable]
+            fn spo_resolve_dispute(&mut self, id: U64, overturned: bool) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_resolve_dispute");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                let ownership = &self.$ownership;
+                let proposal = self.$sponsorship.resolve_dispute(
+                    id.into(),
+                    overturned,
+                    env::predecessor_account_id(),
+                    |account_id| ownership.is_owner_or_role(account_id, $crate::ownership::Role::REVIEWER),
+                );
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action(
+                    "spo_resolve_dispute",
+                    format!("proposal {} overturned {}", proposal.id, overturned),
+                );)?)?
+                self.$sponsorship.reload(proposal)
+            }
+
+            fn spo_quote_submission(&self, author_id: AccountId, submission: ProposalSubmission<$sponsorship_type>) -> SubmissionQuote {
+                self.$sponsorship.quote_submission(
+                    author_id,
+                    submission,
+                    <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::validate_msg,
+                )
+            }
+
+            #[payable]
+            fn spo_fund_draft(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_fund_draft");
+                let proposal = self.$sponsorship.fund_draft(id.into());
+                $(self.$on_status_change(&proposal);)?
+                if !self
+                    .$sponsorship
+                    .is_trusted(proposal.author_id.clone(), proposal.tag.clone())
+                    || self.$sponsorship.requires_two_phase(&proposal)
+                {
+                    return proposal;
+                }
+                let proposal = self.$sponsorship.accept(proposal.id);
+                $(self.$on_status_change(&proposal);)?
+                self.$sponsorship.reload(proposal)
+            }
+
             #[payable]
             fn spo_accept(&mut self, id: U64) -> Proposal<$sponsorship_type> {
-                assert_one_yocto();
+                $crate::metered!("spo_accept");
+                near_sdk::assert_one_yocto();
                 self.$ownership.assert_owner();
                 let proposal = self.$sponsorship.accept(id.into());
-                $(self.$on_status_change(&proposal);)?
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action("spo_accept", format!("proposal {}", proposal.id));)?)?
+                self.$sponsorship.reload(proposal)
+            }
+
+            #[payable]
+            fn spo_approve(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_approve");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner_or_role($crate::ownership::Role::REVIEWER);
+                let proposal = self
+                    .$sponsorship
+                    .approve(id.into(), env::predecessor_account_id());
+                $($(self.$on_admin_action("spo_approve", format!("proposal {}", proposal.id));)?)?
                 proposal
             }
 
+            #[payable]
+            fn spo_claim_review(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_claim_review");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner_or_role($crate::ownership::Role::REVIEWER);
+                let ownership = &self.$ownership;
+                self.$sponsorship.claim_review(
+                    id.into(),
+                    env::predecessor_account_id(),
+                    |account_id| ownership.is_owner_or_role(account_id, $crate::ownership::Role::REVIEWER),
+                )
+            }
+
+            #[payable]
+            fn spo_release_review(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_release_review");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner_or_role($crate::ownership::Role::REVIEWER);
+                let caller = env::predecessor_account_id();
+                let is_owner = self.$ownership.is_owner(&caller);
+                let proposal = self.$sponsorship.release_review(id.into(), is_owner);
+                $($(if is_owner {
+                    self.$on_admin_action("spo_release_review", format!("proposal {}", proposal.id));
+                })?)?
+                proposal
+            }
+
+            #[payable]
+            fn spo_finalize(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_finalize");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner_or_role($crate::ownership::Role::REVIEWER);
+                let ownership = &self.$ownership;
+                let proposal = self.$sponsorship.finalize(
+                    id.into(),
+                    env::predecessor_account_id(),
+                    |account_id| ownership.is_owner_or_role(account_id, $crate::ownership::Role::REVIEWER),
+                );
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action("spo_finalize", format!("proposal {}", proposal.id));)?)?
+                self.$sponsorship.reload(proposal)
+            }
+
             #[payable]
             fn spo_reject(&mut self, id: U64) -> Proposal<$sponsorship_type> {
-                assert_one_yocto();
+                $crate::metered!("spo_reject");
+                near_sdk::assert_one_yocto();
                 self.$ownership.assert_owner();
                 let proposal = self.$sponsorship.reject(id.into());
-                $(self.$on_status_change(&proposal);)?
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action("spo_reject", format!("proposal {}", proposal.id));)?)?
                 proposal
             }
 
             #[payable]
             fn spo_rescind(&mut self, id: U64) -> Proposal<$sponsorship_type> {
-                assert_one_yocto();
+                $crate::metered!("spo_rescind");
+                near_sdk::assert_one_yocto();
                 let proposal = self.$sponsorship.rescind(id.into());
                 $(self.$on_status_change(&proposal);)?
                 proposal
             }
+
+            #[payable]
+            fn spo_accept_at(&mut self, id: U64, timestamp: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_accept_at");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                let proposal = self.$sponsorship.schedule_accept(id.into(), timestamp.into());
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action(
+                    "spo_accept_at",
+                    format!("proposal {} at {}", proposal.id, u64::from(timestamp)),
+                );)?)?
+                proposal
+            }
+
+            fn spo_execute_scheduled(&mut self, id: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_execute_scheduled");
+                // Permissionless: the owner's approval has already been recorded
+                let proposal = self.$sponsorship.execute_scheduled(id.into());
+                $(self.$on_status_change(&proposal);)?
+                self.$sponsorship.reload(proposal)
+            }
+
+            #[payable]
+            fn spo_accept_by_uid(&mut self, uid: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_accept_by_uid");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                let proposal = self.$sponsorship.accept_by_uid(uid.into());
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action("spo_accept_by_uid", format!("proposal {}", proposal.id));)?)?
+                self.$sponsorship.reload(proposal)
+            }
+
+            #[payable]
+            fn spo_reject_by_uid(&mut self, uid: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_reject_by_uid");
+                near_sdk::assert_one_yocto();
+                self.$ownership.assert_owner();
+                let proposal = self.$sponsorship.reject_by_uid(uid.into());
+                $(self.$on_status_change(&proposal);
+                $(self.$on_admin_action("spo_reject_by_uid", format!("proposal {}", proposal.id));)?)?
+                proposal
+            }
+
+            #[payable]
+            fn spo_rescind_by_uid(&mut self, uid: U64) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_rescind_by_uid");
+                near_sdk::assert_one_yocto();
+                let proposal = self.$sponsorship.rescind_by_uid(uid.into());
+                $(self.$on_status_change(&proposal);)?
+                proposal
+            }
+
+            fn spo_get_signing_key(&self, account_id: AccountId) -> Option<PublicKey> {
+                self.$sponsorship.get_signing_key(&account_id)
+            }
+
+            fn spo_get_signing_nonce(&self, account_id: AccountId) -> U64 {
+                self.$sponsorship.get_signing_nonce(&account_id).into()
+            }
+
+            /// Registers the caller's key for signed requests. Needs a full
+            /// access key, like any call that takes a deposit, so that a
+            /// function call access key cannot add another signer.
+            #[payable]
+            fn spo_set_signing_key(&mut self, public_key: Option<PublicKey>) {
+                $crate::metered!("spo_set_signing_key");
+                near_sdk::assert_one_yocto();
+                self.$sponsorship
+                    .set_signing_key(near_sdk::env::predecessor_account_id(), public_key);
+            }
+
+            #[payable]
+            fn spo_rescind_signed(&mut self, payload: String, signature: Base64VecU8) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_rescind_signed");
+                near_sdk::assert_one_yocto();
+                let proposal = self.$sponsorship.rescind_signed(&payload, &signature);
+                $(self.$on_status_change(&proposal);)?
+                proposal
+            }
+        }
+
+        impl $crate::sponsorship::Submittable<$sponsorship_type> for $contract {
+            fn spo_submit(&mut self, submission: ProposalSubmission<$sponsorship_type>) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_submit");
+                // submit manages its own deposit requirements
+                let proposal = self.$sponsorship.submit(
+                    submission,
+                    <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::validate_msg,
+                );
+                $(self.$on_status_change(&proposal);)?
+                if !self
+                    .$sponsorship
+                    .is_trusted(proposal.author_id.clone(), proposal.tag.clone())
+                    || self.$sponsorship.requires_two_phase(&proposal)
+                {
+                    return proposal;
+                }
+                // Trusted authors skip review once the submission passes validation
+                let proposal = self.$sponsorship.accept(proposal.id);
+                $(self.$on_status_change(&proposal);)?
+                self.$sponsorship.reload(proposal)
+            }
+
+            fn spo_submit_draft(&mut self, submission: ProposalSubmission<$sponsorship_type>) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_submit_draft");
+                // The message must still match its tag, but the handler only
+                // checks whether it can be accepted once `spo_fund_draft` runs
+                self.$sponsorship.submit_draft(
+                    submission,
+                    <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::validate_msg,
+                )
+            }
+
+            fn spo_submit_signed(&mut self, payload: String, signature: Base64VecU8) -> Proposal<$sponsorship_type> {
+                $crate::metered!("spo_submit_signed");
+                let proposal = self.$sponsorship.submit_signed(
+                    &payload,
+                    &signature,
+                    <$contract as $crate::sponsorship::MessageValidator<$sponsorship_type>>::validate_msg,
+                );
+                $(self.$on_status_change(&proposal);)?
+                if !self
+                    .$sponsorship
+                    .is_trusted(proposal.author_id.clone(), proposal.tag.clone())
+                    || self.$sponsorship.requires_two_phase(&proposal)
+                {
+                    return proposal;
+                }
+                // As in spo_submit
+                let proposal = self.$sponsorship.accept(proposal.id);
+                $(self.$on_status_change(&proposal);)?
+                self.$sponsorship.reload(proposal)
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn spo_submit() {
+            near_sdk::env::setup_panic_hook();
+            let mut contract: $contract = near_sdk::env::state_read().unwrap_or_default();
+            let submission = $crate::sponsorship::parse_submission_input(
+                contract.$sponsorship.get_max_submission_bytes(),
+            );
+            let result = <$contract as $crate::sponsorship::Submittable<$sponsorship_type>>::spo_submit(&mut contract, submission);
+            near_sdk::env::value_return(&near_sdk::serde_json::to_vec(&result).unwrap());
+            near_sdk::env::state_write(&contract);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn spo_submit_draft() {
+            near_sdk::env::setup_panic_hook();
+            let mut contract: $contract = near_sdk::env::state_read().unwrap_or_default();
+            let submission = $crate::sponsorship::parse_submission_input(
+                contract.$sponsorship.get_max_submission_bytes(),
+            );
+            let result = <$contract as $crate::sponsorship::Submittable<$sponsorship_type>>::spo_submit_draft(&mut contract, submission);
+            near_sdk::env::value_return(&near_sdk::serde_json::to_vec(&result).unwrap());
+            near_sdk::env::state_write(&contract);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn spo_submit_signed() {
+            near_sdk::env::setup_panic_hook();
+            let mut contract: $contract = near_sdk::env::state_read().unwrap_or_default();
+            let (payload, signature) = $crate::sponsorship::parse_signed_submission_input(
+                contract.$sponsorship.get_max_submission_bytes(),
+            );
+            let result = <$contract as $crate::sponsorship::Submittable<$sponsorship_type>>::spo_submit_signed(&mut contract, payload, signature);
+            near_sdk::env::value_return(&near_sdk::serde_json::to_vec(&result).unwrap());
+            near_sdk::env::state_write(&contract);
         }
     };
 }
@@ -0,0 +1,201 @@
+use crate::*;
+
+/// Every storage prefix the contract uses is built from these two enums, so
+/// adding a collection means adding a variant here rather than picking a
+/// byte by hand.
+///
+/// A top-level key is either used directly by a collection or passed to a
+/// subsystem (`Ownership::new`, `Sponsorship::new`, ...) that nests its own
+/// collections under it with `prefix_key`, never both. Both enums serialize
+/// to a single byte, so nested prefixes are all exactly two bytes long and
+/// none is a prefix of another.
+///
+/// Variants must only ever be appended: the discriminant is the key.
+#[derive(BorshStorageKey, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[allow(non_camel_case_types)]
+pub(crate) enum StorageKey {
+    OWNERSHIP,
+    SPONSORSHIP,
+    BADGES,
+    AWARDS,
+    ACCOUNT_AWARDS,
+    CLAIM_KEYS,
+    BADGE_INDEXES,
+    AUDIT_LOG,
+    PROPOSAL_TERMS,
+    BADGE_REPORTS,
+    ACTIVATION_QUEUE,
+    EXPIRATION_QUEUE,
+    KEEPER_TASKS,
+    GROUP_VERIFICATIONS,
+    ORACLES,
+    ATTESTATIONS,
+    MERKLE_DROPS,
+    MERKLE_CLAIMS,
+    AWARD_VALIDITY,
+    GROUP_TRANSFERS,
+    VIEW_RELAYERS,
+    BADGE_VIEWS,
+    SUPPORTERS,
+    RECENT_DONATIONS,
+    DONOR_TOTALS,
+    TOP_DONORS,
+    BADGE_STREAMS,
+    WRAP_TOKENS,
+    TREASURY_POSITIONS,
+    REFERRAL_REWARDS,
+    PROMO_CODES,
+    FREE_TIER_ACCOUNTS,
+    PENDING_EXECUTIONS,
+    WAITLIST,
+    PROPOSAL_UPDATES,
+    GROUP_STATS,
+    ACCOUNT_AWARD_SETS,
+    /// Followed by the SHA-256 of the account ID, one set per account
+    ACCOUNT_AWARD_SET,
+    GROUP_MEDIA,
+    PRUNED_BADGE_IDS,
+    TERMS_OFFERS,
+    PACKAGES,
+    PROPOSAL_PACKAGES,
+    FEATURED_UNTIL,
+    FEATURED_QUEUE,
+    /// Award sets as kept from state version 3 on. Earlier ones stay under
+    /// `ACCOUNT_AWARD_SETS` until the account is next awarded a badge.
+    ACCOUNT_AWARD_SETS_V3,
+    CLAIM_ISSUERS,
+    SIGNED_CLAIM_NONCES,
+    // Feature-gated variants stay last so that the discriminants above do
+    // not depend on which features are enabled
+    #[cfg(feature = "metrics")]
+    METHOD_METRICS,
+    #[cfg(feature = "sandbox")]
+    TIME_OFFSET,
+}
+
+/// Collections nested under a top-level key.
+///
+/// State version 1 keyed its nested collections with hand-picked ASCII
+/// suffixes, all `b'a'` or above. The discriminants here stay well below
+/// that, so `migrate` can read the old collections while it fills the new
+/// ones.
+#[derive(BorshStorageKey, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[allow(non_camel_case_types)]
+pub(crate) enum NestedKey {
+    // Ownership
+    PROPOSED_OWNER,
+    REVIEWERS,
+    // Sponsorship
+    TAGS,
+    PROPOSALS,
+    PROPOSAL_BODIES,
+    PROPOSAL_IDS_BY_UID,
+    PROPOSAL_IDS_BY_NONCE,
+    PROPOSAL_IDS_BY_CREATED_AT,
+    AUTHOR_STATS,
+    TRUSTED_AUTHORS,
+    TAG_CONFIGS,
+    PROPOSAL_DURATION,
+    // Audit log
+    ADMIN_ACTIONS,
+    // Badge indexes
+    INDEX_CREATED_AT,
+    INDEX_START_AT,
+    INDEX_EXPIRES_AT,
+    INDEX_NAME,
+    INDEX_ID,
+    INDEX_GROUP_ID,
+    // Badge store
+    BADGE_RECORDS,
+    BADGE_IDS,
+    // Sponsorship, added after the groups above to keep their keys stable
+    ESCROWS,
+    // Badge indexes, added after the groups above to keep their keys stable
+    INDEX_SPONSOR_ID,
+    // Sponsorship, added after the groups above to keep their keys stable
+    UNWINDS,
+    // Ownership, added after the groups above to keep their keys stable
+    MODERATORS,
+    // Sponsorship, added after the groups above to keep their keys stable
+    PENDING_DEPOSITS_BY_TAG,
+    // Sponsorship, added after the groups above to keep their keys stable
+    RESUBMISSIONS,
+    // Sponsorship, added after the groups above to keep their keys stable
+    REJECTED_MSG_HASHES,
+    // Sponsorship, added after the groups above to keep their keys stable
+    SIGNING_KEYS,
+    SIGNING_NONCES,
+    // Method metrics
+    #[cfg(feature = "metrics")]
+    METRICS_METHODS,
+    #[cfg(feature = "metrics")]
+    METRICS_COUNTERS,
+}
+
+pub(crate) fn prefix_key(prefix: &[u8], key: NestedKey) -> Vec<u8> {
+    [prefix, &key.into_storage_key()].concat()
+}
+
+// State version 2 kept its collections in near-sdk 4's `collections`, which
+// lay their entries out differently from the `store` collections that
+// replaced them. `LookupMap`, `LookupSet` and `LazyOption` read the same in
+// both; the helpers below move the rest. Each one reads every entry, removes
+// the old ones, and fills the new collection under the same prefix, which
+// is written when the contract state is.
+
+pub(crate) fn migrate_set<T>(
+    mut old: collections::UnorderedSet<T>,
+    prefix: Vec<u8>,
+) -> IterableSet<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    let elements = old.to_vec();
+    old.clear();
+    let mut new = IterableSet::new(prefix);
+    new.extend(elements);
+    new
+}
+
+pub(crate) fn migrate_map<K, V>(
+    mut old: collections::UnorderedMap<K, V>,
+    prefix: Vec<u8>,
+) -> IterableMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+{
+    let entries = old.to_vec();
+    old.clear();
+    let mut new = IterableMap::new(prefix);
+    new.extend(entries);
+    new
+}
+
+pub(crate) fn migrate_vector<T>(mut old: collections::Vector<T>, prefix: Vec<u8>) -> Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    let elements = old.to_vec();
+    old.clear();
+    let mut new = Vector::new(prefix);
+    new.extend(elements);
+    new
+}
+
+pub(crate) fn migrate_tree_map<K, V>(
+    mut old: collections::TreeMap<K, V>,
+    prefix: Vec<u8>,
+) -> TreeMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+{
+    let entries = old.to_vec();
+    old.clear();
+    let mut new = TreeMap::new(prefix);
+    new.extend(entries);
+    new
+}